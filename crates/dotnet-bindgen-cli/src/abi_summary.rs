@@ -0,0 +1,96 @@
+//! A plain text summary of the exact ABI of each exported function, for checking the Rust side of
+//! the bindings against the generated C# when debugging.
+//!
+//! Each argument and return value is given as the Rust type it was most likely declared as,
+//! followed by the `<T as BindgenAbiConvert>::AbiType` it crosses the boundary as, eg
+//! ```text
+//! fn greet(name: &str) -> String
+//!     thunk: __bindgen_thunk_greet, extern "C"
+//!     name: &str => SliceAbi<u8>
+//!     return: String => OwnedSliceAbi<u8>
+//! ```
+
+use std::io::{self, Write};
+
+use dotnet_bindgen_core::*;
+
+use crate::data::BindgenData;
+use crate::diff::{function_signature, type_name};
+
+/// Writes the summary of every function in the given data, in the order they were extracted.
+pub fn write_abi_summary(data: &BindgenData, out: &mut dyn Write) -> io::Result<()> {
+    let functions = data.descriptors.iter().filter_map(|d| match d {
+        BindgenExportDescriptor::Function(f) => Some(f),
+        _ => None,
+    });
+
+    for (idx, f) in functions.enumerate() {
+        if idx > 0 {
+            writeln!(out)?;
+        }
+        write_function_summary(f, out)?;
+    }
+
+    Ok(())
+}
+
+fn write_function_summary(f: &BindgenFunctionDescriptor, out: &mut dyn Write) -> io::Result<()> {
+    let abi = match f.calling_convention {
+        BindgenCallingConvention::C => "extern \"C\"",
+        BindgenCallingConvention::System => "extern \"system\"",
+    };
+    // Foreign functions are called directly, rather than through a generated thunk.
+    let kind = if f.no_thunk { "foreign" } else { "thunk" };
+
+    writeln!(out, "{}", function_signature(f))?;
+    writeln!(out, "    {}: {}, {}", kind, f.thunk_name, abi)?;
+
+    // Receivers are passed exactly as they are, so need no conversion.
+    if let (Some(receiver), Some(owner)) = (f.receiver, &f.owner) {
+        let self_ty = match receiver {
+            BindgenReceiver::Value => owner.clone(),
+            BindgenReceiver::Ref => format!("&{}", owner),
+            BindgenReceiver::RefMut => format!("&mut {}", owner),
+        };
+        writeln!(out, "    self: {} => {}", self_ty, self_ty)?;
+    }
+
+    for arg in &f.arguments {
+        writeln!(out, "    {}: {} => {}", arg.name, type_name(&arg.ty), abi_type_name(&arg.ty))?;
+    }
+
+    if f.return_ty != BindgenTypeDescriptor::Void {
+        writeln!(out, "    return: {} => {}", type_name(&f.return_ty), abi_type_name(&f.return_ty))?;
+    }
+
+    Ok(())
+}
+
+/// The `BindgenAbiConvert::AbiType` a value of the described type crosses the boundary as.
+///
+/// This mirrors the impls in dotnet-bindgen-core, where FfiStable types are their own AbiType.
+fn abi_type_name(ty: &BindgenTypeDescriptor) -> String {
+    use BindgenTypeDescriptor as Desc;
+
+    match ty {
+        Desc::Bool => "u8".to_string(),
+        Desc::Array { elem_type, len } => format!("ArrayAbi<{}, {}>", type_name(elem_type), len),
+        Desc::Slice { elem_type }
+        | Desc::MutSlice { elem_type }
+        | Desc::OptionSlice { elem_type }
+        | Desc::UninitSlice { elem_type } => format!("SliceAbi<{}>", type_name(elem_type)),
+        Desc::Str => "SliceAbi<u8>".to_string(),
+        Desc::OwnedSlice { elem_type } => format!("OwnedSliceAbi<{}>", abi_type_name(elem_type)),
+        Desc::OwnedStr => "OwnedSliceAbi<u8>".to_string(),
+        Desc::CString => "*mut c_char".to_string(),
+        Desc::Arc { .. } => "*const ()".to_string(),
+        Desc::Duration => "DurationAbi".to_string(),
+        Desc::Int { width: 128, .. } => "Int128Abi".to_string(),
+        Desc::Tuple { elements } => format!(
+            "TupleAbi{}<{}>",
+            elements.len(),
+            elements.iter().map(abi_type_name).collect::<Vec<_>>().join(", "),
+        ),
+        _ => type_name(ty),
+    }
+}
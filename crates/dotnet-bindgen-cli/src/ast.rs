@@ -2,9 +2,13 @@ use std::fmt;
 use std::io;
 use std::string::ToString;
 
-static INDENT_TOK: &'static str = "    ";
+static INDENT_TOK: &str = "    ";
 
 fn render_indent(f: &mut dyn io::Write, ctx: &RenderContext) -> Result<(), io::Error> {
+    if ctx.minify {
+        return Ok(());
+    }
+
     for _ in 0..ctx.indent_level {
         write!(f, "{}", INDENT_TOK)?;
     }
@@ -12,6 +16,15 @@ fn render_indent(f: &mut dyn io::Write, ctx: &RenderContext) -> Result<(), io::E
     Ok(())
 }
 
+/// The blank line separating consecutive members or types, which is left out when minifying.
+fn render_separator(f: &mut dyn io::Write, ctx: &RenderContext) -> Result<(), io::Error> {
+    if ctx.minify {
+        return Ok(());
+    }
+
+    writeln!(f)
+}
+
 macro_rules! render_ln {
     ($f:ident, &$ctx:ident, $($args:expr),+) => {
         {
@@ -29,9 +42,31 @@ macro_rules! render_ln {
     }
 }
 
+/// The access modifier given to every generated type.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Visibility {
+    #[default]
+    Public,
+    Internal,
+}
+
+impl fmt::Display for Visibility {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Visibility::Public => write!(f, "public"),
+            Visibility::Internal => write!(f, "internal"),
+        }
+    }
+}
+
 #[derive(Clone, Copy, Default)]
 pub struct RenderContext {
     indent_level: u8,
+    type_visibility: Visibility,
+
+    /// Leave out indentation and blank lines. Each statement is still given its own line, as
+    /// preprocessor directives must be.
+    minify: bool,
 }
 
 impl RenderContext {
@@ -45,6 +80,11 @@ impl RenderContext {
 
 pub trait AstNode {
     fn render(&self, f: &mut dyn io::Write, ctx: RenderContext) -> Result<(), io::Error>;
+
+    /// The names of the structs in this node which don't have an explicit StructLayout.
+    fn structs_without_layout(&self) -> Vec<String> {
+        Vec::new()
+    }
 }
 
 impl<T: fmt::Display> AstNode for T {
@@ -57,34 +97,51 @@ pub struct Root {
     pub file_comment: Option<BlockComment>,
     pub using_statements: Vec<UsingStatement>,
     pub children: Vec<Box<dyn AstNode>>,
+
+    /// The access modifier of every type declared in the file. Members are left public, which
+    /// makes them only as visible as the type they are declared in.
+    pub type_visibility: Visibility,
+
+    /// Render without indentation or blank lines, eg for embedding the source elsewhere.
+    pub minify: bool,
 }
 
 impl Root {
+    /// The using statements without duplicates, ordered with the `System` namespaces first and
+    /// alphabetically after that, as `dotnet format` would.
+    fn sorted_using_statements(&self) -> Vec<&UsingStatement> {
+        let mut usings: Vec<_> = self.using_statements.iter().collect();
+        usings.sort_by_key(|using| (!using.is_system(), using.path.as_str()));
+        usings.dedup_by_key(|using| using.path.as_str());
+        usings
+    }
+
     pub fn render(&self, f: &mut dyn io::Write) -> Result<(), io::Error> {
-        let ctx = RenderContext::default();
+        let ctx = RenderContext {
+            type_visibility: self.type_visibility,
+            minify: self.minify,
+            ..RenderContext::default()
+        };
 
         let mut first = true;
 
-        match &self.file_comment {
-            Some(c) => {
-                c.render(f, ctx)?;
-                first = false;
-            }
-            None => (),
+        if let Some(c) = &self.file_comment {
+            c.render(f, ctx)?;
+            first = false;
         }
 
         if !first && !self.using_statements.is_empty() {
-            write!(f, "\n")?;
+            render_separator(f, &ctx)?;
         }
 
-        for using in &self.using_statements {
+        for using in self.sorted_using_statements() {
             using.render(f, ctx)?;
             first = false;
         }
 
         for child in &self.children {
             if !first {
-                write!(f, "\n")?;
+                render_separator(f, &ctx)?;
             }
 
             child.render(f, ctx)?;
@@ -102,8 +159,14 @@ pub struct BlockComment {
 impl AstNode for BlockComment {
     fn render(&self, f: &mut dyn io::Write, ctx: RenderContext) -> Result<(), io::Error> {
         render_ln!(f, &ctx, "/*")?;
-        for line in &self.text {
-            render_ln!(f, &ctx, " * {}", line)?;
+        for line in self.text.iter().flat_map(|l| l.split('\n')) {
+            // A `*/` would end the comment early, leaving the rest of it to be compiled
+            let line = line.trim_end_matches('\r').replace("*/", "* /");
+            if line.is_empty() {
+                render_ln!(f, &ctx, " *")?;
+            } else {
+                render_ln!(f, &ctx, " * {}", line)?;
+            }
         }
         render_ln!(f, &ctx, " */")?;
 
@@ -115,12 +178,42 @@ pub struct UsingStatement {
     pub path: String,
 }
 
+impl UsingStatement {
+    fn is_system(&self) -> bool {
+        self.path == "System" || self.path.starts_with("System.")
+    }
+}
+
 impl AstNode for UsingStatement {
     fn render(&self, f: &mut dyn io::Write, ctx: RenderContext) -> Result<(), io::Error> {
         render_ln!(f, &ctx, "using {};", self.path)
     }
 }
 
+/// Disables or restores a set of compiler/analyzer warnings, eg `#pragma warning disable CA1060`
+pub struct PragmaWarning {
+    pub disable: bool,
+    pub ids: Vec<String>,
+}
+
+impl AstNode for PragmaWarning {
+    fn render(&self, f: &mut dyn io::Write, ctx: RenderContext) -> Result<(), io::Error> {
+        let action = if self.disable { "disable" } else { "restore" };
+        render_ln!(f, &ctx, "#pragma warning {} {}", action, self.ids.join(", "))
+    }
+}
+
+/// Fails the compilation with the given message, eg `#error Needs net7.0`
+pub struct PreprocessorError {
+    pub message: String,
+}
+
+impl AstNode for PreprocessorError {
+    fn render(&self, f: &mut dyn io::Write, ctx: RenderContext) -> Result<(), io::Error> {
+        render_ln!(f, &ctx, "#error {}", self.message)
+    }
+}
+
 /// Renders its children between a pair of curly braces
 pub struct Scope {
     pub children: Vec<Box<dyn AstNode>>,
@@ -144,12 +237,98 @@ impl AstNode for UnsafeStatement {
     }
 }
 
+/// The head of an if block, the body of which is rendered as the following Scope
+pub struct IfStatement {
+    pub condition: Box<dyn AstNode>,
+}
+
+impl AstNode for IfStatement {
+    fn render(&self, f: &mut dyn io::Write, ctx: RenderContext) -> Result<(), io::Error> {
+        render_indent(f, &ctx)?;
+        write!(f, "if (")?;
+        self.condition.render(f, ctx)?;
+        writeln!(f, ")")
+    }
+}
+
+/// A while loop, rendering its own body in a Scope
+pub struct WhileStatement {
+    pub condition: Box<dyn AstNode>,
+    pub body: Vec<Box<dyn AstNode>>,
+}
+
+impl AstNode for WhileStatement {
+    fn render(&self, f: &mut dyn io::Write, ctx: RenderContext) -> Result<(), io::Error> {
+        render_indent(f, &ctx)?;
+        write!(f, "while (")?;
+        self.condition.render(f, ctx)?;
+        writeln!(f, ")")?;
+        render_ln!(f, &ctx, "{{")?;
+        for child in &self.body {
+            child.render(f, ctx.indented())?;
+        }
+        render_ln!(f, &ctx, "}}")
+    }
+}
+
+/// A try block with a finally block, both rendering their own Scope
+pub struct TryFinallyStatement {
+    pub body: Vec<Box<dyn AstNode>>,
+    pub finally: Vec<Box<dyn AstNode>>,
+}
+
+impl AstNode for TryFinallyStatement {
+    fn render(&self, f: &mut dyn io::Write, ctx: RenderContext) -> Result<(), io::Error> {
+        render_ln!(f, &ctx, "try")?;
+        render_ln!(f, &ctx, "{{")?;
+        for child in &self.body {
+            child.render(f, ctx.indented())?;
+        }
+        render_ln!(f, &ctx, "}}")?;
+        render_ln!(f, &ctx, "finally")?;
+        render_ln!(f, &ctx, "{{")?;
+        for child in &self.finally {
+            child.render(f, ctx.indented())?;
+        }
+        render_ln!(f, &ctx, "}}")
+    }
+}
+
+/// A single line guard clause, eg `if (foo is null) throw new ArgumentNullException(...);`
+pub struct ThrowIfStatement {
+    pub condition: Box<dyn AstNode>,
+    pub exception: Box<dyn AstNode>,
+}
+
+impl AstNode for ThrowIfStatement {
+    fn render(&self, f: &mut dyn io::Write, ctx: RenderContext) -> Result<(), io::Error> {
+        render_indent(f, &ctx)?;
+        write!(f, "if (")?;
+        self.condition.render(f, ctx)?;
+        write!(f, ") throw ")?;
+        self.exception.render(f, ctx)?;
+        writeln!(f, ";")
+    }
+}
+
+pub struct UncheckedStatement {}
+
+impl AstNode for UncheckedStatement {
+    fn render(&self, f: &mut dyn io::Write, ctx: RenderContext) -> Result<(), io::Error> {
+        render_ln!(f, &ctx, "unchecked")
+    }
+}
+
 pub struct Namespace {
     pub name: String,
     pub children: Vec<Box<dyn AstNode>>,
 }
 
 impl AstNode for Namespace {
+    fn structs_without_layout(&self) -> Vec<String> {
+        self.children.iter().flat_map(|c| c.structs_without_layout()).collect()
+    }
+
     fn render(&self, f: &mut dyn io::Write, ctx: RenderContext) -> Result<(), io::Error> {
         render_ln!(f, &ctx, "namespace {}", self.name)?;
         render_ln!(f, &ctx, "{{")?;
@@ -157,7 +336,7 @@ impl AstNode for Namespace {
         let mut first = true;
         for child in &self.children {
             if !first {
-                write!(f, "\n")?;
+                render_separator(f, &ctx)?;
             }
             first = false;
 
@@ -170,6 +349,24 @@ impl AstNode for Namespace {
     }
 }
 
+/// Only compiles the child when a preprocessor condition holds, eg `#if NET5_0_OR_GREATER`
+pub struct Conditional {
+    pub condition: String,
+    pub child: Box<dyn AstNode>,
+}
+
+impl AstNode for Conditional {
+    fn structs_without_layout(&self) -> Vec<String> {
+        self.child.structs_without_layout()
+    }
+
+    fn render(&self, f: &mut dyn io::Write, ctx: RenderContext) -> Result<(), io::Error> {
+        render_ln!(f, &ctx, "#if {}", self.condition)?;
+        self.child.render(f, ctx)?;
+        render_ln!(f, &ctx, "#endif")
+    }
+}
+
 #[derive(Clone, Debug)]
 pub enum CSharpType {
     Void,
@@ -186,12 +383,34 @@ pub enum CSharpType {
     UInt32,
     UInt64,
 
+    Single,
+    Double,
+
     Bool,
 
+    /// A UTF-16 code unit
+    Char,
+
+    String,
+
+    /// System.Object, the base of all types.
+    Object,
+
     Array {
         elem_type: Box<CSharpType>,
     },
 
+    /// A by-reference parameter type, eg `ref Foo` or `in Foo` when readonly.
+    Ref {
+        target: Box<CSharpType>,
+        is_readonly: bool,
+    },
+
+    /// System.Span<T>, a view over managed, stack, or native memory.
+    Span {
+        elem_type: Box<CSharpType>,
+    },
+
     Ptr {
         target: Box<CSharpType>,
     },
@@ -199,12 +418,55 @@ pub enum CSharpType {
     Struct {
         name: Ident,
     },
+
+    /// An unmanaged C# 9 function pointer, eg `delegate* unmanaged[Cdecl]<Int32, void>`.
+    FunctionPointer {
+        args: Vec<CSharpType>,
+        return_ty: Box<CSharpType>,
+    },
+
+    /// A System.ValueTuple, eg `(Int32[], Int32)`.
+    Tuple {
+        elem_types: Vec<CSharpType>,
+    },
+
+    /// A type outside of the generated namespace, referred to by its fully qualified path so that
+    /// it doesn't rely on a using statement, eg `System.Numerics.Vector3`.
+    Named {
+        path: String,
+    },
 }
 
 impl CSharpType {
     pub fn intptr() -> Self {
         Self::Struct { name: "IntPtr".into() }
     }
+
+    pub fn named(path: &str) -> Self {
+        Self::Named { path: path.to_string() }
+    }
+}
+
+impl CSharpType {
+    /// Whether this is a pointer type, which can only be named in an unsafe context.
+    pub fn is_pointer(&self) -> bool {
+        matches!(self, CSharpType::Ptr { .. } | CSharpType::FunctionPointer { .. })
+    }
+
+    /// The C# keyword for an integral type, eg `byte` for Byte.
+    pub fn integral_keyword(&self) -> Option<&'static str> {
+        match self {
+            CSharpType::SByte => Some("sbyte"),
+            CSharpType::Int16 => Some("short"),
+            CSharpType::Int32 => Some("int"),
+            CSharpType::Int64 => Some("long"),
+            CSharpType::Byte => Some("byte"),
+            CSharpType::UInt16 => Some("ushort"),
+            CSharpType::UInt32 => Some("uint"),
+            CSharpType::UInt64 => Some("ulong"),
+            _ => None,
+        }
+    }
 }
 
 impl fmt::Display for CSharpType {
@@ -219,10 +481,30 @@ impl fmt::Display for CSharpType {
             CSharpType::UInt16 => write!(f, "UInt16"),
             CSharpType::UInt32 => write!(f, "UInt32"),
             CSharpType::UInt64 => write!(f, "UInt64"),
+            CSharpType::Single => write!(f, "Single"),
+            CSharpType::Double => write!(f, "Double"),
             CSharpType::Bool => write!(f, "bool"),
+            CSharpType::Char => write!(f, "char"),
+            CSharpType::String => write!(f, "string"),
+            CSharpType::Object => write!(f, "object"),
             CSharpType::Array { elem_type } => write!(f, "{}[]", elem_type),
+            CSharpType::Span { elem_type } => write!(f, "Span<{}>", elem_type),
+            CSharpType::Ref { target, is_readonly: false } => write!(f, "ref {}", target),
+            CSharpType::Ref { target, is_readonly: true } => write!(f, "in {}", target),
             CSharpType::Ptr { target } => write!(f, "{}*", target),
             CSharpType::Struct { name } => write!(f, "{}", name),
+            CSharpType::Named { path } => write!(f, "{}", path),
+            CSharpType::Tuple { elem_types } => {
+                let elem_types: Vec<_> = elem_types.iter().map(ToString::to_string).collect();
+                write!(f, "({})", elem_types.join(", "))
+            }
+            CSharpType::FunctionPointer { args, return_ty } => {
+                write!(f, "delegate* unmanaged[Cdecl]<")?;
+                for arg in args {
+                    write!(f, "{}, ", arg)?;
+                }
+                write!(f, "{}>", return_ty)
+            }
         }
     }
 }
@@ -252,14 +534,64 @@ pub enum LiteralValue {
     QuotedString(String),
     EnumValue(String, String),
     Number(i64),
+    /// Rendered in hex, as it is typically a hash or bit pattern
+    UInt64(u64),
+    Bool(bool),
+    Null,
+    /// A `typeof` expression, eg `typeof(Int32[])`
+    TypeOf(CSharpType),
+    /// An implicitly typed array, eg `new[] { typeof(CallConvCdecl) }`
+    Array(Vec<LiteralValue>),
+    /// Written out as is, eg a float literal such as `1.5f`
+    Verbatim(String),
+}
+
+/// Escapes a string for use inside a regular C# string literal.
+///
+/// Anything outside of printable ASCII is written as `\u` escapes of its UTF-16 code units, so
+/// the literal means the same thing whatever encoding the source file is read as.
+fn escape_string(val: &str) -> String {
+    let mut escaped = String::with_capacity(val.len());
+    for c in val.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            ' '..='~' => escaped.push(c),
+            _ => {
+                for unit in c.encode_utf16(&mut [0; 2]) {
+                    escaped.push_str(&format!("\\u{:04X}", unit));
+                }
+            }
+        }
+    }
+
+    escaped
 }
 
 impl fmt::Display for LiteralValue {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            LiteralValue::QuotedString(val) => write!(f, "\"{}\"", val),
+            LiteralValue::QuotedString(val) => write!(f, "\"{}\"", escape_string(val)),
             LiteralValue::EnumValue(e, v) => write!(f, "{}.{}", e, v),
             LiteralValue::Number(num) => write!(f, "{}", num),
+            LiteralValue::UInt64(num) => write!(f, "0x{:016X}UL", num),
+            LiteralValue::Bool(b) => write!(f, "{}", b),
+            LiteralValue::Null => write!(f, "null"),
+            LiteralValue::TypeOf(ty) => write!(f, "typeof({})", ty),
+            LiteralValue::Verbatim(val) => write!(f, "{}", val),
+            LiteralValue::Array(values) => {
+                write!(f, "new[] {{ ")?;
+                for (idx, value) in values.iter().enumerate() {
+                    if idx > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", value)?;
+                }
+                write!(f, " }}")
+            }
         }
     }
 }
@@ -271,18 +603,96 @@ pub struct Attribute {
 }
 
 impl Attribute {
-    pub fn dll_import(binary: &str, entrypoint: &str) -> Self {
+    /// A DllImport attribute for a method named `method_name`.
+    ///
+    /// The EntryPoint is only given when it differs from the method name, as the runtime already
+    /// looks up a symbol with the same name as the method otherwise. The calling convention is
+    /// always given, as the runtime's default differs between platforms.
+    pub fn dll_import(
+        binary: &str,
+        method_name: &str,
+        entrypoint: &str,
+        calling_convention: &str,
+    ) -> Self {
+        let mut named_parameters = Vec::new();
+
+        if method_name != entrypoint {
+            named_parameters.push((
+                Ident("EntryPoint".to_string()),
+                LiteralValue::QuotedString(entrypoint.to_string()),
+            ));
+        }
+
+        named_parameters.push((
+            Ident("CallingConvention".to_string()),
+            LiteralValue::EnumValue("CallingConvention".to_string(), calling_convention.to_string()),
+        ));
+
         Self {
             name: "DllImport".to_string(),
             positional_parameters: vec![LiteralValue::QuotedString(binary.to_string())],
-            named_parameters: vec![(
+            named_parameters,
+        }
+    }
+
+    /// A LibraryImport attribute for a method named `method_name`, whose marshalling code is
+    /// generated at compile time by the net7.0 interop source generator.
+    ///
+    /// Unlike DllImport, it has no CallingConvention, which is given with `unmanaged_call_conv`.
+    pub fn library_import(binary: &str, method_name: &str, entrypoint: &str) -> Self {
+        let mut named_parameters = Vec::new();
+
+        if method_name != entrypoint {
+            named_parameters.push((
                 Ident("EntryPoint".to_string()),
                 LiteralValue::QuotedString(entrypoint.to_string()),
+            ));
+        }
+
+        Self {
+            name: "LibraryImport".to_string(),
+            positional_parameters: vec![LiteralValue::QuotedString(binary.to_string())],
+            named_parameters,
+        }
+    }
+
+    /// An UnmanagedCallConv attribute, giving the calling convention of a LibraryImport method.
+    ///
+    /// Takes the same names as DllImport's CallingConvention, eg `Cdecl`.
+    pub fn unmanaged_call_conv(calling_convention: &str) -> Self {
+        let call_conv = match calling_convention {
+            "StdCall" => "CallConvStdcall",
+            _ => "CallConvCdecl",
+        };
+
+        Self {
+            name: "UnmanagedCallConv".to_string(),
+            positional_parameters: Vec::new(),
+            named_parameters: vec![(
+                Ident("CallConvs".to_string()),
+                LiteralValue::Array(vec![LiteralValue::TypeOf(CSharpType::named(&format!(
+                    "System.Runtime.CompilerServices.{}",
+                    call_conv
+                )))]),
             )],
         }
     }
 
-    pub fn struct_layout(layout_kind: &str) -> Self {
+    /// An attribute with no parameters, eg `[In]`
+    pub fn marker(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            positional_parameters: Vec::new(),
+            named_parameters: Vec::new(),
+        }
+    }
+
+    /// A `[StructLayout(LayoutKind.X)]`, deliberately without a `Pack`.
+    ///
+    /// `Pack` caps the alignment of each field, rather than giving the alignment of the struct, and
+    /// the default already aligns fields naturally, exactly as `repr(C)` does. Packed Rust structs,
+    /// the only ones which would need it, are rejected by the macro.
+    pub fn struct_layout(layout_kind: LayoutKind) -> Self {
         Self {
             name: "StructLayout".to_string(),
             positional_parameters: vec![LiteralValue::EnumValue(
@@ -294,18 +704,35 @@ impl Attribute {
     }
 }
 
-impl AstNode for Attribute {
-    fn render(&self, f: &mut dyn io::Write, ctx: RenderContext) -> Result<(), io::Error> {
-        render_indent(f, &ctx)?;
-        write!(f, "[{}", self.name)?;
+/// The layouts a generated struct may be given.
+///
+/// `LayoutKind.Auto` is deliberately missing, as it lets the runtime reorder fields, which breaks
+/// blittability and so any struct passed across the P/Invoke boundary. `LayoutKind.Explicit` would
+/// need the offset of each field, which the descriptors don't carry.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LayoutKind {
+    Sequential,
+}
+
+impl fmt::Display for LayoutKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LayoutKind::Sequential => write!(f, "Sequential"),
+        }
+    }
+}
+
+impl Attribute {
+    /// Renders the attribute without the surrounding brackets, eg `DllImport("foo")`
+    fn render_contents(&self, f: &mut dyn io::Write) -> Result<(), io::Error> {
+        write!(f, "{}", self.name)?;
 
         if self.positional_parameters.len() + self.named_parameters.len() == 0 {
-            write!(f, "]\n")?;
             return Ok(());
-        } else {
-            write!(f, "(")?;
         }
 
+        write!(f, "(")?;
+
         let mut first = true;
         for param in &self.positional_parameters {
             if !first {
@@ -325,9 +752,30 @@ impl AstNode for Attribute {
             write!(f, "{} = {}", key, value)?;
         }
 
-        write!(f, ")]\n")?;
+        write!(f, ")")
+    }
+}
+
+impl AstNode for Attribute {
+    fn render(&self, f: &mut dyn io::Write, ctx: RenderContext) -> Result<(), io::Error> {
+        render_indent(f, &ctx)?;
+        write!(f, "[")?;
+        self.render_contents(f)?;
+        writeln!(f, "]")
+    }
+}
+
+/// An attribute applied to the whole module, eg `[module: SkipLocalsInit]`
+pub struct ModuleAttribute {
+    pub attribute: Attribute,
+}
 
-        Ok(())
+impl AstNode for ModuleAttribute {
+    fn render(&self, f: &mut dyn io::Write, ctx: RenderContext) -> Result<(), io::Error> {
+        render_indent(f, &ctx)?;
+        write!(f, "[module: ")?;
+        self.attribute.render_contents(f)?;
+        writeln!(f, "]")
     }
 }
 
@@ -339,7 +787,7 @@ impl AstNode for Statement {
     fn render(&self, f: &mut dyn io::Write, ctx: RenderContext) -> Result<(), io::Error> {
         render_indent(f, &ctx)?;
         self.expr.render(f, ctx)?;
-        write!(f, ";\n")
+        writeln!(f, ";")
     }
 }
 
@@ -370,34 +818,33 @@ impl fmt::Display for FieldAccess {
     }
 }
 
-pub struct IndexAccess {
-    pub element: Box<dyn AstNode>,
-    pub index: i32,
+/// A new array with the given elements, eg `new object[] { a, b }`
+pub struct ArrayInitializer {
+    pub elem_type: CSharpType,
+    pub elements: Vec<Box<dyn AstNode>>,
 }
 
-impl fmt::Display for IndexAccess {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let mut elem_render_buf: Vec<u8> = Vec::new();
-        self.element.render(&mut elem_render_buf, RenderContext::default())
-            .map_err(|_| fmt::Error)?;
-        let rendered_elem = std::str::from_utf8(&elem_render_buf).expect("Rendered to invalid utf8!");
-
-        write!(f, "({})[{}]", rendered_elem, self.index)
+impl AstNode for ArrayInitializer {
+    fn render(&self, f: &mut dyn io::Write, ctx: RenderContext) -> Result<(), io::Error> {
+        write!(f, "new {}[] {{", self.elem_type)?;
+        for (idx, element) in self.elements.iter().enumerate() {
+            write!(f, "{}", if idx > 0 { ", " } else { " " })?;
+            element.render(f, ctx)?;
+        }
+        write!(f, " }}")
     }
 }
 
-pub struct AddressOf {
-    pub element: Box<dyn AstNode>
+pub struct NewArray {
+    pub elem_type: CSharpType,
+    pub len: Box<dyn AstNode>,
 }
 
-impl fmt::Display for AddressOf {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let mut elem_render_buf: Vec<u8> = Vec::new();
-        self.element.render(&mut elem_render_buf, RenderContext::default())
-            .map_err(|_| fmt::Error)?;
-        let rendered_elem = std::str::from_utf8(&elem_render_buf).expect("Rendered to invalid utf8!");
-
-        write!(f, "&({})", rendered_elem)
+impl AstNode for NewArray {
+    fn render(&self, f: &mut dyn io::Write, ctx: RenderContext) -> Result<(), io::Error> {
+        write!(f, "new {}[", self.elem_type)?;
+        self.len.render(f, ctx)?;
+        write!(f, "]")
     }
 }
 
@@ -431,6 +878,50 @@ impl AstNode for BinaryExpression {
     }
 }
 
+pub struct UnaryExpression {
+    pub element: Box<dyn AstNode>,
+    pub operation_sym: &'static str,
+}
+
+impl AstNode for UnaryExpression {
+    fn render(&self, f: &mut dyn io::Write, ctx: RenderContext) -> Result<(), io::Error> {
+        write!(f, "{}(", self.operation_sym)?;
+        self.element.render(f, ctx)?;
+        write!(f, ")")
+    }
+}
+
+/// A lambda with implicitly typed arguments, eg `() => Foo(a)` or `x => x.Bar`
+pub struct Lambda {
+    pub args: Vec<Ident>,
+    pub body: Box<dyn AstNode>,
+}
+
+impl AstNode for Lambda {
+    fn render(&self, f: &mut dyn io::Write, ctx: RenderContext) -> Result<(), io::Error> {
+        match self.args.as_slice() {
+            [arg] => write!(f, "{} => ", arg)?,
+            args => {
+                let args: Vec<_> = args.iter().map(|a| a.to_string()).collect();
+                write!(f, "({}) => ", args.join(", "))?;
+            }
+        }
+        self.body.render(f, ctx)
+    }
+}
+
+/// An argument passed by reference, eg `ref foo`
+pub struct RefArgument {
+    pub element: Box<dyn AstNode>,
+}
+
+impl AstNode for RefArgument {
+    fn render(&self, f: &mut dyn io::Write, ctx: RenderContext) -> Result<(), io::Error> {
+        write!(f, "ref ")?;
+        self.element.render(f, ctx)
+    }
+}
+
 pub struct TernaryExpression {
     pub test: Box<dyn AstNode>,
     pub true_branch: Box<dyn AstNode>,
@@ -461,37 +952,60 @@ impl AstNode for FixedAssignment {
 
         write!(f, "fixed ({} {} = ", self.ty, self.id)?;
         self.rhs.render(f, ctx)?;
-        write!(f, ")\n")
+        writeln!(f, ")")
     }
 }
 
 pub struct MethodInvocation {
-    pub target: Option<Ident>,
+    pub target: Option<Box<dyn AstNode>>,
     pub method_name: Ident,
-    pub args: Vec<Ident>,
+    pub args: Vec<Box<dyn AstNode>>,
 }
 
-impl fmt::Display for MethodInvocation {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+impl AstNode for MethodInvocation {
+    fn render(&self, f: &mut dyn io::Write, ctx: RenderContext) -> Result<(), io::Error> {
         if let Some(t) = &self.target {
-            write!(f, "{}.", t)?;
+            t.render(f, ctx)?;
+            write!(f, ".")?;
         }
 
         write!(f, "{}(", self.method_name)?;
+        render_comma_separated(f, ctx, &self.args)?;
+        write!(f, ")")
+    }
+}
 
-        let mut first = true;
-        for arg in &self.args {
-            if !first {
-                write!(f, ", ")?;
-            }
-            first = false;
+pub struct NewObject {
+    pub ty: CSharpType,
+    pub args: Vec<Box<dyn AstNode>>,
+}
 
-            write!(f, "{}", arg)?;
-        }
+impl AstNode for NewObject {
+    fn render(&self, f: &mut dyn io::Write, ctx: RenderContext) -> Result<(), io::Error> {
+        write!(f, "new {}(", self.ty)?;
+        render_comma_separated(f, ctx, &self.args)?;
         write!(f, ")")
     }
 }
 
+fn render_comma_separated(
+    f: &mut dyn io::Write,
+    ctx: RenderContext,
+    nodes: &[Box<dyn AstNode>],
+) -> Result<(), io::Error> {
+    let mut first = true;
+    for node in nodes {
+        if !first {
+            write!(f, ", ")?;
+        }
+        first = false;
+
+        node.render(f, ctx)?;
+    }
+
+    Ok(())
+}
+
 pub struct ReturnStatement {
     pub value: Option<Box<dyn AstNode>>,
 }
@@ -503,7 +1017,7 @@ impl AstNode for ReturnStatement {
                 render_indent(f, &ctx)?;
                 write!(f, "return ")?;
                 v.render(f, ctx)?;
-                write!(f, ";\n")
+                writeln!(f, ";")
             }
             None => render_ln!(f, &ctx, "return;"),
         }
@@ -511,12 +1025,25 @@ impl AstNode for ReturnStatement {
 }
 
 pub struct MethodArgument {
+    /// Rendered inline before the argument, eg `[In, Out] byte[] buffer`
+    pub attributes: Vec<Attribute>,
     pub name: Ident,
     pub ty: CSharpType,
 }
 
 impl AstNode for MethodArgument {
     fn render(&self, f: &mut dyn io::Write, _ctx: RenderContext) -> Result<(), io::Error> {
+        if !self.attributes.is_empty() {
+            write!(f, "[")?;
+            for (idx, attribute) in self.attributes.iter().enumerate() {
+                if idx > 0 {
+                    write!(f, ", ")?;
+                }
+                attribute.render_contents(f)?;
+            }
+            write!(f, "] ")?;
+        }
+
         write!(f, "{} {}", self.ty, self.name)
     }
 }
@@ -527,6 +1054,13 @@ pub struct Method {
     pub is_static: bool,
     pub is_extern: bool,
     pub is_unsafe: bool,
+    /// Non-public overrides are `protected`, as C# doesn't allow private ones
+    pub is_override: bool,
+    /// A partial method declaration, which is implemented elsewhere, eg by a source generator.
+    ///
+    /// Those returning void compile to nothing unless implemented. These are implicitly private,
+    /// and must not be given an access modifier, whereas those returning a value must be given one.
+    pub is_partial: bool,
     pub name: String,
     pub return_ty: CSharpType,
     pub args: Vec<MethodArgument>,
@@ -542,7 +1076,9 @@ impl AstNode for Method {
         render_indent(f, &ctx)?;
         if self.is_public {
             write!(f, "public ")?;
-        } else {
+        } else if self.is_override {
+            write!(f, "protected ")?;
+        } else if !self.is_partial || !matches!(self.return_ty, CSharpType::Void) {
             write!(f, "private ")?;
         }
 
@@ -550,6 +1086,10 @@ impl AstNode for Method {
             write!(f, "static ")?;
         }
 
+        if self.is_partial {
+            write!(f, "partial ")?;
+        }
+
         if self.is_extern {
             write!(f, "extern ")?;
         }
@@ -558,6 +1098,10 @@ impl AstNode for Method {
             write!(f, "unsafe ")?;
         }
 
+        if self.is_override {
+            write!(f, "override ")?;
+        }
+
         write!(f, "{} {}(", self.return_ty, self.name)?;
 
         let mut first = true;
@@ -573,12 +1117,12 @@ impl AstNode for Method {
         let body = match &self.body {
             Some(b) => b,
             None => {
-                write!(f, ");\n")?;
+                writeln!(f, ");")?;
                 return Ok(());
             }
         };
 
-        write!(f, ")\n")?;
+        writeln!(f, ")")?;
         render_ln!(f, &ctx, "{{")?;
         for node in body {
             node.render(f, ctx.indented())?;
@@ -589,71 +1133,401 @@ impl AstNode for Method {
     }
 }
 
+pub enum ConstructorKind {
+    Instance,
+    /// An instance constructor which chains to the base class constructor, eg `: base(true)`
+    Base(Vec<LiteralValue>),
+    /// Static constructors take no access modifier and no arguments
+    Static,
+    /// A finalizer, eg `~Foo()`, which takes no access modifier and no arguments
+    Finalizer,
+}
+
+/// A constructor (or finalizer) for the object it is placed in.
+pub struct Constructor {
+    pub kind: ConstructorKind,
+    pub object_name: String,
+    pub args: Vec<MethodArgument>,
+    pub body: Vec<Box<dyn AstNode>>,
+}
+
+impl AstNode for Constructor {
+    fn render(&self, f: &mut dyn io::Write, ctx: RenderContext) -> Result<(), io::Error> {
+        render_indent(f, &ctx)?;
+        match self.kind {
+            ConstructorKind::Instance | ConstructorKind::Base(_) => write!(f, "public {}(", self.object_name)?,
+            ConstructorKind::Static => write!(f, "static {}(", self.object_name)?,
+            ConstructorKind::Finalizer => write!(f, "~{}(", self.object_name)?,
+        }
+
+        for (idx, arg) in self.args.iter().enumerate() {
+            if idx > 0 {
+                write!(f, ", ")?;
+            }
+            arg.render(f, ctx)?;
+        }
+        write!(f, ")")?;
+
+        if let ConstructorKind::Base(base_args) = &self.kind {
+            write!(f, " : base(")?;
+            for (idx, arg) in base_args.iter().enumerate() {
+                if idx > 0 {
+                    write!(f, ", ")?;
+                }
+                write!(f, "{}", arg)?;
+            }
+            write!(f, ")")?;
+        }
+
+        writeln!(f)?;
+        render_ln!(f, &ctx, "{{")?;
+        for node in &self.body {
+            node.render(f, ctx.indented())?;
+        }
+        render_ln!(f, &ctx, "}}")?;
+
+        Ok(())
+    }
+}
+
 pub struct Field {
+    pub is_public: bool,
+    pub is_readonly: bool,
     pub name: String,
     pub ty: CSharpType,
+
+    /// Makes this a `const` field with the given value
+    pub const_value: Option<LiteralValue>,
+
+    /// Makes this an inline `fixed` buffer of the given number of elements, eg `fixed byte Name[32]`
+    pub fixed_len: Option<u64>,
+
+    pub is_static: bool,
+
+    /// Initializes the field with the given expression, eg `= NativeLibrary.Load(...)`
+    pub initializer: Option<Box<dyn AstNode>>,
 }
 
 impl AstNode for Field {
     fn render(&self, f: &mut dyn io::Write, ctx: RenderContext) -> Result<(), io::Error> {
-        render_ln!(f, &ctx, "public {} {};", self.ty, self.name)
+        let visibility = if self.is_public { "public" } else { "private" };
+        if let Some(value) = &self.const_value {
+            return render_ln!(f, &ctx, "{} const {} {} = {};", visibility, self.ty, self.name, value);
+        }
+
+        if let Some(len) = self.fixed_len {
+            return render_ln!(f, &ctx, "{} unsafe fixed {} {}[{}];", visibility, self.ty, self.name, len);
+        }
+
+        let static_part = if self.is_static { "static " } else { "" };
+        let readonly_part = if self.is_readonly { "readonly " } else { "" };
+        // Pointer types can only be declared in an unsafe context
+        let unsafe_part = if self.ty.is_pointer() { "unsafe " } else { "" };
+        render_indent(f, &ctx)?;
+        write!(f, "{} {}{}{}{} {}", visibility, static_part, unsafe_part, readonly_part, self.ty, self.name)?;
+        if let Some(initializer) = &self.initializer {
+            write!(f, " = ")?;
+            initializer.render(f, ctx)?;
+        }
+        writeln!(f, ";")
     }
 }
 
+/// A get-only property, eg `public string Value { get { ... } }`
+pub struct Property {
+    pub is_unsafe: bool,
+    pub name: String,
+    pub ty: CSharpType,
+    pub getter: Vec<Box<dyn AstNode>>,
+
+    /// Only compiles the property when this preprocessor condition holds, eg `NET5_0_OR_GREATER`
+    pub condition: Option<String>,
+}
+
+impl AstNode for Property {
+    fn render(&self, f: &mut dyn io::Write, ctx: RenderContext) -> Result<(), io::Error> {
+        if let Some(condition) = &self.condition {
+            render_ln!(f, &ctx, "#if {}", condition)?;
+        }
+
+        let unsafe_part = if self.is_unsafe { "unsafe " } else { "" };
+        render_ln!(f, &ctx, "public {}{} {}", unsafe_part, self.ty, self.name)?;
+        render_ln!(f, &ctx, "{{")?;
+
+        let inner_ctx = ctx.indented();
+        render_ln!(f, &inner_ctx, "get")?;
+        render_ln!(f, &inner_ctx, "{{")?;
+        for node in &self.getter {
+            node.render(f, inner_ctx.indented())?;
+        }
+        render_ln!(f, &inner_ctx, "}}")?;
+
+        render_ln!(f, &ctx, "}}")?;
+
+        if self.condition.is_some() {
+            render_ln!(f, &ctx, "#endif")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
 pub enum ObjectType {
     Class,
     Struct,
+    /// A positional `record struct`, whose fields are rendered as the parameters of its primary
+    /// constructor, eg `record struct Point(Int32 X, Int32 Y)`
+    RecordStruct,
 }
 
 pub struct Object {
     pub attributes: Vec<Attribute>,
     pub object_type: ObjectType,
     pub is_static: bool,
+    pub is_sealed: bool,
+    pub is_readonly: bool,
+    /// Makes a struct a stack-only `ref struct`
+    pub is_ref: bool,
+    /// Makes the whole body an unsafe context, for objects with members using pointers
+    pub is_unsafe: bool,
+    /// Allows other parts of the object to be declared elsewhere, eg to implement partial methods
+    pub is_partial: bool,
+    /// Declares a nested object `private`, rather than with the visibility of the generated types
+    pub is_private: bool,
+    /// Declares the object `internal`, for helpers which aren't part of the bindings' API
+    pub is_internal: bool,
     pub name: String,
+    /// The base class and/or interfaces this object derives from.
+    pub base_types: Vec<CSharpType>,
+    pub constructors: Vec<Constructor>,
     pub methods: Vec<Method>,
     pub fields: Vec<Field>,
+    pub properties: Vec<Property>,
+    /// Objects declared inside this one, eg `static class Trig` inside `static class Math`
+    pub nested: Vec<Object>,
+}
+
+impl Object {
+    /// Whether any of this object's own members, not those of nested objects, can only be declared
+    /// in an unsafe context.
+    pub fn has_unsafe_member(&self) -> bool {
+        self.methods.iter().any(|m| m.is_unsafe)
+            || self.properties.iter().any(|p| p.is_unsafe)
+            || self.fields.iter().any(|f| f.ty.is_pointer() || f.fixed_len.is_some())
+    }
 }
 
 impl AstNode for Object {
+    fn structs_without_layout(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.nested.iter().flat_map(|o| o.structs_without_layout()).collect();
+        if self.object_type != ObjectType::Class && !self.attributes.iter().any(|a| a.name == "StructLayout") {
+            names.insert(0, self.name.clone());
+        }
+        names
+    }
+
     fn render(&self, f: &mut dyn io::Write, ctx: RenderContext) -> Result<(), io::Error> {
         for attr in &self.attributes {
             attr.render(f, ctx)?;
         }
 
         let static_part = if self.is_static { "static " } else { "" };
+        let sealed_part = if self.is_sealed { "sealed " } else { "" };
+        let readonly_part = if self.is_readonly { "readonly " } else { "" };
+        let unsafe_part = if self.is_unsafe { "unsafe " } else { "" };
+        let ref_part = if self.is_ref { "ref " } else { "" };
+        let partial_part = if self.is_partial { "partial " } else { "" };
         let object_type = match self.object_type {
             ObjectType::Class => "class ",
             ObjectType::Struct => "struct ",
+            ObjectType::RecordStruct => "record struct ",
         };
 
-        render_ln!(
+        render_indent(f, &ctx)?;
+        write!(
             f,
-            &ctx,
-            "public {}{}{}",
+            "{} {}{}{}{}{}{}{}{}",
+            if self.is_private {
+                "private".to_string()
+            } else if self.is_internal {
+                Visibility::Internal.to_string()
+            } else {
+                ctx.type_visibility.to_string()
+            },
             static_part,
+            sealed_part,
+            readonly_part,
+            unsafe_part,
+            ref_part,
+            partial_part,
             object_type,
-            self.name
+            self.name,
         )?;
+
+        let is_record = self.object_type == ObjectType::RecordStruct;
+        if is_record {
+            let parameters: Vec<_> = self.fields.iter().map(|field| format!("{} {}", field.ty, field.name)).collect();
+            write!(f, "({})", parameters.join(", "))?;
+        }
+
+        let mut first = true;
+        for base_type in &self.base_types {
+            write!(f, "{}{}", if first { " : " } else { ", " }, base_type)?;
+            first = false;
+        }
+        writeln!(f)?;
         render_ln!(f, &ctx, "{{")?;
 
         let mut first = true;
 
-        for field in &self.fields {
+        // A record struct's fields are declared by its parameters
+        if !is_record {
+            for field in &self.fields {
+                first = false;
+                field.render(f, ctx.indented())?;
+            }
+        }
+
+        for constructor in &self.constructors {
+            if !first {
+                render_separator(f, &ctx)?;
+            }
+            first = false;
+
+            constructor.render(f, ctx.indented())?;
+        }
+
+        for property in &self.properties {
+            if !first {
+                render_separator(f, &ctx)?;
+            }
             first = false;
-            field.render(f, ctx.indented())?;
+
+            property.render(f, ctx.indented())?;
         }
 
         for method in &self.methods {
             if !first {
-                write!(f, "\n")?;
+                render_separator(f, &ctx)?;
             }
             first = false;
 
             method.render(f, ctx.indented())?;
         }
 
+        for object in &self.nested {
+            if !first {
+                render_separator(f, &ctx)?;
+            }
+            first = false;
+
+            object.render(f, ctx.indented())?;
+        }
+
         render_ln!(f, &ctx, "}}")?;
 
         Ok(())
     }
 }
+
+pub struct EnumVariant {
+    pub name: String,
+    /// Wide enough for the discriminants of both `long` and `ulong` enums
+    pub value: i128,
+}
+
+/// An enum with explicit values for every variant, eg `public enum Color : byte { Red = 0 }`
+pub struct Enum {
+    pub name: String,
+    pub underlying_type: CSharpType,
+    pub variants: Vec<EnumVariant>,
+}
+
+impl AstNode for Enum {
+    fn render(&self, f: &mut dyn io::Write, ctx: RenderContext) -> Result<(), io::Error> {
+        // Older compilers only accept the keyword form of the underlying type, eg `byte` rather
+        // than `Byte`.
+        match self.underlying_type.integral_keyword() {
+            Some(keyword) => render_ln!(f, &ctx, "{} enum {} : {}", ctx.type_visibility, self.name, keyword)?,
+            None => render_ln!(f, &ctx, "{} enum {} : {}", ctx.type_visibility, self.name, self.underlying_type)?,
+        }
+        render_ln!(f, &ctx, "{{")?;
+
+        let inner_ctx = ctx.indented();
+        for variant in &self.variants {
+            render_ln!(f, &inner_ctx, "{} = {},", variant.name, variant.value)?;
+        }
+
+        render_ln!(f, &ctx, "}}")
+    }
+}
+
+/// An interface declaring the signatures of the given methods, whose bodies and modifiers are
+/// ignored.
+pub struct Interface {
+    pub name: String,
+    pub methods: Vec<Method>,
+}
+
+impl AstNode for Interface {
+    fn render(&self, f: &mut dyn io::Write, ctx: RenderContext) -> Result<(), io::Error> {
+        render_ln!(f, &ctx, "{} interface {}", ctx.type_visibility, self.name)?;
+        render_ln!(f, &ctx, "{{")?;
+
+        let inner_ctx = ctx.indented();
+        for method in &self.methods {
+            render_indent(f, &inner_ctx)?;
+            if method.is_unsafe {
+                write!(f, "unsafe ")?;
+            }
+            write!(f, "{} {}(", method.return_ty, method.name)?;
+
+            for (idx, arg) in method.args.iter().enumerate() {
+                if idx > 0 {
+                    write!(f, ", ")?;
+                }
+                arg.render(f, inner_ctx)?;
+            }
+
+            writeln!(f, ");")?;
+        }
+
+        render_ln!(f, &ctx, "}}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(node: &dyn AstNode) -> String {
+        let mut rendered = Vec::new();
+        node.render(&mut rendered, RenderContext::default()).unwrap();
+        String::from_utf8(rendered).unwrap()
+    }
+
+    #[test]
+    fn string_literals_are_escaped() {
+        let literal = LiteralValue::QuotedString("say \"hi\"\\ */\r\n\tcaf\u{e9} \u{1F980}\0".to_string());
+        assert_eq!(
+            literal.to_string(),
+            r#""say \"hi\"\\ */\r\n\tcaf\u00E9 \uD83E\uDD80\u0000""#,
+        );
+    }
+
+    #[test]
+    fn block_comments_stay_closed() {
+        let comment = BlockComment {
+            text: vec![
+                "say \"hi\"\\ caf\u{e9}".to_string(),
+                "ends */ early\nand wraps".to_string(),
+                String::new(),
+            ],
+        };
+        assert_eq!(
+            render(&comment),
+            "/*\n * say \"hi\"\\ caf\u{e9}\n * ends * / early\n * and wraps\n *\n */\n",
+        );
+    }
+}
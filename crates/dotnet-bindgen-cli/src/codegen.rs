@@ -1,9 +1,12 @@
+use std::collections::{BTreeSet, HashMap};
 use std::convert::{TryFrom, TryInto};
 
 use heck::{CamelCase, MixedCase};
 
 use crate::ast;
 use crate::data::BindgenData;
+use crate::diagnostics;
+use crate::error::BindgenError;
 use crate::path_ext::BinBaseName;
 
 use dotnet_bindgen_core as core;
@@ -52,6 +55,13 @@ impl BindingType {
             BindingType::Complex(c) => c.idiomatic_type.clone(),
         }
     }
+
+    fn descriptor(&self) -> Option<&core::BindgenTypeDescriptor> {
+        match self {
+            BindingType::Simple(s) => s.descriptor.as_ref(),
+            BindingType::Complex(c) => Some(&c.descriptor),
+        }
+    }
 }
 
 impl TryFrom<core::BindgenTypeDescriptor> for BindingType {
@@ -122,36 +132,224 @@ impl TryFrom<core::BindgenTypeDescriptor> for BindingType {
                 descriptor: Some(descriptor),
                 cs_type: CS::UInt64,
             }),
-            Desc::Slice { elem_type } => {
+            Desc::Int {
+                width: 128,
+                signed,
+            } => BindingType::Complex(ComplexBindingType {
+                thunk_type: CS::Struct {
+                    name: ast::Ident::new(INT128_ABI_STRUCT),
+                },
+                idiomatic_type: CS::Struct {
+                    name: ast::Ident::new(if *signed { "Int128" } else { "UInt128" }),
+                },
+                descriptor,
+            }),
+            Desc::Float { width: 32 } => BindingType::Simple(SimpleBindingType {
+                descriptor: Some(descriptor),
+                cs_type: CS::Single,
+            }),
+            Desc::Float { width: 64 } => BindingType::Simple(SimpleBindingType {
+                descriptor: Some(descriptor),
+                cs_type: CS::Double,
+            }),
+            // The System.Numerics vectors are blittable, with exactly the same layout as the
+            // ArrayAbi the array crosses the boundary as.
+            Desc::Array { elem_type, len } => {
+                let vector = match (&**elem_type, len) {
+                    (Desc::Float { width: 32 }, 2) => "System.Numerics.Vector2",
+                    (Desc::Float { width: 32 }, 3) => "System.Numerics.Vector3",
+                    (Desc::Float { width: 32 }, 4) => "System.Numerics.Vector4",
+                    _ => return Err("Can only generate code for arrays of 2, 3, or 4 f32s, which map to System.Numerics vectors"),
+                };
+
+                BindingType::Simple(SimpleBindingType {
+                    descriptor: Some(descriptor),
+                    cs_type: CS::named(vector),
+                })
+            },
+            Desc::Slice { elem_type }
+            | Desc::MutSlice { elem_type }
+            | Desc::UninitSlice { elem_type }
+            | Desc::OptionSlice { elem_type } => {
                 let elem_type = match BindingType::try_from(*elem_type.clone())? {
-                    BindingType::Simple(s) => s.cs_type,
+                    BindingType::Simple(s) => Box::new(s.cs_type),
                     BindingType::Complex(_) => {
                         return Err("Can't generate code for slices of non-trivial types yet")
                     }
                 };
 
+                // Uninitialized buffers are taken as a Span, so the caller can hand over pooled
+                // or stackalloc'd memory rather than a freshly zeroed array.
+                let idiomatic_type = match &descriptor {
+                    Desc::UninitSlice { .. } => CS::Span { elem_type },
+                    _ => CS::Array { elem_type },
+                };
+
                 BindingType::Complex(ComplexBindingType {
                     descriptor,
                     thunk_type: CS::Struct {
                         name: ast::Ident::new("SliceAbi"),
                     },
-                    idiomatic_type: CS::Array {
-                        elem_type: Box::new(elem_type),
+                    idiomatic_type,
+                })
+            },
+            Desc::Struct(s) if holds_buffers(s) => {
+                for field in s.fields.iter().filter(|f| is_buffer_field(&f.ty)) {
+                    BindingType::try_from(field.ty.clone())?;
+                }
+
+                BindingType::Complex(ComplexBindingType {
+                    thunk_type: CS::Struct {
+                        name: buffer_struct_abi_name(s).as_str().into(),
+                    },
+                    idiomatic_type: CS::Struct {
+                        name: ast::Ident::new(struct_cs_name(s)),
+                    },
+                    descriptor,
+                })
+            },
+            // Crosses the boundary as a blittable `...TupleAbi` struct, which the wrapper method
+            // copies into a ValueTuple.
+            Desc::Tuple { elements } => {
+                let elements = elements
+                    .iter()
+                    .map(|element| BindingType::try_from(element.clone()))
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                for element in &elements {
+                    match element {
+                        BindingType::Simple(s) if !s.cs_type.is_pointer() => (),
+                        BindingType::Complex(c) if is_buffer_field(&c.descriptor) => (),
+                        _ => return Err("Tuples can only hold integers, floats, enums, structs, slices, and strings"),
+                    }
+                }
+
+                BindingType::Complex(ComplexBindingType {
+                    thunk_type: CS::Struct {
+                        name: tuple_abi_name(&elements).as_str().into(),
+                    },
+                    idiomatic_type: CS::Tuple {
+                        elem_types: elements.iter().map(BindingType::idiomatic_type).collect(),
                     },
+                    descriptor,
                 })
             },
             Desc::Struct(s) => {
-                let name = ast::Ident::new(&s.name);
+                let name = ast::Ident::new(struct_cs_name(s));
                 BindingType::Simple(SimpleBindingType {
                     descriptor: Some(descriptor),
                     cs_type: CS::Struct { name }
                 })
             },
+            // Passed straight through as `in`/`ref` parameters, which the marshaller pins and hands
+            // over as a pointer to the caller's value.
+            Desc::Ref { target } | Desc::MutRef { target } => {
+                let target = match BindingType::try_from(*target.clone())? {
+                    BindingType::Simple(s) => Box::new(s.cs_type),
+                    BindingType::Complex(_) => {
+                        return Err("Can't generate code for references to non-trivial types yet")
+                    }
+                };
+
+                let cs_type = CS::Ref {
+                    target,
+                    is_readonly: matches!(descriptor, Desc::Ref { .. }),
+                };
+
+                BindingType::Complex(ComplexBindingType {
+                    descriptor,
+                    thunk_type: cs_type.clone(),
+                    idiomatic_type: cs_type,
+                })
+            },
             Desc::Bool => BindingType::Complex(ComplexBindingType {
                 descriptor,
                 thunk_type: CS::Byte,
                 idiomatic_type: CS::Bool,
             }),
+            Desc::Str => BindingType::Complex(ComplexBindingType {
+                descriptor,
+                thunk_type: CS::Struct {
+                    name: ast::Ident::new("SliceAbi"),
+                },
+                idiomatic_type: CS::String,
+            }),
+            // Pointers to opaque types are typed, so handles to different types can't be mixed up
+            Desc::Ptr { pointee: Some(pointee), .. } => match &**pointee {
+                Desc::Opaque { name } => BindingType::Simple(SimpleBindingType {
+                    cs_type: CS::Ptr {
+                        target: Box::new(CS::Struct { name: ast::Ident::new(name) }),
+                    },
+                    descriptor: Some(descriptor),
+                }),
+                _ => return Err("Only opaque types can be described as the target of a pointer"),
+            },
+            Desc::RawPtr | Desc::Ptr { .. } => BindingType::Simple(SimpleBindingType {
+                descriptor: Some(descriptor),
+                cs_type: CS::intptr(),
+            }),
+            Desc::Opaque { .. } => return Err("Opaque types can only be passed behind a pointer"),
+            // Unless generating C# 9 function pointers, see `function_pointer_type`.
+            Desc::FnPtr { .. } => BindingType::Simple(SimpleBindingType {
+                descriptor: Some(descriptor),
+                cs_type: CS::intptr(),
+            }),
+            Desc::Enum(e) => BindingType::Simple(SimpleBindingType {
+                cs_type: CS::Struct {
+                    name: ast::Ident::new(&e.name),
+                },
+                descriptor: Some(descriptor),
+            }),
+            Desc::OwnedSlice { elem_type } => {
+                let elem_type = match BindingType::try_from(*elem_type.clone())? {
+                    BindingType::Simple(s) => Box::new(s.cs_type),
+                    // Returned as bytes or owned strings, which are converted while being copied out
+                    BindingType::Complex(c) if matches!(**elem_type, Desc::Bool | Desc::OwnedStr) => {
+                        Box::new(c.idiomatic_type)
+                    }
+                    BindingType::Complex(_) => {
+                        return Err("Can't generate code for owned slices of non-trivial types yet")
+                    }
+                };
+
+                BindingType::Complex(ComplexBindingType {
+                    descriptor,
+                    thunk_type: CS::Struct {
+                        name: ast::Ident::new("OwnedSliceAbi"),
+                    },
+                    idiomatic_type: CS::Array { elem_type },
+                })
+            },
+            // Passed as a new reference from the handle, as the callee takes ownership of it.
+            // Returns are given straight to the P/Invoke marshaller instead, see BindingMethod::new.
+            Desc::Arc { name } => BindingType::Complex(ComplexBindingType {
+                thunk_type: CS::intptr(),
+                idiomatic_type: CS::Struct {
+                    name: handle_class_name(name).as_str().into(),
+                },
+                descriptor,
+            }),
+            Desc::CString => BindingType::Complex(ComplexBindingType {
+                descriptor,
+                thunk_type: CS::intptr(),
+                idiomatic_type: CS::String,
+            }),
+            Desc::OwnedStr => BindingType::Complex(ComplexBindingType {
+                descriptor,
+                thunk_type: CS::Struct {
+                    name: ast::Ident::new("OwnedSliceAbi"),
+                },
+                idiomatic_type: CS::String,
+            }),
+            Desc::Duration => BindingType::Complex(ComplexBindingType {
+                descriptor,
+                thunk_type: CS::Struct {
+                    name: ast::Ident::new("DurationAbi"),
+                },
+                idiomatic_type: CS::Struct {
+                    name: ast::Ident::new("TimeSpan"),
+                },
+            }),
             _ => return Err("Unrecognized type"),
         };
 
@@ -159,910 +357,7304 @@ impl TryFrom<core::BindgenTypeDescriptor> for BindingType {
     }
 }
 
-#[derive(Clone, Debug)]
-struct BindingMethodArgument {
-    ty: BindingType,
-    rust_name: String,
-    cs_name: String,
+/// Whether a string in the given encoding can be passed to an argument of the given type.
+fn string_encoding_fits(encoding: core::BindgenStringEncoding, ty: &core::BindgenTypeDescriptor) -> bool {
+    use core::BindgenStringEncoding as Enc;
+    use core::BindgenTypeDescriptor as Desc;
+
+    let slice_of = |width| matches!(
+        ty,
+        Desc::Slice { elem_type } if **elem_type == Desc::Int { width, signed: false }
+    );
+
+    match encoding {
+        Enc::Utf8 => *ty == Desc::Str || slice_of(8),
+        Enc::Utf16 => slice_of(16),
+        Enc::Ansi => slice_of(8),
+    }
 }
 
-impl TryFrom<core::BindgenFunctionArgumentDescriptor> for BindingMethodArgument {
-    type Error = &'static str;
+/// The name of the `[Conditional("DEBUG")]` validators of a wrapper method with `debug_only_validation`.
+fn validator_name(cs_name: &str) -> String {
+    format!("Validate{}Arguments", cs_name)
+}
 
-    fn try_from(descriptor: core::BindgenFunctionArgumentDescriptor) -> Result<Self, Self::Error> {
-        let ty = descriptor.ty.try_into()?;
-        let rust_name = descriptor.name.to_string();
-        let cs_name = descriptor.name.to_mixed_case();
-        Ok(Self {
-            ty,
-            rust_name,
-            cs_name,
-        })
+/// Calls the validator of a wrapper method, passing along the arguments it checks.
+fn validator_call(cs_name: &str, guarded: &[&BindingMethodArgument]) -> BodyElement {
+    BodyElement::MethodCall {
+        target: None,
+        method_name: validator_name(cs_name),
+        args: guarded
+            .iter()
+            .map(|arg| BodyElement::Ident(arg.cs_name.as_str().into()))
+            .collect(),
     }
 }
 
-impl BindingMethodArgument {
-    fn transform_body_fragment(&self) -> ArgTransformBodyFragment {
-        let (elements, output_ident) = match &self.ty {
-            BindingType::Simple(_) => (
-                Vec::new(),
-                AbstractIdent::Explicit(self.cs_name.to_string()),
-            ),
-            BindingType::Complex(complex_ty) => {
-                let elements = match &complex_ty.descriptor {
-                    core::BindgenTypeDescriptor::Bool => {
-                        let source_ident = Box::new(BodyElement::Ident(AbstractIdent::Explicit(
-                            self.cs_name.to_string(),
-                        )));
+/// Whether a default value can be passed to an argument of the given type, which for an integer
+/// means it must fit in its width.
+fn default_fits(default: &core::BindgenDefaultValue, ty: &core::BindgenTypeDescriptor) -> bool {
+    use core::BindgenDefaultValue as Value;
+    use core::BindgenTypeDescriptor as Desc;
 
-                        vec![
-                            BodyElement::DeclareLocal {
-                                id: AbstractIdent::Generated(0),
-                                ty: ast::CSharpType::Byte,
-                            },
-                            BodyElement::Assignment {
-                                lhs: Box::new(BodyElement::Ident(0.into())),
-                                rhs: Box::new(BodyElement::TernaryExpression {
-                                    test: source_ident.clone(),
-                                    true_branch: Box::new(
-                                        BodyElement::LiteralValue(LiteralValue::Number(1))
-                                    ),
-                                    false_branch: Box::new(
-                                        BodyElement::LiteralValue(LiteralValue::Number(0))
-                                    ),
-                                })
-                            },
-                        ]
-                    },
-                    core::BindgenTypeDescriptor::Slice { elem_type: _ } => {
-                        let elem_type = match &complex_ty.idiomatic_type {
-                            ast::CSharpType::Array { elem_type } => elem_type.clone(),
-                            _ => unreachable!(),
-                        };
+    match (default, ty) {
+        (Value::Bool(_), Desc::Bool) | (Value::Float(_), Desc::Float { .. }) | (Value::Str(_), Desc::Str) => true,
+        (Value::Int(i), Desc::Int { width, signed: true }) => {
+            let half = 1i128 << (width - 1);
+            (-half..half).contains(&(*i as i128))
+        }
+        (Value::UInt(u), Desc::Int { width, signed: false }) => (*u as u128) < 1u128 << width,
+        _ => false,
+    }
+}
 
-                        let source_ident = Box::new(BodyElement::Ident(AbstractIdent::Explicit(
-                            self.cs_name.to_string(),
-                        )));
+/// The `delegate* unmanaged[Cdecl]<...>` type a function pointer is bound as for C# 9 and up, in
+/// place of an IntPtr.
+///
+/// Its arguments and return value are called across the boundary exactly as the Rust function
+/// pointer sees them, so are given as their native types.
+fn function_pointer_type(descriptor: &core::BindgenTypeDescriptor) -> Result<Option<ast::CSharpType>, &'static str> {
+    let (args, return_ty) = match descriptor {
+        core::BindgenTypeDescriptor::FnPtr { args, return_ty } => (args, return_ty),
+        _ => return Ok(None),
+    };
 
-                        // TODO: The following is horrendous - replacing with a builder might help.
-                        // Eg, something like:
-                        //     let elements = ArgTransformFragmentBuilder::new()
-                        //        .declare_struct(0.into(), "SliceAbi")
-                        //        .assign_field_to_field(0.into(), "Len", self.cs_name.into(), "Length")
-                        //        .fixed_assign_arr_ptr(1.into(), self.cs_name)
-                        //        .build();
+    let native_type = |desc: &core::BindgenTypeDescriptor| -> Result<ast::CSharpType, &'static str> {
+        Ok(BindingType::try_from(desc.clone())?.native_type())
+    };
 
-                        vec![
-                            BodyElement::DeclareLocal {
-                                id: AbstractIdent::Generated(0),
-                                ty: ast::CSharpType::Struct {
-                                    name: "SliceAbi".into(),
-                                },
-                            },
-                            BodyElement::Assignment {
-                                lhs: Box::new(BodyElement::FieldAccess {
-                                    element: Box::new(BodyElement::Ident(0.into())),
-                                    field_name: "Len".to_string(),
-                                }),
-                                rhs: Box::new(BodyElement::Cast {
-                                    ty: ast::CSharpType::UInt64,
-                                    element: Box::new(BodyElement::FieldAccess {
-                                        element: source_ident.clone(),
-                                        field_name: "Length".to_string(),
-                                    }),
-                                })
-                            },
-                            BodyElement::Unsafe,
-                            BodyElement::FixedAssignment {
-                                ty: ast::CSharpType::Ptr {
-                                    target: Box::new((*elem_type.clone()).into()),
-                                },
-                                id: AbstractIdent::Generated(1),
-                                rhs: Box::new(BodyElement::AddressOf {
-                                    element: Box::new(BodyElement::IndexAccess {
-                                        element: source_ident.clone(),
-                                        index: 0,
-                                    }),
-                                }),
-                            },
-                            BodyElement::Assignment {
-                                lhs: Box::new(BodyElement::FieldAccess {
-                                    element: Box::new(BodyElement::Ident(0.into())),
-                                    field_name: "Ptr".to_string(),
-                                }),
-                                rhs: Box::new(BodyElement::Cast {
-                                    ty: ast::CSharpType::intptr(),
-                                    element: Box::new(BodyElement::Ident(1.into())),
-                                }),
-                            },
-                        ]
-                    }
+    Ok(Some(ast::CSharpType::FunctionPointer {
+        args: args.iter().map(native_type).collect::<Result<_, _>>()?,
+        return_ty: Box::new(native_type(return_ty)?),
+    }))
+}
 
-                    // Other descriptor types should fall under the Simple variant
-                    _ => unreachable!(),
-                };
+/// The partial method called on entry to each wrapper method, with `--trace-hooks`.
+const TRACE_ENTER_HOOK: &str = "OnNativeCallEnter";
 
-                (elements, AbstractIdent::Generated(0))
-            }
-        };
+/// The partial method called as each wrapper method returns, with `--trace-hooks`.
+const TRACE_EXIT_HOOK: &str = "OnNativeCallExit";
 
-        ArgTransformBodyFragment {
-            elements,
-            output_ident,
-        }
+/// Declarations of the partial trace hook methods, for each object holding wrapper methods.
+///
+/// The calls compile to nothing unless the user implements the hooks in another part of the
+/// object, eg
+/// ```csharp
+/// public static partial class TopLevelMethods
+/// {
+///     static partial void OnNativeCallEnter(string method, object[] args) =>
+///         Logger.LogTrace("Calling {Method}", method);
+/// }
+/// ```
+/// `public static void EnsureValidated() { }` in TopLevelMethods, which does nothing but make sure its
+/// static constructor has run the version and layout checks.
+fn ensure_validated_method() -> ast::Method {
+    ast::Method {
+        attributes: Vec::new(),
+        is_public: true,
+        is_static: true,
+        is_extern: false,
+        is_unsafe: false,
+        is_override: false,
+        is_partial: false,
+        name: "EnsureValidated".to_string(),
+        return_ty: ast::CSharpType::Void,
+        args: Vec::new(),
+        body: Some(Vec::new()),
     }
 }
 
-/// Abstract identifier for a variable, eventually resolved to a concrete ast::Ident.
-#[derive(Clone, Debug)]
-enum AbstractIdent {
-    Explicit(String),
-    Generated(u32),
+/// A static constructor calling `TopLevelMethods.EnsureValidated()`, for each other class calling
+/// into the library, as their calls don't otherwise run the static constructor of TopLevelMethods.
+fn ensure_validated_constructor(object_name: &str) -> ast::Constructor {
+    ast::Constructor {
+        kind: ast::ConstructorKind::Static,
+        object_name: object_name.to_string(),
+        args: Vec::new(),
+        body: BindingMethodBody {
+            body_elements: vec![BodyElement::MethodCall {
+                target: Some(Box::new(BodyElement::Ident("TopLevelMethods".into()))),
+                method_name: "EnsureValidated".to_string(),
+                args: Vec::new(),
+            }],
+        }.to_ast_nodes(),
+    }
 }
 
-impl From<u32> for AbstractIdent {
-    fn from(num: u32) -> Self {
-        AbstractIdent::Generated(num)
+fn trace_hook_declarations() -> Vec<ast::Method> {
+    let declaration = |name: &str, args: Vec<ast::MethodArgument>| ast::Method {
+        attributes: Vec::new(),
+        is_public: false,
+        is_static: true,
+        is_extern: false,
+        is_unsafe: false,
+        is_override: false,
+        is_partial: true,
+        name: name.to_string(),
+        return_ty: ast::CSharpType::Void,
+        args,
+        body: None,
+    };
+    let arg = |name: &str, ty| ast::MethodArgument {
+        attributes: Vec::new(),
+        name: name.into(),
+        ty,
+    };
+
+    vec![
+        declaration(TRACE_ENTER_HOOK, vec![
+            arg("method", ast::CSharpType::String),
+            arg("args", ast::CSharpType::Array { elem_type: Box::new(ast::CSharpType::Object) }),
+        ]),
+        declaration(TRACE_EXIT_HOOK, vec![
+            arg("method", ast::CSharpType::String),
+            arg("result", ast::CSharpType::Object),
+        ]),
+    ]
+}
+
+/// Whether an argument of the type is an enum, or a slice of them, whose discriminants the thunk
+/// checks.
+fn has_checked_discriminants(descriptor: &core::BindgenTypeDescriptor) -> bool {
+    match descriptor {
+        core::BindgenTypeDescriptor::Enum(_) => true,
+        core::BindgenTypeDescriptor::Slice { elem_type }
+        | core::BindgenTypeDescriptor::MutSlice { elem_type }
+        | core::BindgenTypeDescriptor::OptionSlice { elem_type } => {
+            matches!(**elem_type, core::BindgenTypeDescriptor::Enum(_))
+        }
+        _ => false,
     }
 }
 
-impl From<&str> for AbstractIdent {
-    fn from(name: &str) -> Self {
-        AbstractIdent::Explicit(name.to_string())
+/// Whether values of the type are allocated by Rust, and so must be handed back to be freed.
+///
+/// A struct or tuple holding an owned buffer is freed along with it.
+fn is_owned(descriptor: &core::BindgenTypeDescriptor) -> bool {
+    use core::BindgenTypeDescriptor as Desc;
+
+    match descriptor {
+        Desc::OwnedSlice { .. } | Desc::OwnedStr | Desc::CString => true,
+        Desc::Struct(s) => s.fields.iter().any(|f| matches!(f.ty, Desc::OwnedSlice { .. } | Desc::OwnedStr)),
+        Desc::Tuple { elements } => elements.iter().any(is_owned),
+        _ => false,
     }
 }
 
-impl AbstractIdent {
-    fn generated_id(&self) -> Option<u32> {
-        match self {
-            AbstractIdent::Explicit(_) => None,
-            AbstractIdent::Generated(x) => Some(*x),
+/// Whether values of the type stay owned by Rust when returned, and so are copied out by C#
+/// without being freed.
+fn is_borrowed_buffer(descriptor: &core::BindgenTypeDescriptor) -> bool {
+    match descriptor {
+        core::BindgenTypeDescriptor::Slice { .. } | core::BindgenTypeDescriptor::Str => true,
+        core::BindgenTypeDescriptor::Struct(s) => holds_buffers(s) && !is_owned(descriptor),
+        core::BindgenTypeDescriptor::Tuple { elements } => {
+            elements.iter().any(is_borrowed_buffer) && !is_owned(descriptor)
         }
+        _ => false,
     }
+}
 
-    fn apply_abstract_id_offset(&mut self, offset: u32) {
-        match self {
-            AbstractIdent::Explicit(_) => (),
-            AbstractIdent::Generated(x) => *x += offset,
-        };
-    }
+/// Whether a returned value is copied into a managed value by the wrapper method, rather than
+/// returned as the P/Invoke gave it.
+fn is_copied_out(descriptor: &core::BindgenTypeDescriptor) -> bool {
+    is_owned(descriptor)
+        || is_borrowed_buffer(descriptor)
+        || matches!(descriptor, core::BindgenTypeDescriptor::Tuple { .. })
+}
 
-    fn to_concrete_ident(&self) -> ast::Ident {
-        match self {
-            AbstractIdent::Explicit(name) => ast::Ident(
-                name.to_string()
-            ),
-            AbstractIdent::Generated(idx) => ast::Ident(
-                format!("_gen{}", idx)
-            ),
+/// Whether a struct field holds a slice or string, from a `SliceAbi`, `StrAbi`, `OwnedSliceAbi`,
+/// or `OwnedStrAbi`.
+fn is_buffer_field(descriptor: &core::BindgenTypeDescriptor) -> bool {
+    matches!(
+        descriptor,
+        core::BindgenTypeDescriptor::Slice { .. }
+            | core::BindgenTypeDescriptor::Str
+            | core::BindgenTypeDescriptor::OwnedSlice { .. }
+            | core::BindgenTypeDescriptor::OwnedStr
+    )
+}
+
+/// Whether a struct holds any slices or strings, in which case it can only be returned.
+///
+/// Such a struct is bound as a blittable `...Abi` struct, which the wrapper method copies into a
+/// struct of the original name holding managed arrays and strings.
+fn holds_buffers(descriptor: &core::BindgenStructDescriptor) -> bool {
+    descriptor.fields.iter().any(|f| is_buffer_field(&f.ty))
+}
+
+/// The name of the blittable struct a struct holding slices or strings crosses the boundary as.
+fn buffer_struct_abi_name(descriptor: &core::BindgenStructDescriptor) -> String {
+    format!("{}Abi", struct_cs_name(descriptor))
+}
+
+/// The name of the blittable struct a tuple of the given elements crosses the boundary as, eg
+/// `SliceAbiInt32TupleAbi` for a `(&[i32], i32)`.
+///
+/// Tuples of the same ABI types share the struct.
+fn tuple_abi_name(elements: &[BindingType]) -> String {
+    let mut name: String = elements
+        .iter()
+        .map(|element| element.native_type().to_string())
+        .collect::<String>()
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .collect();
+    name.push_str("TupleAbi");
+    name
+}
+
+/// Checks that a function's `returns_owned` or `returns_borrowed` annotation fits its return type.
+///
+/// Owned buffers and Arcs must be handed to C# to free, and borrowed buffers must not be, so
+/// neither is bound without saying which it is.
+fn check_return_ownership(descriptor: &core::BindgenFunctionDescriptor) -> Result<(), &'static str> {
+    use core::BindgenReturnOwnership as Ownership;
+
+    let ty = &descriptor.return_ty;
+    let must_be_owned = is_owned(ty) || matches!(ty, core::BindgenTypeDescriptor::Arc { .. });
+
+    match descriptor.return_ownership {
+        None if must_be_owned => Err("Functions returning an owned buffer or an Arc must be marked returns_owned"),
+        None if is_borrowed_buffer(ty) => Err("Functions returning a &str or a slice must be marked returns_borrowed"),
+        Some(Ownership::Borrowed) if must_be_owned => {
+            Err("Owned buffers and Arcs can't be returned borrowed, as nothing would free them")
         }
+        Some(Ownership::Owned) if is_borrowed_buffer(ty) => {
+            Err("A &str or a slice can't be returned owned, as C# has no way to free it")
+        }
+        _ => Ok(()),
     }
 }
 
-#[derive(Clone, Debug)]
-enum BinaryOperation {
-    NotEqual,
+/// The struct 128-bit integers cross the boundary as, split into two UInt64 halves.
+const INT128_ABI_STRUCT: &str = "Int128Abi";
+
+/// The name of the C# type a 128-bit integer is bound as, eg `UInt128` for a `u128`.
+fn int128_type_name(descriptor: &core::BindgenTypeDescriptor) -> Option<&'static str> {
+    match descriptor {
+        core::BindgenTypeDescriptor::Int { width: 128, signed: true } => Some("Int128"),
+        core::BindgenTypeDescriptor::Int { width: 128, signed: false } => Some("UInt128"),
+        _ => None,
+    }
 }
 
-impl BinaryOperation {
-    fn sym(&self) -> &'static str {
-        match self {
-            BinaryOperation::NotEqual => "!=",
+/// Whether any function takes or returns a 128-bit integer, which binds as an `Int128` or
+/// `UInt128` and so needs net7.0 or later.
+pub fn uses_int128(descriptors: &[core::BindgenExportDescriptor]) -> bool {
+    descriptors.iter().any(|descriptor| match descriptor {
+        core::BindgenExportDescriptor::Function(f) => f.arguments
+            .iter()
+            .map(|arg| &arg.ty)
+            .chain(std::iter::once(&f.return_ty))
+            .any(|ty| int128_type_name(ty).is_some()),
+        _ => false,
+    })
+}
+
+/// Whether any function is marked `no_gc_transition`, whose P/Invoke is then marked
+/// `[SuppressGCTransition]` and so needs net5.0 or later.
+pub fn uses_suppress_gc_transition(descriptors: &[core::BindgenExportDescriptor]) -> bool {
+    descriptors.iter().any(|descriptor| matches!(descriptor, core::BindgenExportDescriptor::Function(f) if f.no_gc_transition))
+}
+
+/// Whether any statics are exported, whose accessors look them up with `NativeLibrary` and so
+/// need net5.0 or later.
+pub fn uses_static_accessors(descriptors: &[core::BindgenExportDescriptor]) -> bool {
+    descriptors.iter().any(|descriptor| matches!(descriptor, core::BindgenExportDescriptor::Static(_)))
+}
+
+/// Whether any type anywhere in the descriptors, including those nested in other types, matches
+/// the predicate.
+fn uses_type(descriptors: &[core::BindgenExportDescriptor], predicate: &dyn Fn(&core::BindgenTypeDescriptor) -> bool) -> bool {
+    use core::BindgenTypeDescriptor as Desc;
+
+    fn visit(ty: &Desc, predicate: &dyn Fn(&Desc) -> bool) -> bool {
+        predicate(ty) || match ty {
+            Desc::Ptr { pointee: Some(target), .. }
+            | Desc::Ref { target }
+            | Desc::MutRef { target } => visit(target, predicate),
+            Desc::Slice { elem_type }
+            | Desc::MutSlice { elem_type }
+            | Desc::OptionSlice { elem_type }
+            | Desc::UninitSlice { elem_type }
+            | Desc::OwnedSlice { elem_type }
+            | Desc::Array { elem_type, .. } => visit(elem_type, predicate),
+            Desc::FnPtr { args, return_ty } => {
+                args.iter().any(|arg| visit(arg, predicate)) || visit(return_ty, predicate)
+            }
+            Desc::Tuple { elements } => elements.iter().any(|element| visit(element, predicate)),
+            Desc::Struct(s) => s.fields.iter().any(|field| visit(&field.ty, predicate)),
+            _ => false,
         }
     }
-}
 
-#[derive(Clone, Debug)]
-enum LiteralValue {
-    Number(i64),
+    descriptors.iter().any(|descriptor| match descriptor {
+        core::BindgenExportDescriptor::Function(f) => {
+            f.arguments.iter().any(|arg| visit(&arg.ty, predicate)) || visit(&f.return_ty, predicate)
+        }
+        core::BindgenExportDescriptor::Struct(s) => s.fields.iter().any(|field| visit(&field.ty, predicate)),
+        core::BindgenExportDescriptor::Static(s) => visit(&s.ty, predicate),
+        core::BindgenExportDescriptor::Enum(_) => false,
+    })
 }
 
-/// An abstract part of a method body, roughly mapping 1-1 with an ast element.
-#[derive(Clone, Debug)]
-enum BodyElement {
-    Ident(AbstractIdent),
-    /// Declares a new local variable of the given type.
-    DeclareLocal {
-        id: AbstractIdent,
-        ty: ast::CSharpType,
-    },
-    /// Just calls a method.
-    MethodCall {
-        method_name: String,
-        args: Vec<AbstractIdent>,
-    },
-    /// A field/property of a variable, eg `foo.Length`.
-    FieldAccess {
-        element: Box<BodyElement>,
-        field_name: String,
-    },
-    /// An index of some element, eg `foo[12]`.
-    IndexAccess {
-        element: Box<BodyElement>,
-        index: i32,
-    },
-    /// Takes the address of the given element
-    AddressOf {
-        element: Box<BodyElement>,
-    },
-    /// Casts a value to a given type
-    Cast {
-        ty: ast::CSharpType,
-        element: Box<BodyElement>,
-    },
-    Assignment {
-        lhs: Box<BodyElement>,
-        rhs: Box<BodyElement>,
-    },
-    /// Generates a fixed assignment, with subsequent operations inside its scope
-    FixedAssignment {
-        ty: ast::CSharpType,
-        id: AbstractIdent,
-        rhs: Box<BodyElement>,
-    },
-    /// Wraps all elements after it in the rendered AST in an unsafe block
-    Unsafe,
-    Return {
-        element: Option<Box<BodyElement>>,
-    },
-    /// A binary expression, eg `a != b`
-    BinaryExpression {
-        lhs: Box<BodyElement>,
-        rhs: Box<BodyElement>,
-        operation: BinaryOperation,
-    },
-    /// A ternary expression, eg `foo ? a : b`
-    TernaryExpression {
-        test: Box<BodyElement>,
-        true_branch: Box<BodyElement>,
-        false_branch: Box<BodyElement>,
-    },
-    LiteralValue(LiteralValue),
-}
+/// The names of the opaque types pointed to anywhere in the descriptors, in order.
+fn opaque_type_names(descriptors: &[core::BindgenExportDescriptor]) -> BTreeSet<String> {
+    use core::BindgenTypeDescriptor as Desc;
 
-impl BodyElement {
-    /// What is the maximum abstract identifier id in this element, if any are present.
-    fn max_abstract_id(&self) -> Option<u32> {
-        match self {
-            BodyElement::Ident(id) => id.generated_id(),
-            BodyElement::DeclareLocal { id, ty: _ } => id.generated_id(),
-            BodyElement::MethodCall {
-                method_name: _,
-                args,
-            } => args.iter().filter_map(|a| a.generated_id()).max(),
-            BodyElement::FieldAccess {
-                element,
-                field_name: _,
-            } => element.max_abstract_id(),
-            BodyElement::IndexAccess { element, index: _ } => element.max_abstract_id(),
-            BodyElement::AddressOf { element } => element.max_abstract_id(),
-            BodyElement::Cast { ty: _, element } => element.max_abstract_id(),
-            BodyElement::Assignment { lhs, rhs } => {
-                [lhs, rhs].iter().filter_map(|a| a.max_abstract_id()).max()
+    fn visit(ty: &Desc, names: &mut BTreeSet<String>) {
+        match ty {
+            Desc::Opaque { name } => {
+                names.insert(name.clone());
             }
-            BodyElement::FixedAssignment { ty: _, id, rhs } => {
-                [id.generated_id(), rhs.max_abstract_id()]
-                    .iter()
-                    .filter(|a| a.is_some())
-                    .map(|a| a.unwrap())
-                    .max()
-            },
-            BodyElement::Unsafe => None,
-            BodyElement::Return { element: Some(element) } => element.max_abstract_id(),
-            BodyElement::Return { element: None } => None,
-            BodyElement::BinaryExpression { lhs, rhs, operation: _ } => {
-                [lhs, rhs].iter().filter_map(|a| a.max_abstract_id()).max()
-            },
-            BodyElement::TernaryExpression { test, true_branch, false_branch } => {
-                [test, true_branch, false_branch].iter().filter_map(|a| a.max_abstract_id()).max()
-            },
-            BodyElement::LiteralValue {..} => None,
+            Desc::Ptr { pointee: Some(target), .. }
+            | Desc::Ref { target }
+            | Desc::MutRef { target } => visit(target, names),
+            Desc::Slice { elem_type }
+            | Desc::MutSlice { elem_type }
+            | Desc::OptionSlice { elem_type }
+            | Desc::UninitSlice { elem_type }
+            | Desc::OwnedSlice { elem_type }
+            | Desc::Array { elem_type, .. } => visit(elem_type, names),
+            Desc::FnPtr { args, return_ty } => {
+                args.iter().for_each(|arg| visit(arg, names));
+                visit(return_ty, names);
+            }
+            Desc::Struct(s) => s.fields.iter().for_each(|field| visit(&field.ty, names)),
+            _ => (),
         }
     }
 
-    fn apply_abstract_id_offset(&mut self, offset: u32) {
-        match self {
-            BodyElement::Ident(id) => id.apply_abstract_id_offset(offset),
-            BodyElement::DeclareLocal { id, ty: _ } => id.apply_abstract_id_offset(offset),
-            BodyElement::MethodCall {
-                method_name: _,
-                args,
-            } => {
-                for arg in args.iter_mut() {
-                    arg.apply_abstract_id_offset(offset);
-                }
-            }
-            BodyElement::FieldAccess {
-                element,
-                field_name: _,
-            } => element.apply_abstract_id_offset(offset),
-            BodyElement::IndexAccess { element, index: _ } => {
-                element.apply_abstract_id_offset(offset)
+    let mut names = BTreeSet::new();
+    for descriptor in descriptors {
+        match descriptor {
+            core::BindgenExportDescriptor::Function(f) => {
+                f.arguments.iter().for_each(|arg| visit(&arg.ty, &mut names));
+                visit(&f.return_ty, &mut names);
             }
-            BodyElement::AddressOf { element } => element.apply_abstract_id_offset(offset),
-            BodyElement::Cast { ty: _, element } => element.apply_abstract_id_offset(offset),
-            BodyElement::Assignment { lhs, rhs } => {
-                lhs.apply_abstract_id_offset(offset);
-                rhs.apply_abstract_id_offset(offset);
+            core::BindgenExportDescriptor::Struct(s) => {
+                s.fields.iter().for_each(|field| visit(&field.ty, &mut names));
             }
-            BodyElement::FixedAssignment { ty: _, id, rhs } => {
-                id.apply_abstract_id_offset(offset);
-                rhs.apply_abstract_id_offset(offset);
-            },
-            BodyElement::Unsafe => (),
-            BodyElement::Return { element: Some(element) } => element.apply_abstract_id_offset(offset),
-            BodyElement::Return { element: None } => (),
-            BodyElement::BinaryExpression { lhs, rhs, operation: _ } => {
-                lhs.apply_abstract_id_offset(offset);
-                rhs.apply_abstract_id_offset(offset);
-            },
-            BodyElement::TernaryExpression { test, true_branch, false_branch } => {
-                test.apply_abstract_id_offset(offset);
-                true_branch.apply_abstract_id_offset(offset);
-                false_branch.apply_abstract_id_offset(offset);
-            },
-            BodyElement::LiteralValue {..} => (),
+            core::BindgenExportDescriptor::Static(s) => visit(&s.ty, &mut names),
+            core::BindgenExportDescriptor::Enum(_) => (),
         }
     }
 
-    fn requires_new_scope(&self) -> bool {
-        match self {
-            BodyElement::Ident (_) => false,
-            BodyElement::DeclareLocal {..} => false,
-            BodyElement::MethodCall {..} => false,
-            BodyElement::FieldAccess {..} => false,
-            BodyElement::IndexAccess {..} => false,
-            BodyElement::AddressOf {..} => false,
-            BodyElement::Cast {..} => false,
-            BodyElement::Assignment {..} => false,
-            BodyElement::FixedAssignment {..} => true,
-            BodyElement::Unsafe => true,
-            BodyElement::Return{..} => false,
-            BodyElement::BinaryExpression{..} => false,
-            BodyElement::LiteralValue {..} => false,
-            BodyElement::TernaryExpression {..} => false,
-        }
-    }
+    names
+}
 
-    fn is_top_level(&self) -> bool {
-        match self {
-            BodyElement::Ident (_) => false,
-            BodyElement::DeclareLocal {..} => true,
-            BodyElement::MethodCall {..} => false,
-            BodyElement::FieldAccess {..} => false,
-            BodyElement::IndexAccess {..} => false,
-            BodyElement::AddressOf {..} => false,
-            BodyElement::Cast {..} => false,
-            BodyElement::Assignment {..} => false,
-            BodyElement::FixedAssignment {..} => true,
-            BodyElement::Unsafe => true,
-            BodyElement::Return{..} => true,
-            BodyElement::BinaryExpression{..} => false,
-            BodyElement::LiteralValue {..} => false,
-            BodyElement::TernaryExpression {..} => false,
-        }
+/// An empty struct standing in for an opaque Rust type, so that pointers to it are typed.
+///
+/// Values of it never exist in C#, only pointers to the Rust values.
+fn opaque_struct_obj(name: &str) -> ast::Object {
+    ast::Object {
+        attributes: vec![ast::Attribute::struct_layout(ast::LayoutKind::Sequential)],
+        object_type: ast::ObjectType::Struct,
+        is_static: false,
+        is_sealed: false,
+        is_readonly: false,
+        is_ref: false,
+        is_unsafe: false,
+        is_partial: false,
+        is_private: false,
+        is_internal: false,
+        name: name.to_string(),
+        base_types: Vec::new(),
+        constructors: Vec::new(),
+        methods: Vec::new(),
+        fields: Vec::new(),
+        properties: Vec::new(),
+        nested: Vec::new(),
     }
+}
 
-    fn to_ast_node(&self) -> Box<dyn ast::AstNode> {
-        match self {
-            BodyElement::Ident(id) => Box::new(id.to_concrete_ident()),
-            BodyElement::DeclareLocal { id, ty } => Box::new(
-                ast::VariableDeclaration {
-                    name: id.to_concrete_ident(),
-                    ty: ty.clone()
-                }
-            ),
-            BodyElement::MethodCall { method_name, args } => {
-                let args = args.iter()
-                    .map(|a| a.to_concrete_ident())
-                    .collect();
-                Box::new(
-                    ast::MethodInvocation {
-                        target: None,
-                        method_name: ast::Ident(method_name.to_string()),
-                        args,
-                    }
-                )
-            },
-            BodyElement::FieldAccess { element, field_name } => Box::new(
-                ast::FieldAccess {
-                    element: element.to_ast_node(),
-                    field_name: ast::Ident(field_name.to_string()),
-                }
-            ),
-            BodyElement::IndexAccess { element, index } => Box::new(
-                ast::IndexAccess {
-                    element: element.to_ast_node(),
-                    index: *index,
-                }
-            ),
-            BodyElement::AddressOf { element } => Box::new(
-                ast::AddressOf {
-                    element: element.to_ast_node(),
-                }
-            ),
-            BodyElement::Cast { ty, element } => Box::new(
-                ast::Cast {
-                    ty: ty.clone(),
-                    element: element.to_ast_node(),
-                }
-            ),
-            BodyElement::Assignment { lhs, rhs } => Box::new(
-                ast::BinaryExpression {
-                    lhs: lhs.to_ast_node(),
-                    rhs: rhs.to_ast_node(),
-                    operation_sym: "=",
-                }
-            ),
-            BodyElement::FixedAssignment { ty, id, rhs } => Box::new(
-                ast::FixedAssignment {
-                    ty: ty.clone(),
-                    id: id.to_concrete_ident(),
-                    rhs: rhs.to_ast_node(),
-                }
-            ),
-            BodyElement::Unsafe => Box::new(
-                ast::UnsafeStatement {}
-            ),
-            BodyElement::Return { element } => {
-                Box::new(ast::ReturnStatement {
-                    value: match element {
-                        Some(element) => Some(element.to_ast_node()),
-                        None => None,
-                    }
-                })
+/// The class C# allocates a buffer with the Rust allocator through, to hand over to a function
+/// taking a `Vec<u8>`.
+const RUST_BUFFER_CLASS: &str = "RustBuffer";
+
+/// The helper class Stream companions read their arguments into a rented buffer through.
+const POOLED_STREAM_CLASS: &str = "PooledStream";
+
+/// The pool the buffers Stream companions read into are rented from.
+const SHARED_BYTE_POOL: &str = "System.Buffers.ArrayPool<Byte>.Shared";
+
+/// Whether an argument of the type takes ownership of a RustBuffer.
+fn takes_rust_buffer(descriptor: &core::BindgenTypeDescriptor) -> bool {
+    use core::BindgenTypeDescriptor as Desc;
+
+    matches!(descriptor, Desc::OwnedSlice { elem_type } if **elem_type == Desc::Int { width: 8, signed: false })
+}
+
+/// Names a `module = "..."` class mustn't take. The generated code refers to the BCL types and
+/// namespaces here without qualifying them, and a class of the same name in the bindings'
+/// namespace would be found first. The rest are the classes generated next to the module classes.
+const RESERVED_MODULE_NAMES: &[&str] = &[
+    "System", "Microsoft",
+    "SByte", "Int16", "Int32", "Int64", "Int128", "Byte", "UInt16", "UInt32", "UInt64", "UInt128",
+    "Single", "Double", "IntPtr", "UIntPtr", "TimeSpan", "Action",
+    "Array", "Span", "ReadOnlySpan", "GC", "IDisposable", "IEquatable",
+    "ArgumentException", "ArgumentNullException", "InvalidOperationException",
+    "Marshal", "MemoryMarshal", "NativeLibrary", "CallingConvention", "LayoutKind",
+    "DllImport", "LibraryImport", "StructLayout", "UnmanagedCallConv", "SuppressGCTransition",
+    "TopLevelMethods", "SliceAbi", "OwnedSliceAbi", "DurationAbi", "SliceView",
+    RUST_BUFFER_CLASS, POOLED_STREAM_CLASS, NATIVE_SLICE_CLASS,
+];
+
+/// The suffix of the private structs nested in TopLevelMethods, whose `Value` field is offset by
+/// the alignment of the bound struct of the same name, eg `PointAlignmentProbe`.
+const ALIGNMENT_PROBE_SUFFIX: &str = "AlignmentProbe";
+
+/// The largest alignment .NET gives a struct, whatever its fields.
+const MAX_MANAGED_STRUCT_ALIGN: u64 = 8;
+
+/// Refuses functions taking or returning slices of structs aligned to more than the runtime
+/// aligns array elements to, which is the pointer width of the binary.
+///
+/// A pinned array of eg a `#[repr(C, align(16))]` struct can't be handed to native code that
+/// assumes the elements are aligned, eg for SIMD loads.
+fn check_slice_alignment(
+    descriptor: &core::BindgenFunctionDescriptor,
+    pointer_width: u64,
+) -> Result<(), BindgenError> {
+    use core::BindgenTypeDescriptor as Desc;
+
+    fn overaligned_elem(ty: &Desc, max_align: u64) -> Option<(&str, u64)> {
+        match ty {
+            Desc::Slice { elem_type }
+            | Desc::MutSlice { elem_type }
+            | Desc::UninitSlice { elem_type }
+            | Desc::OptionSlice { elem_type } => match &**elem_type {
+                Desc::Struct(s) => s.align.filter(|&align| align > max_align).map(|align| (s.name.as_str(), align)),
+                _ => None,
             },
-            BodyElement::BinaryExpression { lhs, rhs, operation } => Box::new(
-                ast::BinaryExpression {
-                    lhs: lhs.to_ast_node(),
-                    rhs: rhs.to_ast_node(),
-                    operation_sym: operation.sym(),
-                }
-            ),
-            BodyElement::LiteralValue(val) => Box::new(
-                match val {
-                    LiteralValue::Number(num) => ast::LiteralValue::Number(*num),
-                }
-            ),
-            BodyElement::TernaryExpression { test, true_branch, false_branch } => Box::new(
-                ast::TernaryExpression {
-                    test: test.to_ast_node(),
-                    true_branch: true_branch.to_ast_node(),
-                    false_branch: false_branch.to_ast_node(),
-                }
-            )
+            Desc::Tuple { elements } => elements.iter().find_map(|e| overaligned_elem(e, max_align)),
+            _ => None,
         }
     }
+
+    let overaligned = descriptor.arguments
+        .iter()
+        .map(|arg| &arg.ty)
+        .chain(std::iter::once(&descriptor.return_ty))
+        .find_map(|ty| overaligned_elem(ty, pointer_width));
+
+    match overaligned {
+        Some((name, align)) => Err(BindgenError::OveralignedSlice {
+            function: descriptor.real_name.clone(),
+            name: name.to_string(),
+            align,
+            max_align: pointer_width,
+        }),
+        None => Ok(()),
+    }
 }
 
-/// Represents a single part of method body, responsible for converting idiomatic C# types to their
-/// underlying FFI stable equivalents.
+/// Warns about structs aligned to more than 8 bytes which are passed or returned by value.
 ///
-/// Instances of this struct for types which are already FFI stable will look something like:
-/// ```
-/// #let arg_name = "foo".to_string();
-/// let frag = ArgTransformBodyElement {
-///     elements: Vec::new(),
-///     output_ident: AbstractIdent::Explicit(arg_name)
-/// };
-/// ```
+/// By-value structs are otherwise passed the same way by the thunk and the P/Invoke on every
+/// platform, however many there are, as both follow the platform's C ABI. But the runtime doesn't
+/// honour alignments above 8 bytes, so the copy it passes, eg on the stack for SysV or behind a
+/// pointer for win-x64, can be misaligned for native code relying on the alignment.
+fn warn_overaligned_by_value_structs(descriptor: &core::BindgenFunctionDescriptor) {
+    let overaligned = |ty: &core::BindgenTypeDescriptor| match ty {
+        core::BindgenTypeDescriptor::Struct(s) => match s.align {
+            Some(align) if align > MAX_MANAGED_STRUCT_ALIGN => Some((s.name.clone(), align)),
+            _ => None,
+        },
+        _ => None,
+    };
+
+    for arg in &descriptor.arguments {
+        if let Some((name, align)) = overaligned(&arg.ty) {
+            diagnostics::warn(&format!(
+                "{} takes {} by value, which is aligned to {} bytes, but .NET only aligns \
+                 by-value structs to 8. Consider taking it by reference, as &{}",
+                descriptor.real_name, name, align, name,
+            ));
+        }
+    }
+
+    if let Some((name, align)) = overaligned(&descriptor.return_ty) {
+        diagnostics::warn(&format!(
+            "{} returns {} by value, which is aligned to {} bytes, but .NET only aligns \
+             by-value structs to 8. Consider writing it through a &mut {} argument instead",
+            descriptor.real_name, name, align, name,
+        ));
+    }
+}
+
 #[derive(Clone, Debug)]
-struct ArgTransformBodyFragment {
-    elements: Vec<BodyElement>,
-    output_ident: AbstractIdent,
+struct BindingMethodArgument {
+    ty: BindingType,
+    rust_name: String,
+    cs_name: String,
+
+    /// The encoding a string passed to the argument is converted to, if it takes a string.
+    encoding: Option<core::BindgenStringEncoding>,
+
+    /// The custom marshaller the argument is handed to the P/Invoke through, in place of being
+    /// marshalled by the wrapper method.
+    marshaller: Option<CustomMarshaller>,
+
+    /// The value passed by the overloads of the wrapper method which omit the argument.
+    default: Option<core::BindgenDefaultValue>,
 }
 
-impl ArgTransformBodyFragment {
-    fn max_abstract_id(&self) -> Option<u32> {
-        let max = self
-            .elements
-            .iter()
-            .filter_map(|e| e.max_abstract_id())
-            .max();
+impl TryFrom<core::BindgenFunctionArgumentDescriptor> for BindingMethodArgument {
+    type Error = &'static str;
+
+    fn try_from(descriptor: core::BindgenFunctionArgumentDescriptor) -> Result<Self, Self::Error> {
+        if matches!(&descriptor.ty, core::BindgenTypeDescriptor::Struct(s) if holds_buffers(s)) {
+            return Err("Structs holding slices or strings can only be returned, not taken as arguments");
+        }
+        if let core::BindgenTypeDescriptor::Tuple { .. } = descriptor.ty {
+            return Err("Tuples can only be returned, not taken as arguments");
+        }
+        if is_owned(&descriptor.ty) && !takes_rust_buffer(&descriptor.ty) {
+            return Err("Owned buffers can only be returned, not taken as arguments, other than a Vec<u8>");
+        }
+        if let Some(default) = &descriptor.default {
+            if !default_fits(default, &descriptor.ty) {
+                return Err("An argument's default value must be a literal of its type, which must be an integer, float, bool, or &str");
+            }
+        }
+
+        let encoding = match (descriptor.encoding, &descriptor.ty) {
+            (None, core::BindgenTypeDescriptor::Str) => Some(core::BindgenStringEncoding::Utf8),
+            (None, _) => None,
+            (Some(encoding), ty) => {
+                if !string_encoding_fits(encoding, ty) {
+                    return Err("A string argument's type must be &str or &[u8] for utf8, &[u16] for utf16, or &[u8] for ansi");
+                }
+                Some(encoding)
+            }
+        };
+
+        let ty = match encoding {
+            Some(_) => BindingType::Complex(ComplexBindingType {
+                descriptor: descriptor.ty,
+                thunk_type: ast::CSharpType::Struct {
+                    name: ast::Ident::new("SliceAbi"),
+                },
+                idiomatic_type: ast::CSharpType::String,
+            }),
+            None if takes_rust_buffer(&descriptor.ty) => BindingType::Complex(ComplexBindingType {
+                descriptor: descriptor.ty,
+                thunk_type: ast::CSharpType::Struct {
+                    name: ast::Ident::new("OwnedSliceAbi"),
+                },
+                idiomatic_type: ast::CSharpType::Struct {
+                    name: ast::Ident::new(RUST_BUFFER_CLASS),
+                },
+            }),
+            None => descriptor.ty.try_into()?,
+        };
+        let rust_name = descriptor.name.to_string();
+        let cs_name = descriptor.name.to_mixed_case();
+        Ok(Self {
+            ty,
+            rust_name,
+            cs_name,
+            encoding,
+            marshaller: None,
+            default: descriptor.default,
+        })
+    }
+}
+
+impl BindingMethodArgument {
+    /// The C# literal of the argument's default value, if it has one.
+    fn default_literal(&self) -> Option<LiteralValue> {
+        use core::BindgenDefaultValue as Value;
+
+        let literal = match self.default.as_ref()? {
+            Value::Bool(b) => LiteralValue::Bool(*b),
+            Value::Int(i) => LiteralValue::Number(*i),
+            Value::UInt(u) => LiteralValue::Verbatim(u.to_string()),
+            Value::Float(digits) => {
+                // Rust allows `1.`, which C# doesn't, and a double won't implicitly convert to a float.
+                let mut digits = digits.to_string();
+                if digits.ends_with('.') {
+                    digits.push('0');
+                }
+                if self.ty.descriptor() == Some(&core::BindgenTypeDescriptor::Float { width: 32 }) {
+                    digits.push('f');
+                }
+                LiteralValue::Verbatim(digits)
+            }
+            Value::Str(s) => LiteralValue::String(s.to_string()),
+        };
+
+        Some(literal)
+    }
+
+    /// Whether a Stream companion of the method takes a Stream in place of this argument, which is
+    /// the case for any `&[u8]`.
+    fn is_stream_source(&self) -> bool {
+        let byte_slice = core::BindgenTypeDescriptor::Slice {
+            elem_type: Box::new(core::BindgenTypeDescriptor::Int { width: 8, signed: false }),
+        };
+
+        self.ty.descriptor() == Some(&byte_slice)
+    }
+
+    /// Throws an ArgumentNullException if the argument is null, for arguments taking a C# reference
+    /// type which the native side can't accept as null.
+    ///
+    /// Arguments from an `Option<&[T]>` take null as None, so aren't guarded.
+    fn null_guard(&self) -> Option<BodyElement> {
+        if let ast::CSharpType::Span { .. } = self.ty.idiomatic_type() {
+            return None;
+        }
+
+        match self.ty.descriptor()? {
+            core::BindgenTypeDescriptor::Slice { .. }
+            | core::BindgenTypeDescriptor::MutSlice { .. }
+            | core::BindgenTypeDescriptor::Str
+            | core::BindgenTypeDescriptor::Arc { .. } => (),
+            _ => return None,
+        }
+
+        let name = Box::new(BodyElement::Ident(AbstractIdent::Explicit(self.cs_name.to_string())));
+
+        Some(BodyElement::ThrowIf {
+            condition: Box::new(BodyElement::BinaryExpression {
+                lhs: name.clone(),
+                rhs: Box::new(BodyElement::LiteralValue(LiteralValue::Null)),
+                operation: BinaryOperation::Is,
+            }),
+            exception: Box::new(BodyElement::NewObject {
+                ty: ast::CSharpType::named("ArgumentNullException"),
+                args: vec![BodyElement::MethodCall {
+                    target: None,
+                    method_name: "nameof".to_string(),
+                    args: vec![*name],
+                }],
+            }),
+        })
+    }
+
+    /// Body elements which encode the string argument, and describe the result with a SliceAbi.
+    fn encoded_string_elements(
+        &self,
+        encoding: core::BindgenStringEncoding,
+        slice_views: bool,
+    ) -> Vec<BodyElement> {
+        let source_ident = BodyElement::Ident(AbstractIdent::Explicit(
+            self.cs_name.to_string(),
+        ));
+
+        let encoder = match encoding {
+            // C# strings are already UTF-16, so can be pinned and passed along as is.
+            core::BindgenStringEncoding::Utf16 => {
+                return pinned_slice_abi_elements(
+                    Box::new(source_ident),
+                    ast::CSharpType::Char,
+                    0,
+                    1,
+                    false,
+                    slice_views,
+                );
+            }
+
+            core::BindgenStringEncoding::Utf8 => strict_utf8_encoding(),
+
+            // NB: Outside of .NET Framework, code pages other than UTF-8 are only available once
+            // CodePagesEncodingProvider has been registered.
+            core::BindgenStringEncoding::Ansi => BodyElement::MethodCall {
+                target: Some(Box::new(BodyElement::Ident("System.Text.Encoding".into()))),
+                method_name: "GetEncoding".to_string(),
+                args: vec![BodyElement::Ident(
+                    "System.Globalization.CultureInfo.CurrentCulture.TextInfo.ANSICodePage".into(),
+                )],
+            },
+        };
+
+        let mut elements = vec![
+            BodyElement::DeclareLocal {
+                id: AbstractIdent::Generated(1),
+                ty: ast::CSharpType::Array {
+                    elem_type: Box::new(ast::CSharpType::Byte),
+                },
+            },
+            BodyElement::Assignment {
+                lhs: Box::new(BodyElement::Ident(1.into())),
+                rhs: Box::new(BodyElement::MethodCall {
+                    target: Some(Box::new(encoder)),
+                    method_name: "GetBytes".to_string(),
+                    args: vec![source_ident],
+                }),
+            },
+        ];
+
+        elements.extend(pinned_slice_abi_elements(
+            Box::new(BodyElement::Ident(1.into())),
+            ast::CSharpType::Byte,
+            0,
+            2,
+            false,
+            slice_views,
+        ));
+
+        elements
+    }
+
+    fn transform_body_fragment(&self, options: &CodegenOptions) -> ArgTransformBodyFragment {
+        if self.marshaller.is_some() {
+            return ArgTransformBodyFragment {
+                elements: Vec::new(),
+                output_ident: AbstractIdent::Explicit(self.cs_name.to_string()),
+            };
+        }
+
+        if let Some(encoding) = self.encoding {
+            return ArgTransformBodyFragment {
+                elements: self.encoded_string_elements(encoding, options.slice_views),
+                output_ident: AbstractIdent::Generated(0),
+            };
+        }
+
+        let (elements, output_ident) = match &self.ty {
+            BindingType::Simple(_) => (
+                Vec::new(),
+                AbstractIdent::Explicit(self.cs_name.to_string()),
+            ),
+            // Passed through unchanged, with the `ref` added at the call site
+            BindingType::Complex(ComplexBindingType {
+                descriptor: core::BindgenTypeDescriptor::Ref { .. }
+                | core::BindgenTypeDescriptor::MutRef { .. },
+                ..
+            }) => (
+                Vec::new(),
+                AbstractIdent::Explicit(self.cs_name.to_string()),
+            ),
+            BindingType::Complex(complex_ty) => {
+                let elements = match &complex_ty.descriptor {
+                    core::BindgenTypeDescriptor::Bool => {
+                        let source_ident = Box::new(BodyElement::Ident(AbstractIdent::Explicit(
+                            self.cs_name.to_string(),
+                        )));
+
+                        vec![
+                            BodyElement::DeclareLocal {
+                                id: AbstractIdent::Generated(0),
+                                ty: ast::CSharpType::Byte,
+                            },
+                            BodyElement::Assignment {
+                                lhs: Box::new(BodyElement::Ident(0.into())),
+                                rhs: Box::new(BodyElement::TernaryExpression {
+                                    test: source_ident.clone(),
+                                    true_branch: Box::new(
+                                        BodyElement::LiteralValue(LiteralValue::Number(1))
+                                    ),
+                                    false_branch: Box::new(
+                                        BodyElement::LiteralValue(LiteralValue::Number(0))
+                                    ),
+                                })
+                            },
+                        ]
+                    },
+                    core::BindgenTypeDescriptor::Slice { .. }
+                    | core::BindgenTypeDescriptor::MutSlice { .. }
+                    | core::BindgenTypeDescriptor::UninitSlice { .. }
+                    | core::BindgenTypeDescriptor::OptionSlice { .. } => {
+                        let elem_type = match &complex_ty.idiomatic_type {
+                            ast::CSharpType::Array { elem_type }
+                            | ast::CSharpType::Span { elem_type } => elem_type.clone(),
+                            _ => unreachable!(),
+                        };
+
+                        let source_ident = Box::new(BodyElement::Ident(AbstractIdent::Explicit(
+                            self.cs_name.to_string(),
+                        )));
+
+                        let nullable = matches!(
+                            complex_ty.descriptor,
+                            core::BindgenTypeDescriptor::OptionSlice { .. }
+                        );
+
+                        pinned_slice_abi_elements(
+                            source_ident,
+                            *elem_type,
+                            0,
+                            1,
+                            nullable,
+                            options.slice_views,
+                        )
+                    }
+                    core::BindgenTypeDescriptor::Arc { .. } => vec![
+                        BodyElement::DeclareLocal {
+                            id: AbstractIdent::Generated(0),
+                            ty: ast::CSharpType::intptr(),
+                        },
+                        BodyElement::Assignment {
+                            lhs: Box::new(BodyElement::Ident(0.into())),
+                            rhs: Box::new(BodyElement::MethodCall {
+                                target: Some(Box::new(BodyElement::Ident(AbstractIdent::Explicit(
+                                    self.cs_name.to_string(),
+                                )))),
+                                method_name: "DangerousCloneRaw".to_string(),
+                                args: Vec::new(),
+                            }),
+                        },
+                    ],
+                    // The callee takes ownership of the buffer, leaving the RustBuffer empty.
+                    core::BindgenTypeDescriptor::OwnedSlice { .. } => vec![
+                        BodyElement::DeclareLocal {
+                            id: AbstractIdent::Generated(0),
+                            ty: ast::CSharpType::Struct {
+                                name: "OwnedSliceAbi".into(),
+                            },
+                        },
+                        BodyElement::Assignment {
+                            lhs: Box::new(BodyElement::Ident(0.into())),
+                            rhs: Box::new(BodyElement::MethodCall {
+                                target: Some(Box::new(BodyElement::Ident(AbstractIdent::Explicit(
+                                    self.cs_name.to_string(),
+                                )))),
+                                method_name: "Transfer".to_string(),
+                                args: Vec::new(),
+                            }),
+                        },
+                    ],
+                    core::BindgenTypeDescriptor::Int { width: 128, .. } => vec![
+                        BodyElement::DeclareLocal {
+                            id: AbstractIdent::Generated(0),
+                            ty: ast::CSharpType::Struct {
+                                name: INT128_ABI_STRUCT.into(),
+                            },
+                        },
+                        BodyElement::Assignment {
+                            lhs: Box::new(BodyElement::Ident(0.into())),
+                            rhs: Box::new(BodyElement::MethodCall {
+                                target: Some(Box::new(BodyElement::Ident(INT128_ABI_STRUCT.into()))),
+                                method_name: format!("From{}", int128_type_name(&complex_ty.descriptor).unwrap()),
+                                args: vec![BodyElement::Ident(AbstractIdent::Explicit(
+                                    self.cs_name.to_string(),
+                                ))],
+                            }),
+                        },
+                    ],
+                    core::BindgenTypeDescriptor::Duration => vec![
+                        BodyElement::DeclareLocal {
+                            id: AbstractIdent::Generated(0),
+                            ty: ast::CSharpType::Struct {
+                                name: "DurationAbi".into(),
+                            },
+                        },
+                        BodyElement::Assignment {
+                            lhs: Box::new(BodyElement::Ident(0.into())),
+                            rhs: Box::new(BodyElement::MethodCall {
+                                target: Some(Box::new(BodyElement::Ident("DurationAbi".into()))),
+                                method_name: "FromTimeSpan".to_string(),
+                                args: vec![BodyElement::Ident(AbstractIdent::Explicit(
+                                    self.cs_name.to_string(),
+                                ))],
+                            }),
+                        },
+                    ],
+
+                    // Other descriptor types should fall under the Simple variant
+                    _ => unreachable!(),
+                };
+
+                (elements, AbstractIdent::Generated(0))
+            }
+        };
+
+        ArgTransformBodyFragment {
+            elements,
+            output_ident,
+        }
+    }
+}
+
+/// Body elements which pin a managed array or span, and describe it with a SliceAbi local.
+///
+/// All elements following these will be inside the scope of the pinning `fixed` statement.
+///
+/// With `slice_views`, the SliceAbi is only ever built from a SliceView declared inside that
+/// scope. As SliceView is a ref struct, the compiler then rejects any attempt to stash the pinned
+/// pointer somewhere that outlives the pin.
+///
+/// A `nullable` source may be null, which is described with a null pointer. As an empty array is
+/// also pinned as a null pointer, it's described with a dangling one instead so the two can be
+/// told apart.
+fn pinned_slice_abi_elements(
+    source: Box<BodyElement>,
+    elem_type: ast::CSharpType,
+    abi_id: u32,
+    ptr_id: u32,
+    nullable: bool,
+    slice_views: bool,
+) -> Vec<BodyElement> {
+    let null_test = |operation| BodyElement::BinaryExpression {
+        lhs: source.clone(),
+        rhs: Box::new(BodyElement::LiteralValue(LiteralValue::Null)),
+        operation,
+    };
+
+    let mut length = BodyElement::FieldAccess {
+        element: source.clone(),
+        field_name: "Length".to_string(),
+    };
+    let mut ptr = BodyElement::Cast {
+        ty: ast::CSharpType::intptr(),
+        element: Box::new(BodyElement::Ident(ptr_id.into())),
+    };
+
+    if nullable {
+        length = BodyElement::TernaryExpression {
+            test: Box::new(null_test(BinaryOperation::Equal)),
+            true_branch: Box::new(BodyElement::LiteralValue(LiteralValue::Number(0))),
+            false_branch: Box::new(length),
+        };
+        ptr = BodyElement::TernaryExpression {
+            test: Box::new(BodyElement::BinaryExpression {
+                lhs: Box::new(null_test(BinaryOperation::NotEqual)),
+                rhs: Box::new(BodyElement::BinaryExpression {
+                    lhs: Box::new(BodyElement::Ident(ptr_id.into())),
+                    rhs: Box::new(BodyElement::LiteralValue(LiteralValue::Null)),
+                    operation: BinaryOperation::Equal,
+                }),
+                operation: BinaryOperation::And,
+            }),
+            true_branch: Box::new(BodyElement::NewObject {
+                ty: ast::CSharpType::intptr(),
+                args: vec![BodyElement::LiteralValue(LiteralValue::Number(1))],
+            }),
+            false_branch: Box::new(ptr),
+        };
+    }
+
+    if slice_views {
+        let view = BodyElement::NewObject {
+            ty: ast::CSharpType::Struct {
+                name: "SliceView".into(),
+            },
+            args: vec![ptr, length],
+        };
+
+        return vec![
+            BodyElement::Unsafe,
+            BodyElement::FixedAssignment {
+                ty: ast::CSharpType::Ptr {
+                    target: Box::new(elem_type),
+                },
+                id: ptr_id.into(),
+                rhs: source,
+            },
+            BodyElement::DeclareLocal {
+                id: abi_id.into(),
+                ty: ast::CSharpType::Struct {
+                    name: "SliceAbi".into(),
+                },
+            },
+            BodyElement::Assignment {
+                lhs: Box::new(BodyElement::Ident(abi_id.into())),
+                rhs: Box::new(BodyElement::MethodCall {
+                    target: Some(Box::new(view)),
+                    method_name: "ToAbi".to_string(),
+                    args: Vec::new(),
+                }),
+            },
+        ];
+    }
+
+    // TODO: The following is horrendous - replacing with a builder might help.
+    // Eg, something like:
+    //     let elements = ArgTransformFragmentBuilder::new()
+    //        .declare_struct(0.into(), "SliceAbi")
+    //        .assign_field_to_field(0.into(), "Len", self.cs_name.into(), "Length")
+    //        .fixed_assign_arr_ptr(1.into(), self.cs_name)
+    //        .build();
+    //
+    // Pinning the array itself rather than its first element means empty arrays are pinned as a
+    // null pointer instead of throwing.
+
+    vec![
+        BodyElement::DeclareLocal {
+            id: abi_id.into(),
+            ty: ast::CSharpType::Struct {
+                name: "SliceAbi".into(),
+            },
+        },
+        BodyElement::Assignment {
+            lhs: Box::new(BodyElement::FieldAccess {
+                element: Box::new(BodyElement::Ident(abi_id.into())),
+                field_name: "Len".to_string(),
+            }),
+            rhs: Box::new(BodyElement::Cast {
+                ty: ast::CSharpType::UInt64,
+                element: Box::new(length),
+            })
+        },
+        BodyElement::Unsafe,
+        BodyElement::FixedAssignment {
+            ty: ast::CSharpType::Ptr {
+                target: Box::new(elem_type),
+            },
+            id: ptr_id.into(),
+            rhs: source,
+        },
+        BodyElement::Assignment {
+            lhs: Box::new(BodyElement::FieldAccess {
+                element: Box::new(BodyElement::Ident(abi_id.into())),
+                field_name: "Ptr".to_string(),
+            }),
+            rhs: Box::new(ptr),
+        },
+    ]
+}
 
-        match self.output_ident.generated_id() {
-            Some(id) => assert!(id <= max.unwrap()),
+/// The UTF-8 encoder strings are converted with.
+///
+/// A strict encoder throws on lone surrogates, rather than silently substituting them.
+fn strict_utf8_encoding() -> BodyElement {
+    BodyElement::NewObject {
+        ty: ast::CSharpType::named("System.Text.UTF8Encoding"),
+        args: vec![
+            BodyElement::LiteralValue(LiteralValue::Bool(false)),
+            BodyElement::LiteralValue(LiteralValue::Bool(true)),
+        ],
+    }
+}
+
+/// A generated custom marshaller, which an argument is handed to a `[LibraryImport]` P/Invoke
+/// through with `custom_marshallers`.
+///
+/// Each marshaller pins a managed array for the duration of the call, and describes it with a
+/// SliceAbi, just as the hand written `fixed` blocks would.
+#[derive(Clone, Debug)]
+struct CustomMarshaller {
+    /// The name of the marshaller class, eg `Int32SliceMarshaller`
+    name: String,
+
+    /// The managed type the marshaller takes, eg `Int32[]`
+    managed_type: ast::CSharpType,
+
+    /// The element type of the pinned array. Strings are encoded into an array of bytes first.
+    elem_type: ast::CSharpType,
+}
+
+impl CustomMarshaller {
+    /// The marshaller for an argument, if it has one.
+    ///
+    /// Strings are only given one for UTF-8, and slices only when they're always given as an
+    /// array. Other arguments are still marshalled by the wrapper method.
+    fn for_argument(arg: &BindingMethodArgument) -> Option<Self> {
+        match arg.encoding {
+            Some(core::BindgenStringEncoding::Utf8) => {
+                return Some(Self {
+                    name: "Utf8StrMarshaller".to_string(),
+                    managed_type: ast::CSharpType::String,
+                    elem_type: ast::CSharpType::Byte,
+                })
+            }
+            Some(_) => return None,
             None => (),
         }
 
-        max
+        match arg.ty.descriptor()? {
+            core::BindgenTypeDescriptor::Slice { .. } | core::BindgenTypeDescriptor::MutSlice { .. } => (),
+            _ => return None,
+        }
+
+        let managed_type = arg.ty.idiomatic_type();
+        let elem_type = match &managed_type {
+            ast::CSharpType::Array { elem_type } => (**elem_type).clone(),
+            _ => return None,
+        };
+
+        // Eg `System.Numerics.Vector3` -> `Vector3SliceMarshaller`
+        let elem_name = elem_type.to_string();
+        let elem_name = elem_name.rsplit('.').next().unwrap();
+
+        Some(Self {
+            name: format!("{}SliceMarshaller", elem_name),
+            managed_type,
+            elem_type,
+        })
     }
 
-    fn apply_abstract_id_offset(&mut self, offset: u32) {
-        for el in self.elements.iter_mut() {
-            el.apply_abstract_id_offset(offset);
+    /// The attribute handing a P/Invoke argument to this marshaller.
+    fn marshal_using_attribute(&self) -> ast::Attribute {
+        ast::Attribute {
+            name: "System.Runtime.InteropServices.Marshalling.MarshalUsing".to_string(),
+            positional_parameters: vec![ast::LiteralValue::TypeOf(ast::CSharpType::Struct {
+                name: self.name.as_str().into(),
+            })],
+            named_parameters: Vec::new(),
+        }
+    }
+
+    /// The marshaller class, with a stateful `ManagedToUnmanagedIn` shape whose pinnable
+    /// reference is pinned by the generated P/Invoke before `ToUnmanaged` is called.
+    fn to_ast_object(&self) -> ast::Object {
+        let this_field = |name: &str| BodyElement::FieldAccess {
+            element: Box::new(BodyElement::Ident("this".into())),
+            field_name: name.to_string(),
+        };
+        let abi_field = |name: &str| BodyElement::FieldAccess {
+            element: Box::new(BodyElement::Ident(0.into())),
+            field_name: name.to_string(),
+        };
+        let method = |name: &str, is_unsafe, return_ty, args, body_elements| ast::Method {
+            attributes: Vec::new(),
+            is_public: true,
+            is_static: false,
+            is_extern: false,
+            is_unsafe,
+            is_override: false,
+            is_partial: false,
+            name: name.to_string(),
+            return_ty,
+            args,
+            body: Some(BindingMethodBody { body_elements }.to_ast_nodes()),
+        };
+        let slice_abi = ast::CSharpType::Struct {
+            name: "SliceAbi".into(),
+        };
+
+        let managed = BodyElement::Ident("managed".into());
+        let array = match self.managed_type {
+            ast::CSharpType::String => BodyElement::MethodCall {
+                target: Some(Box::new(strict_utf8_encoding())),
+                method_name: "GetBytes".to_string(),
+                args: vec![managed],
+            },
+            _ => managed,
+        };
+
+        let from_managed = method(
+            "FromManaged",
+            false,
+            ast::CSharpType::Void,
+            vec![ast::MethodArgument {
+                attributes: Vec::new(),
+                name: "managed".into(),
+                ty: self.managed_type.clone(),
+            }],
+            vec![BodyElement::Assignment {
+                lhs: Box::new(this_field("array")),
+                rhs: Box::new(array),
+            }],
+        );
+
+        // Unlike pinning the array itself, this gives a valid pointer for an empty array too.
+        let get_pinnable_reference = method(
+            "GetPinnableReference",
+            false,
+            ast::CSharpType::Ref {
+                target: Box::new(self.elem_type.clone()),
+                is_readonly: false,
+            },
+            Vec::new(),
+            vec![BodyElement::Return {
+                element: Some(Box::new(BodyElement::Ref {
+                    element: Box::new(BodyElement::MethodCall {
+                        target: Some(Box::new(BodyElement::Ident("MemoryMarshal".into()))),
+                        method_name: "GetArrayDataReference".to_string(),
+                        args: vec![this_field("array")],
+                    }),
+                })),
+            }],
+        );
+
+        let to_unmanaged = method(
+            "ToUnmanaged",
+            true,
+            slice_abi.clone(),
+            Vec::new(),
+            vec![
+                BodyElement::DeclareLocal {
+                    id: 0.into(),
+                    ty: slice_abi,
+                },
+                BodyElement::Assignment {
+                    lhs: Box::new(abi_field("Ptr")),
+                    rhs: Box::new(BodyElement::Cast {
+                        ty: ast::CSharpType::intptr(),
+                        element: Box::new(BodyElement::MethodCall {
+                            target: Some(Box::new(BodyElement::Ident(
+                                "System.Runtime.CompilerServices.Unsafe".into(),
+                            ))),
+                            method_name: "AsPointer".to_string(),
+                            args: vec![BodyElement::Ref {
+                                element: Box::new(BodyElement::MethodCall {
+                                    target: Some(Box::new(BodyElement::Ident("this".into()))),
+                                    method_name: "GetPinnableReference".to_string(),
+                                    args: Vec::new(),
+                                }),
+                            }],
+                        }),
+                    }),
+                },
+                BodyElement::Assignment {
+                    lhs: Box::new(abi_field("Len")),
+                    rhs: Box::new(BodyElement::Cast {
+                        ty: ast::CSharpType::UInt64,
+                        element: Box::new(BodyElement::FieldAccess {
+                            element: Box::new(this_field("array")),
+                            field_name: "Length".to_string(),
+                        }),
+                    }),
+                },
+                BodyElement::Return {
+                    element: Some(Box::new(BodyElement::Ident(0.into()))),
+                },
+            ],
+        );
+
+        // Nothing is allocated, so there's nothing to free, but the shape requires it.
+        let free = method("Free", false, ast::CSharpType::Void, Vec::new(), Vec::new());
+
+        let stateful = ast::Object {
+            attributes: vec![ast::Attribute::struct_layout(ast::LayoutKind::Sequential)],
+            object_type: ast::ObjectType::Struct,
+            is_static: false,
+            is_sealed: false,
+            is_readonly: false,
+            is_ref: true,
+            is_unsafe: false,
+            is_partial: false,
+            is_private: false,
+            is_internal: false,
+            name: "ManagedToUnmanagedIn".into(),
+            base_types: Vec::new(),
+            constructors: Vec::new(),
+            methods: vec![from_managed, get_pinnable_reference, to_unmanaged, free],
+            fields: vec![ast::Field {
+                is_public: false,
+                is_readonly: false,
+                name: "array".to_string(),
+                ty: ast::CSharpType::Array {
+                    elem_type: Box::new(self.elem_type.clone()),
+                },
+                const_value: None,
+                fixed_len: None,
+                is_static: false,
+                initializer: None,
+            }],
+            properties: Vec::new(),
+            nested: Vec::new(),
+        };
+
+        let custom_marshaller = ast::Attribute {
+            name: "System.Runtime.InteropServices.Marshalling.CustomMarshaller".to_string(),
+            positional_parameters: vec![
+                ast::LiteralValue::TypeOf(self.managed_type.clone()),
+                ast::LiteralValue::EnumValue(
+                    "System.Runtime.InteropServices.Marshalling.MarshalMode".to_string(),
+                    "ManagedToUnmanagedIn".to_string(),
+                ),
+                ast::LiteralValue::TypeOf(ast::CSharpType::Struct {
+                    name: ast::Ident(format!("{}.{}", self.name, stateful.name)),
+                }),
+            ],
+            named_parameters: Vec::new(),
+        };
+
+        ast::Object {
+            attributes: vec![custom_marshaller],
+            object_type: ast::ObjectType::Class,
+            is_static: true,
+            is_sealed: false,
+            is_readonly: false,
+            is_ref: false,
+            is_unsafe: false,
+            is_partial: false,
+            is_private: false,
+            is_internal: false,
+            name: self.name.clone(),
+            base_types: Vec::new(),
+            constructors: Vec::new(),
+            methods: Vec::new(),
+            fields: Vec::new(),
+            properties: Vec::new(),
+            nested: vec![stateful],
+        }
+    }
+}
+
+/// Abstract identifier for a variable, eventually resolved to a concrete ast::Ident.
+#[derive(Clone, Debug)]
+enum AbstractIdent {
+    Explicit(String),
+    Generated(u32),
+}
+
+impl From<u32> for AbstractIdent {
+    fn from(num: u32) -> Self {
+        AbstractIdent::Generated(num)
+    }
+}
+
+impl From<&str> for AbstractIdent {
+    fn from(name: &str) -> Self {
+        AbstractIdent::Explicit(name.to_string())
+    }
+}
+
+impl AbstractIdent {
+    fn generated_id(&self) -> Option<u32> {
+        match self {
+            AbstractIdent::Explicit(_) => None,
+            AbstractIdent::Generated(x) => Some(*x),
+        }
+    }
+
+    fn apply_abstract_id_offset(&mut self, offset: u32) {
+        match self {
+            AbstractIdent::Explicit(_) => (),
+            AbstractIdent::Generated(x) => *x += offset,
+        };
+    }
+
+    fn to_concrete_ident(&self) -> ast::Ident {
+        match self {
+            AbstractIdent::Explicit(name) => ast::Ident(
+                name.to_string()
+            ),
+            AbstractIdent::Generated(idx) => ast::Ident(
+                format!("_gen{}", idx)
+            ),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+enum BinaryOperation {
+    Equal,
+    NotEqual,
+    And,
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Remainder,
+    ShiftRight,
+    /// A type test, eg `foo is Bar`.
+    Is,
+}
+
+impl BinaryOperation {
+    fn sym(&self) -> &'static str {
+        match self {
+            BinaryOperation::Equal => "==",
+            BinaryOperation::NotEqual => "!=",
+            BinaryOperation::And => "&&",
+            BinaryOperation::Add => "+",
+            BinaryOperation::Subtract => "-",
+            BinaryOperation::Multiply => "*",
+            BinaryOperation::Divide => "/",
+            BinaryOperation::Remainder => "%",
+            BinaryOperation::ShiftRight => ">>",
+            BinaryOperation::Is => "is",
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+enum LiteralValue {
+    Number(i64),
+    Bool(bool),
+    String(String),
+    Null,
+    /// Written out as is, eg `1.5f`
+    Verbatim(String),
+}
+
+/// An abstract part of a method body, roughly mapping 1-1 with an ast element.
+#[derive(Clone, Debug)]
+enum BodyElement {
+    Ident(AbstractIdent),
+    /// Declares a new local variable of the given type.
+    DeclareLocal {
+        id: AbstractIdent,
+        ty: ast::CSharpType,
+    },
+    /// Just calls a method, optionally on some target, eg `foo.Bar(a, b)`.
+    MethodCall {
+        target: Option<Box<BodyElement>>,
+        method_name: String,
+        args: Vec<BodyElement>,
+    },
+    /// Constructs a new object, eg `new Foo(a, b)`.
+    NewObject {
+        ty: ast::CSharpType,
+        args: Vec<BodyElement>,
+    },
+    /// A field/property of a variable, eg `foo.Length`.
+    FieldAccess {
+        element: Box<BodyElement>,
+        field_name: String,
+    },
+    /// Allocates a new managed array, eg `new Byte[len]`.
+    NewArray {
+        elem_type: ast::CSharpType,
+        len: Box<BodyElement>,
+    },
+    /// Allocates a new managed array holding the given elements, eg `new object[] { a, b }`.
+    NewArrayInit {
+        elem_type: ast::CSharpType,
+        elements: Vec<BodyElement>,
+    },
+    /// Casts a value to a given type
+    Cast {
+        ty: ast::CSharpType,
+        element: Box<BodyElement>,
+    },
+    Assignment {
+        lhs: Box<BodyElement>,
+        rhs: Box<BodyElement>,
+    },
+    /// Generates a fixed assignment, with subsequent operations inside its scope
+    FixedAssignment {
+        ty: ast::CSharpType,
+        id: AbstractIdent,
+        rhs: Box<BodyElement>,
+    },
+    /// Wraps all elements after it in the rendered AST in an unsafe block
+    Unsafe,
+    /// Wraps all elements after it in the rendered AST in an unchecked block
+    Unchecked,
+    /// Wraps all elements after it in the rendered AST in an if block
+    If {
+        condition: Box<BodyElement>,
+    },
+    /// Repeats the given elements while the condition holds
+    While {
+        condition: Box<BodyElement>,
+        body: Vec<BodyElement>,
+    },
+    /// Runs the given elements, followed by the finally elements even if they throw
+    TryFinally {
+        body: Vec<BodyElement>,
+        finally: Vec<BodyElement>,
+    },
+    /// Throws the given exception if the condition holds, without wrapping the elements after it
+    ThrowIf {
+        condition: Box<BodyElement>,
+        exception: Box<BodyElement>,
+    },
+    Return {
+        element: Option<Box<BodyElement>>,
+    },
+    /// A binary expression, eg `a != b`
+    BinaryExpression {
+        lhs: Box<BodyElement>,
+        rhs: Box<BodyElement>,
+        operation: BinaryOperation,
+    },
+    /// A logical negation, eg `!(a)`
+    Not {
+        element: Box<BodyElement>,
+    },
+    /// Reads the value a pointer points to, eg `*(a)`
+    Deref {
+        element: Box<BodyElement>,
+    },
+    /// An expression evaluated with overflow checking, eg `checked(a)`
+    Checked {
+        element: Box<BodyElement>,
+    },
+    /// An argument passed by reference, eg `ref a`
+    Ref {
+        element: Box<BodyElement>,
+    },
+    /// A lambda evaluating a single expression, eg `() => Foo(a)` or `pair => pair.Key`
+    Lambda {
+        args: Vec<String>,
+        body: Box<BodyElement>,
+    },
+    /// A ternary expression, eg `foo ? a : b`
+    TernaryExpression {
+        test: Box<BodyElement>,
+        true_branch: Box<BodyElement>,
+        false_branch: Box<BodyElement>,
+    },
+    LiteralValue(LiteralValue),
+}
+
+impl BodyElement {
+    /// The elements directly inside this one, in the order they are evaluated.
+    fn children(&self) -> Vec<&BodyElement> {
+        match self {
+            BodyElement::MethodCall { target, method_name: _, args } => {
+                target.iter().map(|t| t.as_ref()).chain(args.iter()).collect()
+            }
+            BodyElement::NewObject { ty: _, args } => args.iter().collect(),
+            BodyElement::NewArrayInit { elem_type: _, elements } => elements.iter().collect(),
+            BodyElement::While { condition, body } => std::iter::once(condition.as_ref()).chain(body.iter()).collect(),
+            BodyElement::TryFinally { body, finally } => body.iter().chain(finally.iter()).collect(),
+            BodyElement::FieldAccess { element, .. }
+            | BodyElement::Cast { element, .. }
+            | BodyElement::Not { element }
+            | BodyElement::Deref { element }
+            | BodyElement::Checked { element }
+            | BodyElement::Ref { element }
+            | BodyElement::Return { element: Some(element) } => vec![element],
+            BodyElement::NewArray { len, .. } => vec![len],
+            BodyElement::FixedAssignment { rhs, .. } => vec![rhs],
+            BodyElement::If { condition } => vec![condition],
+            BodyElement::Lambda { args: _, body } => vec![body],
+            BodyElement::Assignment { lhs, rhs }
+            | BodyElement::BinaryExpression { lhs, rhs, .. } => vec![lhs, rhs],
+            BodyElement::ThrowIf { condition, exception } => vec![condition, exception],
+            BodyElement::TernaryExpression { test, true_branch, false_branch } => {
+                vec![test, true_branch, false_branch]
+            }
+            BodyElement::Ident(_)
+            | BodyElement::DeclareLocal { .. }
+            | BodyElement::Unsafe
+            | BodyElement::Unchecked
+            | BodyElement::Return { element: None }
+            | BodyElement::LiteralValue(_) => Vec::new(),
+        }
+    }
+
+    fn is_generated_ident(&self, id: u32) -> bool {
+        matches!(self, BodyElement::Ident(ident) if ident.generated_id() == Some(id))
+    }
+
+    /// The number of elements in this one, including itself, matching the predicate.
+    fn uses(&self, predicate: &dyn Fn(&BodyElement) -> bool) -> usize {
+        usize::from(predicate(self)) + self.children().iter().map(|c| c.uses(predicate)).sum::<usize>()
+    }
+
+    /// Whether evaluating the element has no side effects, so can be moved past other such
+    /// elements. Properties, eg `.Length`, are assumed not to have any.
+    fn is_pure(&self) -> bool {
+        match self {
+            BodyElement::Ident(_) | BodyElement::LiteralValue(_) => true,
+            BodyElement::FieldAccess { .. }
+            | BodyElement::Cast { .. }
+            | BodyElement::Not { .. }
+            | BodyElement::BinaryExpression { .. }
+            | BodyElement::TernaryExpression { .. } => self.children().iter().all(|c| c.is_pure()),
+            _ => false,
+        }
+    }
+
+    /// Whether the only use of the given temporary within this statement can be replaced by the
+    /// value assigned to it, or None if it isn't used.
+    ///
+    /// Anything evaluated before the use would then be evaluated before the value, so must be pure.
+    /// For a value with side effects, it must be a local or literal, which the value can't change.
+    fn inline_site(&self, id: u32, value_is_pure: bool) -> Option<bool> {
+        let used = |e: &BodyElement| e.uses(&|e| e.is_generated_ident(id)) > 0;
+
+        // The parts of the element which are evaluated unconditionally, and exactly once.
+        let evaluated: Vec<&BodyElement> = match self {
+            BodyElement::Ident(_) => return used(self).then_some(true),
+            BodyElement::Assignment { lhs, rhs } => {
+                if used(lhs) {
+                    return Some(false);
+                }
+                vec![lhs, rhs]
+            }
+            BodyElement::BinaryExpression { lhs, rhs, operation: BinaryOperation::And } => {
+                if used(rhs) {
+                    return Some(false);
+                }
+                vec![lhs]
+            }
+            BodyElement::ThrowIf { condition, exception } => {
+                if used(exception) {
+                    return Some(false);
+                }
+                vec![condition]
+            }
+            BodyElement::TernaryExpression { test, true_branch, false_branch } => {
+                if used(true_branch) || used(false_branch) {
+                    return Some(false);
+                }
+                vec![test]
+            }
+            BodyElement::Ref { .. }
+            | BodyElement::Lambda { .. }
+            | BodyElement::FixedAssignment { .. }
+            | BodyElement::If { .. }
+            | BodyElement::While { .. }
+            | BodyElement::TryFinally { .. } => return used(self).then_some(false),
+            _ => self.children(),
+        };
+
+        let mut movable = true;
+        for element in evaluated {
+            match element.inline_site(id, value_is_pure) {
+                Some(safe) => return Some(safe && movable),
+                None => {
+                    movable &= match value_is_pure {
+                        true => element.is_pure(),
+                        false => matches!(element, BodyElement::Ident(_) | BodyElement::LiteralValue(_)),
+                    }
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Replaces each use of the given temporary with the value.
+    fn replace_generated_ident(&mut self, id: u32, value: &BodyElement) {
+        if self.is_generated_ident(id) {
+            *self = value.clone();
+            return;
+        }
+
+        match self {
+            BodyElement::MethodCall { target, method_name: _, args } => {
+                target.iter_mut().for_each(|t| t.replace_generated_ident(id, value));
+                args.iter_mut().for_each(|a| a.replace_generated_ident(id, value));
+            }
+            BodyElement::NewObject { ty: _, args } => args.iter_mut().for_each(|a| a.replace_generated_ident(id, value)),
+            BodyElement::NewArrayInit { elem_type: _, elements } => {
+                elements.iter_mut().for_each(|e| e.replace_generated_ident(id, value))
+            }
+            BodyElement::FieldAccess { element, .. }
+            | BodyElement::Cast { element, .. }
+            | BodyElement::Not { element }
+            | BodyElement::Deref { element }
+            | BodyElement::Checked { element }
+            | BodyElement::Return { element: Some(element) } => element.replace_generated_ident(id, value),
+            BodyElement::NewArray { len, .. } => len.replace_generated_ident(id, value),
+            BodyElement::Assignment { lhs: _, rhs } => rhs.replace_generated_ident(id, value),
+            BodyElement::BinaryExpression { lhs, rhs, .. } => {
+                lhs.replace_generated_ident(id, value);
+                rhs.replace_generated_ident(id, value);
+            }
+            BodyElement::ThrowIf { condition, exception: _ } => condition.replace_generated_ident(id, value),
+            BodyElement::TernaryExpression { test, .. } => test.replace_generated_ident(id, value),
+            // Never inlined into, see `inline_site`
+            _ => (),
+        }
+    }
+
+    /// What is the maximum abstract identifier id in this element, if any are present.
+    fn max_abstract_id(&self) -> Option<u32> {
+        match self {
+            BodyElement::Ident(id) => id.generated_id(),
+            BodyElement::DeclareLocal { id, ty: _ } => id.generated_id(),
+            BodyElement::MethodCall {
+                target,
+                method_name: _,
+                args,
+            } => target
+                .iter()
+                .map(|t| t.as_ref())
+                .chain(args.iter())
+                .filter_map(|a| a.max_abstract_id())
+                .max(),
+            BodyElement::NewObject { ty: _, args } => {
+                args.iter().filter_map(|a| a.max_abstract_id()).max()
+            }
+            BodyElement::FieldAccess {
+                element,
+                field_name: _,
+            } => element.max_abstract_id(),
+            BodyElement::NewArray { elem_type: _, len } => len.max_abstract_id(),
+            BodyElement::NewArrayInit { elem_type: _, elements } => {
+                elements.iter().filter_map(|e| e.max_abstract_id()).max()
+            }
+            BodyElement::Cast { ty: _, element } => element.max_abstract_id(),
+            BodyElement::Assignment { lhs, rhs } => {
+                [lhs, rhs].iter().filter_map(|a| a.max_abstract_id()).max()
+            }
+            BodyElement::FixedAssignment { ty: _, id, rhs } => {
+                [id.generated_id(), rhs.max_abstract_id()]
+                    .iter()
+                    .flatten()
+                    .copied()
+                    .max()
+            },
+            BodyElement::Unsafe => None,
+            BodyElement::Unchecked => None,
+            BodyElement::If { condition } => condition.max_abstract_id(),
+            BodyElement::While { condition, body } => {
+                body.iter().filter_map(|e| e.max_abstract_id()).chain(condition.max_abstract_id()).max()
+            },
+            BodyElement::TryFinally { body, finally } => {
+                body.iter().chain(finally.iter()).filter_map(|e| e.max_abstract_id()).max()
+            },
+            BodyElement::ThrowIf { condition, exception } => {
+                [condition, exception].iter().filter_map(|a| a.max_abstract_id()).max()
+            },
+            BodyElement::Not { element } => element.max_abstract_id(),
+            BodyElement::Deref { element } => element.max_abstract_id(),
+            BodyElement::Checked { element } => element.max_abstract_id(),
+            BodyElement::Ref { element } => element.max_abstract_id(),
+            BodyElement::Lambda { args: _, body } => body.max_abstract_id(),
+            BodyElement::Return { element: Some(element) } => element.max_abstract_id(),
+            BodyElement::Return { element: None } => None,
+            BodyElement::BinaryExpression { lhs, rhs, operation: _ } => {
+                [lhs, rhs].iter().filter_map(|a| a.max_abstract_id()).max()
+            },
+            BodyElement::TernaryExpression { test, true_branch, false_branch } => {
+                [test, true_branch, false_branch].iter().filter_map(|a| a.max_abstract_id()).max()
+            },
+            BodyElement::LiteralValue {..} => None,
+        }
+    }
+
+    fn apply_abstract_id_offset(&mut self, offset: u32) {
+        match self {
+            BodyElement::Ident(id) => id.apply_abstract_id_offset(offset),
+            BodyElement::DeclareLocal { id, ty: _ } => id.apply_abstract_id_offset(offset),
+            BodyElement::MethodCall {
+                target,
+                method_name: _,
+                args,
+            } => {
+                if let Some(target) = target {
+                    target.apply_abstract_id_offset(offset);
+                }
+                for arg in args.iter_mut() {
+                    arg.apply_abstract_id_offset(offset);
+                }
+            }
+            BodyElement::NewObject { ty: _, args } => {
+                for arg in args.iter_mut() {
+                    arg.apply_abstract_id_offset(offset);
+                }
+            }
+            BodyElement::FieldAccess {
+                element,
+                field_name: _,
+            } => element.apply_abstract_id_offset(offset),
+            BodyElement::NewArray { elem_type: _, len } => len.apply_abstract_id_offset(offset),
+            BodyElement::NewArrayInit { elem_type: _, elements } => {
+                for element in elements.iter_mut() {
+                    element.apply_abstract_id_offset(offset);
+                }
+            }
+            BodyElement::Cast { ty: _, element } => element.apply_abstract_id_offset(offset),
+            BodyElement::Assignment { lhs, rhs } => {
+                lhs.apply_abstract_id_offset(offset);
+                rhs.apply_abstract_id_offset(offset);
+            }
+            BodyElement::FixedAssignment { ty: _, id, rhs } => {
+                id.apply_abstract_id_offset(offset);
+                rhs.apply_abstract_id_offset(offset);
+            },
+            BodyElement::Unsafe => (),
+            BodyElement::Unchecked => (),
+            BodyElement::If { condition } => condition.apply_abstract_id_offset(offset),
+            BodyElement::While { condition, body } => {
+                condition.apply_abstract_id_offset(offset);
+                for el in body.iter_mut() {
+                    el.apply_abstract_id_offset(offset);
+                }
+            },
+            BodyElement::TryFinally { body, finally } => {
+                for el in body.iter_mut().chain(finally.iter_mut()) {
+                    el.apply_abstract_id_offset(offset);
+                }
+            },
+            BodyElement::ThrowIf { condition, exception } => {
+                condition.apply_abstract_id_offset(offset);
+                exception.apply_abstract_id_offset(offset);
+            },
+            BodyElement::Not { element } => element.apply_abstract_id_offset(offset),
+            BodyElement::Deref { element } => element.apply_abstract_id_offset(offset),
+            BodyElement::Checked { element } => element.apply_abstract_id_offset(offset),
+            BodyElement::Ref { element } => element.apply_abstract_id_offset(offset),
+            BodyElement::Lambda { args: _, body } => body.apply_abstract_id_offset(offset),
+            BodyElement::Return { element: Some(element) } => element.apply_abstract_id_offset(offset),
+            BodyElement::Return { element: None } => (),
+            BodyElement::BinaryExpression { lhs, rhs, operation: _ } => {
+                lhs.apply_abstract_id_offset(offset);
+                rhs.apply_abstract_id_offset(offset);
+            },
+            BodyElement::TernaryExpression { test, true_branch, false_branch } => {
+                test.apply_abstract_id_offset(offset);
+                true_branch.apply_abstract_id_offset(offset);
+                false_branch.apply_abstract_id_offset(offset);
+            },
+            BodyElement::LiteralValue {..} => (),
+        }
+    }
+
+    fn requires_new_scope(&self) -> bool {
+        match self {
+            BodyElement::Ident (_) => false,
+            BodyElement::DeclareLocal {..} => false,
+            BodyElement::MethodCall {..} => false,
+            BodyElement::NewObject {..} => false,
+            BodyElement::FieldAccess {..} => false,
+            BodyElement::NewArray {..} => false,
+            BodyElement::NewArrayInit {..} => false,
+            BodyElement::Cast {..} => false,
+            BodyElement::Assignment {..} => false,
+            BodyElement::FixedAssignment {..} => true,
+            BodyElement::Unsafe => true,
+            BodyElement::Unchecked => true,
+            BodyElement::If {..} => true,
+            BodyElement::While {..} => false,
+            BodyElement::TryFinally {..} => false,
+            BodyElement::ThrowIf {..} => false,
+            BodyElement::Return{..} => false,
+            BodyElement::BinaryExpression{..} => false,
+            BodyElement::Not {..} => false,
+            BodyElement::Deref {..} => false,
+            BodyElement::Checked {..} => false,
+            BodyElement::Ref {..} => false,
+            BodyElement::Lambda {..} => false,
+            BodyElement::LiteralValue {..} => false,
+            BodyElement::TernaryExpression {..} => false,
+        }
+    }
+
+    fn is_top_level(&self) -> bool {
+        match self {
+            BodyElement::Ident (_) => false,
+            BodyElement::DeclareLocal {..} => true,
+            BodyElement::MethodCall {..} => false,
+            BodyElement::NewObject {..} => false,
+            BodyElement::FieldAccess {..} => false,
+            BodyElement::NewArray {..} => false,
+            BodyElement::NewArrayInit {..} => false,
+            BodyElement::Cast {..} => false,
+            BodyElement::Assignment {..} => false,
+            BodyElement::FixedAssignment {..} => true,
+            BodyElement::Unsafe => true,
+            BodyElement::Unchecked => true,
+            BodyElement::If {..} => true,
+            BodyElement::While {..} => true,
+            BodyElement::TryFinally {..} => true,
+            BodyElement::ThrowIf {..} => true,
+            BodyElement::Return{..} => true,
+            BodyElement::BinaryExpression{..} => false,
+            BodyElement::Not {..} => false,
+            BodyElement::Deref {..} => false,
+            BodyElement::Checked {..} => false,
+            BodyElement::Ref {..} => false,
+            BodyElement::Lambda {..} => false,
+            BodyElement::LiteralValue {..} => false,
+            BodyElement::TernaryExpression {..} => false,
+        }
+    }
+
+    fn to_ast_node(&self) -> Box<dyn ast::AstNode> {
+        match self {
+            BodyElement::Ident(id) => Box::new(id.to_concrete_ident()),
+            BodyElement::DeclareLocal { id, ty } => Box::new(
+                ast::VariableDeclaration {
+                    name: id.to_concrete_ident(),
+                    ty: ty.clone()
+                }
+            ),
+            BodyElement::MethodCall { target, method_name, args } => {
+                let args = args.iter()
+                    .map(|a| a.to_ast_node())
+                    .collect();
+                Box::new(
+                    ast::MethodInvocation {
+                        target: target.as_ref().map(|t| t.to_ast_node()),
+                        method_name: ast::Ident(method_name.to_string()),
+                        args,
+                    }
+                )
+            },
+            BodyElement::NewObject { ty, args } => Box::new(
+                ast::NewObject {
+                    ty: ty.clone(),
+                    args: args.iter().map(|a| a.to_ast_node()).collect(),
+                }
+            ),
+            BodyElement::FieldAccess { element, field_name } => Box::new(
+                ast::FieldAccess {
+                    element: element.to_ast_node(),
+                    field_name: ast::Ident(field_name.to_string()),
+                }
+            ),
+            BodyElement::NewArray { elem_type, len } => Box::new(
+                ast::NewArray {
+                    elem_type: elem_type.clone(),
+                    len: len.to_ast_node(),
+                }
+            ),
+            BodyElement::NewArrayInit { elem_type, elements } => Box::new(
+                ast::ArrayInitializer {
+                    elem_type: elem_type.clone(),
+                    elements: elements.iter().map(|e| e.to_ast_node()).collect(),
+                }
+            ),
+            BodyElement::Cast { ty, element } => Box::new(
+                ast::Cast {
+                    ty: ty.clone(),
+                    element: element.to_ast_node(),
+                }
+            ),
+            BodyElement::Assignment { lhs, rhs } => Box::new(
+                ast::BinaryExpression {
+                    lhs: lhs.to_ast_node(),
+                    rhs: rhs.to_ast_node(),
+                    operation_sym: "=",
+                }
+            ),
+            BodyElement::FixedAssignment { ty, id, rhs } => Box::new(
+                ast::FixedAssignment {
+                    ty: ty.clone(),
+                    id: id.to_concrete_ident(),
+                    rhs: rhs.to_ast_node(),
+                }
+            ),
+            BodyElement::Unsafe => Box::new(
+                ast::UnsafeStatement {}
+            ),
+            BodyElement::Unchecked => Box::new(
+                ast::UncheckedStatement {}
+            ),
+            BodyElement::If { condition } => Box::new(
+                ast::IfStatement {
+                    condition: condition.to_ast_node(),
+                }
+            ),
+            BodyElement::While { condition, body } => Box::new(
+                ast::WhileStatement {
+                    condition: condition.to_ast_node(),
+                    body: BindingMethodBody { body_elements: body.clone() }.to_ast_nodes(),
+                }
+            ),
+            BodyElement::TryFinally { body, finally } => Box::new(
+                ast::TryFinallyStatement {
+                    body: BindingMethodBody { body_elements: body.clone() }.to_ast_nodes(),
+                    finally: BindingMethodBody { body_elements: finally.clone() }.to_ast_nodes(),
+                }
+            ),
+            BodyElement::ThrowIf { condition, exception } => Box::new(
+                ast::ThrowIfStatement {
+                    condition: condition.to_ast_node(),
+                    exception: exception.to_ast_node(),
+                }
+            ),
+            BodyElement::Not { element } => Box::new(
+                ast::UnaryExpression {
+                    element: element.to_ast_node(),
+                    operation_sym: "!",
+                }
+            ),
+            BodyElement::Checked { element } => Box::new(
+                ast::UnaryExpression {
+                    element: element.to_ast_node(),
+                    operation_sym: "checked",
+                }
+            ),
+            BodyElement::Deref { element } => Box::new(
+                ast::UnaryExpression {
+                    element: element.to_ast_node(),
+                    operation_sym: "*",
+                }
+            ),
+            BodyElement::Ref { element } => Box::new(
+                ast::RefArgument {
+                    element: element.to_ast_node(),
+                }
+            ),
+            BodyElement::Lambda { args, body } => Box::new(
+                ast::Lambda {
+                    args: args.iter().map(|a| a.as_str().into()).collect(),
+                    body: body.to_ast_node(),
+                }
+            ),
+            BodyElement::Return { element } => {
+                Box::new(ast::ReturnStatement {
+                    value: match element {
+                        Some(element) => Some(element.to_ast_node()),
+                        None => None,
+                    }
+                })
+            },
+            BodyElement::BinaryExpression { lhs, rhs, operation } => Box::new(
+                ast::BinaryExpression {
+                    lhs: lhs.to_ast_node(),
+                    rhs: rhs.to_ast_node(),
+                    operation_sym: operation.sym(),
+                }
+            ),
+            BodyElement::LiteralValue(val) => Box::new(
+                match val {
+                    LiteralValue::Number(num) => ast::LiteralValue::Number(*num),
+                    LiteralValue::Bool(b) => ast::LiteralValue::Bool(*b),
+                    LiteralValue::String(s) => ast::LiteralValue::QuotedString(s.to_string()),
+                    LiteralValue::Null => ast::LiteralValue::Null,
+                    LiteralValue::Verbatim(s) => ast::LiteralValue::Verbatim(s.to_string()),
+                }
+            ),
+            BodyElement::TernaryExpression { test, true_branch, false_branch } => Box::new(
+                ast::TernaryExpression {
+                    test: test.to_ast_node(),
+                    true_branch: true_branch.to_ast_node(),
+                    false_branch: false_branch.to_ast_node(),
+                }
+            )
+        }
+    }
+}
+
+/// Represents a single part of method body, responsible for converting idiomatic C# types to their
+/// underlying FFI stable equivalents.
+///
+/// Instances of this struct for types which are already FFI stable will look something like:
+/// ```
+/// #let arg_name = "foo".to_string();
+/// let frag = ArgTransformBodyElement {
+///     elements: Vec::new(),
+///     output_ident: AbstractIdent::Explicit(arg_name)
+/// };
+/// ```
+#[derive(Clone, Debug)]
+struct ArgTransformBodyFragment {
+    elements: Vec<BodyElement>,
+    output_ident: AbstractIdent,
+}
+
+impl ArgTransformBodyFragment {
+    /// The largest generated id used anywhere in this fragment, including its output.
+    fn max_abstract_id(&self) -> Option<u32> {
+        self.elements
+            .iter()
+            .filter_map(|e| e.max_abstract_id())
+            .chain(self.output_ident.generated_id())
+            .max()
+    }
+
+    fn apply_abstract_id_offset(&mut self, offset: u32) {
+        for el in self.elements.iter_mut() {
+            el.apply_abstract_id_offset(offset);
+        }
+
+        self.output_ident.apply_abstract_id_offset(offset)
+    }
+}
+
+/// Declares the local that a `returns_buffer` function writes the buffer's length to.
+///
+/// It's passed by reference, so must be definitely assigned beforehand.
+fn buffer_len_fragment() -> ArgTransformBodyFragment {
+    ArgTransformBodyFragment {
+        elements: vec![
+            BodyElement::DeclareLocal {
+                id: 0.into(),
+                ty: buffer_len_type(),
+            },
+            BodyElement::Assignment {
+                lhs: Box::new(BodyElement::Ident(0.into())),
+                rhs: Box::new(BodyElement::Ident("UIntPtr.Zero".into())),
+            },
+        ],
+        output_ident: 0.into(),
+    }
+}
+
+/// The length written by a `returns_buffer` function is a usize, which is pointer sized.
+fn buffer_len_type() -> ast::CSharpType {
+    ast::CSharpType::Struct {
+        name: "UIntPtr".into(),
+    }
+}
+
+/// Whether an owned slice returned by the given function is wrapped in a disposable class, rather
+/// than copied into an array.
+///
+/// Slices of bools and of dictionary pairs are always copied, as their elements are converted on
+/// the way.
+fn wraps_owned_buffer(descriptor: &core::BindgenFunctionDescriptor, options: &CodegenOptions) -> bool {
+    match &descriptor.return_ty {
+        core::BindgenTypeDescriptor::OwnedSlice { elem_type } => {
+            options.owned_buffers
+                && !options.no_copy_slices
+                && !descriptor.returns_dictionary
+                && !matches!(
+                    **elem_type,
+                    core::BindgenTypeDescriptor::Bool | core::BindgenTypeDescriptor::OwnedStr
+                )
+        }
+        _ => false,
+    }
+}
+
+/// The generic class a slice return is wrapped in with `no_copy_slices`.
+const NATIVE_SLICE_CLASS: &str = "NativeSlice";
+
+/// Whether a slice returned by the given function is wrapped in a `NativeSlice<T>` over the native
+/// memory, rather than copied into an array.
+///
+/// As with `wraps_owned_buffer`, slices of bools and of dictionary pairs are always copied.
+fn wraps_native_slice(descriptor: &core::BindgenFunctionDescriptor, options: &CodegenOptions) -> bool {
+    match &descriptor.return_ty {
+        core::BindgenTypeDescriptor::OwnedSlice { elem_type } | core::BindgenTypeDescriptor::Slice { elem_type } => {
+            options.no_copy_slices
+                && !descriptor.returns_dictionary
+                && !matches!(
+                    **elem_type,
+                    core::BindgenTypeDescriptor::Bool | core::BindgenTypeDescriptor::OwnedStr
+                )
+        }
+        _ => false,
+    }
+}
+
+/// The `NativeSlice<T>` a slice of the given type is returned as, eg `NativeSlice<UInt32>`.
+fn native_slice_type(return_ty: &BindingType) -> ast::CSharpType {
+    match return_ty.idiomatic_type() {
+        ast::CSharpType::Array { elem_type } => {
+            ast::CSharpType::named(&format!("{}<{}>", NATIVE_SLICE_CLASS, elem_type))
+        }
+        _ => unreachable!(),
+    }
+}
+
+/// Stops the JIT zeroing the `_genN` temporaries, which are always assigned before being read.
+///
+/// Only takes effect in code compiled with unsafe blocks allowed, and needs net5.0 or later.
+fn skip_locals_init_attribute() -> ast::Attribute {
+    ast::Attribute::marker("System.Runtime.CompilerServices.SkipLocalsInit")
+}
+
+/// The name of the P/Invoke method for the given function's thunk.
+///
+/// Functions from an `extern` block have no thunk, so their P/Invoke is given a name in the same
+/// reserved `__bindgen` namespace as the thunks, and an EntryPoint for the foreign symbol.
+fn dll_import_name(descriptor: &core::BindgenFunctionDescriptor) -> String {
+    if descriptor.no_thunk {
+        format!("__bindgen_extern_{}", descriptor.thunk_name)
+    } else {
+        descriptor.thunk_name.to_string()
+    }
+}
+
+/// The name of the SafeHandle class for an `Arc` of the given Rust type, eg `EngineHandle` for
+/// `my_crate::Engine`.
+fn handle_class_name(rust_type_name: &str) -> String {
+    let path = rust_type_name.split('<').next().unwrap_or(rust_type_name);
+    let name = path.rsplit("::").next().unwrap_or(path);
+    format!("{}Handle", name)
+}
+
+/// The name of the class wrapping an owned slice returned by the given function.
+fn owned_buffer_class_name(descriptor: &core::BindgenFunctionDescriptor) -> String {
+    let owner = descriptor.owner.as_deref().unwrap_or("");
+    format!("{}{}Buffer", owner, descriptor.real_name.to_camel_case())
+}
+
+/// A span over native memory, given an IntPtr to its start and its length as a UInt64.
+fn native_span(ptr: BodyElement, len: BodyElement, elem_type: ast::CSharpType) -> BodyElement {
+    BodyElement::NewObject {
+        ty: ast::CSharpType::Span {
+            elem_type: Box::new(elem_type),
+        },
+        args: vec![
+            BodyElement::Cast {
+                ty: ast::CSharpType::Ptr {
+                    target: Box::new(ast::CSharpType::Void),
+                },
+                element: Box::new(ptr),
+            },
+            BodyElement::Checked {
+                element: Box::new(BodyElement::Cast {
+                    ty: ast::CSharpType::Int32,
+                    element: Box::new(len),
+                }),
+            },
+        ],
+    }
+}
+
+/// A span over the memory described by an OwnedSliceAbi, only valid until it is freed.
+fn owned_span(abi: BodyElement, elem_type: ast::CSharpType) -> BodyElement {
+    native_span(
+        BodyElement::FieldAccess {
+            element: Box::new(abi.clone()),
+            field_name: "Ptr".to_string(),
+        },
+        BodyElement::FieldAccess {
+            element: Box::new(abi),
+            field_name: "Len".to_string(),
+        },
+        elem_type,
+    )
+}
+
+/// Body elements which copy the byte buffer returned by a `returns_buffer` function into a
+/// managed array and return it.
+///
+/// All elements following these will be inside an unsafe scope.
+fn returned_buffer_copy_elements(
+    call: BodyElement,
+    len: BodyElement,
+    ptr_id: u32,
+    result_id: u32,
+) -> Vec<BodyElement> {
+    let byte_array = ast::CSharpType::Array {
+        elem_type: Box::new(ast::CSharpType::Byte),
+    };
+
+    let len = BodyElement::Cast {
+        ty: ast::CSharpType::UInt64,
+        element: Box::new(len),
+    };
+
+    vec![
+        BodyElement::DeclareLocal {
+            id: ptr_id.into(),
+            ty: ast::CSharpType::intptr(),
+        },
+        BodyElement::Assignment {
+            lhs: Box::new(BodyElement::Ident(ptr_id.into())),
+            rhs: Box::new(call),
+        },
+        BodyElement::Unsafe,
+        BodyElement::DeclareLocal {
+            id: result_id.into(),
+            ty: byte_array,
+        },
+        BodyElement::Assignment {
+            lhs: Box::new(BodyElement::Ident(result_id.into())),
+            rhs: Box::new(BodyElement::MethodCall {
+                target: Some(Box::new(native_span(
+                    BodyElement::Ident(ptr_id.into()),
+                    len,
+                    ast::CSharpType::Byte,
+                ))),
+                method_name: "ToArray".to_string(),
+                args: Vec::new(),
+            }),
+        },
+        BodyElement::Return {
+            element: Some(Box::new(BodyElement::Ident(result_id.into()))),
+        },
+    ]
+}
+
+/// Decodes the NUL-terminated string at the given pointer, eg one returned as a `CString`.
+///
+/// `Marshal.PtrToStringUTF8` isn't part of netstandard2.0, so UTF-8 strings are measured by
+/// searching for the NUL instead.
+fn c_string_copy(ptr: BodyElement, encoding: Option<core::BindgenStringEncoding>) -> BodyElement {
+    if encoding == Some(core::BindgenStringEncoding::Ansi) {
+        return BodyElement::MethodCall {
+            target: Some(Box::new(BodyElement::Ident("Marshal".into()))),
+            method_name: "PtrToStringAnsi".to_string(),
+            args: vec![ptr],
+        };
+    }
+
+    // Eg `new ReadOnlySpan<Byte>((void*)(ptr), Int32.MaxValue).IndexOf((Byte)(0))`
+    let len = BodyElement::MethodCall {
+        target: Some(Box::new(BodyElement::NewObject {
+            ty: ast::CSharpType::named("ReadOnlySpan<Byte>"),
+            args: vec![
+                BodyElement::Cast {
+                    ty: ast::CSharpType::Ptr {
+                        target: Box::new(ast::CSharpType::Void),
+                    },
+                    element: Box::new(ptr.clone()),
+                },
+                BodyElement::Ident("Int32.MaxValue".into()),
+            ],
+        })),
+        method_name: "IndexOf".to_string(),
+        args: vec![BodyElement::Cast {
+            ty: ast::CSharpType::Byte,
+            element: Box::new(BodyElement::LiteralValue(LiteralValue::Number(0))),
+        }],
+    };
+
+    BodyElement::MethodCall {
+        target: Some(Box::new(BodyElement::Ident("System.Text.Encoding.UTF8".into()))),
+        method_name: "GetString".to_string(),
+        args: vec![
+            BodyElement::Cast {
+                ty: ast::CSharpType::Ptr {
+                    target: Box::new(ast::CSharpType::Byte),
+                },
+                element: Box::new(ptr),
+            },
+            len,
+        ],
+    }
+}
+
+/// Decodes the UTF-8 bytes described by an OwnedSliceAbi into a managed string.
+fn owned_str_copy(abi: BodyElement) -> BodyElement {
+    BodyElement::MethodCall {
+        target: Some(Box::new(BodyElement::Ident("System.Text.Encoding.UTF8".into()))),
+        method_name: "GetString".to_string(),
+        args: vec![
+            BodyElement::Cast {
+                ty: ast::CSharpType::Ptr {
+                    target: Box::new(ast::CSharpType::Byte),
+                },
+                element: Box::new(BodyElement::FieldAccess {
+                    element: Box::new(abi.clone()),
+                    field_name: "Ptr".to_string(),
+                }),
+            },
+            BodyElement::Checked {
+                element: Box::new(BodyElement::Cast {
+                    ty: ast::CSharpType::Int32,
+                    element: Box::new(BodyElement::FieldAccess {
+                        element: Box::new(abi),
+                        field_name: "Len".to_string(),
+                    }),
+                }),
+            },
+        ],
+    }
+}
+
+/// Body elements which copy a buffer returned by the given call into a managed array or string,
+/// free the original if given the thunk to free it with, and return the copy.
+///
+/// A returned `Vec<String>` has each of its strings copied, before the free thunk frees both them
+/// and the outer buffer.
+///
+/// Returned `CString`s are decoded in the given encoding, or as UTF-8 if none is given.
+///
+/// All elements following these will be inside an unsafe scope.
+fn owned_copy_elements(
+    call: BodyElement,
+    return_ty: &BindingType,
+    encoding: Option<core::BindgenStringEncoding>,
+    free_thunk_name: Option<&str>,
+    abi_id: u32,
+    result_id: u32,
+) -> Vec<BodyElement> {
+    let abi = BodyElement::Ident(abi_id.into());
+    let result = BodyElement::Ident(result_id.into());
+
+    let copy_elements = match return_ty.descriptor() {
+        Some(core::BindgenTypeDescriptor::Struct(s)) => {
+            let fields = s.fields.iter().map(|field| (field.name.to_camel_case(), &field.ty));
+            fields_copy_elements(fields, &abi, &result)
+        }
+        Some(core::BindgenTypeDescriptor::Tuple { elements }) => {
+            let items = elements.iter().enumerate().map(|(idx, ty)| (format!("Item{}", idx + 1), ty));
+            fields_copy_elements(items, &abi, &result)
+        }
+        _ => vec![BodyElement::Assignment {
+            lhs: Box::new(result.clone()),
+            rhs: Box::new(buffer_copy(abi.clone(), return_ty, encoding)),
+        }],
+    };
+
+    let mut elements = vec![
+        BodyElement::DeclareLocal {
+            id: abi_id.into(),
+            ty: return_ty.native_type(),
+        },
+        BodyElement::Assignment {
+            lhs: Box::new(abi.clone()),
+            rhs: Box::new(call),
+        },
+        BodyElement::Unsafe,
+        BodyElement::DeclareLocal {
+            id: result_id.into(),
+            ty: return_ty.idiomatic_type(),
+        },
+    ];
+    elements.extend(copy_elements);
+    if let Some(free_thunk_name) = free_thunk_name {
+        elements.push(BodyElement::MethodCall {
+            target: None,
+            method_name: free_thunk_name.to_string(),
+            args: vec![abi],
+        });
+    }
+    elements.push(BodyElement::Return {
+        element: Some(Box::new(result)),
+    });
+
+    elements
+}
+
+/// Assigns each field of a returned struct or tuple holding slices or strings from its `...Abi`
+/// struct, copying the slices and strings into managed arrays and strings.
+fn fields_copy_elements<'a>(
+    fields: impl Iterator<Item = (String, &'a core::BindgenTypeDescriptor)>,
+    abi: &BodyElement,
+    result: &BodyElement,
+) -> Vec<BodyElement> {
+    fields
+        .map(|(name, ty)| {
+            let field_of = |element: &BodyElement| Box::new(BodyElement::FieldAccess {
+                element: Box::new(element.clone()),
+                field_name: name.clone(),
+            });
+
+            let value = if is_buffer_field(ty) {
+                let ty = BindingType::try_from(ty.clone())
+                    .expect("Buffer fields are checked when binding the struct or tuple");
+                Box::new(buffer_copy(*field_of(abi), &ty, None))
+            } else {
+                field_of(abi)
+            };
+
+            BodyElement::Assignment {
+                lhs: field_of(result),
+                rhs: value,
+            }
+        })
+        .collect()
+}
+
+/// Copies a returned buffer, described by its AbiType, into a managed array or string.
+fn buffer_copy(
+    abi: BodyElement,
+    ty: &BindingType,
+    encoding: Option<core::BindgenStringEncoding>,
+) -> BodyElement {
+    match ty.idiomatic_type() {
+        _ if ty.descriptor() == Some(&core::BindgenTypeDescriptor::CString) => {
+            c_string_copy(abi.clone(), encoding)
+        }
+        // Eg `Array.ConvertAll(new Span<Byte>(...).ToArray(), b => b != 0)`
+        ast::CSharpType::Array { elem_type } if matches!(*elem_type, ast::CSharpType::Bool) => BodyElement::MethodCall {
+            target: Some(Box::new(BodyElement::Ident("Array".into()))),
+            method_name: "ConvertAll".to_string(),
+            args: vec![
+                BodyElement::MethodCall {
+                    target: Some(Box::new(owned_span(abi.clone(), ast::CSharpType::Byte))),
+                    method_name: "ToArray".to_string(),
+                    args: Vec::new(),
+                },
+                BodyElement::Lambda {
+                    args: vec!["b".to_string()],
+                    body: Box::new(BodyElement::BinaryExpression {
+                        lhs: Box::new(BodyElement::Ident("b".into())),
+                        rhs: Box::new(BodyElement::LiteralValue(LiteralValue::Number(0))),
+                        operation: BinaryOperation::NotEqual,
+                    }),
+                },
+            ],
+        },
+        // Eg `Array.ConvertAll(new Span<OwnedSliceAbi>(...).ToArray(), s => Encoding.UTF8.GetString(...))`
+        ast::CSharpType::Array { elem_type } if matches!(*elem_type, ast::CSharpType::String) => BodyElement::MethodCall {
+            target: Some(Box::new(BodyElement::Ident("Array".into()))),
+            method_name: "ConvertAll".to_string(),
+            args: vec![
+                BodyElement::MethodCall {
+                    target: Some(Box::new(owned_span(abi.clone(), ast::CSharpType::Struct {
+                        name: "OwnedSliceAbi".into(),
+                    }))),
+                    method_name: "ToArray".to_string(),
+                    args: Vec::new(),
+                },
+                BodyElement::Lambda {
+                    args: vec!["s".to_string()],
+                    body: Box::new(owned_str_copy(BodyElement::Ident("s".into()))),
+                },
+            ],
+        },
+        ast::CSharpType::Array { elem_type } => BodyElement::MethodCall {
+            target: Some(Box::new(owned_span(abi.clone(), *elem_type))),
+            method_name: "ToArray".to_string(),
+            args: Vec::new(),
+        },
+        ast::CSharpType::String => owned_str_copy(abi),
+        _ => unreachable!(),
+    }
+}
+
+/// The key and value fields of the pairs returned by a `returns_dictionary` function.
+#[derive(Clone, Debug)]
+struct DictionaryPair {
+    key: BindingStructField,
+    value: BindingStructField,
+}
+
+impl DictionaryPair {
+    fn new(descriptor: &core::BindgenFunctionDescriptor) -> Result<Option<Self>, &'static str> {
+        if !descriptor.returns_dictionary {
+            return Ok(None);
+        }
+
+        let fields = match &descriptor.return_ty {
+            core::BindgenTypeDescriptor::OwnedSlice { elem_type } => match &**elem_type {
+                core::BindgenTypeDescriptor::Struct(s) => s.fields.as_slice(),
+                _ => &[],
+            },
+            _ => &[],
+        };
+
+        match fields {
+            [key, value] => Ok(Some(Self {
+                key: BindingStructField::new(key, false)?,
+                value: BindingStructField::new(value, false)?,
+            })),
+            _ => Err("returns_dictionary functions must return a Vec of a struct with two fields"),
+        }
+    }
+
+    fn dictionary_type(&self) -> ast::CSharpType {
+        ast::CSharpType::named(&format!(
+            "System.Collections.Generic.Dictionary<{}, {}>",
+            self.key.ty.cs_type,
+            self.value.ty.cs_type,
+        ))
+    }
+
+    /// Eg `System.Linq.Enumerable.ToDictionary(pairs, pair => pair.Key, pair => pair.Value)`
+    ///
+    /// Throws if the same key appears more than once.
+    fn to_dictionary(&self, pairs: BodyElement) -> BodyElement {
+        let field_of_pair = |field: &BindingStructField| BodyElement::Lambda {
+            args: vec!["pair".to_string()],
+            body: Box::new(BodyElement::FieldAccess {
+                element: Box::new(BodyElement::Ident("pair".into())),
+                field_name: field.cs_name.clone(),
+            }),
+        };
+
+        BodyElement::MethodCall {
+            target: Some(Box::new(BodyElement::Ident("System.Linq.Enumerable".into()))),
+            method_name: "ToDictionary".to_string(),
+            args: vec![pairs, field_of_pair(&self.key), field_of_pair(&self.value)],
+        }
+    }
+}
+
+/// Folds generated temporaries which are assigned once and then used once, in the next statement
+/// other than a declaration, into that statement.
+///
+/// Level 1 only folds values without side effects, eg `(data).Length`, while level 2 also folds
+/// method calls and allocations, as long as only locals and literals are evaluated before the
+/// use. Neither folds into or out of the scopes opened by `unsafe`, `fixed`, and `if`, into a
+/// position which isn't evaluated exactly once, eg a lambda or ternary branch, or anything passed
+/// by `ref`.
+fn inline_temporaries(elements: Vec<BodyElement>, level: u8) -> Vec<BodyElement> {
+    let mut elements: Vec<_> = elements
+        .into_iter()
+        .map(|element| match element {
+            BodyElement::While { condition, body } => BodyElement::While {
+                condition,
+                body: inline_temporaries(body, level),
+            },
+            BodyElement::TryFinally { body, finally } => BodyElement::TryFinally {
+                body: inline_temporaries(body, level),
+                finally: inline_temporaries(finally, level),
+            },
+            other => other,
+        })
+        .collect();
+
+    // Going backwards lets a chain of temporaries fold into each other.
+    for idx in (0..elements.len().saturating_sub(2)).rev() {
+        let (id, ty) = match &elements[idx] {
+            BodyElement::DeclareLocal { id, ty } => match id.generated_id() {
+                Some(id) => (id, ty.clone()),
+                None => continue,
+            },
+            _ => continue,
+        };
+        let value = match &elements[idx + 1] {
+            BodyElement::Assignment { lhs, rhs } if matches!(
+                &**lhs,
+                BodyElement::Ident(assigned) if assigned.generated_id() == Some(id)
+            ) => (**rhs).clone(),
+            _ => continue,
+        };
+
+        let foldable = match level {
+            1 => value.is_pure(),
+            _ => value.uses(&|e| matches!(e, BodyElement::Ref { .. })) == 0,
+        };
+        let next = match elements[idx + 2..]
+            .iter()
+            .position(|e| !matches!(e, BodyElement::DeclareLocal { .. }))
+        {
+            Some(offset) => idx + 2 + offset,
+            None => continue,
+        };
+
+        let single_use = elements[idx + 2..]
+            .iter()
+            .map(|e| e.uses(&|e| e.is_generated_ident(id)))
+            .sum::<usize>() == 1;
+        let in_next_statement = matches!(
+            elements[next],
+            BodyElement::MethodCall { .. }
+                | BodyElement::Assignment { .. }
+                | BodyElement::Return { .. }
+                | BodyElement::ThrowIf { .. }
+        ) && elements[next].inline_site(id, value.is_pure()) == Some(true);
+
+        if foldable && single_use && in_next_statement {
+            // The value was implicitly converted to the temporary's type, which the use may rely on.
+            // The generated method calls already return the type of the temporary they're assigned to.
+            let value = match &value {
+                BodyElement::NewObject { ty: value_ty, .. } | BodyElement::Cast { ty: value_ty, .. }
+                    if value_ty.to_string() == ty.to_string() => value,
+                BodyElement::MethodCall { .. } => value,
+                _ => BodyElement::Cast { ty, element: Box::new(value) },
+            };
+            elements[next].replace_generated_ident(id, &value);
+            elements.drain(idx..idx + 2);
+        }
+    }
+
+    elements
+}
+
+#[derive(Clone, Debug)]
+struct BindingMethodBody {
+    body_elements: Vec<BodyElement>,
+}
+
+impl BindingMethodBody {
+    pub fn new(
+        descriptor: &core::BindgenFunctionDescriptor,
+        args: &[BindingMethodArgument],
+        return_ty: &BindingType,
+        dictionary: Option<&DictionaryPair>,
+        options: &CodegenOptions,
+    ) -> Self {
+        let mut body = Self::marshalling(descriptor, args, return_ty, dictionary, options);
+        if options.inline_temporaries > 0 {
+            body.body_elements = inline_temporaries(body.body_elements, options.inline_temporaries);
+        }
+        body
+    }
+
+    /// The body marshalling each argument, calling the native method, and marshalling its result.
+    fn marshalling(
+        descriptor: &core::BindgenFunctionDescriptor,
+        args: &[BindingMethodArgument],
+        return_ty: &BindingType,
+        dictionary: Option<&DictionaryPair>,
+        options: &CodegenOptions,
+    ) -> Self {
+        let buffer_len_idx = args
+            .iter()
+            .position(|a| descriptor.buffer_len_arg.as_deref() == Some(a.rust_name.as_str()));
+
+        let mut transform_fragments: Vec<_> = args
+            .iter()
+            .enumerate()
+            .map(|(idx, a)| if Some(idx) == buffer_len_idx {
+                buffer_len_fragment()
+            } else {
+                a.transform_body_fragment(options)
+            })
+            .collect();
+
+        // Ensure that their generated idents from each fragment don't intersect, by shifting each
+        // fragment's ids past the range used by all of the fragments before it.
+        let mut offset = 0;
+        for frag in transform_fragments.iter_mut() {
+            if let Some(m) = frag.max_abstract_id() {
+                frag.apply_abstract_id_offset(offset);
+                offset += m + 1;
+            }
+        }
+
+        let guarded: Vec<_> = args.iter().filter(|arg| arg.null_guard().is_some()).collect();
+        let guards = if options.debug_only_validation && !guarded.is_empty() {
+            vec![validator_call(&descriptor.real_name.to_camel_case(), &guarded)]
+        } else {
+            guarded.iter().filter_map(|arg| arg.null_guard()).collect()
+        };
+
+        let mut body_elements: Vec<_> = guards
+            .into_iter()
+            .chain(transform_fragments.iter().flat_map(|frag| frag.elements.iter().cloned()))
+            .collect();
+
+        // Add one final body element, calling the bound method with all of the (possibly) transformed arguments.
+        let mut invocation_args: Vec<_> = transform_fragments
+            .iter()
+            .map(|frag| BodyElement::Ident(frag.output_ident.clone()))
+            .collect();
+
+        // The native method writes the buffer's length through the reference.
+        if let Some(idx) = buffer_len_idx {
+            invocation_args[idx] = BodyElement::Ref {
+                element: Box::new(invocation_args[idx].clone()),
+            };
+        }
+
+        // `ref` arguments must be passed with the modifier, while `in` arguments can be passed bare.
+        for (idx, arg) in args.iter().enumerate() {
+            if let Some(core::BindgenTypeDescriptor::MutRef { .. }) = arg.ty.descriptor() {
+                invocation_args[idx] = BodyElement::Ref {
+                    element: Box::new(invocation_args[idx].clone()),
+                };
+            }
+        }
+
+        // Instance methods are bound on the C# struct, so pass along the struct itself.
+        let this = BodyElement::Ident("this".into());
+        match descriptor.receiver {
+            None => (),
+            Some(core::BindgenReceiver::Value) | Some(core::BindgenReceiver::Ref) => {
+                invocation_args.insert(0, this)
+            }
+            Some(core::BindgenReceiver::RefMut) => invocation_args.insert(
+                0,
+                BodyElement::Ref { element: Box::new(this) },
+            ),
+        }
+
+        // The thunk can't throw, so reports invalid UTF-8 or an undefined enum discriminant through
+        // a flag, after which its return value is garbage.
+        let invalid_arg = BodyElement::Ident(offset.into());
+        if descriptor.reports_invalid_args {
+            body_elements.push(BodyElement::DeclareLocal {
+                id: offset.into(),
+                ty: ast::CSharpType::Byte,
+            });
+            body_elements.push(BodyElement::Assignment {
+                lhs: Box::new(invalid_arg.clone()),
+                rhs: Box::new(BodyElement::LiteralValue(LiteralValue::Number(0))),
+            });
+            invocation_args.push(BodyElement::Ref {
+                element: Box::new(invalid_arg.clone()),
+            });
+        }
+
+        let mut underlying_call = BodyElement::MethodCall {
+            target: None,
+            method_name: dll_import_name(descriptor),
+            args: invocation_args,
+        };
+
+        if descriptor.reports_invalid_args {
+            // Only the checks for the kinds of argument the function takes, as a struct argument
+            // may also give the thunk a flag which it never sets.
+            let check = |flag: i64, message: &str| BodyElement::ThrowIf {
+                condition: Box::new(BodyElement::BinaryExpression {
+                    lhs: Box::new(invalid_arg.clone()),
+                    rhs: Box::new(BodyElement::LiteralValue(LiteralValue::Number(flag))),
+                    operation: BinaryOperation::Equal,
+                }),
+                exception: Box::new(BodyElement::NewObject {
+                    ty: ast::CSharpType::named("ArgumentException"),
+                    args: vec![BodyElement::LiteralValue(LiteralValue::String(message.to_string()))],
+                }),
+            };
+            let mut checks = Vec::new();
+            if descriptor.arguments.iter().any(|arg| arg.ty == core::BindgenTypeDescriptor::Str) {
+                checks.push(check(1, "A string argument was not valid UTF-8"));
+            }
+            if descriptor.arguments.iter().any(|arg| has_checked_discriminants(&arg.ty)) {
+                checks.push(check(2, "An enum argument was not one of its defined values"));
+            }
+
+            if descriptor.return_ty == core::BindgenTypeDescriptor::Void {
+                body_elements.push(underlying_call);
+                body_elements.extend(checks);
+                return Self { body_elements };
+            }
+
+            body_elements.push(BodyElement::DeclareLocal {
+                id: (offset + 1).into(),
+                ty: return_ty.native_type(),
+            });
+            body_elements.push(BodyElement::Assignment {
+                lhs: Box::new(BodyElement::Ident((offset + 1).into())),
+                rhs: Box::new(underlying_call),
+            });
+            body_elements.extend(checks);
+            underlying_call = BodyElement::Ident((offset + 1).into());
+            offset += 2;
+        }
+
+        if let Some(idx) = buffer_len_idx {
+            let len = BodyElement::Ident(transform_fragments[idx].output_ident.clone());
+            body_elements.extend(returned_buffer_copy_elements(underlying_call, len, offset, offset + 1));
+            return Self { body_elements };
+        }
+
+        if is_copied_out(&descriptor.return_ty) {
+            // Borrowed buffers are only copied out, as they're still Rust's
+            let free_thunk_name = if is_owned(&descriptor.return_ty) {
+                Some(
+                    descriptor.free_thunk_name
+                        .as_deref()
+                        .expect("Functions returning owned buffers must have a free thunk"),
+                )
+            } else {
+                None
+            };
+
+            if wraps_native_slice(descriptor, options) {
+                let mut args = vec![underlying_call];
+                args.extend(free_thunk_name.map(|name| BodyElement::Ident(name.into())));
+
+                body_elements.push(BodyElement::Return {
+                    element: Some(Box::new(BodyElement::NewObject {
+                        ty: native_slice_type(return_ty),
+                        args,
+                    })),
+                });
+            } else if wraps_owned_buffer(descriptor, options) {
+                body_elements.push(BodyElement::Return {
+                    element: Some(Box::new(BodyElement::NewObject {
+                        ty: ast::CSharpType::Struct {
+                            name: owned_buffer_class_name(descriptor).as_str().into(),
+                        },
+                        args: vec![underlying_call],
+                    })),
+                });
+            } else {
+                let mut copy_elements = owned_copy_elements(
+                    underlying_call,
+                    return_ty,
+                    descriptor.return_encoding,
+                    free_thunk_name,
+                    offset,
+                    offset + 1,
+                );
+
+                // Return the copied pairs as a dictionary rather than as an array
+                if let Some(pair) = dictionary {
+                    copy_elements.pop();
+                    copy_elements.push(BodyElement::Return {
+                        element: Some(Box::new(pair.to_dictionary(BodyElement::Ident((offset + 1).into())))),
+                    });
+                }
+
+                body_elements.extend(copy_elements);
+            }
+
+            return Self { body_elements };
+        }
+
+        if descriptor.return_ty == core::BindgenTypeDescriptor::Duration {
+            underlying_call = BodyElement::MethodCall {
+                target: Some(Box::new(underlying_call)),
+                method_name: "ToTimeSpan".to_string(),
+                args: Vec::new(),
+            };
+        }
+
+        if let Some(type_name) = int128_type_name(&descriptor.return_ty) {
+            underlying_call = BodyElement::MethodCall {
+                target: Some(Box::new(underlying_call)),
+                method_name: format!("To{}", type_name),
+                args: Vec::new(),
+            };
+        }
+
+        if descriptor.return_ty != core::BindgenTypeDescriptor::Void {
+            body_elements.push(BodyElement::Return {
+                element: Some(Box::new(underlying_call))
+            });
+        } else {
+            body_elements.push(underlying_call);
+        }
+
+        Self { body_elements }
+    }
+
+    pub fn to_ast_nodes(&self) -> Vec<Box<dyn ast::AstNode>> {
+        fn render_elements<'a>(elements: &'a mut impl Iterator<Item = &'a BodyElement>) -> Vec<Box<dyn ast::AstNode>> {
+            let mut ast_nodes = Vec::new();
+            let mut next = elements.next();
+            while let Some(el) = next {
+                ast_nodes.push({
+                    let node = el.to_ast_node();
+                    if el.is_top_level() {
+                        node
+                    } else {
+                        Box::new(ast::Statement {
+                            expr: node
+                        })
+                    }
+                });
+
+                if el.requires_new_scope() {
+                    ast_nodes.push(Box::new(ast::Scope {
+                        children: render_elements(elements),
+                    }));
+                    break;
+                }
+
+                next = elements.next();
+            }
+
+            ast_nodes
+        }
+
+        render_elements(&mut self.body_elements.iter())
+    }
+}
+
+#[derive(Clone, Debug)]
+struct BindingMethod {
+    args: Vec<BindingMethodArgument>,
+
+    return_ty: BindingType,
+
+    /// The name of the binary containing the method, suitable for using directly in a DllImport attribute.
+    binary_name: String,
+
+    /// The name of the method that received the original #[dotnet_bindgen] attribute
+    /// 
+    /// This isn't neccesarily unique among the bindings, or the name of the symbol in the binary,
+    /// as the if a thunk is generated the method doens't have to have #[no_mangle] attached.
+    rust_name: String,
+
+    /// The exported symbol the P/Invoke binds to, which is the generated rust thunk, or the foreign
+    /// function itself for functions from an `extern` block.
+    ///
+    /// Guaranteed to be unique among the bindings.
+    symbol_name: String,
+
+    /// The name of the private P/Invoke for the thunk, which is only given an EntryPoint when it
+    /// differs from `symbol_name`.
+    dll_import_name: String,
+
+    /// The name of the C# method to expose from the bindings BindingMethodBody
+    /// 
+    /// Typically just rust_name.to_camel_case().
+    cs_name: String,
+
+    /// If a C# thunk must be generated, the body of that thunk.
+    cs_thunk_body: Option<BindingMethodBody>,
+
+    /// The struct this is an associated function of, if any
+    owner: Option<String>,
+
+    /// The nested static classes a free function is bound in, outermost first. Empty for functions
+    /// bound in TopLevelMethods.
+    module: Vec<String>,
+
+    /// How the method takes self. Methods without a receiver are bound as static methods.
+    receiver: Option<core::BindgenReceiver>,
+
+    /// Whether to generate a Task returning overload that runs the method on the thread pool
+    async_wrapper: bool,
+
+    /// A private copy of the method taking a Span in place of each `&[u8]`, which holds the only
+    /// copy of the marshalling. The wrapper method hands it its arrays, and the method taking a
+    /// Stream in their place hands it its rented buffers.
+    stream_target: Option<Box<BindingMethod>>,
+
+    calling_convention: core::BindgenCallingConvention,
+
+    /// Whether the P/Invoke is marked `[SuppressGCTransition]`, which needs net5.0 or later
+    no_gc_transition: bool,
+
+    /// The symbol name of the Rust thunk which frees a returned value, if anything is returned.
+    rust_free_thunk_name: Option<String>,
+
+    /// The symbol name of the Rust thunk which takes another reference to a returned Arc.
+    rust_clone_thunk_name: Option<String>,
+
+    /// The class an owned slice return is wrapped in, rather than being copied, if any.
+    owned_buffer_class: Option<String>,
+
+    /// Whether a returned slice is wrapped in a `NativeSlice<T>`, rather than being copied.
+    native_slice: bool,
+
+    /// For a `returns_dictionary` method, the fields of the returned pairs.
+    ///
+    /// The wrapper method returns a `Dictionary` in place of an array of pairs.
+    dictionary: Option<DictionaryPair>,
+
+    /// For a `returns_buffer` method, the rust name of the argument its length is written to.
+    ///
+    /// The wrapper method returns a byte array in place of taking this argument.
+    buffer_len_arg: Option<String>,
+
+    /// For a `fill_buffer` method, the rust name of the byte buffer argument it fills.
+    ///
+    /// An overload omitting this argument returns a freshly allocated array instead.
+    fill_buffer_arg: Option<String>,
+
+    /// Mark the wrapper methods with `[DebuggerStepThrough]`.
+    debugger_step_through: bool,
+
+    /// Check the wrapper methods' arguments in `[Conditional("DEBUG")]` validators.
+    debug_only_validation: bool,
+
+    /// Mark the wrapper methods with `[SkipLocalsInit]`.
+    skip_locals_init: bool,
+
+    /// Call the partial trace hooks from the wrapper methods.
+    trace_hooks: bool,
+
+    /// Declare the P/Invoke with `[LibraryImport]`, as a partial method implemented by the interop
+    /// source generator.
+    library_import: bool,
+
+    /// The thunk takes a trailing `ref Byte`, which it sets if a string argument isn't valid UTF-8.
+    reports_invalid_args: bool,
+}
+
+impl BindingMethod {
+    pub fn new(
+        binary_name: &str,
+        descriptor: &core::BindgenFunctionDescriptor,
+        options: &CodegenOptions,
+    ) -> Result<Self, &'static str> {
+        let binary_name = binary_name.to_string();
+
+        let mut args = descriptor
+            .arguments
+            .iter()
+            .map(|arg_desc| BindingMethodArgument::try_from(arg_desc.clone()))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        warn_overaligned_by_value_structs(descriptor);
+
+        if let core::BindgenTypeDescriptor::Ref { .. } | core::BindgenTypeDescriptor::MutRef { .. } = descriptor.return_ty {
+            return Err("Can't generate code for functions returning a reference");
+        }
+        if !descriptor.no_thunk {
+            check_return_ownership(descriptor)?;
+        }
+
+        let mut return_ty = descriptor.return_ty.clone().try_into()?;
+
+        if let Some(encoding) = descriptor.return_encoding {
+            if descriptor.return_ty != core::BindgenTypeDescriptor::CString {
+                return Err("return_encoding is only valid on functions returning a CString");
+            }
+            if encoding == core::BindgenStringEncoding::Utf16 {
+                return Err("A returned CString can only be decoded as utf8 or ansi");
+            }
+        }
+
+        // The marshaller wraps a returned pointer in the SafeHandle itself.
+        if let core::BindgenTypeDescriptor::Arc { name } = &descriptor.return_ty {
+            if descriptor.clone_thunk_name.is_none() || descriptor.free_thunk_name.is_none() {
+                return Err("Functions returning an Arc must name it as Arc<T>, so that it gets a clone thunk");
+            }
+
+            return_ty = BindingType::Simple(SimpleBindingType {
+                descriptor: Some(descriptor.return_ty.clone()),
+                cs_type: ast::CSharpType::Struct {
+                    name: handle_class_name(name).as_str().into(),
+                },
+            });
+        }
+
+        if options.function_pointers {
+            for ty in args.iter_mut().map(|arg| &mut arg.ty).chain(std::iter::once(&mut return_ty)) {
+                let descriptor = ty.descriptor().cloned();
+                if let Some(cs_type) = descriptor.as_ref().map(function_pointer_type).transpose()?.flatten() {
+                    *ty = BindingType::Simple(SimpleBindingType { descriptor, cs_type });
+                }
+            }
+        }
+
+        if options.custom_marshallers {
+            for arg in &mut args {
+                arg.marshaller = CustomMarshaller::for_argument(arg);
+            }
+        }
+
+        if let Some(len_arg) = &descriptor.buffer_len_arg {
+            let len_arg_is_ptr = descriptor.arguments
+                .iter()
+                .any(|a| &a.name == len_arg && a.ty == core::BindgenTypeDescriptor::RawPtr);
+
+            if descriptor.return_ty != core::BindgenTypeDescriptor::RawPtr || !len_arg_is_ptr {
+                return Err("A returns_buffer function must return a pointer, and take its length argument as a pointer");
+            }
+        }
+
+        // The overloads omitting defaulted arguments drop them from the end of the wrapper's arguments.
+        let defaults = args
+            .iter()
+            .filter(|arg| descriptor.buffer_len_arg.as_ref() != Some(&arg.rust_name))
+            .map(|arg| arg.default.is_some());
+        if defaults.skip_while(|defaulted| !defaulted).any(|defaulted| !defaulted) {
+            return Err("Arguments with default values must come after all those without");
+        }
+
+        let rust_name = descriptor.real_name.to_string();
+        let symbol_name = descriptor.thunk_name.to_string();
+        let cs_name = rust_name.to_camel_case();
+
+        let dictionary = DictionaryPair::new(descriptor)?;
+        let cs_thunk_body = Some(BindingMethodBody::new(
+            descriptor,
+            &args,
+            &return_ty,
+            dictionary.as_ref(),
+            options,
+        ));
+
+        let owned_buffer_class = if wraps_owned_buffer(descriptor, options) {
+            Some(owned_buffer_class_name(descriptor))
+        } else {
+            None
+        };
+
+        let mut method = Self {
+            binary_name,
+            args,
+            return_ty,
+            rust_name,
+            symbol_name,
+            dll_import_name: dll_import_name(descriptor),
+            cs_name,
+            cs_thunk_body,
+            owner: descriptor.owner.clone(),
+            module: descriptor.module
+                .as_ref()
+                .map(|module| module.split('.').map(str::to_string).collect())
+                .unwrap_or_default(),
+            receiver: descriptor.receiver,
+            calling_convention: descriptor.calling_convention,
+            no_gc_transition: descriptor.no_gc_transition,
+            async_wrapper: options.async_wrappers && descriptor.blocking,
+            stream_target: None,
+            rust_free_thunk_name: descriptor.free_thunk_name.clone(),
+            rust_clone_thunk_name: descriptor.clone_thunk_name.clone(),
+            owned_buffer_class,
+            native_slice: wraps_native_slice(descriptor, options),
+            dictionary,
+            buffer_len_arg: descriptor.buffer_len_arg.clone(),
+            fill_buffer_arg: descriptor.fill_buffer_arg.clone(),
+            debugger_step_through: options.debugger_step_through,
+            debug_only_validation: options.debug_only_validation,
+            skip_locals_init: options.skip_locals_init,
+            trace_hooks: options.trace_hooks,
+            library_import: options.custom_marshallers,
+            reports_invalid_args: descriptor.reports_invalid_args,
+        };
+
+        // The LibraryImport marshallers only take arrays, and a Stream can't be read into the
+        // receiver of an instance method.
+        if options.stream_overloads && !options.custom_marshallers && method.receiver.is_none() {
+            method.stream_target = method.stream_target(descriptor, options).map(Box::new);
+        }
+
+        if let Some(reason) = method.async_unsupported_reason().filter(|_| method.async_wrapper) {
+            diagnostics::warn(&format!("{} gets no async overload, as it {}", descriptor.real_name, reason));
+        }
+
+        Ok(method)
+    }
+
+    /// A copy of the method taking a Span in place of each of its `&[u8]` arguments, if it has
+    /// any, so that only the filled part of a rented buffer is handed over.
+    fn stream_target(
+        &self,
+        descriptor: &core::BindgenFunctionDescriptor,
+        options: &CodegenOptions,
+    ) -> Option<BindingMethod> {
+        if !self.args.iter().any(BindingMethodArgument::is_stream_source) {
+            return None;
+        }
+
+        let mut target = self.clone();
+        for arg in target.args.iter_mut().filter(|arg| arg.is_stream_source()) {
+            if let BindingType::Complex(ty) = &mut arg.ty {
+                ty.idiomatic_type = ast::CSharpType::Span {
+                    elem_type: Box::new(ast::CSharpType::Byte),
+                };
+            }
+        }
+
+        target.cs_thunk_body = Some(BindingMethodBody::new(
+            descriptor,
+            &target.args,
+            &target.return_ty,
+            target.dictionary.as_ref(),
+            options,
+        ));
+        target.async_wrapper = false;
+
+        Some(target)
+    }
+
+    /// The given wrapper body, with calls to the partial trace hooks on entry and before each return.
+    ///
+    /// Only blittable values are captured, anything else is handed to the hooks as null.
+    fn traced_body(&self, body: &BindingMethodBody) -> BindingMethodBody {
+        let method_name = || BodyElement::LiteralValue(LiteralValue::String(self.cs_name.clone()));
+        let captured = |ty: &BindingType, value: BodyElement| match ty {
+            BindingType::Simple(s) if !matches!(s.cs_type, ast::CSharpType::FunctionPointer { .. }) => value,
+            _ => BodyElement::LiteralValue(LiteralValue::Null),
+        };
+        let exit = |result| BodyElement::MethodCall {
+            target: None,
+            method_name: TRACE_EXIT_HOOK.to_string(),
+            args: vec![method_name(), result],
+        };
+
+        let mut body_elements = vec![BodyElement::MethodCall {
+            target: None,
+            method_name: TRACE_ENTER_HOOK.to_string(),
+            args: vec![
+                method_name(),
+                BodyElement::NewArrayInit {
+                    elem_type: ast::CSharpType::Object,
+                    elements: self.wrapper_args()
+                        .map(|arg| captured(&arg.ty, BodyElement::Ident(arg.cs_name.as_str().into())))
+                        .collect(),
+                },
+            ],
+        }];
+
+        let mut next_id = body.body_elements
+            .iter()
+            .filter_map(|e| e.max_abstract_id())
+            .max()
+            .map_or(0, |id| id + 1);
+        let mut returns = false;
+
+        for element in &body.body_elements {
+            match element {
+                BodyElement::Return { element: Some(value) } => {
+                    let result = AbstractIdent::Generated(next_id);
+                    next_id += 1;
+                    returns = true;
+
+                    body_elements.extend(vec![
+                        BodyElement::DeclareLocal {
+                            id: result.clone(),
+                            ty: self.wrapper_return_type(),
+                        },
+                        BodyElement::Assignment {
+                            lhs: Box::new(BodyElement::Ident(result.clone())),
+                            rhs: value.clone(),
+                        },
+                        exit(captured(&self.return_ty, BodyElement::Ident(result.clone()))),
+                        BodyElement::Return {
+                            element: Some(Box::new(BodyElement::Ident(result))),
+                        },
+                    ]);
+                }
+                other => body_elements.push(other.clone()),
+            }
+        }
+
+        if !returns {
+            body_elements.push(exit(BodyElement::LiteralValue(LiteralValue::Null)));
+        }
+
+        BindingMethodBody { body_elements }
+    }
+
+    /// Whether any of the method's arguments or return value are bound as C# pointers, eg function
+    /// pointers or pointers to opaque types, which can only be named in an unsafe context.
+    fn uses_pointers(&self) -> bool {
+        self.args
+            .iter()
+            .map(|arg| &arg.ty)
+            .chain(std::iter::once(&self.return_ty))
+            .any(|ty| ty.native_type().is_pointer())
+    }
+
+    /// The attributes placed on the wrapper methods, which only forward to the native method.
+    fn wrapper_attributes(&self) -> Vec<ast::Attribute> {
+        let mut attributes = Vec::new();
+        if self.debugger_step_through {
+            attributes.push(ast::Attribute::marker("System.Diagnostics.DebuggerStepThrough"));
+        }
+        if self.skip_locals_init {
+            attributes.push(skip_locals_init_attribute());
+        }
+        attributes
+    }
+
+    /// Generate the ast nodes for this bound method
+    /// 
+    /// This may be more than one method, eg if a thunk is needed to marshall arguments/return values to/from
+    /// an FFI stable representation.
+    pub fn to_ast_methods(&self) -> Vec<ast::Method> {
+        let mut methods = vec![
+            self.dll_imported_method(),
+            match &self.stream_target {
+                Some(target) => self.span_forwarding_method(target),
+                None => self.thunk_method(),
+            },
+        ];
+
+        // An owned buffer class calls the free thunk itself.
+        if self.owned_buffer_class.is_none() {
+            methods.extend(self.free_dll_imported_method());
+        }
+        methods.extend(self.validator_methods());
+        methods.extend(self.default_overloads());
+        methods.extend(self.fill_buffer_method());
+        methods.extend(self.async_method());
+        if let Some(target) = &self.stream_target {
+            methods.push(ast::Method {
+                is_public: false,
+                ..target.thunk_method()
+            });
+            methods.extend(target.validator_methods());
+            methods.push(self.stream_method(target));
+        }
+
+        methods
+    }
+
+    /// Calls the Span taking copy of the method, passing the Span given for each `&[u8]` argument
+    /// in its place, and returns its result if it has one.
+    fn stream_target_call(
+        &self,
+        target: &BindingMethod,
+        mut span: impl FnMut(&BindingMethodArgument) -> BodyElement,
+    ) -> BodyElement {
+        let invocation = BodyElement::MethodCall {
+            target: None,
+            method_name: target.cs_name.to_string(),
+            args: self.wrapper_args()
+                .map(|arg| if arg.is_stream_source() {
+                    span(arg)
+                } else {
+                    BodyElement::Ident(arg.cs_name.as_str().into())
+                })
+                .collect(),
+        };
+
+        match self.wrapper_return_type() {
+            ast::CSharpType::Void => invocation,
+            _ => BodyElement::Return {
+                element: Some(Box::new(invocation)),
+            },
+        }
+    }
+
+    /// The null checks of the `&[u8]` arguments, which the Span taking copy of the method can't
+    /// make itself.
+    fn stream_source_guards(&self) -> Vec<BodyElement> {
+        let stream_args: Vec<_> = self.args.iter().filter(|arg| arg.is_stream_source()).collect();
+        if self.debug_only_validation {
+            vec![validator_call(&self.cs_name, &stream_args)]
+        } else {
+            stream_args.iter().filter_map(|arg| arg.null_guard()).collect()
+        }
+    }
+
+    /// The wrapper method of a method with a Stream companion, which checks its arrays and hands
+    /// them to the Span taking copy as is.
+    fn span_forwarding_method(&self, target: &BindingMethod) -> ast::Method {
+        let forward = self.stream_target_call(target, |arg| BodyElement::NewObject {
+            ty: ast::CSharpType::Span {
+                elem_type: Box::new(ast::CSharpType::Byte),
+            },
+            args: vec![BodyElement::Ident(arg.cs_name.as_str().into())],
+        });
+        let body_elements = self.stream_source_guards().into_iter().chain(Some(forward)).collect();
+
+        ast::Method {
+            body: Some(BindingMethodBody { body_elements }.to_ast_nodes()),
+            ..self.thunk_method()
+        }
+    }
+
+    /// A companion of the wrapper method taking a Stream in place of each `&[u8]`, which is read to
+    /// the end into a buffer rented from the shared ArrayPool.
+    ///
+    /// It's named `...FromStream` rather than overloading the wrapper method, as a null argument
+    /// would be ambiguous between the array and Stream overloads. The buffers are returned to the
+    /// pool once the native method returns, even if it throws.
+    fn stream_method(&self, target: &BindingMethod) -> ast::Method {
+        let stream_args: Vec<_> = self.args.iter().filter(|arg| arg.is_stream_source()).collect();
+        let length = |idx: usize| AbstractIdent::Generated(2 * idx as u32);
+        let buffer = |idx: usize| AbstractIdent::Generated(2 * idx as u32 + 1);
+
+        let mut stream_idx = 0;
+        let mut body_elements = vec![self.stream_target_call(target, |_| {
+            stream_idx += 1;
+            BodyElement::NewObject {
+                ty: ast::CSharpType::Span {
+                    elem_type: Box::new(ast::CSharpType::Byte),
+                },
+                args: vec![
+                    BodyElement::Ident(buffer(stream_idx - 1)),
+                    BodyElement::LiteralValue(LiteralValue::Number(0)),
+                    BodyElement::Ident(length(stream_idx - 1)),
+                ],
+            }
+        })];
+
+        for (idx, arg) in stream_args.iter().enumerate().rev() {
+            body_elements = vec![
+                BodyElement::DeclareLocal {
+                    id: length(idx),
+                    ty: ast::CSharpType::Int32,
+                },
+                BodyElement::Assignment {
+                    lhs: Box::new(BodyElement::Ident(length(idx))),
+                    rhs: Box::new(BodyElement::LiteralValue(LiteralValue::Number(0))),
+                },
+                BodyElement::DeclareLocal {
+                    id: buffer(idx),
+                    ty: ast::CSharpType::Array {
+                        elem_type: Box::new(ast::CSharpType::Byte),
+                    },
+                },
+                BodyElement::Assignment {
+                    lhs: Box::new(BodyElement::Ident(buffer(idx))),
+                    rhs: Box::new(BodyElement::MethodCall {
+                        target: Some(Box::new(BodyElement::Ident(POOLED_STREAM_CLASS.into()))),
+                        method_name: "ReadToEnd".to_string(),
+                        args: vec![
+                            BodyElement::Ident(arg.cs_name.as_str().into()),
+                            BodyElement::Ref {
+                                element: Box::new(BodyElement::Ident(length(idx))),
+                            },
+                        ],
+                    }),
+                },
+                BodyElement::TryFinally {
+                    body: body_elements,
+                    finally: vec![BodyElement::MethodCall {
+                        target: Some(Box::new(BodyElement::Ident(SHARED_BYTE_POOL.into()))),
+                        method_name: "Return".to_string(),
+                        args: vec![BodyElement::Ident(buffer(idx))],
+                    }],
+                },
+            ];
+        }
+
+        let body_elements = self.stream_source_guards().into_iter().chain(body_elements).collect();
+
+        let args = self.wrapper_args()
+            .map(|arg| ast::MethodArgument {
+                attributes: Vec::new(),
+                name: arg.cs_name.as_str().into(),
+                ty: if arg.is_stream_source() {
+                    ast::CSharpType::named("System.IO.Stream")
+                } else {
+                    arg.ty.idiomatic_type()
+                },
+            })
+            .collect();
+
+        ast::Method {
+            attributes: self.wrapper_attributes(),
+            is_public: true,
+            is_static: true,
+            is_extern: false,
+            is_unsafe: self.uses_pointers(),
+            is_override: false,
+            is_partial: false,
+            name: format!("{}FromStream", self.cs_name),
+            return_ty: self.wrapper_return_type(),
+            args,
+            body: Some(BindingMethodBody { body_elements }.to_ast_nodes()),
+        }
+    }
+
+    /// The arguments taken by the idiomatic wrapper method, which omit the length argument of a
+    /// `returns_buffer` method.
+    fn wrapper_args(&self) -> impl Iterator<Item = &BindingMethodArgument> {
+        self.args
+            .iter()
+            .filter(move |arg| self.buffer_len_arg.as_ref() != Some(&arg.rust_name))
+    }
+
+    /// With `debug_only_validation`, the `[Conditional("DEBUG")]` validators holding the null checks
+    /// of the wrapper method, and of its Stream companion if it has one.
+    ///
+    /// With a Stream companion, the wrapper method only checks its arrays, as the Span taking copy it
+    /// forwards to checks everything else.
+    fn validator_methods(&self) -> Vec<ast::Method> {
+        if !self.debug_only_validation {
+            return Vec::new();
+        }
+
+        let wrapper_args = self.args
+            .iter()
+            .filter(|arg| arg.null_guard().is_some())
+            .filter(|arg| self.stream_target.is_none() || arg.is_stream_source())
+            .map(|arg| (arg, arg.ty.idiomatic_type()));
+        let stream_args = self.stream_target
+            .iter()
+            .flat_map(|_| self.args.iter().filter(|arg| arg.is_stream_source()))
+            .map(|arg| (arg, ast::CSharpType::named("System.IO.Stream")));
+
+        vec![wrapper_args.collect::<Vec<_>>(), stream_args.collect()]
+            .into_iter()
+            .filter(|guarded| !guarded.is_empty())
+            .map(|guarded| ast::Method {
+                attributes: vec![ast::Attribute {
+                    name: "System.Diagnostics.Conditional".to_string(),
+                    positional_parameters: vec![ast::LiteralValue::QuotedString("DEBUG".to_string())],
+                    named_parameters: Vec::new(),
+                }],
+                is_public: false,
+                is_static: true,
+                is_extern: false,
+                is_unsafe: false,
+                is_override: false,
+                is_partial: false,
+                name: validator_name(&self.cs_name),
+                return_ty: ast::CSharpType::Void,
+                args: guarded
+                    .iter()
+                    .map(|(arg, ty)| ast::MethodArgument {
+                        attributes: Vec::new(),
+                        name: arg.cs_name.as_str().into(),
+                        ty: ty.clone(),
+                    })
+                    .collect(),
+                body: Some(BindingMethodBody {
+                    body_elements: guarded.iter().filter_map(|(arg, _)| arg.null_guard()).collect(),
+                }.to_ast_nodes()),
+            })
+            .collect()
+    }
+
+    /// Overloads of the wrapper method omitting its trailing arguments with default values, one for
+    /// each number of them omitted, which pass the defaults in their place.
+    fn default_overloads(&self) -> Vec<ast::Method> {
+        let args: Vec<_> = self.wrapper_args().collect();
+        let required = args
+            .iter()
+            .rposition(|arg| arg.default.is_none())
+            .map_or(0, |idx| idx + 1);
+
+        (required..args.len())
+            .map(|len| {
+                let invocation = BodyElement::MethodCall {
+                    target: None,
+                    method_name: self.cs_name.to_string(),
+                    args: args
+                        .iter()
+                        .enumerate()
+                        .map(|(idx, arg)| match arg.default_literal() {
+                            Some(literal) if idx >= len => BodyElement::LiteralValue(literal),
+                            _ => BodyElement::Ident(arg.cs_name.as_str().into()),
+                        })
+                        .collect(),
+                };
+
+                let body_elements = match self.wrapper_return_type() {
+                    ast::CSharpType::Void => vec![invocation],
+                    _ => vec![BodyElement::Return {
+                        element: Some(Box::new(invocation)),
+                    }],
+                };
+
+                ast::Method {
+                    attributes: self.wrapper_attributes(),
+                    is_public: true,
+                    is_static: self.receiver.is_none(),
+                    is_extern: false,
+                    is_unsafe: self.uses_pointers(),
+                    is_override: false,
+                    is_partial: false,
+                    name: self.cs_name.to_string(),
+                    return_ty: self.wrapper_return_type(),
+                    args: args[..len]
+                        .iter()
+                        .map(|arg| ast::MethodArgument {
+                            attributes: Vec::new(),
+                            name: arg.cs_name.as_str().into(),
+                            ty: arg.ty.idiomatic_type(),
+                        })
+                        .collect(),
+                    body: Some(BindingMethodBody { body_elements }.to_ast_nodes()),
+                }
+            })
+            .collect()
+    }
+
+    /// Why the method can't have an `...Async` overload, if it can't.
+    ///
+    /// Instance methods of structs and methods taking a Span, a by-ref argument, or a pointer
+    /// can't be captured by the lambda.
+    fn async_unsupported_reason(&self) -> Option<&'static str> {
+        let takes_span = self.wrapper_args().any(|arg| matches!(
+            arg.ty.idiomatic_type(),
+            ast::CSharpType::Span { .. } | ast::CSharpType::Ref { .. }
+        ));
+
+        if self.receiver.is_some() {
+            Some("is an instance method")
+        } else if takes_span {
+            Some("takes a Span or a by-ref argument")
+        } else if self.uses_pointers() {
+            Some("takes or returns a pointer")
+        } else {
+            None
+        }
+    }
+
+    /// An `...Async` overload of the wrapper method, which runs it on the thread pool.
+    fn async_method(&self) -> Option<ast::Method> {
+        if !self.async_wrapper || self.async_unsupported_reason().is_some() {
+            return None;
+        }
+
+        let return_ty = match self.wrapper_return_type() {
+            ast::CSharpType::Void => ast::CSharpType::named("System.Threading.Tasks.Task"),
+            ty => ast::CSharpType::Named {
+                path: format!("System.Threading.Tasks.Task<{}>", ty),
+            },
+        };
+
+        let invocation = BodyElement::MethodCall {
+            target: None,
+            method_name: self.cs_name.to_string(),
+            args: self.wrapper_args()
+                .map(|arg| BodyElement::Ident(arg.cs_name.as_str().into()))
+                .collect(),
+        };
+
+        let body_elements = vec![BodyElement::Return {
+            element: Some(Box::new(BodyElement::MethodCall {
+                target: Some(Box::new(BodyElement::Ident("System.Threading.Tasks.Task".into()))),
+                method_name: "Run".to_string(),
+                args: vec![BodyElement::Lambda {
+                    args: Vec::new(),
+                    body: Box::new(invocation),
+                }],
+            })),
+        }];
+
+        let args = self.wrapper_args()
+            .map(|arg| ast::MethodArgument {
+                attributes: Vec::new(),
+                name: arg.cs_name.as_str().into(),
+                ty: arg.ty.idiomatic_type(),
+            })
+            .collect();
+
+        Some(ast::Method {
+            attributes: Vec::new(),
+            is_public: true,
+            is_static: true,
+            is_extern: false,
+            is_unsafe: false,
+            is_override: false,
+            is_partial: false,
+            name: format!("{}Async", self.cs_name),
+            return_ty,
+            args,
+            body: Some(BindingMethodBody { body_elements }.to_ast_nodes()),
+        })
+    }
+
+    /// Finds the byte buffer argument of a "fill my buffer, tell me the size" style method, as
+    /// marked with `#[dotnet_bindgen(fill_buffer)]`.
+    ///
+    /// Such methods take a `&mut [u8]`, and return a `usize` which is the size of buffer required.
+    /// If the given buffer is too small, nothing is written to it.
+    fn fill_buffer_arg_index(&self) -> Option<usize> {
+        let buffer_arg = self.fill_buffer_arg.as_ref()?;
+        self.args.iter().position(|arg| &arg.rust_name == buffer_arg)
+    }
+
+    /// An overload of the wrapper method which omits the byte buffer argument of a fill-buffer
+    /// style method, and instead returns a freshly allocated array of exactly the right size.
+    ///
+    /// The wrapper is called twice, first with an empty buffer to query the required size. If the
+    /// second call reports a different size, the buffer wasn't filled as expected, so it throws.
+    fn fill_buffer_method(&self) -> Option<ast::Method> {
+        let buffer_idx = self.fill_buffer_arg_index()?;
+
+        let byte_array = ast::CSharpType::Array {
+            elem_type: Box::new(ast::CSharpType::Byte),
+        };
+
+        let invocation_args = self.args
+            .iter()
+            .enumerate()
+            .map(|(idx, arg)| BodyElement::Ident(if idx == buffer_idx {
+                AbstractIdent::Generated(0)
+            } else {
+                AbstractIdent::Explicit(arg.cs_name.to_string())
+            }))
+            .collect();
+
+        let invocation = BodyElement::MethodCall {
+            target: None,
+            method_name: self.cs_name.to_string(),
+            args: invocation_args,
+        };
+
+        let body_elements = vec![
+            BodyElement::DeclareLocal {
+                id: 0.into(),
+                ty: byte_array.clone(),
+            },
+            BodyElement::Assignment {
+                lhs: Box::new(BodyElement::Ident(0.into())),
+                rhs: Box::new(BodyElement::NewArray {
+                    elem_type: ast::CSharpType::Byte,
+                    len: Box::new(BodyElement::LiteralValue(LiteralValue::Number(0))),
+                }),
+            },
+            BodyElement::DeclareLocal {
+                id: 1.into(),
+                ty: self.return_ty.native_type(),
+            },
+            BodyElement::Assignment {
+                lhs: Box::new(BodyElement::Ident(1.into())),
+                rhs: Box::new(invocation.clone()),
+            },
+            BodyElement::Assignment {
+                lhs: Box::new(BodyElement::Ident(0.into())),
+                rhs: Box::new(BodyElement::NewArray {
+                    elem_type: ast::CSharpType::Byte,
+                    len: Box::new(BodyElement::Ident(1.into())),
+                }),
+            },
+            BodyElement::DeclareLocal {
+                id: 2.into(),
+                ty: self.return_ty.native_type(),
+            },
+            BodyElement::Assignment {
+                lhs: Box::new(BodyElement::Ident(2.into())),
+                rhs: Box::new(invocation),
+            },
+            BodyElement::ThrowIf {
+                condition: Box::new(BodyElement::BinaryExpression {
+                    lhs: Box::new(BodyElement::Ident(2.into())),
+                    rhs: Box::new(BodyElement::Ident(1.into())),
+                    operation: BinaryOperation::NotEqual,
+                }),
+                exception: Box::new(BodyElement::NewObject {
+                    ty: ast::CSharpType::named("InvalidOperationException"),
+                    args: vec![BodyElement::LiteralValue(LiteralValue::String(format!(
+                        "{} reported a different buffer size on the second call",
+                        self.rust_name,
+                    )))],
+                }),
+            },
+            BodyElement::Return {
+                element: Some(Box::new(BodyElement::Ident(0.into()))),
+            },
+        ];
+
+        let args = self.args
+            .iter()
+            .enumerate()
+            .filter(|(idx, _)| *idx != buffer_idx)
+            .map(|(_, arg)| ast::MethodArgument {
+                attributes: Vec::new(),
+                name: arg.cs_name.as_str().into(),
+                ty: arg.ty.idiomatic_type(),
+            })
+            .collect();
+
+        Some(ast::Method {
+            attributes: self.wrapper_attributes(),
+            is_public: true,
+            is_static: self.receiver.is_none(),
+            is_extern: false,
+            is_unsafe: self.uses_pointers(),
+            is_override: false,
+            is_partial: false,
+            name: self.cs_name.to_string(),
+            return_ty: byte_array,
+            args,
+            body: Some(BindingMethodBody { body_elements }.to_ast_nodes()),
+        })
+    }
+
+    /// The return type of the idiomatic wrapper method.
+    ///
+    /// TODO: Make this always the idiomatic type + add the relevant marshalling to the body.
+    fn wrapper_return_type(&self) -> ast::CSharpType {
+        if self.buffer_len_arg.is_some() {
+            return ast::CSharpType::Array {
+                elem_type: Box::new(ast::CSharpType::Byte),
+            };
+        }
+
+        if let Some(pair) = &self.dictionary {
+            return pair.dictionary_type();
+        }
+
+        if let Some(class) = &self.owned_buffer_class {
+            return ast::CSharpType::Struct {
+                name: class.as_str().into(),
+            };
+        }
+
+        if self.native_slice {
+            return native_slice_type(&self.return_ty);
+        }
+
+        match self.return_ty.descriptor() {
+            Some(core::BindgenTypeDescriptor::Duration) => self.return_ty.idiomatic_type(),
+            Some(desc) if int128_type_name(desc).is_some() => self.return_ty.idiomatic_type(),
+            Some(desc) if is_copied_out(desc) => self.return_ty.idiomatic_type(),
+            _ => self.return_ty.native_type(),
+        }
+    }
+
+    fn calling_convention_name(&self) -> &'static str {
+        match self.calling_convention {
+            core::BindgenCallingConvention::C => "Cdecl",
+            core::BindgenCallingConvention::System => "StdCall",
+        }
+    }
+
+    /// The P/Invoke for the thunk which frees an owned buffer returned by this method, if it
+    /// returns one.
+    fn free_dll_imported_method(&self) -> Option<ast::Method> {
+        if !matches!(self.return_ty.descriptor(), Some(desc) if is_owned(desc)) {
+            return None;
+        }
+
+        let name = self.rust_free_thunk_name.clone()?;
+
+        Some(ast::Method {
+            attributes: vec![ast::Attribute::dll_import(
+                &self.binary_name,
+                &name,
+                &name,
+                self.calling_convention_name(),
+            )],
+            is_public: false,
+            is_static: true,
+            is_extern: true,
+            is_unsafe: false,
+            is_override: false,
+            is_partial: false,
+            name,
+            return_ty: ast::CSharpType::Void,
+            args: vec![ast::MethodArgument {
+                attributes: Vec::new(),
+                name: "value".into(),
+                ty: self.return_ty.native_type(),
+            }],
+            body: None,
+        })
+    }
+
+    /// The SafeHandle class for an Arc returned by this method, if it returns one.
+    ///
+    /// `ReleaseHandle` drops the handle's reference, and `DangerousCloneRaw` takes a new one, to
+    /// hand over to a function taking the Arc by value.
+    fn handle_obj(&self) -> Option<ast::Object> {
+        let class_name = match self.return_ty.descriptor()? {
+            core::BindgenTypeDescriptor::Arc { name } => handle_class_name(name),
+            _ => return None,
+        };
+        let clone_thunk_name = self.rust_clone_thunk_name.clone()?;
+        let free_thunk_name = self.rust_free_thunk_name.clone()?;
+
+        let dll_import = |name: &str, return_ty, arg_ty| ast::Method {
+            attributes: vec![ast::Attribute::dll_import(
+                &self.binary_name,
+                name,
+                name,
+                self.calling_convention_name(),
+            )],
+            is_public: false,
+            is_static: true,
+            is_extern: true,
+            is_unsafe: false,
+            is_override: false,
+            is_partial: false,
+            name: name.to_string(),
+            return_ty,
+            args: vec![ast::MethodArgument {
+                attributes: Vec::new(),
+                name: "handle".into(),
+                ty: arg_ty,
+            }],
+            body: None,
+        };
+        let method = |is_override: bool, name: &str, return_ty, body_elements| ast::Method {
+            attributes: Vec::new(),
+            is_public: !is_override,
+            is_static: false,
+            is_extern: false,
+            is_unsafe: false,
+            is_override,
+            is_partial: false,
+            name: name.to_string(),
+            return_ty,
+            args: Vec::new(),
+            body: Some(BindingMethodBody { body_elements }.to_ast_nodes()),
+        };
+        let call = |method_name: &str, arg: &str| BodyElement::MethodCall {
+            target: None,
+            method_name: method_name.to_string(),
+            args: vec![BodyElement::Ident(arg.into())],
+        };
+
+        // The marshaller needs a parameterless constructor to create the handle for a return.
+        let constructor = ast::Constructor {
+            kind: ast::ConstructorKind::Base(vec![ast::LiteralValue::Bool(true)]),
+            object_name: class_name.clone(),
+            args: Vec::new(),
+            body: Vec::new(),
+        };
+
+        let clone_raw = method(
+            false,
+            "DangerousCloneRaw",
+            ast::CSharpType::intptr(),
+            vec![BodyElement::Return {
+                element: Some(Box::new(call(&clone_thunk_name, "this"))),
+            }],
+        );
+
+        let release_handle = method(
+            true,
+            "ReleaseHandle",
+            ast::CSharpType::Bool,
+            vec![
+                call(&free_thunk_name, "handle"),
+                BodyElement::Return {
+                    element: Some(Box::new(BodyElement::LiteralValue(LiteralValue::Bool(true)))),
+                },
+            ],
+        );
+
+        Some(ast::Object {
+            attributes: Vec::new(),
+            object_type: ast::ObjectType::Class,
+            is_static: false,
+            is_sealed: true,
+            is_readonly: false,
+            is_ref: false,
+            is_unsafe: false,
+            is_partial: false,
+            is_private: false,
+            is_internal: false,
+            name: class_name.clone(),
+            base_types: vec![ast::CSharpType::named(
+                "Microsoft.Win32.SafeHandles.SafeHandleZeroOrMinusOneIsInvalid",
+            )],
+            constructors: vec![constructor],
+            methods: vec![
+                dll_import(
+                    &clone_thunk_name,
+                    ast::CSharpType::intptr(),
+                    ast::CSharpType::Struct {
+                        name: class_name.as_str().into(),
+                    },
+                ),
+                dll_import(&free_thunk_name, ast::CSharpType::Void, ast::CSharpType::intptr()),
+                clone_raw,
+                release_handle,
+            ],
+            fields: Vec::new(),
+            properties: Vec::new(),
+            nested: Vec::new(),
+        })
+    }
+
+    /// The blittable `...TupleAbi` struct a tuple returned by this method crosses the boundary as,
+    /// with a field for each element named as in the ValueTuple it's copied into.
+    fn tuple_abi_obj(&self) -> Option<ast::Object> {
+        let elements = match self.return_ty.descriptor()? {
+            core::BindgenTypeDescriptor::Tuple { elements } => elements,
+            _ => return None,
+        };
+
+        let fields = elements
+            .iter()
+            .enumerate()
+            .map(|(idx, element)| ast::Field {
+                is_public: true,
+                is_readonly: false,
+                name: format!("Item{}", idx + 1),
+                ty: BindingType::try_from(element.clone())
+                    .expect("Tuple elements are checked when binding the method")
+                    .native_type(),
+                const_value: None,
+                fixed_len: None,
+                is_static: false,
+                initializer: None,
+            })
+            .collect();
+
+        Some(ast::Object {
+            attributes: vec![ast::Attribute::struct_layout(ast::LayoutKind::Sequential)],
+            object_type: ast::ObjectType::Struct,
+            is_static: false,
+            is_sealed: false,
+            is_readonly: false,
+            is_ref: false,
+            is_unsafe: false,
+            is_partial: false,
+            is_private: false,
+            is_internal: false,
+            name: self.return_ty.native_type().to_string(),
+            base_types: Vec::new(),
+            constructors: Vec::new(),
+            methods: Vec::new(),
+            fields,
+            properties: Vec::new(),
+            nested: Vec::new(),
+        })
+    }
+
+    /// A disposable class owning a slice returned by this method, which exposes it as a Span
+    /// without copying, and frees it on Dispose or finalization.
+    fn owned_buffer_obj(&self) -> Option<ast::Object> {
+        let class_name = self.owned_buffer_class.clone()?;
+
+        let elem_type = match self.return_ty.idiomatic_type() {
+            ast::CSharpType::Array { elem_type } => *elem_type,
+            _ => unreachable!(),
+        };
+        let abi_ty = self.return_ty.native_type();
+        let this_abi = || BodyElement::FieldAccess {
+            element: Box::new(BodyElement::Ident("this".into())),
+            field_name: "abi".to_string(),
+        };
+        let call_free = || BodyElement::MethodCall {
+            target: None,
+            method_name: "Free".to_string(),
+            args: Vec::new(),
+        };
+        let method = |is_public, is_unsafe, name: &str, return_ty, body_elements| ast::Method {
+            attributes: Vec::new(),
+            is_public,
+            is_static: false,
+            is_extern: false,
+            is_unsafe,
+            is_override: false,
+            is_partial: false,
+            name: name.to_string(),
+            return_ty,
+            args: Vec::new(),
+            body: Some(BindingMethodBody { body_elements }.to_ast_nodes()),
+        };
+
+        let constructor = ast::Constructor {
+            kind: ast::ConstructorKind::Instance,
+            object_name: class_name.clone(),
+            args: vec![ast::MethodArgument {
+                attributes: Vec::new(),
+                name: "abi".into(),
+                ty: abi_ty.clone(),
+            }],
+            body: BindingMethodBody {
+                body_elements: vec![BodyElement::Assignment {
+                    lhs: Box::new(this_abi()),
+                    rhs: Box::new(BodyElement::Ident("abi".into())),
+                }],
+            }.to_ast_nodes(),
+        };
+
+        let finalizer = ast::Constructor {
+            kind: ast::ConstructorKind::Finalizer,
+            object_name: class_name.clone(),
+            args: Vec::new(),
+            body: BindingMethodBody {
+                body_elements: vec![call_free()],
+            }.to_ast_nodes(),
+        };
+
+        let as_span = method(
+            true,
+            true,
+            "AsSpan",
+            ast::CSharpType::Span {
+                elem_type: Box::new(elem_type.clone()),
+            },
+            vec![BodyElement::Return {
+                element: Some(Box::new(owned_span(this_abi(), elem_type))),
+            }],
+        );
+
+        let dispose = method(
+            true,
+            false,
+            "Dispose",
+            ast::CSharpType::Void,
+            vec![
+                call_free(),
+                BodyElement::MethodCall {
+                    target: Some(Box::new(BodyElement::Ident("GC".into()))),
+                    method_name: "SuppressFinalize".to_string(),
+                    args: vec![BodyElement::Ident("this".into())],
+                },
+            ],
+        );
+
+        // Resetting to an empty buffer makes freeing it again a no-op, so Dispose can safely be
+        // called more than once.
+        let free = method(
+            false,
+            false,
+            "Free",
+            ast::CSharpType::Void,
+            vec![
+                BodyElement::MethodCall {
+                    target: None,
+                    method_name: self.rust_free_thunk_name.clone()?,
+                    args: vec![this_abi()],
+                },
+                BodyElement::Assignment {
+                    lhs: Box::new(this_abi()),
+                    rhs: Box::new(BodyElement::NewObject {
+                        ty: abi_ty.clone(),
+                        args: Vec::new(),
+                    }),
+                },
+            ],
+        );
+
+        Some(ast::Object {
+            attributes: Vec::new(),
+            object_type: ast::ObjectType::Class,
+            is_static: false,
+            is_sealed: true,
+            is_readonly: false,
+            is_ref: false,
+            is_unsafe: false,
+            is_partial: false,
+            is_private: false,
+            is_internal: false,
+            name: class_name.clone(),
+            base_types: vec![ast::CSharpType::Struct {
+                name: "IDisposable".into(),
+            }],
+            constructors: vec![constructor, finalizer],
+            methods: vec![as_span, dispose, free, self.free_dll_imported_method()?],
+            fields: vec![ast::Field {
+                is_public: false,
+                is_readonly: false,
+                name: "abi".to_string(),
+                ty: abi_ty,
+                const_value: None,
+                fixed_len: None,
+                is_static: false,
+                initializer: None,
+            }],
+            properties: Vec::new(),
+            nested: Vec::new(),
+        })
+    }
+
+    fn dll_imported_method(&self) -> ast::Method {
+        let name = self.dll_import_name.to_string();
+
+        let mut attributes = if self.library_import {
+            vec![
+                ast::Attribute::library_import(&self.binary_name, &name, &self.symbol_name),
+                ast::Attribute::unmanaged_call_conv(self.calling_convention_name()),
+            ]
+        } else {
+            vec![ast::Attribute::dll_import(
+                &self.binary_name,
+                &name,
+                &self.symbol_name,
+                self.calling_convention_name(),
+            )]
+        };
+
+        if self.no_gc_transition {
+            attributes.push(ast::Attribute::marker("SuppressGCTransition"));
+        }
+
+        let return_ty = self.return_ty.native_type();
+
+        let mut args: Vec<_> = self.args
+            .iter()
+            .map(|arg| match &arg.marshaller {
+                Some(marshaller) => ast::MethodArgument {
+                    attributes: vec![marshaller.marshal_using_attribute()],
+                    name: arg.rust_name.as_str().into(),
+                    ty: arg.ty.idiomatic_type(),
+                },
+                None => ast::MethodArgument {
+                    attributes: Vec::new(),
+                    name: arg.rust_name.as_str().into(),
+                    ty: if self.buffer_len_arg.as_ref() == Some(&arg.rust_name) {
+                        ast::CSharpType::Ref {
+                            target: Box::new(buffer_len_type()),
+                            is_readonly: false,
+                        }
+                    } else {
+                        arg.ty.native_type()
+                    },
+                },
+            })
+            .collect();
+
+        if self.reports_invalid_args {
+            args.push(ast::MethodArgument {
+                attributes: Vec::new(),
+                name: "invalidArg".into(),
+                ty: ast::CSharpType::Ref {
+                    target: Box::new(ast::CSharpType::Byte),
+                    is_readonly: false,
+                },
+            });
+        }
+
+        if let (Some(receiver), Some(owner)) = (self.receiver, &self.owner) {
+            let owner_ty = ast::CSharpType::Struct {
+                name: owner.as_str().into(),
+            };
+
+            // Rust references are just pointers, which is exactly how ref/in parameters of
+            // blittable types are marshalled.
+            let ty = match receiver {
+                core::BindgenReceiver::Value => owner_ty,
+                core::BindgenReceiver::Ref => ast::CSharpType::Ref {
+                    target: Box::new(owner_ty),
+                    is_readonly: true,
+                },
+                core::BindgenReceiver::RefMut => ast::CSharpType::Ref {
+                    target: Box::new(owner_ty),
+                    is_readonly: false,
+                },
+            };
+
+            args.insert(0, ast::MethodArgument {
+                attributes: Vec::new(),
+                name: "self".into(),
+                ty,
+            });
+        }
+
+        ast::Method {
+            attributes,
+            is_public: false,
+            is_static: true,
+            is_extern: !self.library_import,
+            is_unsafe: self.uses_pointers(),
+            is_override: false,
+            is_partial: self.library_import,
+            name,
+            return_ty,
+            args,
+            body: None,
+        }
+    }
+
+    fn thunk_method(&self) -> ast::Method {
+        let attributes = self.wrapper_attributes();
+
+        let name = self.cs_name.to_string();
+
+        let return_ty = self.wrapper_return_type();
+
+        let args = self.wrapper_args()
+            .map(|arg| ast::MethodArgument {
+                attributes: Vec::new(),
+                name: arg.cs_name.as_str().into(),
+                ty: arg.ty.idiomatic_type(),
+            })
+            .collect();
+        
+        let thunk_body = self.cs_thunk_body.as_ref().unwrap();
+        let body = Some(if self.trace_hooks {
+            self.traced_body(thunk_body).to_ast_nodes()
+        } else {
+            thunk_body.to_ast_nodes()
+        });
+
+        ast::Method {
+            attributes,
+            is_public: true,
+            is_static: self.receiver.is_none(),
+            is_extern: false,
+            is_unsafe: self.uses_pointers(),
+            is_override: false,
+            is_partial: false,
+            name,
+            return_ty,
+            args,
+            body,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct BindingStructField {
+    /// The name of this field in the generated C# (CamelCase transform rust_name)
+    cs_name: String,
+
+    /// The type of this field. Restricted to simple binding types to make the entire struct FFI stable.
+    ///
+    /// For a fixed buffer, this is the type of its elements. For a function pointer with
+    /// `function_pointers`, this is its `delegate* unmanaged[Cdecl]<...>` type.
+    ty: SimpleBindingType,
+
+    /// The number of elements, if this is an inline `fixed` buffer of an array field
+    fixed_len: Option<u64>,
+}
+
+impl BindingStructField {
+    fn new(descriptor: &core::BindgenStructFieldDescriptor, function_pointers: bool) -> Result<Self, &'static str> {
+        let cs_name = descriptor.name.to_camel_case();
+
+        let ty = match descriptor.ty.clone().try_into() {
+            Ok(BindingType::Simple(mut s)) => {
+                if function_pointers {
+                    if let Some(cs_type) = function_pointer_type(&descriptor.ty)? {
+                        s.cs_type = cs_type;
+                    }
+                }
+                s
+            }
+            // Only bound in the `...Abi` struct, as the type it crosses the boundary as
+            Ok(BindingType::Complex(c)) if is_buffer_field(&descriptor.ty) => SimpleBindingType {
+                descriptor: Some(descriptor.ty.clone()),
+                cs_type: c.thunk_type,
+            },
+            Ok(_) => return Err("Can't create bindings for structs with non-ffi-stable fields"),
+            // Arrays which don't map to a System.Numerics vector are inlined as fixed buffers
+            Err(e) => match &descriptor.ty {
+                core::BindgenTypeDescriptor::Array { elem_type, len } => {
+                    return Ok(Self {
+                        cs_name,
+                        ty: fixed_buffer_elem_type(elem_type)?,
+                        fixed_len: Some(*len),
+                    })
+                }
+                _ => return Err(e),
+            },
+        };
+
+        Ok(Self {
+            cs_name,
+            ty,
+            fixed_len: None,
+        })
+    }
+
+    fn to_ast_field(&self, is_readonly: bool) -> ast::Field {
+        ast::Field {
+            is_public: true,
+            is_readonly,
+            name: self.cs_name.clone(),
+            ty: self.ty.cs_type.clone(),
+            const_value: None,
+            fixed_len: self.fixed_len,
+            is_static: false,
+            initializer: None,
+        }
+    }
+
+    fn to_ast_property(&self) -> Option<ast::Property> {
+        let len = self.fixed_len?;
+        Some(fixed_buffer_span_property(&self.cs_name, &self.ty.cs_type, len))
+    }
+}
+
+/// The element type of an array struct field, which C# only allows to be a primitive integer or
+/// float when inlined as a fixed buffer.
+fn fixed_buffer_elem_type(
+    elem_type: &core::BindgenTypeDescriptor,
+) -> Result<SimpleBindingType, &'static str> {
+    match elem_type.clone().try_into()? {
+        BindingType::Simple(s)
+            if s.cs_type.integral_keyword().is_some()
+                || matches!(s.cs_type, ast::CSharpType::Single | ast::CSharpType::Double) =>
+        {
+            Ok(s)
+        }
+        _ => Err("Array struct fields must be arrays of integers or floats"),
+    }
+}
+
+/// A `Span` over the whole of a fixed buffer, named after the buffer, eg
+///     `public unsafe Span<Byte> BytesSpan { get { return MemoryMarshal.CreateSpan(ref Bytes[0], 32); } }`
+///
+/// `MemoryMarshal.CreateSpan` isn't available on netstandard2.0, so the property is only compiled
+/// for frameworks which have it.
+fn fixed_buffer_span_property(buffer_name: &str, elem_ty: &ast::CSharpType, len: u64) -> ast::Property {
+    let body_elements = vec![BodyElement::Return {
+        element: Some(Box::new(BodyElement::MethodCall {
+            target: Some(Box::new(BodyElement::Ident("System.Runtime.InteropServices.MemoryMarshal".into()))),
+            method_name: "CreateSpan".to_string(),
+            args: vec![
+                BodyElement::Ref {
+                    element: Box::new(BodyElement::Ident(format!("{}[0]", buffer_name).as_str().into())),
+                },
+                BodyElement::LiteralValue(LiteralValue::Number(len as i64)),
+            ],
+        })),
+    }];
+
+    ast::Property {
+        is_unsafe: true,
+        name: format!("{}Span", buffer_name),
+        ty: ast::CSharpType::Span {
+            elem_type: Box::new(elem_ty.clone()),
+        },
+        getter: BindingMethodBody { body_elements }.to_ast_nodes(),
+        condition: Some("NETCOREAPP2_1_OR_GREATER || NETSTANDARD2_1_OR_GREATER".to_string()),
+    }
+}
+
+/// The name of a bound struct in the generated C#.
+///
+/// Both languages use CamelCase for type names, so this is the Rust name unless overridden.
+fn struct_cs_name(descriptor: &core::BindgenStructDescriptor) -> &str {
+    descriptor.cs_name.as_deref().unwrap_or(&descriptor.name)
+}
+
+/// The inline byte buffer of a `fixed_string` struct, which precedes its length field.
+struct FixedStringBuffer {
+    /// The name of the buffer field in the generated C#
+    cs_name: String,
+
+    /// The capacity of the buffer in bytes
+    capacity: u64,
+}
+
+impl FixedStringBuffer {
+    /// The name of the property reading the buffer as a string
+    const PROPERTY_NAME: &'static str = "Value";
+
+    fn new(descriptor: &core::BindgenStructDescriptor) -> Result<Self, &'static str> {
+        use dotnet_bindgen_core::BindgenTypeDescriptor as Desc;
+
+        let byte = Desc::Int { width: 8, signed: false };
+        match &descriptor.fields[..] {
+            [bytes, len] if len.ty == byte => match &bytes.ty {
+                Desc::Array { elem_type, len } if **elem_type == byte => Ok(Self {
+                    cs_name: bytes.name.to_camel_case(),
+                    capacity: *len,
+                }),
+                _ => Err("fixed_string structs must have exactly two fields, of types [u8; N] and u8"),
+            },
+            _ => Err("fixed_string structs must have exactly two fields, of types [u8; N] and u8"),
+        }
+    }
+
+    fn to_ast_field(&self) -> ast::Field {
+        ast::Field {
+            is_public: true,
+            is_readonly: false,
+            name: self.cs_name.clone(),
+            ty: ast::CSharpType::Byte,
+            const_value: None,
+            fixed_len: Some(self.capacity),
+            is_static: false,
+            initializer: None,
+        }
+    }
+
+    /// Decodes the first `len` bytes of the buffer as UTF-8, eg
+    ///     `fixed (Byte* _gen0 = Bytes) { return Encoding.UTF8.GetString(_gen0, System.Math.Min(Len, 32)); }`
+    ///
+    /// Math is qualified, as a `module = "Math"` class would otherwise be found first.
+    fn to_ast_property(&self, len: &BindingStructField) -> ast::Property {
+        let len = BodyElement::MethodCall {
+            target: Some(Box::new(BodyElement::Ident("System.Math".into()))),
+            method_name: "Min".to_string(),
+            args: vec![
+                BodyElement::Ident(len.cs_name.as_str().into()),
+                BodyElement::LiteralValue(LiteralValue::Number(self.capacity as i64)),
+            ],
+        };
+
+        let body_elements = vec![
+            BodyElement::FixedAssignment {
+                ty: ast::CSharpType::Ptr {
+                    target: Box::new(ast::CSharpType::Byte),
+                },
+                id: 0.into(),
+                rhs: Box::new(BodyElement::Ident(self.cs_name.as_str().into())),
+            },
+            BodyElement::Return {
+                element: Some(Box::new(BodyElement::MethodCall {
+                    target: Some(Box::new(BodyElement::Ident("System.Text.Encoding.UTF8".into()))),
+                    method_name: "GetString".to_string(),
+                    args: vec![BodyElement::Ident(0.into()), len],
+                })),
+            },
+        ];
+
+        ast::Property {
+            is_unsafe: true,
+            name: Self::PROPERTY_NAME.to_string(),
+            ty: ast::CSharpType::String,
+            getter: BindingMethodBody { body_elements }.to_ast_nodes(),
+            condition: None,
+        }
+    }
+
+    fn to_ast_span_property(&self) -> ast::Property {
+        fixed_buffer_span_property(&self.cs_name, &ast::CSharpType::Byte, self.capacity)
+    }
+}
+
+struct BindingStruct {
+    /// The name of the struct in the generated C#
+    name: String,
+
+    /// Ordered set of fields. Repr(C) in Rust should map 1-1 with C# StructLayout.Sequential
+    ///
+    /// The buffer of a `fixed_string` struct is held separately, leaving just its length here.
+    fields: Vec<BindingStructField>,
+
+    fixed_string: Option<FixedStringBuffer>,
+
+    /// Set of methods to grant this struct
+    methods: Vec<BindingMethod>,
+
+    /// The size of the Rust struct in bytes, if the descriptor gave one
+    size: Option<u64>,
+
+    /// The alignment of the Rust struct in bytes, if the descriptor gave one
+    align: Option<u64>,
+
+    /// For a struct holding slices or strings, the name of the struct holding managed arrays and
+    /// strings in their place. The struct itself is then the blittable `...Abi` struct.
+    managed_name: Option<String>,
+}
+
+impl BindingStruct {
+    fn new(
+        descriptor: &core::BindgenStructDescriptor,
+        methods: Vec<BindingMethod>,
+        function_pointers: bool,
+    ) -> Result<Self, &'static str> {
+        let fixed_string = if descriptor.fixed_string {
+            Some(FixedStringBuffer::new(descriptor)?)
+        } else {
+            None
+        };
+
+        let fields = descriptor.fields
+            .iter()
+            .skip(usize::from(fixed_string.is_some()))
+            .map(|field| BindingStructField::new(field, function_pointers))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let (name, managed_name) = if holds_buffers(descriptor) {
+            if !methods.is_empty() {
+                return Err("Can't bind methods on a struct holding slices or strings");
+            }
+            if fixed_string.is_some() || fields.iter().any(|f| f.fixed_len.is_some()) {
+                return Err("Structs holding slices or strings can't also hold inline arrays");
+            }
+            (buffer_struct_abi_name(descriptor), Some(struct_cs_name(descriptor).to_string()))
+        } else {
+            (struct_cs_name(descriptor).to_string(), None)
+        };
+
+        Ok(Self {
+            name,
+            fields,
+            fixed_string,
+            methods,
+            size: descriptor.size,
+            align: descriptor.align,
+            managed_name,
+        })
+    }
+
+    /// For a struct holding slices or strings, the struct the wrapper methods return, which holds
+    /// a managed copy of each.
+    fn managed_obj(&self) -> Option<ast::Object> {
+        let name = self.managed_name.as_ref()?;
+
+        let fields = self.fields
+            .iter()
+            .map(|field| {
+                let ty = match &field.ty.descriptor {
+                    Some(desc) if is_buffer_field(desc) => BindingType::try_from(desc.clone())
+                        .expect("Buffer fields are checked when binding the struct")
+                        .idiomatic_type(),
+                    _ => field.ty.cs_type.clone(),
+                };
+
+                ast::Field {
+                    ty,
+                    ..field.to_ast_field(false)
+                }
+            })
+            .collect();
+
+        Some(ast::Object {
+            attributes: vec![ast::Attribute::struct_layout(ast::LayoutKind::Sequential)],
+            object_type: ast::ObjectType::Struct,
+            is_static: false,
+            is_sealed: false,
+            is_readonly: false,
+            is_ref: false,
+            is_unsafe: false,
+            is_partial: false,
+            is_private: false,
+            is_internal: false,
+            name: name.clone(),
+            base_types: Vec::new(),
+            constructors: Vec::new(),
+            methods: Vec::new(),
+            fields,
+            properties: Vec::new(),
+            nested: Vec::new(),
+        })
+    }
+
+    /// A public, non-extern method with the given body.
+    fn member_method(
+        name: &str,
+        return_ty: ast::CSharpType,
+        args: Vec<ast::MethodArgument>,
+        is_static: bool,
+        is_override: bool,
+        body_elements: Vec<BodyElement>,
+    ) -> ast::Method {
+        ast::Method {
+            attributes: Vec::new(),
+            is_public: true,
+            is_static,
+            is_extern: false,
+            is_unsafe: false,
+            is_override,
+            is_partial: false,
+            name: name.to_string(),
+            return_ty,
+            args,
+            body: Some(BindingMethodBody { body_elements }.to_ast_nodes()),
+        }
+    }
+
+    fn has_fixed_buffer(&self) -> bool {
+        self.fixed_string.is_some() || self.fields.iter().any(|f| f.fixed_len.is_some())
+    }
+
+    /// Whether every field is a plain integer, float, or enum, as the positional parameters of a
+    /// record struct must be.
+    fn has_only_scalar_fields(&self) -> bool {
+        use core::BindgenTypeDescriptor as Desc;
+
+        !self.fields.is_empty()
+            && self.fixed_string.is_none()
+            && self.fields.iter().all(|f| {
+                f.fixed_len.is_none()
+                    && matches!(f.ty.descriptor, Some(Desc::Int { .. } | Desc::Float { .. } | Desc::Enum(_)))
+            })
+    }
+
+    /// Whether any field is a pointer, eg a `delegate*`, which can only be touched from an unsafe
+    /// context.
+    fn has_pointer_field(&self) -> bool {
+        self.fields.iter().any(|f| f.ty.cs_type.is_pointer())
+    }
+
+    /// The members compared by the equality methods.
+    ///
+    /// Fixed buffers can't be compared with `==`, so fixed strings are compared by their value.
+    fn equality_members(&self) -> Vec<&str> {
+        match &self.fixed_string {
+            Some(_) => vec![FixedStringBuffer::PROPERTY_NAME],
+            None => self.fields.iter().map(|field| field.cs_name.as_str()).collect(),
+        }
+    }
+
+    /// The members needed to implement `IEquatable<T>` in terms of the fields of this struct,
+    /// along with the matching `object.Equals`/`GetHashCode` overrides and `==`/`!=` operators.
+    fn equality_methods(&self) -> Vec<ast::Method> {
+        let self_ty = ast::CSharpType::Struct {
+            name: ast::Ident::new(&self.name),
+        };
+        let ident = |name: &str| Box::new(BodyElement::Ident(name.into()));
+        let call = |target: Option<Box<BodyElement>>, method_name: &str, args: Vec<BodyElement>| {
+            Box::new(BodyElement::MethodCall {
+                target,
+                method_name: method_name.to_string(),
+                args,
+            })
+        };
+        let binary = |lhs: Box<BodyElement>, operation: BinaryOperation, rhs: Box<BodyElement>| {
+            Box::new(BodyElement::BinaryExpression { lhs, rhs, operation })
+        };
+        let arg = |name: &str, ty: ast::CSharpType| ast::MethodArgument {
+            attributes: Vec::new(),
+            name: name.into(),
+            ty,
+        };
+        let ret = |element: Box<BodyElement>| BodyElement::Return {
+            element: Some(element),
+        };
+
+        // Field1.Equals(other.Field1) && Field2.Equals(other.Field2) && ...
+        //
+        // Unlike `==`, Equals treats a NaN float as equal to itself, as IEquatable<T> requires.
+        let fields_equal = self.equality_members()
+            .into_iter()
+            .map(|member| call(
+                Some(ident(member)),
+                "Equals",
+                vec![BodyElement::FieldAccess {
+                    element: ident("other"),
+                    field_name: member.to_string(),
+                }],
+            ))
+            .fold(None, |acc, field_eq| match acc {
+                None => Some(field_eq),
+                Some(acc) => Some(binary(acc, BinaryOperation::And, field_eq)),
+            })
+            .expect("Expected a struct with equality members to have at least one field");
+
+        let mut hash_body = vec![
+            BodyElement::Unchecked,
+            BodyElement::DeclareLocal {
+                id: 0.into(),
+                ty: ast::CSharpType::Int32,
+            },
+            BodyElement::Assignment {
+                lhs: Box::new(BodyElement::Ident(0.into())),
+                rhs: Box::new(BodyElement::LiteralValue(LiteralValue::Number(17))),
+            },
+        ];
+        for member in self.equality_members() {
+            hash_body.push(BodyElement::Assignment {
+                lhs: Box::new(BodyElement::Ident(0.into())),
+                rhs: binary(
+                    binary(
+                        Box::new(BodyElement::Ident(0.into())),
+                        BinaryOperation::Multiply,
+                        Box::new(BodyElement::LiteralValue(LiteralValue::Number(31))),
+                    ),
+                    BinaryOperation::Add,
+                    call(Some(ident(member)), "GetHashCode", Vec::new()),
+                ),
+            });
+        }
+        hash_body.push(ret(Box::new(BodyElement::Ident(0.into()))));
+
+        let equals_call = call(
+            Some(ident("left")),
+            "Equals",
+            vec![BodyElement::Ident("right".into())],
+        );
+
+        vec![
+            Self::member_method(
+                "Equals",
+                ast::CSharpType::Bool,
+                vec![arg("other", self_ty.clone())],
+                false,
+                false,
+                vec![ret(fields_equal)],
+            ),
+            Self::member_method(
+                "Equals",
+                ast::CSharpType::Bool,
+                vec![arg("obj", ast::CSharpType::Object)],
+                false,
+                true,
+                vec![ret(binary(
+                    binary(ident("obj"), BinaryOperation::Is, ident(&self.name)),
+                    BinaryOperation::And,
+                    call(None, "Equals", vec![BodyElement::Cast {
+                        ty: self_ty.clone(),
+                        element: ident("obj"),
+                    }]),
+                ))],
+            ),
+            Self::member_method(
+                "GetHashCode",
+                ast::CSharpType::Int32,
+                Vec::new(),
+                false,
+                true,
+                hash_body,
+            ),
+            Self::member_method(
+                "operator ==",
+                ast::CSharpType::Bool,
+                vec![arg("left", self_ty.clone()), arg("right", self_ty.clone())],
+                true,
+                false,
+                vec![ret(equals_call.clone())],
+            ),
+            Self::member_method(
+                "operator !=",
+                ast::CSharpType::Bool,
+                vec![arg("left", self_ty.clone()), arg("right", self_ty)],
+                true,
+                false,
+                vec![ret(Box::new(BodyElement::Not { element: equals_call }))],
+            ),
+        ]
+    }
+
+    /// Whether the struct is generated as a readonly struct, whose methods only get `this` by `in`.
+    fn is_readonly(&self, options: &CodegenOptions) -> bool {
+        let is_static = self.fields.is_empty();
+        let is_record = options.record_structs && self.has_only_scalar_fields();
+
+        // Fixed buffers can't be readonly, so neither can a struct containing one. Nor can a struct
+        // with pointer fields, as its constructor would have to be unsafe.
+        is_record
+            || options.readonly_structs && !is_static && !self.has_fixed_buffer() && !self.has_pointer_field()
+    }
+
+    fn to_ast_object(&self, options: &CodegenOptions) -> ast::Object {
+        let is_static = self.fields.is_empty();
+        let is_record = options.record_structs && self.has_only_scalar_fields();
+        let object_type = if is_static {
+            ast::ObjectType::Class
+        } else if is_record {
+            ast::ObjectType::RecordStruct
+        } else {
+            ast::ObjectType::Struct
+        };
+
+        let name = self.name.clone();
+        let is_readonly = self.is_readonly(options);
+
+        let mut fields: Vec<_> = self.fields
+            .iter()
+            .map(|f| f.to_ast_field(is_readonly))
+            .collect();
+
+        let mut properties: Vec<_> = self.fields
+            .iter()
+            .filter_map(BindingStructField::to_ast_property)
+            .collect();
+        if let Some(buffer) = &self.fixed_string {
+            fields.insert(0, buffer.to_ast_field());
+            properties.insert(0, buffer.to_ast_property(&self.fields[0]));
+            properties.insert(1, buffer.to_ast_span_property());
+        }
+
+        let mut constructors = Vec::new();
+
+        // Readonly fields can't be assigned after construction, so give a way to construct one.
+        // Record structs already have their positional constructor.
+        if is_readonly && !is_record {
+            constructors.push(self.fieldwise_constructor());
+        }
+
+        let nested = Vec::new();
+
+        let mut methods: Vec<_> = self.methods
+            .iter()
+            .flat_map(|m| m.to_ast_methods())
+            .collect();
+
+        if options.trace_hooks && !self.methods.is_empty() {
+            methods.extend(trace_hook_declarations());
+        }
+        let is_partial = (options.trace_hooks || options.custom_marshallers) && !self.methods.is_empty();
+
+        let mut base_types = Vec::new();
+        // Fixed buffers can't be compared with `==`, so only fixed strings have a value to compare.
+        // Pointers can only be compared, or hashed, in an unsafe context. The SliceAbis of a struct
+        // holding slices can't be compared at all.
+        let has_fixed_field = self.fields.iter().any(|f| f.fixed_len.is_some());
+        let skip_equality = if is_record {
+            Some("is generated as a record struct, which keeps the record's own value equality")
+        } else if has_fixed_field {
+            Some("has a fixed buffer field, which can't be compared")
+        } else if self.has_pointer_field() {
+            Some("has a pointer field, which can only be compared in an unsafe context")
+        } else if self.managed_name.is_some() {
+            Some("holds slices or strings, whose ABI fields can't be compared")
+        } else {
+            None
+        };
+        if options.struct_equality && !is_static {
+            match skip_equality {
+                Some(reason) => diagnostics::warn(&format!(
+                    "{} gets no generated equality members, as it {}",
+                    self.name, reason,
+                )),
+                None => {
+                    base_types.push(ast::CSharpType::Struct {
+                        name: ast::Ident(format!("IEquatable<{}>", self.name)),
+                    });
+                    methods.extend(self.equality_methods());
+                }
+            }
+        }
+
+        let mut obj = ast::Object {
+            attributes: vec![ast::Attribute::struct_layout(ast::LayoutKind::Sequential)],
+            object_type,
+            is_static,
+            is_sealed: false,
+            is_readonly,
+            is_ref: false,
+            is_unsafe: false,
+            is_partial,
+            is_private: false,
+            is_internal: false,
+            name,
+            base_types,
+            constructors,
+            methods,
+            fields,
+            properties,
+            nested,
+        };
+        if options.custom_marshallers {
+            obj.is_unsafe = obj.has_unsafe_member();
+        }
+
+        obj
+    }
+
+    /// The alignment checked by the layout checks, if any.
+    ///
+    /// .NET never aligns a struct to more than 8 bytes, so an overaligned struct would always fail
+    /// the check. Passing one by value is warned about instead.
+    fn asserted_align(&self) -> Option<u64> {
+        self.align.filter(|&align| align <= MAX_MANAGED_STRUCT_ALIGN)
+    }
+
+    /// A private struct holding a byte followed by this struct, which the field is then offset
+    /// from by this struct's alignment.
+    fn alignment_probe(&self) -> Option<ast::Object> {
+        if self.fields.is_empty() {
+            return None;
+        }
+        self.asserted_align()?;
+
+        let field = |name: &str, ty| ast::Field {
+            is_public: true,
+            is_readonly: false,
+            name: name.to_string(),
+            ty,
+            const_value: None,
+            fixed_len: None,
+            is_static: false,
+            initializer: None,
+        };
+
+        Some(ast::Object {
+            attributes: vec![ast::Attribute::struct_layout(ast::LayoutKind::Sequential)],
+            object_type: ast::ObjectType::Struct,
+            is_static: false,
+            is_sealed: false,
+            is_readonly: false,
+            is_ref: false,
+            is_unsafe: false,
+            is_partial: false,
+            is_private: true,
+            is_internal: false,
+            name: format!("{}{}", self.name, ALIGNMENT_PROBE_SUFFIX),
+            base_types: Vec::new(),
+            constructors: Vec::new(),
+            methods: Vec::new(),
+            fields: vec![
+                field("Pad", ast::CSharpType::Byte),
+                field("Value", ast::CSharpType::Struct { name: self.name.as_str().into() }),
+            ],
+            properties: Vec::new(),
+            nested: Vec::new(),
+        })
+    }
+
+    /// Checks throwing if the C# layout doesn't have the same size and alignment as the Rust one,
+    /// eg `if (Marshal.SizeOf<Point>() != 8) throw new InvalidOperationException(...);`
+    ///
+    /// These run from the static constructor of TopLevelMethods, as a struct's own static
+    /// constructor isn't run by creating or passing around values of it. Every other class calling
+    /// into the library runs it first through `TopLevelMethods.EnsureValidated()`.
+    fn layout_checks(&self) -> Vec<BodyElement> {
+        if self.fields.is_empty() {
+            return Vec::new();
+        }
+
+        let check = |actual, expected: u64, property: &str| BodyElement::ThrowIf {
+            condition: Box::new(BodyElement::BinaryExpression {
+                lhs: Box::new(actual),
+                rhs: Box::new(BodyElement::LiteralValue(LiteralValue::Number(expected as i64))),
+                operation: BinaryOperation::NotEqual,
+            }),
+            exception: Box::new(BodyElement::NewObject {
+                ty: ast::CSharpType::named("InvalidOperationException"),
+                args: vec![BodyElement::LiteralValue(LiteralValue::String(format!(
+                    "The {} of {} in C# doesn't match its {} of {} bytes in Rust",
+                    property, self.name, property, expected
+                )))],
+            }),
+        };
+
+        let marshalled_size = BodyElement::MethodCall {
+            target: Some(Box::new(BodyElement::Ident("Marshal".into()))),
+            method_name: format!("SizeOf<{}>", self.name),
+            args: Vec::new(),
+        };
+        let marshalled_align = BodyElement::MethodCall {
+            target: Some(Box::new(BodyElement::MethodCall {
+                target: Some(Box::new(BodyElement::Ident("Marshal".into()))),
+                method_name: format!("OffsetOf<{}{}>", self.name, ALIGNMENT_PROBE_SUFFIX),
+                args: vec![BodyElement::LiteralValue(LiteralValue::String("Value".to_string()))],
+            })),
+            method_name: "ToInt64".to_string(),
+            args: Vec::new(),
+        };
+
+        self.size
+            .map(|size| check(marshalled_size, size, "size"))
+            .into_iter()
+            .chain(self.asserted_align().map(|align| check(marshalled_align, align, "alignment")))
+            .collect()
+    }
+
+    /// A constructor taking a value for every field, in declaration order.
+    fn fieldwise_constructor(&self) -> ast::Constructor {
+        let args = self.fields
+            .iter()
+            .map(|field| ast::MethodArgument {
+                attributes: Vec::new(),
+                name: field.cs_name.to_mixed_case().as_str().into(),
+                ty: field.ty.cs_type.clone(),
+            })
+            .collect();
+
+        let body_elements = self.fields
+            .iter()
+            .map(|field| BodyElement::Assignment {
+                lhs: Box::new(BodyElement::FieldAccess {
+                    element: Box::new(BodyElement::Ident("this".into())),
+                    field_name: field.cs_name.clone(),
+                }),
+                rhs: Box::new(BodyElement::Ident(field.cs_name.to_mixed_case().as_str().into())),
+            })
+            .collect();
+
+        ast::Constructor {
+            kind: ast::ConstructorKind::Instance,
+            object_name: self.name.clone(),
+            args,
+            body: BindingMethodBody { body_elements }.to_ast_nodes(),
+        }
+    }
+}
+
+/// A C-like enum, which is blittable so can be used anywhere its underlying integer type can.
+fn enum_to_ast(descriptor: &core::BindgenEnumDescriptor) -> Result<ast::Enum, &'static str> {
+    let underlying_type = match BindingType::try_from(*descriptor.repr.clone())? {
+        BindingType::Simple(s) if s.cs_type.integral_keyword().is_some() => s.cs_type,
+        _ => return Err("Enums must be represented by a fixed size integer"),
+    };
+
+    let (width, signed) = match *descriptor.repr {
+        core::BindgenTypeDescriptor::Int { width, signed } => (width as u32, signed),
+        _ => return Err("Enums must be represented by a fixed size integer"),
+    };
+    let (min, max) = if signed {
+        (-(1i128 << (width - 1)), (1i128 << (width - 1)) - 1)
+    } else {
+        (0, (1i128 << width) - 1)
+    };
+
+    let variants = descriptor.variants
+        .iter()
+        .map(|v| {
+            // Discriminants of u64 enums above i64::MAX arrive wrapped around.
+            let value = match (width, signed) {
+                (64, false) => v.value as u64 as i128,
+                _ => v.value as i128,
+            };
+
+            if value < min || value > max {
+                return Err("An enum discriminant doesn't fit in the integer type the enum is represented by");
+            }
+
+            Ok(ast::EnumVariant {
+                name: v.name.clone(),
+                value,
+            })
+        })
+        .collect::<Result<_, _>>()?;
+
+    Ok(ast::Enum {
+        name: descriptor.name.clone(),
+        underlying_type,
+        variants,
+    })
+}
+
+/// The private field of TopLevelMethods caching the handle that statics are read through.
+const LIBRARY_HANDLE_FIELD: &str = "libraryHandle";
+
+/// A static exported from the bound binary, exposed to C# as a method that reads its current value.
+struct BindingStatic {
+    /// The symbol name of the static, as it appears in the Rust source
+    rust_name: String,
+
+    cs_name: String,
+
+    cs_type: ast::CSharpType,
+
+    mutable: bool,
+
+    /// The name of the binary containing the static, as would be given to a DllImport attribute
+    binary_name: String,
+}
+
+impl BindingStatic {
+    fn new(binary_name: &str, descriptor: &core::BindgenStaticDescriptor) -> Result<Self, &'static str> {
+        // Anything needing conversion would need a managed copy of the static kept in sync with
+        // the native one, so only support types that can be read as they are.
+        let cs_type = match descriptor.ty.clone().try_into()? {
+            BindingType::Simple(s) => s.cs_type,
+            BindingType::Complex(_) => return Err("Can't create bindings for statics of non-ffi-stable types"),
+        };
+
+        if descriptor.mutable {
+            diagnostics::warn(&format!(
+                "static mut {} is read without any synchronisation, \
+                 the generated accessor is not thread-safe",
+                descriptor.name
+            ));
+        }
+
+        Ok(Self {
+            rust_name: descriptor.name.to_string(),
+            cs_name: descriptor.name.to_camel_case(),
+            cs_type,
+            mutable: descriptor.mutable,
+            binary_name: binary_name.to_string(),
+        })
+    }
+
+    /// The field holding the handle of the binary the statics are exported from, loaded once by
+    /// the first accessor to run, eg
+    /// ```csharp
+    /// private static readonly IntPtr libraryHandle = NativeLibrary.Load("lib", ..., null);
+    /// ```
+    fn library_handle_field(&self) -> ast::Field {
+        // The assembly overload of Load probes for the library the same way DllImport does.
+        let load_library = BindingStatic::call(
+            "NativeLibrary",
+            "Load",
+            vec![
+                BodyElement::LiteralValue(LiteralValue::String(self.binary_name.clone())),
+                BindingStatic::call("System.Reflection.Assembly", "GetExecutingAssembly", Vec::new()),
+                BodyElement::LiteralValue(LiteralValue::Null),
+            ],
+        );
+
+        ast::Field {
+            is_public: false,
+            is_readonly: true,
+            name: LIBRARY_HANDLE_FIELD.to_string(),
+            ty: ast::CSharpType::intptr(),
+            const_value: None,
+            fixed_len: None,
+            is_static: true,
+            initializer: Some(load_library.to_ast_node()),
+        }
+    }
+
+    fn call(target: &str, method_name: &str, args: Vec<BodyElement>) -> BodyElement {
+        BodyElement::MethodCall {
+            target: Some(Box::new(BodyElement::Ident(AbstractIdent::Explicit(target.to_string())))),
+            method_name: method_name.to_string(),
+            args,
+        }
+    }
+
+    /// Eg:
+    /// ```csharp
+    /// public static Config GetConfig()
+    /// {
+    ///     IntPtr _gen0;
+    ///     _gen0 = NativeLibrary.GetExport(libraryHandle, "CONFIG");
+    ///     return Marshal.PtrToStructure<Config>(_gen0);
+    /// }
+    /// ```
+    ///
+    /// Primitives are read straight from the pointer instead, eg `return *((Int32*)(_gen0));`
+    fn to_ast_method(&self) -> ast::Method {
+        let is_primitive = matches!(
+            self.cs_type,
+            ast::CSharpType::SByte
+                | ast::CSharpType::Int16
+                | ast::CSharpType::Int32
+                | ast::CSharpType::Int64
+                | ast::CSharpType::Byte
+                | ast::CSharpType::UInt16
+                | ast::CSharpType::UInt32
+                | ast::CSharpType::UInt64
+                | ast::CSharpType::Single
+                | ast::CSharpType::Double
+                | ast::CSharpType::Bool
+                | ast::CSharpType::Char
+        );
+
+        let mut body_elements = vec![
+            BodyElement::DeclareLocal {
+                id: 0.into(),
+                ty: ast::CSharpType::intptr(),
+            },
+            BodyElement::Assignment {
+                lhs: Box::new(BodyElement::Ident(0.into())),
+                rhs: Box::new(BindingStatic::call(
+                    "NativeLibrary",
+                    "GetExport",
+                    vec![
+                        BodyElement::Ident(AbstractIdent::Explicit(LIBRARY_HANDLE_FIELD.to_string())),
+                        BodyElement::LiteralValue(LiteralValue::String(self.rust_name.clone())),
+                    ],
+                )),
+            },
+        ];
+
+        if is_primitive {
+            body_elements.push(BodyElement::Unsafe);
+            body_elements.push(BodyElement::Return {
+                element: Some(Box::new(BodyElement::Deref {
+                    element: Box::new(BodyElement::Cast {
+                        ty: ast::CSharpType::Ptr { target: Box::new(self.cs_type.clone()) },
+                        element: Box::new(BodyElement::Ident(0.into())),
+                    }),
+                })),
+            });
+        } else {
+            body_elements.push(BodyElement::Return {
+                element: Some(Box::new(BindingStatic::call(
+                    "Marshal",
+                    &format!("PtrToStructure<{}>", self.cs_type),
+                    vec![BodyElement::Ident(0.into())],
+                ))),
+            });
+        }
+
+        // Make it obvious at the call site that a mutable static is only a snapshot.
+        let prefix = if self.mutable { "Read" } else { "Get" };
+
+        ast::Method {
+            attributes: Vec::new(),
+            is_public: true,
+            is_static: true,
+            is_extern: false,
+            is_unsafe: false,
+            is_override: false,
+            is_partial: false,
+            name: format!("{}{}", prefix, self.cs_name),
+            return_ty: self.cs_type.clone(),
+            args: Vec::new(),
+            body: Some(BindingMethodBody { body_elements }.to_ast_nodes()),
+        }
+    }
+}
+
+/// A registry of bound Rust structs and enums which are represented by an existing C# type, rather
+/// than by one generated alongside the bindings.
+///
+/// The C# type must have the same layout as the Rust type, as values are passed across the
+/// boundary as is.
+#[derive(Clone, Debug, Default)]
+pub struct TypeMapper {
+    /// Rust type name -> full path of the C# type
+    mappings: HashMap<String, String>,
+}
+
+impl TypeMapper {
+    /// Binds the Rust struct or enum named `rust_name` as the C# type at `cs_path`, eg
+    /// `Acme.Geometry.Point`.
+    pub fn map(&mut self, rust_name: &str, cs_path: &str) {
+        self.mappings.insert(rust_name.to_string(), cs_path.to_string());
+    }
+
+    pub fn is_mapped(&self, rust_name: &str) -> bool {
+        self.mappings.contains_key(rust_name)
+    }
+
+    /// The given descriptors, with every mapped struct or enum renamed to the C# type it maps to.
+    fn apply(&self, descriptors: &[core::BindgenExportDescriptor]) -> Vec<core::BindgenExportDescriptor> {
+        let mut descriptors = descriptors.to_vec();
+        if self.mappings.is_empty() {
+            return descriptors;
+        }
+
+        for descriptor in &mut descriptors {
+            match descriptor {
+                core::BindgenExportDescriptor::Function(f) => {
+                    for arg in &mut f.arguments {
+                        self.apply_to_type(&mut arg.ty);
+                    }
+                    self.apply_to_type(&mut f.return_ty);
+                }
+                core::BindgenExportDescriptor::Struct(s) => self.apply_to_struct(s),
+                // Mapped enums aren't generated, so are still recognised by their Rust name here
+                core::BindgenExportDescriptor::Enum(_) => (),
+                core::BindgenExportDescriptor::Static(s) => self.apply_to_type(&mut s.ty),
+            }
+        }
+
+        descriptors
+    }
+
+    fn apply_to_type(&self, ty: &mut core::BindgenTypeDescriptor) {
+        use dotnet_bindgen_core::BindgenTypeDescriptor as Desc;
+
+        match ty {
+            Desc::Slice { elem_type }
+            | Desc::MutSlice { elem_type }
+            | Desc::UninitSlice { elem_type }
+            | Desc::OptionSlice { elem_type }
+            | Desc::OwnedSlice { elem_type }
+            | Desc::Array { elem_type, .. } => self.apply_to_type(elem_type),
+            Desc::Ref { target } | Desc::MutRef { target } => self.apply_to_type(target),
+            Desc::FnPtr { args, return_ty } => {
+                for arg in args {
+                    self.apply_to_type(arg);
+                }
+                self.apply_to_type(return_ty);
+            }
+            Desc::Tuple { elements } => {
+                for element in elements {
+                    self.apply_to_type(element);
+                }
+            }
+            Desc::Struct(s) => self.apply_to_struct(s),
+            Desc::Enum(e) => self.apply_to_enum(e),
+            _ => (),
+        }
+    }
+
+    /// Mapped structs keep their Rust name, as that's what impl blocks refer to them by.
+    fn apply_to_struct(&self, descriptor: &mut core::BindgenStructDescriptor) {
+        if let Some(path) = self.mappings.get(&descriptor.name) {
+            descriptor.cs_name = Some(path.clone());
+        }
+
+        for field in &mut descriptor.fields {
+            self.apply_to_type(&mut field.ty);
+        }
+    }
+
+    fn apply_to_enum(&self, descriptor: &mut core::BindgenEnumDescriptor) {
+        if let Some(path) = self.mappings.get(&descriptor.name) {
+            descriptor.name = path.clone();
+        }
+    }
+}
+
+/// User configurable knobs that affect the generated source, but not the extracted data.
+#[derive(Clone, Debug)]
+pub struct CodegenOptions {
+    /// Lines of the block comment placed at the very top of the generated file.
+    pub file_header: Vec<String>,
+
+    /// Generate `IEquatable<T>`, `Equals`, `GetHashCode`, and `==`/`!=` for bound structs.
+    ///
+    /// Structs with array fields are skipped, as their fixed buffers can't be compared, as are
+    /// structs with pointer fields, eg `delegate*`s.
+    pub struct_equality: bool,
+
+    /// Generate bound structs as `readonly struct`s with readonly fields and a fieldwise constructor.
+    pub readonly_structs: bool,
+
+    /// Generate bound structs whose fields are all integers, floats, or enums as positional
+    /// `readonly record struct`s, which have value equality and `with` expressions. Needs C# 10.
+    ///
+    /// Takes precedence over `readonly_structs` and `struct_equality` for those structs.
+    pub record_structs: bool,
+
+    /// Describe pinned slices through a stack-only `ref struct SliceView` while marshalling.
+    pub slice_views: bool,
+
+    /// Generate `...Async` overloads returning a `Task` for functions marked as blocking.
+    pub async_wrappers: bool,
+
+    /// Generate a `...FromStream` companion of each static method taking a `&[u8]`, which reads a
+    /// `Stream` into a buffer rented from the shared `ArrayPool` in place of the byte array.
+    pub stream_overloads: bool,
+
+    /// Warning IDs disabled for the whole of the generated file. No pragma is emitted when empty.
+    pub disabled_warnings: Vec<String>,
+
+    /// The library name used in DllImport attributes, in place of the analyzed binary's name.
+    pub dll_name_override: Option<String>,
+
+    /// Import the native functions from the executable itself, as `__Internal`, for a library
+    /// statically linked into the app, eg for NativeAOT or iOS. Takes precedence over the
+    /// `dll_name_override`.
+    pub static_link: bool,
+
+    /// The library to load in place of the DllImport name on each OS, as the name of an
+    /// `OSPlatform` property and a library name, eg `("Windows", "foo_win")`.
+    ///
+    /// When given, a DllImportResolver loading them is registered by a module initializer. That
+    /// needs net5.0 or later, so other frameworks always load the DllImport name.
+    pub platform_dll_names: Vec<(String, String)>,
+
+    /// A dotted namespace prepended to the generated namespace, eg `Acme.Native`.
+    pub namespace_prefix: Option<String>,
+
+    /// Generate an interface declaring the free function wrappers in TopLevelMethods, and a class
+    /// implementing it. Functions bound in a `module` aren't included.
+    pub emit_interface: bool,
+
+    /// Return owned slices wrapped in a disposable class, rather than copying them into an array.
+    pub owned_buffers: bool,
+
+    /// Return owned and borrowed slices as a disposable `NativeSlice<T>`, whose `AsSpan()` views
+    /// the native memory without copying it. Takes precedence over `owned_buffers`.
+    ///
+    /// Disposing an owned slice frees it, after which any span from `AsSpan()` dangles. A borrowed
+    /// slice is never freed, so its spans are only valid for as long as Rust keeps it alive.
+    pub no_copy_slices: bool,
+
+    /// Mark the wrapper methods forwarding to the native methods with `[DebuggerStepThrough]`.
+    pub debugger_step_through: bool,
+
+    /// Move the null checks of the wrapper methods' arguments into private validators marked
+    /// `[Conditional("DEBUG")]`, so that release builds of the calling code skip them.
+    ///
+    /// A null slice or string then only fails once the wrapper uses it, eg with a
+    /// NullReferenceException when it takes the length of an array.
+    pub debug_only_validation: bool,
+
+    /// Mark the wrapper methods, and the module, with `[SkipLocalsInit]`. Needs net5.0 or later,
+    /// and the project to allow unsafe blocks.
+    pub skip_locals_init: bool,
+
+    /// Bind function pointer arguments and struct fields as C# 9 `delegate* unmanaged[Cdecl]<...>`
+    /// types, rather than as an IntPtr.
+    pub function_pointers: bool,
+
+    /// Call partial `OnNativeCallEnter`/`OnNativeCallExit` hooks around each native call, which
+    /// compile to nothing unless implemented.
+    pub trace_hooks: bool,
+
+    /// Declare the P/Invokes with `[LibraryImport]`, handing slices and UTF-8 strings to generated
+    /// custom marshallers rather than pinning them in the wrapper methods. Needs net7.0 or later.
+    ///
+    /// Bound structs are blittable, so are passed as they are without a marshaller. The partial
+    /// classes holding the P/Invokes are also `unsafe` when any of their members use pointers.
+    pub custom_marshallers: bool,
+
+    /// Bound types which are represented by existing C# types, so aren't generated.
+    pub type_mapper: TypeMapper,
+
+    /// The access modifier of the generated types, eg `internal` to keep them out of the public
+    /// API of the assembly they are compiled into.
+    pub type_visibility: ast::Visibility,
+
+    /// Render the generated source without indentation or blank lines between members.
+    pub minify: bool,
+
+    /// Fold single-use temporaries of the wrapper methods into the statement using them. 0 leaves
+    /// them be, 1 folds side effect free values, and 2 also folds method calls and allocations.
+    pub inline_temporaries: u8,
+}
+
+impl Default for CodegenOptions {
+    fn default() -> Self {
+        Self {
+            file_header: vec![
+                "This is a generated file, do not modify by hand.".to_string(),
+                format!("Generated by dotnet-bindgen-cli v{}", env!("CARGO_PKG_VERSION")),
+            ],
+            struct_equality: false,
+            readonly_structs: false,
+            record_structs: false,
+            slice_views: false,
+            async_wrappers: false,
+            stream_overloads: false,
+            // The analyzer rules that the generated P/Invoke declarations, `__bindgen` thunk
+            // names, and public struct fields trip as a matter of course.
+            disabled_warnings: ["CA1051", "CA1060", "CA1707", "CA1815", "CA5392"]
+                .iter()
+                .map(|id| id.to_string())
+                .collect(),
+            dll_name_override: None,
+            static_link: false,
+            platform_dll_names: Vec::new(),
+            namespace_prefix: None,
+            emit_interface: false,
+            owned_buffers: false,
+            no_copy_slices: false,
+            debugger_step_through: false,
+            debug_only_validation: false,
+            skip_locals_init: false,
+            function_pointers: false,
+            trace_hooks: false,
+            custom_marshallers: false,
+            type_mapper: TypeMapper::default(),
+            type_visibility: ast::Visibility::Public,
+            minify: false,
+            inline_temporaries: 0,
+        }
+    }
+}
+
+/// The library name which the runtime resolves to the running executable, rather than a file.
+const STATIC_LINK_DLL_NAME: &str = "__Internal";
+
+/// Maps a BindgenTypeDescriptor to the type it appears as in the generated thunk
+struct CodegenInfo<'a> {
+    /// Raw descriptor data extracted from the binary
+    data: &'a BindgenData,
+
+    /// The extracted descriptors, with the type mappings from the options applied
+    descriptors: Vec<core::BindgenExportDescriptor>,
+
+    options: &'a CodegenOptions,
+
+    /// The parsed name of the library. Eg "libbindings_demo.so" -> "bindings_demo".
+    lib_name: String,
+
+    /// The name of the library to load at runtime, which is the lib_name unless overridden, or
+    /// `__Internal` when statically linked.
+    ///
+    /// It should be sufficient to use this string as the first argument to a DllImportAttribute.
+    dll_name: String,
+}
+
+impl<'a> CodegenInfo<'a> {
+    fn new(data: &'a BindgenData, options: &'a CodegenOptions) -> Self {
+        let lib_name = data.source_file.bin_base_name();
+        let dll_name = if options.static_link {
+            STATIC_LINK_DLL_NAME.to_string()
+        } else {
+            options.dll_name_override.clone().unwrap_or_else(|| lib_name.clone())
+        };
+        Self {
+            data,
+            descriptors: options.type_mapper.apply(&data.descriptors),
+            options,
+            lib_name,
+            dll_name,
+        }
+    }
+
+    /// A stack-only view of a pinned slice, which can only be turned into a SliceAbi.
+    fn slice_view_obj() -> ast::Object {
+        let this_field = |name: &str| BodyElement::FieldAccess {
+            element: Box::new(BodyElement::Ident("this".into())),
+            field_name: name.to_string(),
+        };
+        let abi_field = |name: &str| BodyElement::FieldAccess {
+            element: Box::new(BodyElement::Ident(0.into())),
+            field_name: name.to_string(),
+        };
+
+        let constructor = ast::Constructor {
+            kind: ast::ConstructorKind::Instance,
+            object_name: "SliceView".to_string(),
+            args: vec![
+                ast::MethodArgument {
+                    attributes: Vec::new(),
+                    name: "ptr".into(),
+                    ty: ast::CSharpType::intptr(),
+                },
+                ast::MethodArgument {
+                    attributes: Vec::new(),
+                    name: "length".into(),
+                    ty: ast::CSharpType::Int32,
+                },
+            ],
+            body: BindingMethodBody {
+                body_elements: vec![
+                    BodyElement::Assignment {
+                        lhs: Box::new(this_field("Ptr")),
+                        rhs: Box::new(BodyElement::Ident("ptr".into())),
+                    },
+                    BodyElement::Assignment {
+                        lhs: Box::new(this_field("Length")),
+                        rhs: Box::new(BodyElement::Ident("length".into())),
+                    },
+                ],
+            }.to_ast_nodes(),
+        };
+
+        let slice_abi = ast::CSharpType::Struct {
+            name: "SliceAbi".into(),
+        };
+
+        let to_abi = ast::Method {
+            attributes: Vec::new(),
+            is_public: true,
+            is_static: false,
+            is_extern: false,
+            is_unsafe: false,
+            is_override: false,
+            is_partial: false,
+            name: "ToAbi".to_string(),
+            return_ty: slice_abi.clone(),
+            args: Vec::new(),
+            body: Some(BindingMethodBody {
+                body_elements: vec![
+                    BodyElement::DeclareLocal {
+                        id: 0.into(),
+                        ty: slice_abi,
+                    },
+                    BodyElement::Assignment {
+                        lhs: Box::new(abi_field("Ptr")),
+                        rhs: Box::new(this_field("Ptr")),
+                    },
+                    BodyElement::Assignment {
+                        lhs: Box::new(abi_field("Len")),
+                        rhs: Box::new(BodyElement::Cast {
+                            ty: ast::CSharpType::UInt64,
+                            element: Box::new(this_field("Length")),
+                        }),
+                    },
+                    BodyElement::Return {
+                        element: Some(Box::new(BodyElement::Ident(0.into()))),
+                    },
+                ],
+            }.to_ast_nodes()),
+        };
+
+        ast::Object {
+            attributes: vec![ast::Attribute::struct_layout(ast::LayoutKind::Sequential)],
+            object_type: ast::ObjectType::Struct,
+            is_static: false,
+            is_sealed: false,
+            is_readonly: true,
+            is_ref: true,
+            is_unsafe: false,
+            is_partial: false,
+            is_private: false,
+            is_internal: false,
+            name: "SliceView".into(),
+            base_types: Vec::new(),
+            constructors: vec![constructor],
+            methods: vec![to_abi],
+            fields: vec![
+                ast::Field {
+                    is_public: true,
+                    is_readonly: true,
+                    name: "Ptr".to_string(),
+                    ty: ast::CSharpType::intptr(),
+                    const_value: None,
+                    fixed_len: None,
+                    is_static: false,
+                    initializer: None,
+                },
+                ast::Field {
+                    is_public: true,
+                    is_readonly: true,
+                    name: "Length".to_string(),
+                    ty: ast::CSharpType::Int32,
+                    const_value: None,
+                    fixed_len: None,
+                    is_static: false,
+                    initializer: None,
+                },
+            ],
+            properties: Vec::new(),
+            nested: Vec::new(),
+        }
+    }
+
+    fn slice_abi_obj() -> ast::Object {
+        ast::Object {
+            attributes: vec![ast::Attribute::struct_layout(ast::LayoutKind::Sequential)],
+            object_type: ast::ObjectType::Struct,
+            is_static: false,
+            is_sealed: false,
+            is_readonly: false,
+            is_ref: false,
+            is_unsafe: false,
+            is_partial: false,
+            is_private: false,
+            is_internal: false,
+            name: "SliceAbi".into(),
+            base_types: Vec::new(),
+            constructors: Vec::new(),
+            methods: Vec::new(),
+            fields: vec![
+                ast::Field {
+                    is_public: true,
+                    is_readonly: false,
+                    name: "Ptr".to_string(),
+                    ty: ast::CSharpType::Struct {
+                        name: ast::Ident::new("IntPtr"),
+                    },
+                    const_value: None,
+                    fixed_len: None,
+                    is_static: false,
+                    initializer: None,
+                },
+                ast::Field {
+                    is_public: true,
+                    is_readonly: false,
+                    name: "Len".to_string(),
+                    ty: ast::CSharpType::UInt64,
+                    const_value: None,
+                    fixed_len: None,
+                    is_static: false,
+                    initializer: None,
+                },
+            ],
+            properties: Vec::new(),
+            nested: Vec::new(),
+        }
+    }
+
+    /// FfiStable representation of a 128-bit integer, along with conversions to and from both
+    /// Int128 and UInt128.
+    ///
+    /// Passing the halves in a plain struct keeps both sides to the C ABI's struct convention, as
+    /// Rust and .NET pass `i128` and `Int128` themselves differently on some platforms.
+    fn int128_abi_obj() -> ast::Object {
+        let abi_ty = ast::CSharpType::Struct {
+            name: INT128_ABI_STRUCT.into(),
+        };
+        let abi_field = |name: &str| BodyElement::FieldAccess {
+            element: Box::new(BodyElement::Ident(0.into())),
+            field_name: name.to_string(),
+        };
+        let this_field = |name: &str| BodyElement::FieldAccess {
+            element: Box::new(BodyElement::Ident("this".into())),
+            field_name: name.to_string(),
+        };
+        let value = || Box::new(BodyElement::Ident("value".into()));
+        let method = |name: String, is_static: bool, return_ty, args, body_elements| ast::Method {
+            attributes: Vec::new(),
+            is_public: true,
+            is_static,
+            is_extern: false,
+            is_unsafe: false,
+            is_override: false,
+            is_partial: false,
+            name,
+            return_ty,
+            args,
+            body: Some(BindingMethodBody { body_elements }.to_ast_nodes()),
+        };
+
+        let mut methods = Vec::new();
+        for type_name in ["Int128", "UInt128"] {
+            let int_ty = ast::CSharpType::Struct {
+                name: type_name.into(),
+            };
+
+            // The casts to UInt64 truncate, keeping just the half being assigned
+            methods.push(method(
+                format!("From{}", type_name),
+                true,
+                abi_ty.clone(),
+                vec![ast::MethodArgument {
+                    attributes: Vec::new(),
+                    name: "value".into(),
+                    ty: int_ty.clone(),
+                }],
+                vec![
+                    BodyElement::Unchecked,
+                    BodyElement::DeclareLocal {
+                        id: 0.into(),
+                        ty: abi_ty.clone(),
+                    },
+                    BodyElement::Assignment {
+                        lhs: Box::new(abi_field("Lo")),
+                        rhs: Box::new(BodyElement::Cast {
+                            ty: ast::CSharpType::UInt64,
+                            element: value(),
+                        }),
+                    },
+                    BodyElement::Assignment {
+                        lhs: Box::new(abi_field("Hi")),
+                        rhs: Box::new(BodyElement::Cast {
+                            ty: ast::CSharpType::UInt64,
+                            element: Box::new(BodyElement::BinaryExpression {
+                                lhs: value(),
+                                rhs: Box::new(BodyElement::LiteralValue(LiteralValue::Number(64))),
+                                operation: BinaryOperation::ShiftRight,
+                            }),
+                        }),
+                    },
+                    BodyElement::Return {
+                        element: Some(Box::new(BodyElement::Ident(0.into()))),
+                    },
+                ],
+            ));
+
+            methods.push(method(
+                format!("To{}", type_name),
+                false,
+                int_ty.clone(),
+                Vec::new(),
+                vec![BodyElement::Return {
+                    element: Some(Box::new(BodyElement::NewObject {
+                        ty: int_ty,
+                        args: vec![this_field("Hi"), this_field("Lo")],
+                    })),
+                }],
+            ));
+        }
+
+        let field = |name: &str| ast::Field {
+            is_public: true,
+            is_readonly: false,
+            name: name.to_string(),
+            ty: ast::CSharpType::UInt64,
+            const_value: None,
+            fixed_len: None,
+            is_static: false,
+            initializer: None,
+        };
+
+        ast::Object {
+            attributes: vec![ast::Attribute::struct_layout(ast::LayoutKind::Sequential)],
+            object_type: ast::ObjectType::Struct,
+            is_static: false,
+            is_sealed: false,
+            is_readonly: false,
+            is_ref: false,
+            is_unsafe: false,
+            is_partial: false,
+            is_private: false,
+            is_internal: false,
+            name: INT128_ABI_STRUCT.into(),
+            base_types: Vec::new(),
+            constructors: Vec::new(),
+            methods,
+            fields: vec![field("Lo"), field("Hi")],
+            properties: Vec::new(),
+            nested: Vec::new(),
+        }
+    }
+
+    /// FfiStable representation of a buffer allocated by Rust, which must be freed by Rust.
+    fn owned_slice_abi_obj() -> ast::Object {
+        ast::Object {
+            attributes: vec![ast::Attribute::struct_layout(ast::LayoutKind::Sequential)],
+            object_type: ast::ObjectType::Struct,
+            is_static: false,
+            is_sealed: false,
+            is_readonly: false,
+            is_ref: false,
+            is_unsafe: false,
+            is_partial: false,
+            is_private: false,
+            is_internal: false,
+            name: "OwnedSliceAbi".into(),
+            base_types: Vec::new(),
+            constructors: Vec::new(),
+            methods: Vec::new(),
+            fields: vec![
+                ast::Field {
+                    is_public: true,
+                    is_readonly: false,
+                    name: "Ptr".to_string(),
+                    ty: ast::CSharpType::intptr(),
+                    const_value: None,
+                    fixed_len: None,
+                    is_static: false,
+                    initializer: None,
+                },
+                ast::Field {
+                    is_public: true,
+                    is_readonly: false,
+                    name: "Len".to_string(),
+                    ty: ast::CSharpType::UInt64,
+                    const_value: None,
+                    fixed_len: None,
+                    is_static: false,
+                    initializer: None,
+                },
+                ast::Field {
+                    is_public: true,
+                    is_readonly: false,
+                    name: "Cap".to_string(),
+                    ty: ast::CSharpType::UInt64,
+                    const_value: None,
+                    fixed_len: None,
+                    is_static: false,
+                    initializer: None,
+                },
+            ],
+            properties: Vec::new(),
+            nested: Vec::new(),
+        }
+    }
+
+    /// A disposable class owning a byte buffer allocated with the Rust allocator, which C# fills in
+    /// through a Span and hands over to a function taking a `Vec<u8>` without a copy.
+    ///
+    /// Handing it over leaves the RustBuffer empty, as Rust then owns and frees the buffer. A
+    /// buffer which is never handed over is freed on Dispose or finalization.
+    fn rust_buffer_obj(&self) -> ast::Object {
+        let owned_slice_abi = ast::CSharpType::Struct {
+            name: "OwnedSliceAbi".into(),
+        };
+        let this_abi = || BodyElement::FieldAccess {
+            element: Box::new(BodyElement::Ident("this".into())),
+            field_name: "abi".to_string(),
+        };
+        let this_abi_field = |name: &str| BodyElement::FieldAccess {
+            element: Box::new(this_abi()),
+            field_name: name.to_string(),
+        };
+        let call = |method_name: &str, args| BodyElement::MethodCall {
+            target: None,
+            method_name: method_name.to_string(),
+            args,
+        };
+        let suppress_finalize = || BodyElement::MethodCall {
+            target: Some(Box::new(BodyElement::Ident("GC".into()))),
+            method_name: "SuppressFinalize".to_string(),
+            args: vec![BodyElement::Ident("this".into())],
+        };
+        let reset_abi = || BodyElement::Assignment {
+            lhs: Box::new(this_abi()),
+            rhs: Box::new(BodyElement::NewObject {
+                ty: owned_slice_abi.clone(),
+                args: Vec::new(),
+            }),
+        };
+        let method = |is_public, is_unsafe, name: &str, return_ty, body_elements| ast::Method {
+            attributes: Vec::new(),
+            is_public,
+            is_static: false,
+            is_extern: false,
+            is_unsafe,
+            is_override: false,
+            is_partial: false,
+            name: name.to_string(),
+            return_ty,
+            args: Vec::new(),
+            body: Some(BindingMethodBody { body_elements }.to_ast_nodes()),
+        };
+        let arg = |name: &str, ty| ast::MethodArgument {
+            attributes: Vec::new(),
+            name: name.into(),
+            ty,
+        };
+        let dll_import = |name: &str, return_ty, args| ast::Method {
+            attributes: vec![ast::Attribute::dll_import(&self.dll_name, name, name, "Cdecl")],
+            is_public: false,
+            is_static: true,
+            is_extern: true,
+            is_unsafe: false,
+            is_override: false,
+            is_partial: false,
+            name: name.to_string(),
+            return_ty,
+            args,
+            body: None,
+        };
+
+        let len = || BodyElement::Cast {
+            ty: ast::CSharpType::UInt64,
+            element: Box::new(BodyElement::Ident("length".into())),
+        };
+        let constructor = ast::Constructor {
+            kind: ast::ConstructorKind::Instance,
+            object_name: RUST_BUFFER_CLASS.to_string(),
+            args: vec![arg("length", ast::CSharpType::Int32)],
+            body: BindingMethodBody {
+                body_elements: vec![
+                    BodyElement::Assignment {
+                        lhs: Box::new(this_abi_field("Ptr")),
+                        rhs: Box::new(call(core::BINDGEN_ALLOC_SYMBOL, vec![BodyElement::Checked {
+                            element: Box::new(len()),
+                        }])),
+                    },
+                    BodyElement::Assignment {
+                        lhs: Box::new(this_abi_field("Len")),
+                        rhs: Box::new(len()),
+                    },
+                    BodyElement::Assignment {
+                        lhs: Box::new(this_abi_field("Cap")),
+                        rhs: Box::new(len()),
+                    },
+                ],
+            }.to_ast_nodes(),
+        };
+
+        let finalizer = ast::Constructor {
+            kind: ast::ConstructorKind::Finalizer,
+            object_name: RUST_BUFFER_CLASS.to_string(),
+            args: Vec::new(),
+            body: BindingMethodBody {
+                body_elements: vec![call("Free", Vec::new())],
+            }.to_ast_nodes(),
+        };
+
+        let as_span = method(
+            true,
+            true,
+            "AsSpan",
+            ast::CSharpType::Span {
+                elem_type: Box::new(ast::CSharpType::Byte),
+            },
+            vec![BodyElement::Return {
+                element: Some(Box::new(owned_span(this_abi(), ast::CSharpType::Byte))),
+            }],
+        );
+
+        let transfer = method(
+            true,
+            false,
+            "Transfer",
+            owned_slice_abi.clone(),
+            vec![
+                BodyElement::DeclareLocal {
+                    id: 0.into(),
+                    ty: owned_slice_abi.clone(),
+                },
+                BodyElement::Assignment {
+                    lhs: Box::new(BodyElement::Ident(0.into())),
+                    rhs: Box::new(this_abi()),
+                },
+                reset_abi(),
+                suppress_finalize(),
+                BodyElement::Return {
+                    element: Some(Box::new(BodyElement::Ident(0.into()))),
+                },
+            ],
+        );
+
+        let dispose = method(
+            true,
+            false,
+            "Dispose",
+            ast::CSharpType::Void,
+            vec![call("Free", Vec::new()), suppress_finalize()],
+        );
+
+        // Resetting to an empty buffer makes freeing it again a no-op, so Dispose can safely be
+        // called more than once.
+        let free = method(
+            false,
+            false,
+            "Free",
+            ast::CSharpType::Void,
+            vec![
+                call(core::BINDGEN_DEALLOC_SYMBOL, vec![this_abi_field("Ptr"), this_abi_field("Cap")]),
+                reset_abi(),
+            ],
+        );
+
+        ast::Object {
+            attributes: Vec::new(),
+            object_type: ast::ObjectType::Class,
+            is_static: false,
+            is_sealed: true,
+            is_readonly: false,
+            is_ref: false,
+            is_unsafe: false,
+            is_partial: false,
+            is_private: false,
+            is_internal: false,
+            name: RUST_BUFFER_CLASS.to_string(),
+            base_types: vec![ast::CSharpType::Struct {
+                name: "IDisposable".into(),
+            }],
+            constructors: vec![constructor, finalizer],
+            methods: vec![
+                as_span,
+                transfer,
+                dispose,
+                free,
+                dll_import(
+                    core::BINDGEN_ALLOC_SYMBOL,
+                    ast::CSharpType::intptr(),
+                    vec![arg("len", ast::CSharpType::UInt64)],
+                ),
+                dll_import(
+                    core::BINDGEN_DEALLOC_SYMBOL,
+                    ast::CSharpType::Void,
+                    vec![arg("ptr", ast::CSharpType::intptr()), arg("len", ast::CSharpType::UInt64)],
+                ),
+            ],
+            fields: vec![ast::Field {
+                is_public: false,
+                is_readonly: false,
+                name: "abi".to_string(),
+                ty: owned_slice_abi,
+                const_value: None,
+                fixed_len: None,
+                is_static: false,
+                initializer: None,
+            }],
+            properties: Vec::new(),
+            nested: Vec::new(),
         }
-
-        self.output_ident.apply_abstract_id_offset(offset)
     }
-}
-
-#[derive(Clone, Debug)]
-struct BindingMethodBody {
-    body_elements: Vec<BodyElement>,
-}
 
-impl BindingMethodBody {
-    pub fn new(
-        descriptor: &core::BindgenFunctionDescriptor,
-        args: &[BindingMethodArgument]
-    ) -> Self {
-        let mut transform_fragments: Vec<_> =
-            args.iter().map(|a| a.transform_body_fragment()).collect();
+    /// A class whose module initializer registers a DllImportResolver, which loads the library
+    /// named for the current OS in place of the DllImport name, eg
+    ///     `return libraryName != "foo" ? IntPtr.Zero : RuntimeInformation.IsOSPlatform(OSPlatform.Windows) ? NativeLibrary.Load("foo_win", assembly, searchPath) : IntPtr.Zero;`
+    ///
+    /// Returning `IntPtr.Zero` falls back to the runtime's own probing for the DllImport name.
+    fn dll_import_resolver_obj(&self) -> Option<ast::Conditional> {
+        const CLASS_NAME: &str = "NativeLibraryResolver";
 
-        // Ensure that their generated idents from each fragment don't intersect
-        let mut offset = 0;
-        for frag in transform_fragments.iter_mut() {
-            match frag.max_abstract_id() {
-                Some(m) => {
-                    frag.apply_abstract_id_offset(offset);
-                    offset += m + 1;
-                }
-                None => ()
-            }
+        if self.options.platform_dll_names.is_empty() {
+            return None;
         }
 
-        let mut body_elements: Vec<_> = transform_fragments
-            .iter()
-            .flat_map(|frag| frag.elements.iter().cloned())
-            .collect();
-
-        // Add one final body element, calling the bound method with all of the (possibly) transformed arguments.
-        let invocation_args: Vec<AbstractIdent> = transform_fragments
-            .iter()
-            .map(|frag| frag.output_ident.clone())
-            .collect();
+        let ident = |name: &str| Box::new(BodyElement::Ident(name.into()));
+        let arg = |name: &str, ty| ast::MethodArgument {
+            attributes: Vec::new(),
+            name: name.into(),
+            ty,
+        };
+        let string = |value: &str| Box::new(BodyElement::LiteralValue(LiteralValue::String(value.to_string())));
 
-        let underlying_call = BodyElement::MethodCall {
-            method_name: descriptor.thunk_name.to_string(),
-            args: invocation_args,
+        let register = ast::Method {
+            attributes: vec![ast::Attribute::marker("System.Runtime.CompilerServices.ModuleInitializer")],
+            is_public: true,
+            is_static: true,
+            is_extern: false,
+            is_unsafe: false,
+            is_override: false,
+            is_partial: false,
+            name: "Register".to_string(),
+            return_ty: ast::CSharpType::Void,
+            args: Vec::new(),
+            body: Some(BindingMethodBody {
+                body_elements: vec![BodyElement::MethodCall {
+                    target: Some(ident("NativeLibrary")),
+                    method_name: "SetDllImportResolver".to_string(),
+                    args: vec![
+                        BodyElement::FieldAccess {
+                            element: Box::new(BodyElement::Ident(format!("typeof({})", CLASS_NAME).as_str().into())),
+                            field_name: "Assembly".to_string(),
+                        },
+                        BodyElement::Ident("Resolve".into()),
+                    ],
+                }],
+            }.to_ast_nodes()),
         };
 
-        if descriptor.return_ty != core::BindgenTypeDescriptor::Void {
-            body_elements.push(BodyElement::Return {
-                element: Some(Box::new(underlying_call))
+        let resolved = self.options.platform_dll_names
+            .iter()
+            .rev()
+            .fold(BodyElement::Ident("IntPtr.Zero".into()), |otherwise, (os, name)| {
+                BodyElement::TernaryExpression {
+                    test: Box::new(BodyElement::MethodCall {
+                        target: Some(ident("RuntimeInformation")),
+                        method_name: "IsOSPlatform".to_string(),
+                        args: vec![BodyElement::Ident(format!("OSPlatform.{}", os).as_str().into())],
+                    }),
+                    true_branch: Box::new(BodyElement::MethodCall {
+                        target: Some(ident("NativeLibrary")),
+                        method_name: "Load".to_string(),
+                        args: vec![
+                            BodyElement::LiteralValue(LiteralValue::String(name.clone())),
+                            BodyElement::Ident("assembly".into()),
+                            BodyElement::Ident("searchPath".into()),
+                        ],
+                    }),
+                    false_branch: Box::new(otherwise),
+                }
             });
-        } else {
-            body_elements.push(underlying_call);
-        }
 
-        Self { body_elements }
+        let resolve = ast::Method {
+            attributes: Vec::new(),
+            is_public: false,
+            is_static: true,
+            is_extern: false,
+            is_unsafe: false,
+            is_override: false,
+            is_partial: false,
+            name: "Resolve".to_string(),
+            return_ty: ast::CSharpType::intptr(),
+            args: vec![
+                arg("libraryName", ast::CSharpType::String),
+                arg("assembly", ast::CSharpType::named("System.Reflection.Assembly")),
+                arg("searchPath", ast::CSharpType::named("DllImportSearchPath?")),
+            ],
+            body: Some(BindingMethodBody {
+                body_elements: vec![BodyElement::Return {
+                    element: Some(Box::new(BodyElement::TernaryExpression {
+                        test: Box::new(BodyElement::BinaryExpression {
+                            lhs: ident("libraryName"),
+                            rhs: string(&self.dll_name),
+                            operation: BinaryOperation::NotEqual,
+                        }),
+                        true_branch: ident("IntPtr.Zero"),
+                        false_branch: Box::new(resolved),
+                    })),
+                }],
+            }.to_ast_nodes()),
+        };
+
+        Some(ast::Conditional {
+            condition: "NET5_0_OR_GREATER".to_string(),
+            child: Box::new(ast::Object {
+                attributes: Vec::new(),
+                object_type: ast::ObjectType::Class,
+                is_static: true,
+                is_sealed: false,
+                is_readonly: false,
+                is_ref: false,
+                is_unsafe: false,
+                is_partial: false,
+                is_private: false,
+                is_internal: false,
+                name: CLASS_NAME.to_string(),
+                base_types: Vec::new(),
+                constructors: Vec::new(),
+                methods: vec![register, resolve],
+                fields: Vec::new(),
+                properties: Vec::new(),
+                nested: Vec::new(),
+            }),
+        })
     }
 
-    pub fn to_ast_nodes(&self) -> Vec<Box<dyn ast::AstNode>> {
-        fn render_elements<'a>(elements: &'a mut impl Iterator<Item = &'a BodyElement>) -> Vec<Box<dyn ast::AstNode>> {
-            let mut ast_nodes = Vec::new();
-            let mut next = elements.next();
-            while let Some(el) = next {
-                ast_nodes.push({
-                    let node = el.to_ast_node();
-                    if el.is_top_level() {
-                        node
-                    } else {
-                        Box::new(ast::Statement {
-                            expr: node
-                        })
-                    }
-                });
+    /// FfiStable representation of a Duration, along with conversions to and from a TimeSpan.
+    ///
+    /// A Duration can't be negative, so converting a negative TimeSpan throws an OverflowException.
+    fn duration_abi_obj() -> ast::Object {
+        // FieldAccess parenthesizes its target, which isn't valid for a type name
+        let ticks_per_second = || BodyElement::Ident("TimeSpan.TicksPerSecond".into());
+        let value_ticks = || BodyElement::FieldAccess {
+            element: Box::new(BodyElement::Ident("value".into())),
+            field_name: "Ticks".to_string(),
+        };
+        let this_field = |name: &str| BodyElement::FieldAccess {
+            element: Box::new(BodyElement::Ident("this".into())),
+            field_name: name.to_string(),
+        };
+        let abi_field = |name: &str| BodyElement::FieldAccess {
+            element: Box::new(BodyElement::Ident(0.into())),
+            field_name: name.to_string(),
+        };
+        let duration_abi = ast::CSharpType::Struct {
+            name: "DurationAbi".into(),
+        };
+        let time_span = ast::CSharpType::Struct {
+            name: "TimeSpan".into(),
+        };
 
-                if el.requires_new_scope() {
-                    ast_nodes.push(Box::new(ast::Scope {
-                        children: render_elements(elements),
-                    }));
-                    break;
-                }
+        // One tick is 100ns
+        let nanos_per_tick = || BodyElement::LiteralValue(LiteralValue::Number(100));
 
-                next = elements.next();
-            }
+        let from_time_span = ast::Method {
+            attributes: Vec::new(),
+            is_public: true,
+            is_static: true,
+            is_extern: false,
+            is_unsafe: false,
+            is_override: false,
+            is_partial: false,
+            name: "FromTimeSpan".to_string(),
+            return_ty: duration_abi.clone(),
+            args: vec![ast::MethodArgument {
+                attributes: Vec::new(),
+                name: "value".into(),
+                ty: time_span.clone(),
+            }],
+            body: Some(BindingMethodBody {
+                body_elements: vec![
+                    BodyElement::DeclareLocal {
+                        id: 0.into(),
+                        ty: duration_abi.clone(),
+                    },
+                    BodyElement::Assignment {
+                        lhs: Box::new(abi_field("Secs")),
+                        rhs: Box::new(BodyElement::Checked {
+                            element: Box::new(BodyElement::Cast {
+                                ty: ast::CSharpType::UInt64,
+                                element: Box::new(BodyElement::BinaryExpression {
+                                    lhs: Box::new(value_ticks()),
+                                    rhs: Box::new(ticks_per_second()),
+                                    operation: BinaryOperation::Divide,
+                                }),
+                            }),
+                        }),
+                    },
+                    BodyElement::Assignment {
+                        lhs: Box::new(abi_field("Nanos")),
+                        rhs: Box::new(BodyElement::Checked {
+                            element: Box::new(BodyElement::Cast {
+                                ty: ast::CSharpType::UInt32,
+                                element: Box::new(BodyElement::BinaryExpression {
+                                    lhs: Box::new(BodyElement::BinaryExpression {
+                                        lhs: Box::new(value_ticks()),
+                                        rhs: Box::new(ticks_per_second()),
+                                        operation: BinaryOperation::Remainder,
+                                    }),
+                                    rhs: Box::new(nanos_per_tick()),
+                                    operation: BinaryOperation::Multiply,
+                                }),
+                            }),
+                        }),
+                    },
+                    BodyElement::Return {
+                        element: Some(Box::new(BodyElement::Ident(0.into()))),
+                    },
+                ],
+            }.to_ast_nodes()),
+        };
 
-            ast_nodes
-        }
+        let ticks = BodyElement::BinaryExpression {
+            lhs: Box::new(BodyElement::BinaryExpression {
+                lhs: Box::new(this_field("Secs")),
+                rhs: Box::new(BodyElement::Cast {
+                    ty: ast::CSharpType::UInt64,
+                    element: Box::new(ticks_per_second()),
+                }),
+                operation: BinaryOperation::Multiply,
+            }),
+            rhs: Box::new(BodyElement::BinaryExpression {
+                lhs: Box::new(this_field("Nanos")),
+                rhs: Box::new(nanos_per_tick()),
+                operation: BinaryOperation::Divide,
+            }),
+            operation: BinaryOperation::Add,
+        };
 
-        render_elements(&mut self.body_elements.iter())
+        let to_time_span = ast::Method {
+            attributes: Vec::new(),
+            is_public: true,
+            is_static: false,
+            is_extern: false,
+            is_unsafe: false,
+            is_override: false,
+            is_partial: false,
+            name: "ToTimeSpan".to_string(),
+            return_ty: time_span,
+            args: Vec::new(),
+            body: Some(BindingMethodBody {
+                body_elements: vec![
+                    BodyElement::Return {
+                        element: Some(Box::new(BodyElement::MethodCall {
+                            target: Some(Box::new(BodyElement::Ident("TimeSpan".into()))),
+                            method_name: "FromTicks".to_string(),
+                            args: vec![BodyElement::Checked {
+                                element: Box::new(BodyElement::Cast {
+                                    ty: ast::CSharpType::Int64,
+                                    element: Box::new(ticks),
+                                }),
+                            }],
+                        })),
+                    },
+                ],
+            }.to_ast_nodes()),
+        };
+
+        ast::Object {
+            attributes: vec![ast::Attribute::struct_layout(ast::LayoutKind::Sequential)],
+            object_type: ast::ObjectType::Struct,
+            is_static: false,
+            is_sealed: false,
+            is_readonly: false,
+            is_ref: false,
+            is_unsafe: false,
+            is_partial: false,
+            is_private: false,
+            is_internal: false,
+            name: "DurationAbi".into(),
+            base_types: Vec::new(),
+            constructors: Vec::new(),
+            methods: vec![from_time_span, to_time_span],
+            fields: vec![
+                ast::Field {
+                    is_public: true,
+                    is_readonly: false,
+                    name: "Secs".to_string(),
+                    ty: ast::CSharpType::UInt64,
+                    const_value: None,
+                    fixed_len: None,
+                    is_static: false,
+                    initializer: None,
+                },
+                ast::Field {
+                    is_public: true,
+                    is_readonly: false,
+                    name: "Nanos".to_string(),
+                    ty: ast::CSharpType::UInt32,
+                    const_value: None,
+                    fixed_len: None,
+                    is_static: false,
+                    initializer: None,
+                },
+            ],
+            properties: Vec::new(),
+            nested: Vec::new(),
+        }
     }
-}
 
-#[derive(Clone, Debug)]
-struct BindingMethod {
-    args: Vec<BindingMethodArgument>,
+    /// An interface declaring each public top level wrapper method, so that callers can depend on
+    /// it and substitute a fake in tests, along with a class implementing it by forwarding to
+    /// TopLevelMethods.
+    fn native_interface_objs(&self, methods: &[BindingMethod]) -> (ast::Interface, ast::Object) {
+        let interface_name = format!("I{}Native", self.lib_name.to_camel_case());
+        let class_name = format!("{}Native", self.lib_name.to_camel_case());
 
-    return_ty: BindingType,
+        let public_methods = || methods
+            .iter()
+            .flat_map(|m| m.to_ast_methods())
+            .filter(|m| m.is_public);
 
-    /// The name of the binary containing the method, suitable for using directly in a DllImport attribute.
-    binary_name: String,
+        let implementations = public_methods()
+            .map(|m| {
+                let invocation = BodyElement::MethodCall {
+                    target: Some(Box::new(BodyElement::Ident("TopLevelMethods".into()))),
+                    method_name: m.name.clone(),
+                    args: m.args
+                        .iter()
+                        .map(|arg| BodyElement::Ident(arg.name.0.as_str().into()))
+                        .collect(),
+                };
 
-    /// The name of the method that received the original #[dotnet_bindgen] attribute
-    /// 
-    /// This isn't neccesarily unique among the bindings, or the name of the symbol in the binary,
-    /// as the if a thunk is generated the method doens't have to have #[no_mangle] attached.
-    rust_name: String,
+                let body_elements = if matches!(m.return_ty, ast::CSharpType::Void) {
+                    vec![invocation]
+                } else {
+                    vec![BodyElement::Return {
+                        element: Some(Box::new(invocation)),
+                    }]
+                };
 
-    /// The symbol name of the generated rust thunk, if one was generated.
-    /// 
-    /// Guaranteed to be unique among the bindings.
-    rust_thunk_name: String,
+                ast::Method {
+                    is_static: false,
+                    body: Some(BindingMethodBody { body_elements }.to_ast_nodes()),
+                    ..m
+                }
+            })
+            .collect();
 
-    /// The name of the C# method to expose from the bindings BindingMethodBody
-    /// 
-    /// Typically just rust_name.to_camel_case().
-    cs_name: String,
+        let interface = ast::Interface {
+            name: interface_name.clone(),
+            methods: public_methods().collect(),
+        };
 
-    /// If a C# thunk must be generated, the body of that thunk.
-    cs_thunk_body: Option<BindingMethodBody>,
-}
+        let class = ast::Object {
+            attributes: Vec::new(),
+            object_type: ast::ObjectType::Class,
+            is_static: false,
+            is_sealed: false,
+            is_readonly: false,
+            is_ref: false,
+            is_unsafe: false,
+            is_partial: false,
+            is_private: false,
+            is_internal: false,
+            name: class_name,
+            base_types: vec![ast::CSharpType::Struct {
+                name: ast::Ident(interface_name),
+            }],
+            constructors: Vec::new(),
+            methods: implementations,
+            fields: Vec::new(),
+            properties: Vec::new(),
+            nested: Vec::new(),
+        };
 
-impl BindingMethod {
-    pub fn new(binary_name: &str, descriptor: &core::BindgenFunctionDescriptor) -> Result<Self, &'static str> {
-        let binary_name = binary_name.to_string();
+        (interface, class)
+    }
 
-        let args = descriptor
-            .arguments
-            .iter()
-            .map(|arg_desc| BindingMethodArgument::try_from(arg_desc.clone()))
-            .collect::<Result<Vec<_>, _>>()?;
+    /// The `BindingVersion` constant, a hash of every descriptor the bindings were generated from.
+    fn binding_version_field(&self) -> ast::Field {
+        ast::Field {
+            is_public: true,
+            is_readonly: false,
+            name: "BindingVersion".to_string(),
+            ty: ast::CSharpType::UInt64,
+            const_value: Some(ast::LiteralValue::UInt64(core::binding_version(&self.data.descriptors))),
+            fixed_len: None,
+            is_static: false,
+            initializer: None,
+        }
+    }
 
-        let return_ty = descriptor.return_ty.clone().try_into()?;
+    /// A check throwing if the native library's `__bindgen_version` doesn't match the
+    /// `BindingVersion`, along with the P/Invoke it calls.
+    ///
+    /// Only generated when the binary exports `__bindgen_version`, and agrees on its own version.
+    fn binding_version_check(&self) -> Option<(BodyElement, ast::Method)> {
+        let native_version = self.data.native_binding_version?;
+        if native_version != core::binding_version(&self.data.descriptors) {
+            diagnostics::warn(&format!(
+                "{} doesn't match the descriptors read from the binary, so the bindings won't check it",
+                core::BINDGEN_VERSION_SYMBOL
+            ));
+            return None;
+        }
 
-        let rust_name = descriptor.real_name.to_string();
-        let rust_thunk_name = descriptor.thunk_name.to_string();
-        let cs_name = rust_name.to_camel_case();
+        let message = format!(
+            "The native library {} doesn't match these bindings, which need to be regenerated",
+            self.dll_name
+        );
+        let check = BodyElement::ThrowIf {
+            condition: Box::new(BodyElement::BinaryExpression {
+                lhs: Box::new(BodyElement::MethodCall {
+                    target: None,
+                    method_name: core::BINDGEN_VERSION_SYMBOL.to_string(),
+                    args: Vec::new(),
+                }),
+                rhs: Box::new(BodyElement::Ident("BindingVersion".into())),
+                operation: BinaryOperation::NotEqual,
+            }),
+            exception: Box::new(BodyElement::NewObject {
+                ty: ast::CSharpType::named("InvalidOperationException"),
+                args: vec![BodyElement::LiteralValue(LiteralValue::String(message))],
+            }),
+        };
 
-        let cs_thunk_body = Some(BindingMethodBody::new(descriptor, &args));
+        let version_method = ast::Method {
+            attributes: vec![ast::Attribute::dll_import(
+                &self.dll_name,
+                core::BINDGEN_VERSION_SYMBOL,
+                core::BINDGEN_VERSION_SYMBOL,
+                "Cdecl",
+            )],
+            is_public: false,
+            is_static: true,
+            is_extern: true,
+            is_unsafe: false,
+            is_override: false,
+            is_partial: false,
+            name: core::BINDGEN_VERSION_SYMBOL.to_string(),
+            return_ty: ast::CSharpType::UInt64,
+            args: Vec::new(),
+            body: None,
+        };
 
-        Ok(Self {
-            binary_name,
-            args,
-            return_ty,
-            rust_name,
-            rust_thunk_name,
-            cs_name,
-            cs_thunk_body,
-        })
+        Some((check, version_method))
     }
 
-    /// Generate the ast nodes for this bound method
-    /// 
-    /// This may be more than one method, eg if a thunk is needed to marshall arguments/return values to/from
-    /// an FFI stable representation.
-    pub fn to_ast_methods(&self) -> Vec<ast::Method> {
-        vec![
-            self.dll_imported_method(),
-            self.thunk_method(),
-        ]
-    }
+    /// A disposable view over a slice returned with `no_copy_slices`, which holds on to the free
+    /// thunk of the function that returned it, if it was owned.
+    ///
+    /// A borrowed slice is held as an OwnedSliceAbi without a capacity, and is never freed.
+    fn native_slice_obj() -> ast::Object {
+        let owned_slice_abi = ast::CSharpType::Struct {
+            name: "OwnedSliceAbi".into(),
+        };
+        let free_type = ast::CSharpType::named("Action<OwnedSliceAbi>");
+        let this_field = |name: &str| Box::new(BodyElement::FieldAccess {
+            element: Box::new(BodyElement::Ident("this".into())),
+            field_name: name.to_string(),
+        });
+        let arg_field = |name: &str| Box::new(BodyElement::FieldAccess {
+            element: Box::new(BodyElement::Ident("abi".into())),
+            field_name: name.to_string(),
+        });
+        let arg = |name: &str, ty: ast::CSharpType| ast::MethodArgument {
+            attributes: Vec::new(),
+            name: name.into(),
+            ty,
+        };
+        let call_free = || BodyElement::MethodCall {
+            target: None,
+            method_name: "Free".to_string(),
+            args: Vec::new(),
+        };
+        let method = |is_public, is_unsafe, name: &str, return_ty, body_elements| ast::Method {
+            attributes: Vec::new(),
+            is_public,
+            is_static: false,
+            is_extern: false,
+            is_unsafe,
+            is_override: false,
+            is_partial: false,
+            name: name.to_string(),
+            return_ty,
+            args: Vec::new(),
+            body: Some(BindingMethodBody { body_elements }.to_ast_nodes()),
+        };
+        let constructor = |args, body_elements| ast::Constructor {
+            kind: ast::ConstructorKind::Instance,
+            object_name: NATIVE_SLICE_CLASS.to_string(),
+            args,
+            body: BindingMethodBody { body_elements }.to_ast_nodes(),
+        };
 
-    fn dll_imported_method(&self) -> ast::Method {
-        let attributes = vec![
-            ast::Attribute::dll_import(&self.binary_name, &self.rust_thunk_name)
-        ];
+        let owned_constructor = constructor(
+            vec![arg("abi", owned_slice_abi.clone()), arg("free", free_type.clone())],
+            vec![
+                BodyElement::Assignment {
+                    lhs: this_field("abi"),
+                    rhs: Box::new(BodyElement::Ident("abi".into())),
+                },
+                BodyElement::Assignment {
+                    lhs: this_field("free"),
+                    rhs: Box::new(BodyElement::Ident("free".into())),
+                },
+            ],
+        );
 
-        let return_ty = self.return_ty.native_type();
+        let borrowed_constructor = constructor(
+            vec![arg("abi", ast::CSharpType::Struct { name: "SliceAbi".into() })],
+            vec![
+                BodyElement::Assignment {
+                    lhs: Box::new(BodyElement::FieldAccess {
+                        element: this_field("abi"),
+                        field_name: "Ptr".to_string(),
+                    }),
+                    rhs: arg_field("Ptr"),
+                },
+                BodyElement::Assignment {
+                    lhs: Box::new(BodyElement::FieldAccess {
+                        element: this_field("abi"),
+                        field_name: "Len".to_string(),
+                    }),
+                    rhs: arg_field("Len"),
+                },
+            ],
+        );
 
-        let args = self.args
-            .iter()
-            .map(|arg| ast::MethodArgument {
-                name: arg.rust_name.as_str().into(),
-                ty: arg.ty.native_type(),
-            })
-            .collect();
+        let finalizer = ast::Constructor {
+            kind: ast::ConstructorKind::Finalizer,
+            object_name: NATIVE_SLICE_CLASS.to_string(),
+            args: Vec::new(),
+            body: BindingMethodBody {
+                body_elements: vec![call_free()],
+            }.to_ast_nodes(),
+        };
 
-        ast::Method {
-            attributes,
+        let as_span = method(
+            true,
+            true,
+            "AsSpan",
+            ast::CSharpType::named("Span<T>"),
+            vec![BodyElement::Return {
+                element: Some(Box::new(owned_span(*this_field("abi"), ast::CSharpType::named("T")))),
+            }],
+        );
+
+        let dispose = method(
+            true,
+            false,
+            "Dispose",
+            ast::CSharpType::Void,
+            vec![
+                call_free(),
+                BodyElement::MethodCall {
+                    target: Some(Box::new(BodyElement::Ident("GC".into()))),
+                    method_name: "SuppressFinalize".to_string(),
+                    args: vec![BodyElement::Ident("this".into())],
+                },
+            ],
+        );
+
+        // The slice is reset to an empty one before being freed, so Dispose can safely be called
+        // more than once.
+        let free = method(
+            false,
+            false,
+            "Free",
+            ast::CSharpType::Void,
+            vec![
+                BodyElement::DeclareLocal {
+                    id: 0.into(),
+                    ty: owned_slice_abi.clone(),
+                },
+                BodyElement::Assignment {
+                    lhs: Box::new(BodyElement::Ident(0.into())),
+                    rhs: this_field("abi"),
+                },
+                BodyElement::Assignment {
+                    lhs: this_field("abi"),
+                    rhs: Box::new(BodyElement::NewObject {
+                        ty: owned_slice_abi.clone(),
+                        args: Vec::new(),
+                    }),
+                },
+                BodyElement::If {
+                    condition: Box::new(BodyElement::BinaryExpression {
+                        lhs: this_field("free"),
+                        rhs: Box::new(BodyElement::LiteralValue(LiteralValue::Null)),
+                        operation: BinaryOperation::NotEqual,
+                    }),
+                },
+                BodyElement::MethodCall {
+                    target: Some(Box::new(BodyElement::Ident("this".into()))),
+                    method_name: "free".to_string(),
+                    args: vec![BodyElement::Ident(0.into())],
+                },
+            ],
+        );
+
+        let field = |name: &str, ty, is_readonly| ast::Field {
             is_public: false,
-            is_static: true,
-            is_extern: true,
+            is_readonly,
+            name: name.to_string(),
+            ty,
+            const_value: None,
+            fixed_len: None,
+            is_static: false,
+            initializer: None,
+        };
+
+        ast::Object {
+            attributes: Vec::new(),
+            object_type: ast::ObjectType::Class,
+            is_static: false,
+            is_sealed: true,
+            is_readonly: false,
+            is_ref: false,
             is_unsafe: false,
-            name: self.rust_thunk_name.to_string(),
-            return_ty,
-            args,
-            body: None,
+            is_partial: false,
+            is_private: false,
+            is_internal: false,
+            name: format!("{}<T>", NATIVE_SLICE_CLASS),
+            base_types: vec![ast::CSharpType::Struct {
+                name: "IDisposable".into(),
+            }],
+            constructors: vec![owned_constructor, borrowed_constructor, finalizer],
+            methods: vec![as_span, dispose, free],
+            fields: vec![
+                field("abi", owned_slice_abi, false),
+                field("free", free_type, true),
+            ],
+            properties: Vec::new(),
+            nested: Vec::new(),
         }
     }
 
-    fn thunk_method(&self) -> ast::Method {
-        let attributes = Vec::new();
+    /// Reads a Stream to the end into a buffer rented from the shared ArrayPool, for the Stream
+    /// companions. The buffer is doubled in size whenever it fills up.
+    ///
+    /// Only the generated overloads use it, so it's kept out of the bindings' API.
+    fn pooled_stream_obj() -> ast::Object {
+        let ident = |name: &str| Box::new(BodyElement::Ident(name.into()));
+        let number = |num| Box::new(BodyElement::LiteralValue(LiteralValue::Number(num)));
+        let byte_array = ast::CSharpType::Array {
+            elem_type: Box::new(ast::CSharpType::Byte),
+        };
+        let pool_call = |method_name: &str, arg| BodyElement::MethodCall {
+            target: Some(ident(SHARED_BYTE_POOL)),
+            method_name: method_name.to_string(),
+            args: vec![arg],
+        };
+        let buf_length = || Box::new(BodyElement::FieldAccess {
+            element: ident("buf"),
+            field_name: "Length".to_string(),
+        });
 
-        let name = self.cs_name.to_string();
+        let grow = vec![
+            BodyElement::DeclareLocal {
+                id: "grown".into(),
+                ty: byte_array.clone(),
+            },
+            BodyElement::Assignment {
+                lhs: ident("grown"),
+                rhs: Box::new(pool_call("Rent", BodyElement::Checked {
+                    element: Box::new(BodyElement::BinaryExpression {
+                        lhs: buf_length(),
+                        rhs: number(2),
+                        operation: BinaryOperation::Multiply,
+                    }),
+                })),
+            },
+            BodyElement::MethodCall {
+                target: Some(ident("System.Buffer")),
+                method_name: "BlockCopy".to_string(),
+                args: vec![*ident("buf"), *number(0), *ident("grown"), *number(0), *ident("len")],
+            },
+            pool_call("Return", *ident("buf")),
+            BodyElement::Assignment {
+                lhs: ident("buf"),
+                rhs: ident("grown"),
+            },
+        ];
 
-        // TODO: Make this the idiomatic type + add the relevant marshalling to the body.
-        let return_ty = self.return_ty.native_type();
+        let read_loop = vec![
+            BodyElement::Assignment {
+                lhs: ident("read"),
+                rhs: Box::new(BodyElement::MethodCall {
+                    target: Some(ident("stream")),
+                    method_name: "Read".to_string(),
+                    args: vec![
+                        *ident("buf"),
+                        *ident("len"),
+                        BodyElement::BinaryExpression {
+                            lhs: buf_length(),
+                            rhs: ident("len"),
+                            operation: BinaryOperation::Subtract,
+                        },
+                    ],
+                }),
+            },
+            BodyElement::Assignment {
+                lhs: ident("len"),
+                rhs: Box::new(BodyElement::BinaryExpression {
+                    lhs: ident("len"),
+                    rhs: ident("read"),
+                    operation: BinaryOperation::Add,
+                }),
+            },
+            BodyElement::If {
+                condition: Box::new(BodyElement::BinaryExpression {
+                    lhs: ident("len"),
+                    rhs: buf_length(),
+                    operation: BinaryOperation::Equal,
+                }),
+            },
+        ]
+        .into_iter()
+        .chain(grow)
+        .collect();
 
-        let args = self.args
-            .iter()
-            .map(|arg| ast::MethodArgument {
-                name: arg.cs_name.as_str().into(),
-                ty: arg.ty.idiomatic_type(),
-            })
-            .collect();
-        
-        let body = Some(self.cs_thunk_body
-            .as_ref()
-            .unwrap()
-            .to_ast_nodes()
-        );
+        let body_elements = vec![
+            BodyElement::DeclareLocal {
+                id: "buf".into(),
+                ty: byte_array.clone(),
+            },
+            BodyElement::Assignment {
+                lhs: ident("buf"),
+                rhs: Box::new(pool_call("Rent", *number(4096))),
+            },
+            BodyElement::DeclareLocal {
+                id: "done".into(),
+                ty: ast::CSharpType::Bool,
+            },
+            BodyElement::Assignment {
+                lhs: ident("done"),
+                rhs: Box::new(BodyElement::LiteralValue(LiteralValue::Bool(false))),
+            },
+            // The caller only takes ownership of the buffer once it has been filled, so it's
+            // returned to the pool here if reading the stream throws.
+            BodyElement::TryFinally {
+                body: vec![
+                    // Any non-zero value, so that the loop is entered.
+                    BodyElement::DeclareLocal {
+                        id: "read".into(),
+                        ty: ast::CSharpType::Int32,
+                    },
+                    BodyElement::Assignment {
+                        lhs: ident("read"),
+                        rhs: number(1),
+                    },
+                    BodyElement::While {
+                        condition: Box::new(BodyElement::BinaryExpression {
+                            lhs: ident("read"),
+                            rhs: number(0),
+                            operation: BinaryOperation::NotEqual,
+                        }),
+                        body: read_loop,
+                    },
+                    BodyElement::Assignment {
+                        lhs: ident("done"),
+                        rhs: Box::new(BodyElement::LiteralValue(LiteralValue::Bool(true))),
+                    },
+                ],
+                finally: vec![
+                    BodyElement::If {
+                        condition: Box::new(BodyElement::Not { element: ident("done") }),
+                    },
+                    pool_call("Return", *ident("buf")),
+                ],
+            },
+            BodyElement::Return {
+                element: Some(ident("buf")),
+            },
+        ];
 
-        ast::Method {
-            attributes,
+        let read_to_end = ast::Method {
+            attributes: Vec::new(),
             is_public: true,
             is_static: true,
             is_extern: false,
             is_unsafe: false,
-            name,
-            return_ty,
-            args,
-            body,
-        }
-    }
-}
-
-
-struct BindingStructField {
-    /// The name of this field in the generated C# (CamelCase transform rust_name)
-    cs_name: String,
+            is_override: false,
+            is_partial: false,
+            name: "ReadToEnd".to_string(),
+            return_ty: byte_array,
+            args: vec![
+                ast::MethodArgument {
+                    attributes: Vec::new(),
+                    name: "stream".into(),
+                    ty: ast::CSharpType::named("System.IO.Stream"),
+                },
+                ast::MethodArgument {
+                    attributes: Vec::new(),
+                    name: "len".into(),
+                    ty: ast::CSharpType::Ref {
+                        target: Box::new(ast::CSharpType::Int32),
+                        is_readonly: false,
+                    },
+                },
+            ],
+            body: Some(BindingMethodBody { body_elements }.to_ast_nodes()),
+        };
 
-    /// The type of this field. Restricted to simple binding types to make the entire struct FFI stable.
-    ty: SimpleBindingType,
-}
+        let mut obj = CodegenInfo::static_class_obj(POOLED_STREAM_CLASS, vec![read_to_end]);
+        obj.is_internal = true;
+        obj
+    }
 
-impl BindingStructField {
-    fn new(descriptor: &core::BindgenStructFieldDescriptor) -> Result<Self, &'static str> {
-        let cs_name = descriptor.name.to_camel_case();
+    fn static_class_obj(name: &str, methods: Vec<ast::Method>) -> ast::Object {
+        ast::Object {
+            attributes: Vec::new(),
+            object_type: ast::ObjectType::Class,
+            is_static: true,
+            is_sealed: false,
+            is_readonly: false,
+            is_ref: false,
+            is_unsafe: false,
+            is_partial: false,
+            is_private: false,
+            is_internal: false,
+            name: name.to_string(),
+            base_types: Vec::new(),
+            constructors: Vec::new(),
+            methods,
+            fields: Vec::new(),
+            properties: Vec::new(),
+            nested: Vec::new(),
+        }
+    }
 
-        let ty = match descriptor.ty.clone().try_into()? {
-            BindingType::Simple(s) => s,
-            _ => return Err("Can't create bindings for structs with non-ffi-stable fields"),
-        };
+    fn top_level_methods_obj(methods: &[BindingMethod], statics: &[BindingStatic]) -> ast::Object {
+        let methods = methods.iter()
+            .flat_map(|m| m.to_ast_methods())
+            .chain(statics.iter().map(|s| s.to_ast_method()))
+            .collect();
 
-        Ok(Self {
-            cs_name,
-            ty,
-        })
+        let mut obj = CodegenInfo::static_class_obj("TopLevelMethods", methods);
+        obj.fields.extend(statics.first().map(|s| s.library_handle_field()));
+        obj
     }
 
-    fn to_ast_field(&self) -> ast::Field {
-        ast::Field {
-            name: self.cs_name.clone(),
-            ty: self.ty.cs_type.clone(),
+    /// Static classes for the free functions bound with `module = "..."`, nested according to
+    /// their module paths, in the order each module is first seen.
+    ///
+    /// `methods` pairs each method with the part of its module path below the current class. When
+    /// `validates`, each class runs the checks in TopLevelMethods before its first call.
+    fn module_objs(
+        &self,
+        methods: &[(&[String], &BindingMethod)],
+        is_outermost: bool,
+        validates: bool,
+    ) -> Vec<ast::Object> {
+        let mut names: Vec<&String> = Vec::new();
+        for (path, _) in methods {
+            if !names.contains(&&path[0]) {
+                names.push(&path[0]);
+            }
         }
-    }
-}
 
-struct BindingStruct {
-    /// The name of the struct in both the bound Rust, and the generated C# (both are CamelCase by convention)
-    name: String,
+        names.into_iter()
+            .map(|name| {
+                let (inner, below): (Vec<_>, Vec<_>) = methods.iter()
+                    .filter(|(path, _)| &path[0] == name)
+                    .map(|(path, m)| (&path[1..], *m))
+                    .partition(|(path, _)| path.is_empty());
 
-    /// Ordered set of fields. Repr(C) in Rust should map 1-1 with C# StructLayout.Sequential
-    fields: Vec<BindingStructField>,
+                let mut obj = CodegenInfo::static_class_obj(
+                    name,
+                    inner.iter().flat_map(|(_, m)| m.to_ast_methods()).collect(),
+                );
+                obj.nested = self.module_objs(&below, false, validates);
+                if validates {
+                    obj.constructors.push(ensure_validated_constructor(name));
+                }
 
-    /// Set of methods to grant this struct
-    methods: Vec<BindingMethod>,
-}
+                // The source generated P/Invokes are implemented in another part of each class
+                obj.is_partial = self.options.custom_marshallers;
+                obj.is_unsafe = self.options.custom_marshallers && obj.has_unsafe_member();
 
-impl BindingStruct {
-    fn new(descriptor: &core::BindgenStructDescriptor) -> Result<Self, &'static str> {
-        let fields = descriptor.fields
-            .iter()
-            .map(|f| BindingStructField::new(&f))
-            .collect::<Result<Vec<_>, _>>()?;
+                // Nested classes find the hooks declared in their outermost class
+                if self.options.trace_hooks && is_outermost {
+                    obj.is_partial = true;
+                    obj.methods.extend(trace_hook_declarations());
+                }
 
-        let name = descriptor.name.to_string();
+                obj
+            })
+            .collect()
+    }
 
-        Ok(Self {
-            name,
-            fields,
-            methods: Vec::new(),
-        })
+    /// The names of the structs generated as readonly structs.
+    fn readonly_struct_names(&self) -> Vec<&str> {
+        self.descriptors.iter()
+            .filter_map(|descriptor| match descriptor {
+                core::BindgenExportDescriptor::Struct(s) => Some(s),
+                _ => None,
+            })
+            .filter(|descriptor| {
+                BindingStruct::new(descriptor, Vec::new(), self.options.function_pointers)
+                    .is_ok_and(|s| s.is_readonly(self.options))
+            })
+            .map(|descriptor| descriptor.name.as_str())
+            .collect()
     }
 
-    fn to_ast_object(&self) -> ast::Object {
-        let is_static = self.fields.len() == 0;
-        let object_type = if is_static {
-            ast::ObjectType::Class
-        } else {
-            ast::ObjectType::Struct
-        };
+    fn form_ast(&self) -> Result<ast::Root, BindgenError> {
+        // `this` is readonly in a readonly struct, so can only be passed by `in`. The native method
+        // still mutates the struct in place.
+        let readonly_structs = self.readonly_struct_names();
+        let functions: Vec<_> = self.descriptors.iter()
+            .filter_map(|descriptor| match descriptor {
+                core::BindgenExportDescriptor::Function(f) => Some(f),
+                _ => None
+            })
+            .map(|descriptor| match (&descriptor.owner, descriptor.receiver) {
+                (Some(owner), Some(core::BindgenReceiver::RefMut)) if readonly_structs.contains(&owner.as_str()) => {
+                    core::BindgenFunctionDescriptor {
+                        receiver: Some(core::BindgenReceiver::Ref),
+                        ..descriptor.clone()
+                    }
+                }
+                _ => descriptor.clone(),
+            })
+            .collect();
 
-        let name = self.name.clone();
+        for descriptor in &functions {
+            check_slice_alignment(descriptor, self.data.pointer_width)?;
+        }
 
-        let fields = self.fields
-            .iter()
-            .map(|f| f.to_ast_field())
-            .collect();
+        let (mut associated_methods, top_level_methods): (Vec<_>, Vec<_>) = functions.iter()
+            .map(|descriptor| BindingMethod::new(&self.dll_name, descriptor, self.options))
+            .collect::<Result<Vec<_>, _>>().expect("Failed to process method")
+            .into_iter()
+            .partition(|m| m.owner.is_some());
 
-        let methods = self.methods
-            .iter()
-            .flat_map(|m| m.to_ast_methods())
+        let type_names: Vec<&str> = self.descriptors.iter()
+            .filter_map(|descriptor| match descriptor {
+                core::BindgenExportDescriptor::Struct(s) => Some(s.cs_name.as_ref().unwrap_or(&s.name).as_str()),
+                core::BindgenExportDescriptor::Enum(e) => Some(e.name.as_str()),
+                _ => None,
+            })
             .collect();
-
-        ast::Object {
-            attributes: vec![ast::Attribute::struct_layout("Sequential")],
-            object_type,
-            is_static,
-            name,
-            methods,
-            fields,
+        for method in &top_level_methods {
+            if let Some(name) = method.module.iter().find(|name| {
+                RESERVED_MODULE_NAMES.contains(&name.as_str()) || type_names.contains(&name.as_str())
+            }) {
+                return Err(BindgenError::ModuleNameClash {
+                    module: method.module.join("."),
+                    name: name.clone(),
+                });
+            }
         }
-    }
-}
 
-/// Maps a BindgenTypeDescriptor to the type it appears as in the generated thunk
-struct CodegenInfo<'a> {
-    /// Raw descriptor data extracted from the binary
-    data: &'a BindgenData,
+        let owned_buffer_objs: Vec<_> = associated_methods.iter()
+            .chain(top_level_methods.iter())
+            .filter_map(|m| m.owned_buffer_obj())
+            .collect();
 
-    /// The parsed name of the library. Eg "libbindings_demo.so" -> "bindings_demo".
-    ///
-    /// It should be sufficient to use this string as the first argument to a DllImportAttribute.
-    lib_name: String,
-}
+        // Any function returning an Arc of a type can provide its handle class.
+        let mut handle_objs: Vec<ast::Object> = Vec::new();
+        for obj in associated_methods.iter().chain(top_level_methods.iter()).filter_map(|m| m.handle_obj()) {
+            if !handle_objs.iter().any(|o| o.name == obj.name) {
+                handle_objs.push(obj);
+            }
+        }
 
-impl<'a> CodegenInfo<'a> {
-    fn new(data: &'a BindgenData) -> Self {
-        let lib_name = data.source_file.bin_base_name();
-        Self {
-            data,
-            lib_name,
+        // Tuples with the same ABI types share a struct.
+        let mut tuple_abi_objs: Vec<ast::Object> = Vec::new();
+        for obj in associated_methods.iter().chain(top_level_methods.iter()).filter_map(|m| m.tuple_abi_obj()) {
+            if !tuple_abi_objs.iter().any(|o| o.name == obj.name) {
+                tuple_abi_objs.push(obj);
+            }
         }
-    }
 
-    fn slice_abi_obj() -> ast::Object {
-        ast::Object {
-            attributes: vec![ast::Attribute::struct_layout("Sequential")],
-            object_type: ast::ObjectType::Struct,
-            is_static: false,
-            name: "SliceAbi".into(),
-            methods: Vec::new(),
-            fields: vec![
-                ast::Field {
-                    name: "Ptr".to_string(),
-                    ty: ast::CSharpType::Struct {
-                        name: ast::Ident::new("IntPtr"),
-                    },
-                },
-                ast::Field {
-                    name: "Len".to_string(),
-                    ty: ast::CSharpType::UInt64,
-                },
-            ],
+        let mut marshaller_objs: Vec<ast::Object> = Vec::new();
+        for marshaller in associated_methods.iter()
+            .chain(top_level_methods.iter())
+            .flat_map(|m| m.args.iter())
+            .filter_map(|arg| arg.marshaller.as_ref())
+        {
+            if !marshaller_objs.iter().any(|o| o.name == marshaller.name) {
+                marshaller_objs.push(marshaller.to_ast_object());
+            }
         }
-    }
 
-    fn top_level_methods_obj(methods: &[BindingMethod]) -> ast::Object {
-        ast::Object {
-            attributes: Vec::new(),
-            object_type: ast::ObjectType::Class,
-            is_static: true,
-            name: "TopLevelMethods".into(),
-            methods: methods.iter().flat_map(|m| m.to_ast_methods()).collect(),
-            fields: Vec::new(),
+        let takes_rust_buffer = associated_methods.iter()
+            .chain(top_level_methods.iter())
+            .flat_map(|m| m.args.iter())
+            .any(|arg| matches!(arg.ty.descriptor(), Some(desc) if takes_rust_buffer(desc)));
+
+        for method in associated_methods.iter().chain(top_level_methods.iter()) {
+            for arg in &method.args {
+                if let Some(core::BindgenTypeDescriptor::Arc { name }) = arg.ty.descriptor() {
+                    let class_name = handle_class_name(name);
+                    if !handle_objs.iter().any(|o| o.name == class_name) {
+                        panic!(
+                            "{} takes an Arc which no function returns, so there's no {} to pass it as",
+                            method.rust_name,
+                            class_name,
+                        );
+                    }
+                }
+            }
         }
-    }
 
-    fn form_ast(&self) -> ast::Root {
-        let mut objects = self.data.descriptors.iter()
+        let structs = self.descriptors.iter()
             .filter_map(|descriptor| match descriptor {
                 core::BindgenExportDescriptor::Struct(s) => Some(s),
                 _ => None,
             })
-            .map(|descriptor| BindingStruct::new(descriptor))
-            .map(|s| s.map(|s| Box::new(s.to_ast_object()) as Box<dyn ast::AstNode>))
+            .filter_map(|descriptor| {
+                let (methods, rest): (Vec<_>, Vec<_>) = associated_methods
+                    .drain(..)
+                    .partition(|m| m.owner.as_ref() == Some(&descriptor.name));
+                associated_methods = rest;
+
+                if !self.options.type_mapper.is_mapped(&descriptor.name) {
+                    Some(BindingStruct::new(descriptor, methods, self.options.function_pointers))
+                } else if methods.is_empty() {
+                    None
+                } else {
+                    Some(Err("Can't bind methods on a struct mapped to an existing C# type"))
+                }
+            })
             .collect::<Result<Vec<_>, _>>().expect("Failed to process struct");
 
-        let top_level_methods = self.data.descriptors.iter()
+        // Every check runs before the first call into the library, starting with the version check,
+        // as a mismatched binary would fail the layout checks too.
+        let mut static_checks = Vec::new();
+        let version_method = self.binding_version_check().map(|(version_check, version_method)| {
+            static_checks.push(version_check);
+            version_method
+        });
+        static_checks.extend(structs.iter().flat_map(|s| s.layout_checks()));
+        let validates = !static_checks.is_empty();
+        let alignment_probes: Vec<_> = structs.iter().filter_map(|s| s.alignment_probe()).collect();
+
+        let mut objects = structs.iter()
+            .flat_map(|s| {
+                let mut obj = s.to_ast_object(self.options);
+                if validates && !s.methods.is_empty() {
+                    obj.constructors.push(ensure_validated_constructor(&obj.name));
+                }
+                let mut objs = vec![obj];
+                objs.extend(s.managed_obj());
+                objs
+            })
+            .map(|o| Box::new(o) as Box<dyn ast::AstNode>)
+            .collect::<Vec<_>>();
+
+        if let Some(orphan) = associated_methods.first() {
+            return Err(BindgenError::UnboundMethodOwner {
+                owner: orphan.owner.clone().unwrap_or_default(),
+                method: orphan.rust_name.clone(),
+            });
+        }
+
+        let enums = self.descriptors.iter()
             .filter_map(|descriptor| match descriptor {
-                core::BindgenExportDescriptor::Function(f) => Some(f),
+                core::BindgenExportDescriptor::Enum(e) => Some(e),
+                _ => None,
+            })
+            .filter(|descriptor| !self.options.type_mapper.is_mapped(&descriptor.name))
+            .map(|descriptor| enum_to_ast(descriptor).map(|e| Box::new(e) as Box<dyn ast::AstNode>))
+            .collect::<Result<Vec<_>, _>>()?;
+        objects.extend(enums);
+
+        objects.extend(
+            opaque_type_names(&self.descriptors)
+                .iter()
+                .map(|name| Box::new(opaque_struct_obj(name)) as Box<dyn ast::AstNode>),
+        );
+
+        let statics = self.descriptors.iter()
+            .filter_map(|descriptor| match descriptor {
+                core::BindgenExportDescriptor::Static(s) => Some(s),
                 _ => None
             })
-            .map(|descriptor| BindingMethod::new(&self.lib_name, descriptor))
-            .collect::<Result<Vec<_>, _>>().expect("Failed to process method");
+            .map(|descriptor| BindingStatic::new(&self.dll_name, descriptor))
+            .collect::<Result<Vec<_>, _>>().expect("Failed to process static");
+
+        let native_slice = associated_methods.iter().chain(top_level_methods.iter()).any(|m| m.native_slice);
 
         objects.push(Box::new(CodegenInfo::slice_abi_obj()) as Box<dyn ast::AstNode>);
-        objects.push(Box::new(CodegenInfo::top_level_methods_obj(&top_level_methods)) as Box<dyn ast::AstNode>);
+        if uses_type(&self.descriptors, &|ty| matches!(ty, core::BindgenTypeDescriptor::Duration)) {
+            objects.push(Box::new(CodegenInfo::duration_abi_obj()) as Box<dyn ast::AstNode>);
+        }
+        // Owned buffers, and the borrowed slices a NativeSlice holds, cross the boundary as one
+        let owns_buffers = uses_type(&self.descriptors, &|ty| matches!(
+            ty,
+            core::BindgenTypeDescriptor::OwnedSlice { .. } | core::BindgenTypeDescriptor::OwnedStr
+        ));
+        if owns_buffers || native_slice {
+            objects.push(Box::new(CodegenInfo::owned_slice_abi_obj()) as Box<dyn ast::AstNode>);
+        }
+        objects.extend(owned_buffer_objs.into_iter().map(|o| Box::new(o) as Box<dyn ast::AstNode>));
+        objects.extend(handle_objs.into_iter().map(|o| Box::new(o) as Box<dyn ast::AstNode>));
+        objects.extend(tuple_abi_objs.into_iter().map(|o| Box::new(o) as Box<dyn ast::AstNode>));
+        if takes_rust_buffer {
+            objects.push(Box::new(self.rust_buffer_obj()) as Box<dyn ast::AstNode>);
+        }
+        let uses_int128 = uses_int128(&self.descriptors);
+        if uses_int128 {
+            objects.push(Box::new(CodegenInfo::int128_abi_obj()) as Box<dyn ast::AstNode>);
+        }
+        if let Some(resolver) = self.dll_import_resolver_obj() {
+            objects.push(Box::new(resolver) as Box<dyn ast::AstNode>);
+        }
+        objects.extend(marshaller_objs.into_iter().map(|o| Box::new(o) as Box<dyn ast::AstNode>));
+        if self.options.slice_views {
+            objects.push(Box::new(CodegenInfo::slice_view_obj()) as Box<dyn ast::AstNode>);
+        }
+        if native_slice {
+            objects.push(Box::new(CodegenInfo::native_slice_obj()) as Box<dyn ast::AstNode>);
+        }
+        if associated_methods.iter().chain(top_level_methods.iter()).any(|m| m.stream_target.is_some()) {
+            objects.push(Box::new(CodegenInfo::pooled_stream_obj()) as Box<dyn ast::AstNode>);
+        }
+        let (module_methods, top_level_methods): (Vec<_>, Vec<_>) = top_level_methods
+            .into_iter()
+            .partition(|m| !m.module.is_empty());
+        let module_methods: Vec<_> = module_methods.iter().map(|m| (m.module.as_slice(), m)).collect();
+        objects.extend(
+            self.module_objs(&module_methods, true, validates)
+                .into_iter()
+                .map(|o| Box::new(o) as Box<dyn ast::AstNode>),
+        );
 
-        ast::Root {
-            file_comment: Some(ast::BlockComment {
-                text: vec!["This is a generated file, do not modify by hand.".into()],
-            }),
+        let mut top_level_methods_obj = CodegenInfo::top_level_methods_obj(&top_level_methods, &statics);
+        top_level_methods_obj.fields.push(self.binding_version_field());
+        if self.options.trace_hooks {
+            top_level_methods_obj.is_partial = true;
+            top_level_methods_obj.methods.extend(trace_hook_declarations());
+        }
+        if self.options.custom_marshallers {
+            top_level_methods_obj.is_partial = true;
+            top_level_methods_obj.is_unsafe = top_level_methods_obj.has_unsafe_member();
+        }
+        top_level_methods_obj.methods.extend(version_method);
+        if validates {
+            top_level_methods_obj.constructors.push(ast::Constructor {
+                kind: ast::ConstructorKind::Static,
+                object_name: top_level_methods_obj.name.clone(),
+                args: Vec::new(),
+                body: BindingMethodBody { body_elements: static_checks }.to_ast_nodes(),
+            });
+            top_level_methods_obj.methods.push(ensure_validated_method());
+        }
+        top_level_methods_obj.nested.extend(alignment_probes);
+        objects.push(Box::new(top_level_methods_obj) as Box<dyn ast::AstNode>);
+        if self.options.emit_interface {
+            let (interface, class) = self.native_interface_objs(&top_level_methods);
+            objects.push(Box::new(interface));
+            objects.push(Box::new(class));
+        }
+
+        let mut namespace = format!("{}Bindings", self.lib_name.to_camel_case());
+        if let Some(prefix) = &self.options.namespace_prefix {
+            namespace = format!("{}.{}", prefix, namespace);
+        }
+
+        let namespace = ast::Namespace {
+            name: namespace,
+            children: objects,
+        };
+
+        // Structs are only guaranteed a blittable layout when it is given explicitly, so refuse to
+        // generate one which relies on the default.
+        if let Some(name) = ast::AstNode::structs_without_layout(&namespace).into_iter().next() {
+            return Err(BindgenError::MissingStructLayout { name });
+        }
+
+        let mut children: Vec<Box<dyn ast::AstNode>> = vec![Box::new(namespace)];
+
+        // Rather than failing on each use of Int128, fail once with the reason.
+        if uses_int128 {
+            children.insert(0, Box::new(ast::Conditional {
+                condition: "!NET7_0_OR_GREATER".to_string(),
+                child: Box::new(ast::PreprocessorError {
+                    message: "These bindings use Int128/UInt128, which need net7.0 or later".to_string(),
+                }),
+            }));
+        }
+
+        if self.options.skip_locals_init {
+            children.insert(0, Box::new(ast::ModuleAttribute {
+                attribute: skip_locals_init_attribute(),
+            }));
+        }
+
+        if !self.options.disabled_warnings.is_empty() {
+            children.insert(0, Box::new(ast::PragmaWarning {
+                disable: true,
+                ids: self.options.disabled_warnings.clone(),
+            }));
+            children.push(Box::new(ast::PragmaWarning {
+                disable: false,
+                ids: self.options.disabled_warnings.clone(),
+            }));
+        }
+
+        Ok(ast::Root {
+            file_comment: if self.options.file_header.is_empty() {
+                None
+            } else {
+                Some(ast::BlockComment {
+                    text: self.options.file_header.clone(),
+                })
+            },
             using_statements: vec![
                 ast::UsingStatement {
                     path: "System".into(),
@@ -1071,15 +7663,14 @@ impl<'a> CodegenInfo<'a> {
                     path: "System.Runtime.InteropServices".into(),
                 },
             ],
-            children: vec![Box::new(ast::Namespace {
-                name: format!("{}Bindings", self.lib_name.to_camel_case()),
-                children: objects,
-            })],
-        }
+            children,
+            type_visibility: self.options.type_visibility,
+            minify: self.options.minify,
+        })
     }
 }
 
-pub fn form_ast_from_data(data: &BindgenData) -> ast::Root {
-    let info = CodegenInfo::new(data);
+pub fn form_ast_from_data(data: &BindgenData, options: &CodegenOptions) -> Result<ast::Root, BindgenError> {
+    let info = CodegenInfo::new(data, options);
     info.form_ast()
 }
\ No newline at end of file
@@ -24,7 +24,7 @@ impl NativeBinary {
             .file_name()
             .expect("Expect a native binary path to have a filename")
             .to_str()
-            .expect("Expect a native binary filename to be valid unicode".into())
+            .expect("Expect a native binary filename to be valid unicode")
             .to_owned()
     }
 
@@ -52,6 +52,8 @@ pub struct NativeBinarySet {
 }
 
 impl NativeBinarySet {
+    /// The binaries may be named differently for each platform, in which case the set is named
+    /// after the first.
     pub fn new<I: IntoIterator<Item=NativeBinary>>(binaries: I) -> Self {
         let binaries: Vec<_> = binaries.into_iter().collect();
         let base_name = match &binaries.first() {
@@ -59,9 +61,6 @@ impl NativeBinarySet {
             None => panic!("Attempting to construct a NativeBinarySet from zero binaries")
         };
 
-        assert!(binaries.iter().all(|b| b.filepath.bin_base_name() == base_name),
-            "Constructing a NativeBinarySet for binaries with different base names");
-
         Self {
             base_name,
             binaries,
@@ -81,24 +80,328 @@ impl NativeBinarySet {
     }
 }
 
+pub struct PackageReference {
+    name: String,
+    version: String,
+}
+
+impl PackageReference {
+    fn render_proj_xml(&self) -> String {
+        format!(
+            r#"        <PackageReference Include="{}" Version="{}" />
+"#,
+            self.name, self.version
+        )
+    }
+}
+
+/// Something the generated bindings use which not every target framework has.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Feature {
+    /// The LibraryImport source generator, and the custom marshallers it calls
+    LibraryImport,
+    /// Record structs, which need C# 10 and the IsExternalInit type
+    RecordStructs,
+    /// System.Int128 and System.UInt128
+    Int128,
+    /// The DllImportResolver picking each OS's library name, which is only compiled in for
+    /// frameworks which have it
+    DllImportResolver,
+    /// `[module: SkipLocalsInit]`, and the attribute on each wrapper method
+    SkipLocalsInit,
+    /// The accessors of exported statics, which look them up with NativeLibrary
+    StaticAccessors,
+    /// `[SuppressGCTransition]` on the P/Invokes of `no_gc_transition` functions
+    SuppressGCTransition,
+}
+
+impl Feature {
+    /// The earliest .NET version the feature is available in, eg 7 for net7.0.
+    fn min_net_version(self) -> u32 {
+        match self {
+            Feature::LibraryImport | Feature::RecordStructs | Feature::Int128 => 7,
+            Feature::DllImportResolver | Feature::SkipLocalsInit
+            | Feature::StaticAccessors
+            | Feature::SuppressGCTransition => 5,
+        }
+    }
+
+    /// Whether the bindings leave the feature out when built for frameworks without it, so only
+    /// one of the target frameworks needs to have it.
+    fn is_conditional(self) -> bool {
+        matches!(self, Feature::DllImportResolver)
+    }
+
+    fn unsupported_error(self) -> &'static str {
+        match self {
+            Feature::LibraryImport => "LibraryImport needs every target framework to be net7.0 or later",
+            Feature::RecordStructs => "Record structs need every target framework to be net7.0 or later",
+            Feature::Int128 => "Int128 and UInt128 need every target framework to be net7.0 or later",
+            Feature::DllImportResolver => "Per-platform library names need a net5.0 or later target framework",
+            Feature::SkipLocalsInit => "SkipLocalsInit needs every target framework to be net5.0 or later",
+            Feature::StaticAccessors => "Accessors for exported statics need every target framework to be net5.0 or later",
+            Feature::SuppressGCTransition => "SuppressGCTransition needs every target framework to be net5.0 or later",
+        }
+    }
+
+    fn is_supported_by(self, target_framework: &str) -> bool {
+        net_version(target_framework).is_some_and(|version| version >= self.min_net_version())
+    }
+}
+
+/// The major version of a .NET 5 or later target framework moniker, eg 7 for net7.0, or None for
+/// netstandard, netcoreapp, and .NET Framework monikers.
+fn net_version(target_framework: &str) -> Option<u32> {
+    let version = target_framework.strip_prefix("net")?;
+    let major: u32 = version.split('.').next()?.parse().ok()?;
+
+    // .NET Framework monikers have no dot, eg net48
+    (version.contains('.') && major >= 5).then_some(major)
+}
+
 pub struct ProjFile {
-    pub target_framework: String,
-    pub allow_unsafe: bool,
-    pub binary_set: NativeBinarySet,
+    /// More than one target framework builds the project once for each of them.
+    target_frameworks: Vec<String>,
+    allow_unsafe: bool,
+    lang_version: Option<String>,
+    package_references: Vec<PackageReference>,
+    binary_set: NativeBinarySet,
+    /// Copy the native binaries alongside the built assembly, unless they're linked into the app.
+    bundle_native_binaries: bool,
+}
+
+/// Builds a ProjFile, starting from defaults suitable for a bindings library.
+///
+/// Unless given explicitly, the target frameworks are the oldest which have every feature used,
+/// falling back to netstandard2.0.
+pub struct ProjFileBuilder {
+    proj: ProjFile,
+    features: Vec<Feature>,
+}
+
+impl ProjFileBuilder {
+    pub fn new(binary_set: NativeBinarySet) -> Self {
+        Self {
+            features: Vec::new(),
+            proj: ProjFile {
+                target_frameworks: Vec::new(),
+                allow_unsafe: true,
+                lang_version: None,
+                package_references: Vec::new(),
+                binary_set,
+                bundle_native_binaries: true,
+            },
+        }
+    }
+
+    /// Records that the bindings use the given feature, which the target frameworks must have.
+    pub fn feature(mut self, feature: Feature) -> Self {
+        if !self.features.contains(&feature) {
+            self.features.push(feature);
+        }
+        self
+    }
+
+    pub fn target_framework(mut self, target_framework: &str) -> Self {
+        self.proj.target_frameworks = vec![target_framework.to_owned()];
+        self
+    }
+
+    /// Also builds the project for another target framework, eg one with APIs the first lacks.
+    pub fn additional_target_framework(mut self, target_framework: &str) -> Self {
+        self.proj.target_frameworks.push(target_framework.to_owned());
+        self
+    }
+
+    pub fn allow_unsafe(mut self, allow_unsafe: bool) -> Self {
+        self.proj.allow_unsafe = allow_unsafe;
+        self
+    }
+
+    /// The C# language version, left up to the SDK's default for the target framework if unset
+    pub fn lang_version(mut self, lang_version: &str) -> Self {
+        self.proj.lang_version = Some(lang_version.to_owned());
+        self
+    }
+
+    pub fn bundle_native_binaries(mut self, bundle_native_binaries: bool) -> Self {
+        self.proj.bundle_native_binaries = bundle_native_binaries;
+        self
+    }
+
+    pub fn build(self) -> Result<ProjFile, &'static str> {
+        let ProjFileBuilder { mut proj, features } = self;
+
+        if proj.target_frameworks.is_empty() {
+            let required = |conditional: bool| features
+                .iter()
+                .filter(|f| f.is_conditional() == conditional)
+                .map(|f| f.min_net_version())
+                .max();
+
+            let primary = required(false);
+            match primary {
+                Some(version) => proj.target_frameworks.push(format!("net{}.0", version)),
+                None => {
+                    proj.target_frameworks.push("netstandard2.0".to_owned());
+                    // Span<T> isn't part of netstandard2.0 itself
+                    proj.package_references.push(PackageReference {
+                        name: "System.Memory".to_owned(),
+                        version: "4.5.4".to_owned(),
+                    });
+                }
+            }
+
+            // Conditionally compiled features only need an additional framework which has them
+            if let Some(version) = required(true).filter(|&v| v > primary.unwrap_or(0)) {
+                proj.target_frameworks.push(format!("net{}.0", version));
+            }
+        }
+
+        // Everything ends up verbatim in the xml, so must not be able to break out of it.
+        let is_plain_value = |value: &str| {
+            !value.is_empty() && value.chars().all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-')
+        };
+
+        if !proj.target_frameworks.iter().all(|f| is_plain_value(f)) {
+            return Err("The project's target framework must be a plain framework moniker, eg netstandard2.0");
+        }
+
+        if let Some(lang_version) = &proj.lang_version {
+            if !is_plain_value(lang_version) {
+                return Err("The project's language version must be a plain version, eg 8.0 or latest");
+            }
+        }
+
+        if proj.package_references.iter().any(|p| !is_plain_value(&p.name) || !is_plain_value(&p.version)) {
+            return Err("Package references must have a plain name and version");
+        }
+
+        for (idx, package) in proj.package_references.iter().enumerate() {
+            if proj.package_references[..idx].iter().any(|p| p.name == package.name) {
+                return Err("The same package can't be referenced more than once");
+            }
+        }
+
+        if !proj.allow_unsafe {
+            return Err("The generated bindings pin managed memory, so need unsafe blocks allowed");
+        }
+
+        for feature in &features {
+            let supported = |f: &String| feature.is_supported_by(f);
+            let is_supported = if feature.is_conditional() {
+                proj.target_frameworks.iter().any(supported)
+            } else {
+                proj.target_frameworks.iter().all(supported)
+            };
+
+            if !is_supported {
+                return Err(feature.unsupported_error());
+            }
+        }
+
+        Ok(proj)
+    }
 }
 
 impl ProjFile {
+    fn render_package_references_xml(&self) -> String {
+        if self.package_references.is_empty() {
+            return String::new();
+        }
+
+        let mut xml_str = "    <ItemGroup>\n".to_string();
+        for package in &self.package_references {
+            xml_str.push_str(&package.render_proj_xml());
+        }
+        xml_str.push_str("    </ItemGroup>\n");
+
+        xml_str
+    }
+
     pub fn render_proj_xml(&self) -> String {
         format!(r#"<Project Sdk="Microsoft.NET.Sdk">
     <PropertyGroup>
-        <TargetFramework>{}</TargetFramework>
+        {}
         <AllowUnsafeBlocks>{}</AllowUnsafeBlocks>
-    </PropertyGroup>
-{}
+{}    </PropertyGroup>
+{}{}
 </Project>
 "#,
-        self.target_framework,
+        match &self.target_frameworks[..] {
+            [framework] => format!("<TargetFramework>{}</TargetFramework>", framework),
+            frameworks => format!("<TargetFrameworks>{}</TargetFrameworks>", frameworks.join(";")),
+        },
         if self.allow_unsafe { "true" } else { "false" },
-        self.binary_set.render_proj_xml())
+        match &self.lang_version {
+            Some(v) => format!("        <LangVersion>{}</LangVersion>\n", v),
+            None => String::new(),
+        },
+        self.render_package_references_xml(),
+        if self.bundle_native_binaries { self.binary_set.render_proj_xml() } else { String::new() })
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn builder() -> ProjFileBuilder {
+        let binary = NativeBinary::new(NativePlatform::LinuxX64, PathBuf::from("libfoo.so"));
+        ProjFileBuilder::new(NativeBinarySet::new(vec![binary]))
+    }
+
+    #[test]
+    fn picks_the_oldest_framework_with_every_feature() {
+        let proj = builder().build().unwrap();
+        assert_eq!(proj.target_frameworks, vec!["netstandard2.0"]);
+
+        let proj = builder().feature(Feature::DllImportResolver).build().unwrap();
+        assert_eq!(proj.target_frameworks, vec!["netstandard2.0", "net5.0"]);
+
+        let proj = builder().feature(Feature::DllImportResolver).feature(Feature::Int128).build().unwrap();
+        assert_eq!(proj.target_frameworks, vec!["net7.0"]);
+
+        // The module attribute can't be left out of a netstandard2.0 build
+        let proj = builder().feature(Feature::DllImportResolver).feature(Feature::SkipLocalsInit).build().unwrap();
+        assert_eq!(proj.target_frameworks, vec!["net5.0"]);
+
+        let proj = builder().feature(Feature::StaticAccessors).build().unwrap();
+        assert_eq!(proj.target_frameworks, vec!["net5.0"]);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn rejects_features_missing_from_a_target_framework() {
+        for feature in [
+            Feature::LibraryImport,
+            Feature::RecordStructs,
+            Feature::Int128,
+            Feature::SkipLocalsInit,
+            Feature::StaticAccessors,
+            Feature::SuppressGCTransition,
+        ] {
+            let result = builder().target_framework("netstandard2.0").feature(feature).build();
+            assert_eq!(result.err(), Some(feature.unsupported_error()));
+
+            // Every target framework is built with the feature, not just one of them
+            let result = builder()
+                .target_framework("net7.0")
+                .additional_target_framework("netstandard2.1")
+                .feature(feature)
+                .build();
+            assert_eq!(result.err(), Some(feature.unsupported_error()));
+        }
+
+        let result = builder().target_framework("net48").feature(Feature::DllImportResolver).build();
+        assert_eq!(result.err(), Some(Feature::DllImportResolver.unsupported_error()));
+    }
+
+    #[test]
+    fn accepts_conditional_features_in_any_target_framework() {
+        let result = builder()
+            .target_framework("netstandard2.0")
+            .additional_target_framework("net6.0")
+            .feature(Feature::DllImportResolver)
+            .build();
+        assert!(result.is_ok());
+    }
+}
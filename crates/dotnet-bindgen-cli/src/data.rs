@@ -1,66 +1,398 @@
+use std::collections::{BTreeSet, HashSet};
 use std::fs::File;
 use std::io::Read;
 use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
+use goblin::archive::Archive;
 use goblin::elf::Elf;
+use goblin::mach::Mach;
 use goblin::Object;
 
 use dotnet_bindgen_core::*;
 
+use crate::diagnostics;
+use crate::error::BindgenError;
+
+/// Counts the archives linked so far, to give each temporary shared object a unique name, as
+/// binaries are read in parallel.
+static LINKED_ARCHIVE_COUNT: AtomicUsize = AtomicUsize::new(0);
+
 #[derive(Clone, Debug)]
 pub struct BindgenData {
     pub source_file: PathBuf,
     pub descriptors: Vec<BindgenExportDescriptor>,
+
+    /// The `binding_version` the binary computes for itself, if it exports `__bindgen_version`.
+    pub native_binding_version: Option<u64>,
+
+    /// The size in bytes of a pointer in the binary, 4 or 8.
+    pub pointer_width: u64,
 }
 
 impl BindgenData {
-    fn load_elf(elf: &Elf, file_path: &Path) -> Result<Self, &'static str> {
-        let mut descriptors = Vec::new();
+    /// Checks that the binary was built against the same descriptor types as this tool, before
+    /// any of its describe functions are called.
+    fn check_abi_version(lib: &libloading::Library) -> Result<(), BindgenError> {
+        let found = unsafe {
+            match lib.get::<*const u32>(BINDGEN_ABI_VERSION_SYMBOL.as_bytes()) {
+                Ok(version) => **version,
+                Err(_) => 0,
+            }
+        };
+
+        if found != BINDGEN_ABI_VERSION {
+            return Err(BindgenError::AbiVersionMismatch {
+                found,
+                expected: BINDGEN_ABI_VERSION,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Calls the binary's own `__bindgen_version`, if it exports one.
+    fn native_binding_version(lib: &libloading::Library) -> Option<u64> {
+        unsafe {
+            let version_func = lib
+                .get::<unsafe extern "C" fn() -> u64>(BINDGEN_VERSION_SYMBOL.as_bytes())
+                .ok()?;
+            Some(version_func())
+        }
+    }
+
+    /// Calls the describe function with the given symbol name.
+    ///
+    /// The binary carries its own copy of std, so a panic can't be caught here, and would abort the
+    /// whole process. Describe functions catch their own panics instead, returning None.
+    fn call_descriptor_func(
+        lib: &libloading::Library,
+        symbol: &str,
+    ) -> Result<BindgenExportDescriptor, BindgenError> {
+        let descriptor_func: libloading::Symbol<unsafe fn() -> Option<BindgenExportDescriptor>> =
+            unsafe { lib.get(symbol.as_bytes()).unwrap() };
+
+        unsafe { descriptor_func() }.ok_or_else(|| BindgenError::DescriptorExtractionPanicked {
+            symbol: symbol.to_string(),
+        })
+    }
+
+    /// Warns about any section whose name is a truncated version of the bindgen section.
+    ///
+    /// PE binaries silently truncate long section names, so a section name that doesn't survive
+    /// linking intact means the binary was built against a misconfigured core, and anything
+    /// relying on finding that section won't work.
+    fn check_section_names(object: &Object) {
+        let (expected, names): (&str, Vec<String>) = match object {
+            Object::Elf(elf) => (
+                BINDGEN_SECTION_NAME,
+                elf.section_headers
+                    .iter()
+                    .filter_map(|sh| match elf.shdr_strtab.get(sh.sh_name) {
+                        Some(Ok(name)) => Some(name.to_string()),
+                        _ => None,
+                    })
+                    .collect(),
+            ),
+            Object::PE(pe) => (
+                BINDGEN_SECTION_NAME,
+                pe.sections.iter().filter_map(|section| section.name().ok().map(str::to_string)).collect(),
+            ),
+            // The segment name is checked by the core at compile time, only the section is named
+            // in the section table.
+            Object::Mach(Mach::Binary(macho)) => (
+                BINDGEN_MACHO_SECTION_NAME.split(',').nth(1).unwrap_or(BINDGEN_MACHO_SECTION_NAME),
+                macho.segments
+                    .sections()
+                    .flatten()
+                    .filter_map(|section| section.ok())
+                    .filter_map(|(section, _)| section.name().ok().map(str::to_string))
+                    .collect(),
+            ),
+            _ => return,
+        };
+
+        for name in names {
+            // Skip anything too short to be a meaningful match, eg "."
+            let looks_truncated = name.len() > 2
+                && name.len() < expected.len()
+                && expected.starts_with(name.as_str());
+
+            if looks_truncated {
+                diagnostics::warn(&format!(
+                    "found a section named {}, which looks like a truncated {} section",
+                    name, expected
+                ));
+            }
+        }
+    }
+
+    fn load_elf(elf: &Elf, file_path: &Path) -> Result<Self, BindgenError> {
         let lib = libloading::Library::new(file_path).unwrap();
-        for sym in elf.dynsyms.iter() {
-            let name = match elf.dynstrtab.get(sym.st_name) {
-                Some(Ok(s)) => s,
+        let describe_symbols = Self::valid_describe_symbols(elf, &lib)?;
+        let descriptors = Self::extract_descriptors(&lib, describe_symbols.into_iter())?;
+
+        Ok(Self {
+            source_file: file_path.to_owned(),
+            descriptors,
+            native_binding_version: Self::native_binding_version(&lib),
+            pointer_width: if elf.is_64 { 8 } else { 4 },
+        })
+    }
+
+    /// The addresses of every describe function registered in the bindgen section of the loaded
+    /// library, or None if they can't be found, eg because the binary predates the section.
+    fn registered_describe_funcs(elf: &Elf, lib: &libloading::Library) -> Option<HashSet<usize>> {
+        let section = elf.section_headers.iter().find(|sh| {
+            matches!(elf.shdr_strtab.get(sh.sh_name), Some(Ok(name)) if name == BINDGEN_SECTION_NAME)
+        })?;
+
+        // The difference between where the library was loaded and the addresses in the file.
+        let abi_version_sym = elf.dynsyms.iter().find(|sym| {
+            matches!(elf.dynstrtab.get(sym.st_name), Some(Ok(name)) if name == BINDGEN_ABI_VERSION_SYMBOL)
+        })?;
+        let abi_version_addr = unsafe {
+            *lib.get::<*const u8>(BINDGEN_ABI_VERSION_SYMBOL.as_bytes()).ok()? as usize
+        };
+        let load_bias = abi_version_addr.wrapping_sub(abi_version_sym.st_value as usize);
+
+        // The section is an array of function pointers, relocated when the library was loaded.
+        let start = load_bias.wrapping_add(section.sh_addr as usize) as *const usize;
+        let count = section.sh_size as usize / std::mem::size_of::<usize>();
+        let registered = (0..count)
+            .map(|idx| unsafe { std::ptr::read_unaligned(start.add(idx)) })
+            .map(|addr| addr.wrapping_sub(load_bias))
+            .collect();
+
+        Some(registered)
+    }
+
+    /// The exported symbols which look like describe functions and are safe to call as one.
+    ///
+    /// A symbol can match the describe prefix by accident, and calling through it as a describe
+    /// function would be UB. Anything that isn't a function, or isn't registered in the bindgen
+    /// section, is reported and skipped.
+    ///
+    /// The ABI version is checked first, as any binary new enough to pass has the bindgen section,
+    /// so none of the symbols can be verified, or called, if it's missing.
+    fn valid_describe_symbols<'a>(
+        elf: &'a Elf,
+        lib: &libloading::Library,
+    ) -> Result<Vec<&'a str>, BindgenError> {
+        Self::check_abi_version(lib)?;
+
+        let candidates: Vec<_> = elf.dynsyms
+            .iter()
+            .filter_map(|sym| match elf.dynstrtab.get(sym.st_name) {
+                Some(Ok(name)) if name.starts_with(BINDGEN_DESCRIBE_PREFIX) => Some((sym, name)),
+                _ => None,
+            })
+            .collect();
+
+        let registered = match Self::registered_describe_funcs(elf, lib) {
+            Some(registered) => registered,
+            None if candidates.is_empty() => return Ok(Vec::new()),
+            None => return Err(BindgenError::MissingBindgenSection),
+        };
+
+        let valid = candidates
+            .into_iter()
+            .filter(|(sym, name)| {
+                if !sym.is_function() {
+                    diagnostics::warn(&format!("{} isn't a function, skipping it", name));
+                    return false;
+                }
+
+                if !registered.contains(&(sym.st_value as usize)) {
+                    diagnostics::warn(&format!(
+                        "{} isn't registered in the {} section, skipping it",
+                        name, BINDGEN_SECTION_NAME
+                    ));
+                    return false;
+                }
+
+                true
+            })
+            .map(|(_, name)| name)
+            .collect();
+
+        Ok(valid)
+    }
+
+    /// Loads the union of the descriptors defined by each object file in a static archive.
+    ///
+    /// Object files can't be loaded directly, so the archive is first linked into a temporary
+    /// shared object with the system C compiler. This requires the archive to be self contained,
+    /// eg a `staticlib`, as an `rlib` doesn't include its dependencies, and `cc` to use a GNU
+    /// compatible linker, which understands `--whole-archive`.
+    fn load_archive(
+        archive: &Archive,
+        buffer: &[u8],
+        file_path: &Path,
+    ) -> Result<Self, BindgenError> {
+        let mut describe_symbols = BTreeSet::new();
+
+        for member in archive.members() {
+            let member_bytes = archive
+                .extract(member, buffer)
+                .map_err(|_| "Failed to extract an archive member")?;
+
+            // Archives also contain non-object members, eg the metadata in an rlib.
+            let member = match Object::parse(member_bytes) {
+                Ok(member) => member,
+                _ => continue,
+            };
+            Self::check_section_names(&member);
+            let elf = match member {
+                Object::Elf(elf) => elf,
                 _ => continue,
             };
 
-            if !name.starts_with(BINDGEN_DESCRIBE_PREFIX) {
-                continue;
-            }
+            describe_symbols.extend(elf.syms
+                .iter()
+                .filter(|sym| sym.st_shndx != goblin::elf::section_header::SHN_UNDEF as usize)
+                .filter_map(|sym| match elf.strtab.get(sym.st_name) {
+                    Some(Ok(name)) if name.starts_with(BINDGEN_DESCRIBE_PREFIX) => {
+                        Some(name.to_string())
+                    },
+                    _ => None,
+                }));
+        }
 
-            unsafe {
-                let descriptor_func: libloading::Symbol<unsafe fn() -> BindgenExportDescriptor> =
-                    lib.get(name.as_bytes()).unwrap();
-                descriptors.push(descriptor_func());
-            }
+        if describe_symbols.is_empty() {
+            return Err("The archive doesn't contain any bindgen descriptors".into());
         }
 
+        let linked_path = std::env::temp_dir().join(format!(
+            "dotnet-bindgen-{}-{}-{}.so",
+            std::process::id(),
+            LINKED_ARCHIVE_COUNT.fetch_add(1, Ordering::Relaxed),
+            file_path.file_stem().and_then(|s| s.to_str()).unwrap_or("archive"),
+        ));
+
+        let link_failed = |reason: String| BindgenError::ArchiveLinkFailed { reason };
+        let output = Command::new("cc")
+            .arg("-shared")
+            .arg("-o")
+            .arg(&linked_path)
+            .arg("-Wl,--whole-archive")
+            .arg(file_path)
+            .arg("-Wl,--no-whole-archive")
+            .args(["-lpthread", "-ldl", "-lm"])
+            .output()
+            .map_err(|e| link_failed(match e.kind() {
+                std::io::ErrorKind::NotFound => "cc wasn't found".to_string(),
+                _ => format!("couldn't run cc: {}", e),
+            }))?;
+
+        if !output.status.success() {
+            let _ = std::fs::remove_file(&linked_path);
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(link_failed(if stderr.contains("whole-archive") {
+                "the linker cc uses doesn't support --whole-archive, so isn't GNU compatible".to_string()
+            } else {
+                format!("cc failed with\n{}", stderr.trim_end())
+            }));
+        }
+
+        let linked_bytes = std::fs::read(&linked_path);
+        let lib = libloading::Library::new(&linked_path);
+        // The library stays mapped after its file is removed.
+        let _ = std::fs::remove_file(&linked_path);
+        let linked_bytes = linked_bytes.map_err(|_| "Failed to read the shared object linked from the archive")?;
+        let lib = lib.map_err(|_| "Failed to load the shared object linked from the archive")?;
+        let linked_elf = Elf::parse(&linked_bytes)
+            .map_err(|_| "Failed to parse the shared object linked from the archive")?;
+
+        let valid_symbols: HashSet<_> = Self::valid_describe_symbols(&linked_elf, &lib)?
+            .into_iter()
+            .collect();
+        let descriptors = Self::extract_descriptors(
+            &lib,
+            describe_symbols.iter().map(String::as_str).filter(|name| valid_symbols.contains(name)),
+        )?;
+
         Ok(Self {
             source_file: file_path.to_owned(),
             descriptors,
+            native_binding_version: Self::native_binding_version(&lib),
+            pointer_width: if linked_elf.is_64 { 8 } else { 4 },
         })
     }
 
+    /// Calls each of the given describe functions in the loaded library, which must have been
+    /// checked by `valid_describe_symbols`.
+    fn extract_descriptors<'a>(
+        lib: &libloading::Library,
+        describe_symbols: impl Iterator<Item = &'a str>,
+    ) -> Result<Vec<BindgenExportDescriptor>, BindgenError> {
+        let mut descriptors = Vec::new();
+        for name in describe_symbols {
+            match Self::call_descriptor_func(lib, name) {
+                Ok(descriptor) => descriptors.push(descriptor),
+                // One broken descriptor shouldn't stop bindings being generated for the rest.
+                Err(e) => diagnostics::warn(&format!("{}, skipping it", e)),
+            }
+        }
+
+        Ok(descriptors)
+    }
+
     /// Sorts the descriptors in this binding data set, to simplify comparisons with other sets.
     fn sort_descriptors(&mut self) { 
         self.descriptors.sort_by_cached_key(|d| match d {
             BindgenExportDescriptor::Function(f) => f.real_name.clone(),
             BindgenExportDescriptor::Struct(s) => s.name.clone(),
+            BindgenExportDescriptor::Static(s) => s.name.clone(),
+            BindgenExportDescriptor::Enum(e) => e.name.clone(),
         });
     }
 
-    pub fn load(file_path: &Path) -> Result<Self, &'static str> {
+    /// Reads the descriptors from JSON written by the `dump-descriptors` subcommand, rather than
+    /// from a binary.
+    ///
+    /// There's no binary to name the library after, so the JSON file stands in for it as the
+    /// `source_file`, eg `test_lib.json` for `libtest_lib.so`.
+    pub fn load_json(file_path: &Path) -> Result<Self, BindgenError> {
+        let file = File::open(file_path).map_err(|_| "Failed to open the descriptors JSON file")?;
+        let descriptors = serde_json::from_reader(std::io::BufReader::new(file))
+            .map_err(|_| "Failed to parse the descriptors JSON file")?;
+
+        let mut data = Self {
+            source_file: file_path.to_owned(),
+            descriptors,
+            native_binding_version: None,
+            // The JSON doesn't record it, but it could only have been dumped by loading the
+            // binary into a host of the same pointer width.
+            pointer_width: std::mem::size_of::<usize>() as u64,
+        };
+        data.sort_descriptors();
+
+        Ok(data)
+    }
+
+    pub fn load(file_path: &Path) -> Result<Self, BindgenError> {
         let mut fd = File::open(file_path).unwrap();
 
         let mut buffer = Vec::new();
         fd.read_to_end(&mut buffer).unwrap();
 
-        let mut data = match Object::parse(&buffer).unwrap() {
+        let object = Object::parse(&buffer).unwrap();
+        Self::check_section_names(&object);
+
+        let mut data = match object {
             Object::Elf(elf) => Self::load_elf(&elf, file_path),
+            Object::Archive(archive) => Self::load_archive(&archive, &buffer, file_path),
+            // Descriptors are only built by calling the loaded binary's describe functions, so
+            // these need their export tables reading, and a host of the same format to load them.
+            Object::PE(_) | Object::Mach(_) => {
+                Err("PE and Mach-O binaries aren't supported yet, as descriptors can only be read by loading an ELF binary".into())
+            },
             Object::Unknown(magic) => {
-                println!("unknown magic: {:#x}", magic);
-                Err("unknown magic number")
+                diagnostics::warn(&format!("{} has an unknown magic number {:#x}", file_path.display(), magic));
+                Err("unknown magic number".into())
             },
-            _ => Err("Unsupported binary type"),
         }?;
 
         data.sort_descriptors();
@@ -68,3 +400,182 @@ impl BindgenData {
         Ok(data)
     }
 }
+
+// Builds and loads ELF shared objects, so only runs on Linux.
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+
+    /// Runs cargo from the workspace root, building into the given directory under `target`.
+    fn cargo_build(args: &[&str], target_dir: &str) -> PathBuf {
+        let workspace = Path::new(env!("CARGO_MANIFEST_DIR")).join("../..");
+        let target_dir = workspace.join("target").join(target_dir);
+
+        let status = Command::new(std::env::var("CARGO").unwrap_or_else(|_| "cargo".to_string()))
+            .current_dir(&workspace)
+            .args(args)
+            .arg("--target-dir")
+            .arg(&target_dir)
+            .status()
+            .expect("Failed to run cargo");
+        assert!(status.success(), "Failed to build test-lib");
+
+        target_dir
+    }
+
+    fn described_names(data: &BindgenData) -> HashSet<&str> {
+        data.descriptors
+            .iter()
+            .map(|descriptor| match descriptor {
+                BindgenExportDescriptor::Function(f) => f.real_name.as_str(),
+                BindgenExportDescriptor::Struct(s) => s.name.as_str(),
+                BindgenExportDescriptor::Static(s) => s.name.as_str(),
+                BindgenExportDescriptor::Enum(e) => e.name.as_str(),
+            })
+            .collect()
+    }
+
+    /// Builds test-lib with the workspace's `size` profile, ie `opt-level = "z"` with LTO, and
+    /// checks that the describe functions are still exported and registered.
+    ///
+    /// This runs a whole nested cargo build, so is only run when asked for, with
+    /// `cargo test -p dotnet-bindgen-cli -- --ignored`.
+    #[test]
+    #[ignore = "runs a nested cargo build of test-lib"]
+    fn describe_functions_survive_size_optimisation() {
+        let target_dir = cargo_build(&["build", "--quiet", "--package", "test-lib", "--profile", "size"], "size-profile");
+
+        let binary = target_dir.join("size").join("libtest_lib.so");
+        let mut buffer = Vec::new();
+        File::open(&binary).unwrap().read_to_end(&mut buffer).unwrap();
+        let elf = Elf::parse(&buffer).unwrap();
+        let lib = libloading::Library::new(&binary).unwrap();
+        assert!(
+            BindgenData::registered_describe_funcs(&elf, &lib).is_some(),
+            "The {} section was dropped",
+            BINDGEN_SECTION_NAME,
+        );
+
+        let data = BindgenData::load(&binary).unwrap();
+        let names = described_names(&data);
+        for expected in ["fill_buffer", "take_header", "version", "scene_tick", "WordStats"] {
+            assert!(names.contains(expected), "{} wasn't described", expected);
+        }
+    }
+
+    /// Builds test-lib, which has a describe function that panics, and checks the panic is
+    /// reported and skipped rather than aborting the run.
+    #[test]
+    #[ignore = "runs a nested cargo build of test-lib"]
+    fn panicking_describe_function_is_skipped() {
+        let target_dir = cargo_build(&["build", "--quiet", "--package", "test-lib"], "describe-panic");
+        let binary = target_dir.join("debug").join("libtest_lib.so");
+
+        let warnings_before = diagnostics::warning_count();
+        let data = BindgenData::load(&binary).unwrap();
+        assert!(diagnostics::warning_count() > warnings_before, "The panic wasn't reported");
+
+        let names = described_names(&data);
+        assert!(!names.contains("UNDESCRIBABLE"), "UNDESCRIBABLE was described");
+        for expected in ["fill_buffer", "version", "DEFAULT_STRUCT", "WordStats"] {
+            assert!(names.contains(expected), "{} wasn't described", expected);
+        }
+    }
+
+    /// Builds test-lib, then renames its bindgen section, and checks that none of its describe
+    /// functions are called once they can't be verified.
+    #[test]
+    #[ignore = "runs a nested cargo build of test-lib"]
+    fn missing_bindgen_section_is_an_error() {
+        let target_dir = cargo_build(&["build", "--quiet", "--package", "test-lib"], "missing-section");
+        let binary = target_dir.join("debug").join("libtest_lib.so");
+        let renamed = target_dir.join("libtest_lib_renamed_section.so");
+
+        let status = Command::new("objcopy")
+            .arg(format!("--rename-section={}=.nobind", BINDGEN_SECTION_NAME))
+            .arg(&binary)
+            .arg(&renamed)
+            .status()
+            .expect("Failed to run objcopy");
+        assert!(status.success(), "Failed to rename the bindgen section");
+
+        let result = BindgenData::load(&renamed);
+        assert!(matches!(result, Err(BindgenError::MissingBindgenSection)), "{:?}", result.err());
+    }
+
+    /// Builds test-lib, and checks that its thunks report enums and enum slices holding undefined
+    /// discriminants through the invalid argument flag, without calling the function.
+    #[test]
+    #[ignore = "runs a nested cargo build of test-lib"]
+    fn undefined_discriminants_are_rejected() {
+        #[repr(C)]
+        struct SliceAbi {
+            ptr: *const u8,
+            len: u64,
+        }
+
+        type CountColor = extern "C" fn(SliceAbi, u8, &mut u8) -> std::mem::MaybeUninit<u32>;
+        type ReverseColors = extern "C" fn(SliceAbi, &mut u8);
+
+        let target_dir = cargo_build(&["build", "--quiet", "--package", "test-lib"], "undefined-discriminant");
+        let binary = target_dir.join("debug").join("libtest_lib.so");
+        let lib = libloading::Library::new(&binary).unwrap();
+        let count_color = unsafe { *lib.get::<CountColor>(b"__bindgen_thunk_count_color").unwrap() };
+        let reverse_colors = unsafe { *lib.get::<ReverseColors>(b"__bindgen_thunk_reverse_colors").unwrap() };
+        let slice = |colors: &[u8]| SliceAbi { ptr: colors.as_ptr(), len: colors.len() as u64 };
+
+        // Red = 0, Green = 1, Blue = 5
+        let mut invalid_arg = 0;
+        let count = count_color(slice(&[0, 5, 1, 5]), 5, &mut invalid_arg);
+        assert_eq!(invalid_arg, 0);
+        assert_eq!(unsafe { count.assume_init() }, 2);
+
+        let mut invalid_arg = 0;
+        count_color(slice(&[0, 5]), 2, &mut invalid_arg);
+        assert_eq!(invalid_arg, 2, "An undefined enum argument wasn't reported");
+
+        let mut invalid_arg = 0;
+        count_color(slice(&[0, 99, 5]), 5, &mut invalid_arg);
+        assert_eq!(invalid_arg, 2, "An undefined element of an enum slice wasn't reported");
+
+        // Written through, so the pointer mustn't come from an immutable borrow.
+        let mut colors = [0, 1, 7];
+        let mut invalid_arg = 0;
+        reverse_colors(SliceAbi { ptr: colors.as_mut_ptr(), len: 3 }, &mut invalid_arg);
+        assert_eq!(invalid_arg, 2, "An undefined element of a mutable enum slice wasn't reported");
+        assert_eq!(colors, [0, 1, 7], "reverse_colors was called anyway");
+
+        colors[2] = 5;
+        let mut invalid_arg = 0;
+        reverse_colors(SliceAbi { ptr: colors.as_mut_ptr(), len: 3 }, &mut invalid_arg);
+        assert_eq!(invalid_arg, 0);
+        assert_eq!(colors, [5, 1, 0]);
+    }
+
+    /// Builds test-lib as a `staticlib`, and checks the descriptors are read from the archive
+    /// once linked, including twice at once, as binaries are read in parallel.
+    #[test]
+    #[ignore = "runs a nested cargo build of test-lib"]
+    fn load_static_archive() {
+        let target_dir = cargo_build(
+            &["rustc", "--quiet", "--package", "test-lib", "--lib", "--crate-type", "staticlib"],
+            "staticlib",
+        );
+        let archive = target_dir.join("debug").join("libtest_lib.a");
+
+        let loads: Vec<_> = (0..2)
+            .map(|_| {
+                let archive = archive.clone();
+                std::thread::spawn(move || BindgenData::load(&archive).unwrap())
+            })
+            .collect();
+
+        for load in loads {
+            let data = load.join().unwrap();
+            let names = described_names(&data);
+            for expected in ["fill_buffer", "take_header", "version", "Color", "WordStats"] {
+                assert!(names.contains(expected), "{} wasn't described", expected);
+            }
+        }
+    }
+}
@@ -0,0 +1,20 @@
+//! Warnings raised while reading binaries and generating bindings from them.
+//!
+//! Each warning is printed as soon as it's raised, and counted so that `--warn-as-error` can fail
+//! the run once every warning has been reported. Binaries are read in parallel, so the count is
+//! shared between threads.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static WARNING_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+/// Prints a warning, and counts it towards `--warn-as-error`.
+pub fn warn(message: &str) {
+    eprintln!("warning: {}", message);
+    WARNING_COUNT.fetch_add(1, Ordering::Relaxed);
+}
+
+/// The number of warnings raised so far.
+pub fn warning_count() -> usize {
+    WARNING_COUNT.load(Ordering::Relaxed)
+}
@@ -0,0 +1,198 @@
+//! Compares two sets of descriptors, eg from an old and a new build of the same library, and
+//! describes the differences in terms of the Rust items they were generated from.
+
+use std::fmt;
+
+use dotnet_bindgen_core::*;
+
+/// A single item which differs between two sets of descriptors, given as Rust-like signatures.
+pub enum DescriptorChange {
+    Added(String),
+    Removed(String),
+    Changed { old: String, new: String },
+}
+
+impl fmt::Display for DescriptorChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DescriptorChange::Added(new) => write!(f, "+ {}", new),
+            DescriptorChange::Removed(old) => write!(f, "- {}", old),
+            DescriptorChange::Changed { old, new } => write!(f, "~ {}\n  > {}", old, new),
+        }
+    }
+}
+
+/// Identifies the same item across both sets of descriptors.
+fn item_key(descriptor: &BindgenExportDescriptor) -> String {
+    match descriptor {
+        BindgenExportDescriptor::Function(f) => match &f.owner {
+            Some(owner) => format!("fn {}::{}", owner, f.real_name),
+            None => format!("fn {}", f.real_name),
+        },
+        BindgenExportDescriptor::Struct(s) => format!("struct {}", s.name),
+        BindgenExportDescriptor::Enum(e) => format!("enum {}", e.name),
+        BindgenExportDescriptor::Static(s) => format!("static {}", s.name),
+    }
+}
+
+/// Lists the items which were removed, added, or changed between the old and new descriptors,
+/// in that order.
+///
+/// An item whose descriptor changed in a way its signature doesn't show, eg a renamed thunk, is
+/// still listed, with identical old and new signatures.
+pub fn diff_descriptors(
+    old: &[BindgenExportDescriptor],
+    new: &[BindgenExportDescriptor],
+) -> Vec<DescriptorChange> {
+    fn find<'a>(set: &'a [BindgenExportDescriptor], key: &str) -> Option<&'a BindgenExportDescriptor> {
+        set.iter().find(|d| item_key(d) == key)
+    }
+
+    let removed = old
+        .iter()
+        .filter(|d| find(new, &item_key(d)).is_none())
+        .map(|d| DescriptorChange::Removed(signature(d)));
+
+    let added = new
+        .iter()
+        .filter(|d| find(old, &item_key(d)).is_none())
+        .map(|d| DescriptorChange::Added(signature(d)));
+
+    let changed = new.iter().filter_map(|new_d| match find(old, &item_key(new_d)) {
+        Some(old_d) if old_d != new_d => Some(DescriptorChange::Changed {
+            old: signature(old_d),
+            new: signature(new_d),
+        }),
+        _ => None,
+    });
+
+    removed.chain(added).chain(changed).collect()
+}
+
+/// A Rust-like signature of the item a descriptor was generated from.
+fn signature(descriptor: &BindgenExportDescriptor) -> String {
+    match descriptor {
+        BindgenExportDescriptor::Function(f) => function_signature(f),
+        BindgenExportDescriptor::Struct(s) => {
+            let fields: Vec<_> = s
+                .fields
+                .iter()
+                .map(|field| format!("{}: {}", field.name, type_name(&field.ty)))
+                .collect();
+
+            let mut sig = format!("struct {} {{ {} }}", s.name, fields.join(", "));
+            if let Some(cs_name) = &s.cs_name {
+                sig += &format!(" as {}", cs_name);
+            }
+            if let (Some(size), Some(align)) = (s.size, s.align) {
+                sig += &format!(" (size {}, align {})", size, align);
+            }
+            sig
+        }
+        BindgenExportDescriptor::Enum(e) => {
+            let variants: Vec<_> = e
+                .variants
+                .iter()
+                .map(|v| format!("{} = {}", v.name, v.value))
+                .collect();
+
+            format!("enum {}: {} {{ {} }}", e.name, type_name(&e.repr), variants.join(", "))
+        }
+        BindgenExportDescriptor::Static(s) => format!(
+            "static {}{}: {}",
+            if s.mutable { "mut " } else { "" },
+            s.name,
+            type_name(&s.ty),
+        ),
+    }
+}
+
+pub fn function_signature(f: &BindgenFunctionDescriptor) -> String {
+    let mut flags = Vec::new();
+    if f.blocking {
+        flags.push("blocking".to_string());
+    }
+    if f.no_gc_transition {
+        flags.push("no_gc_transition".to_string());
+    }
+    if f.calling_convention == BindgenCallingConvention::System {
+        flags.push("abi = \"system\"".to_string());
+    }
+    if f.returns_dictionary {
+        flags.push("returns_dictionary".to_string());
+    }
+    match f.return_encoding {
+        Some(BindgenStringEncoding::Utf8) => flags.push("return_encoding = \"utf8\"".to_string()),
+        Some(BindgenStringEncoding::Utf16) => flags.push("return_encoding = \"utf16\"".to_string()),
+        Some(BindgenStringEncoding::Ansi) => flags.push("return_encoding = \"ansi\"".to_string()),
+        None => (),
+    }
+    if let Some(module) = &f.module {
+        flags.push(format!("module = \"{}\"", module));
+    }
+
+    let receiver = f.receiver.map(|r| match r {
+        BindgenReceiver::Value => "self",
+        BindgenReceiver::Ref => "&self",
+        BindgenReceiver::RefMut => "&mut self",
+    });
+    let args: Vec<_> = receiver
+        .map(str::to_string)
+        .into_iter()
+        .chain(f.arguments.iter().map(|a| format!("{}: {}", a.name, type_name(&a.ty))))
+        .collect();
+
+    let mut sig = String::new();
+    if !flags.is_empty() {
+        sig += &format!("#[dotnet_bindgen({})] ", flags.join(", "));
+    }
+    if let Some(owner) = &f.owner {
+        sig += &format!("{}::", owner);
+    }
+    sig += &format!("fn {}({})", f.real_name, args.join(", "));
+    if f.return_ty != BindgenTypeDescriptor::Void {
+        sig += &format!(" -> {}", type_name(&f.return_ty));
+    }
+    sig
+}
+
+/// The Rust type a type descriptor was most likely generated from.
+pub fn type_name(ty: &BindgenTypeDescriptor) -> String {
+    use BindgenTypeDescriptor as Desc;
+
+    match ty {
+        Desc::Void => "()".to_string(),
+        Desc::Int { width, signed } => format!("{}{}", if *signed { "i" } else { "u" }, width),
+        Desc::Bool => "bool".to_string(),
+        Desc::Slice { elem_type } => format!("&[{}]", type_name(elem_type)),
+        Desc::MutSlice { elem_type } => format!("&mut [{}]", type_name(elem_type)),
+        Desc::OptionSlice { elem_type } => format!("Option<&[{}]>", type_name(elem_type)),
+        Desc::UninitSlice { elem_type } => format!("&mut [MaybeUninit<{}>]", type_name(elem_type)),
+        Desc::Ref { target } => format!("&{}", type_name(target)),
+        Desc::MutRef { target } => format!("&mut {}", type_name(target)),
+        Desc::Str => "&str".to_string(),
+        Desc::Float { width } => format!("f{}", width),
+        Desc::Array { elem_type, len } => format!("[{}; {}]", type_name(elem_type), len),
+        Desc::OwnedSlice { elem_type } => format!("Vec<{}>", type_name(elem_type)),
+        Desc::OwnedStr => "String".to_string(),
+        Desc::CString => "CString".to_string(),
+        Desc::Arc { name } => format!("Arc<{}>", name),
+        Desc::RawPtr => "*const _".to_string(),
+        Desc::Ptr { name, mutable, .. } => format!("*{} {}", if *mutable { "mut" } else { "const" }, name),
+        Desc::Opaque { name } => name.clone(),
+        Desc::Duration => "Duration".to_string(),
+        Desc::FnPtr { args, return_ty } => {
+            let args: Vec<_> = args.iter().map(type_name).collect();
+            match **return_ty {
+                Desc::Void => format!("extern \"C\" fn({})", args.join(", ")),
+                _ => format!("extern \"C\" fn({}) -> {}", args.join(", "), type_name(return_ty)),
+            }
+        }
+        Desc::Tuple { elements } => {
+            let elements: Vec<_> = elements.iter().map(type_name).collect();
+            format!("({})", elements.join(", "))
+        }
+        Desc::Struct(s) => s.name.clone(),
+        Desc::Enum(e) => e.name.clone(),
+    }
+}
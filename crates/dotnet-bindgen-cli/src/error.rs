@@ -0,0 +1,101 @@
+use std::fmt;
+
+pub enum BindgenError {
+    /// The binary was built against a different version of the descriptor types than this tool.
+    ///
+    /// Descriptors are read straight out of the binary's memory, so reading them across versions
+    /// would silently produce garbage.
+    AbiVersionMismatch { found: u32, expected: u32 },
+
+    /// The binary exports describe functions, but has no bindgen section to verify them against,
+    /// so none of them are safe to call.
+    MissingBindgenSection,
+
+    /// Calling the describe function with the given symbol name panicked.
+    DescriptorExtractionPanicked { symbol: String },
+
+    /// Warnings were raised, and `--warn-as-error` was given.
+    WarningsAsErrors { count: usize },
+
+    /// A method was bound in an impl block for a struct which isn't itself bound.
+    UnboundMethodOwner { owner: String, method: String },
+
+    /// A `module = "..."` path names a class the generated code needs the name of for something
+    /// else, eg `Marshal`.
+    ModuleNameClash { module: String, name: String },
+
+    /// A function takes or returns a slice of structs aligned to more than the runtime aligns
+    /// array elements to, so the pinned array can't be handed over.
+    OveralignedSlice { function: String, name: String, align: u64, max_align: u64 },
+
+    /// A static archive couldn't be linked into a shared object to read its descriptors from.
+    ArchiveLinkFailed { reason: String },
+
+    /// A generated struct would have been left to the default layout, which needn't be blittable.
+    MissingStructLayout { name: String },
+
+    Other(&'static str),
+}
+
+impl fmt::Display for BindgenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BindgenError::AbiVersionMismatch { found, expected } => write!(
+                f,
+                "The binary was built with dotnet-bindgen ABI version {}, but this tool expects version {}",
+                found, expected
+            ),
+            BindgenError::MissingBindgenSection => write!(
+                f,
+                "The binary has no {} section, so its describe functions can't be verified as safe to call",
+                dotnet_bindgen_core::BINDGEN_SECTION_NAME
+            ),
+            BindgenError::DescriptorExtractionPanicked { symbol } => {
+                write!(f, "The describe function {} panicked", symbol)
+            }
+            BindgenError::WarningsAsErrors { count } => write!(
+                f,
+                "{} warning(s) were raised, which --warn-as-error treats as errors",
+                count
+            ),
+            BindgenError::UnboundMethodOwner { owner, method } => write!(
+                f,
+                "{}::{} is declared in a #[dotnet_bindgen] impl block for a struct without #[dotnet_bindgen]",
+                owner, method
+            ),
+            BindgenError::ModuleNameClash { module, name } => write!(
+                f,
+                "The module {} can't be bound, as the generated code already uses the name {}",
+                module, name
+            ),
+            BindgenError::OveralignedSlice { function, name, align, max_align } => write!(
+                f,
+                "{} uses a slice of {}, which is aligned to {} bytes, but .NET only aligns the \
+                 elements of arrays in this binary to {}",
+                function, name, align, max_align
+            ),
+            BindgenError::ArchiveLinkFailed { reason } => write!(
+                f,
+                "Failed to link the archive into a shared object to read its descriptors, as {}",
+                reason
+            ),
+            BindgenError::MissingStructLayout { name } => {
+                write!(f, "The generated struct {} has no explicit StructLayout", name)
+            }
+            BindgenError::Other(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+/// Errors returned from main are printed with their Debug representation, so make that readable.
+impl fmt::Debug for BindgenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+impl From<&'static str> for BindgenError {
+    fn from(msg: &'static str) -> Self {
+        BindgenError::Other(msg)
+    }
+}
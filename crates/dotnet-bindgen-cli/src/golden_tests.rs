@@ -0,0 +1,1645 @@
+//! End-to-end tests, rendering the bindings for a fixed set of descriptors and comparing them
+//! against the committed golden files in `tests/golden`.
+//!
+//! After an intended change to the generated code, rerun with `DOTNET_BINDGEN_BLESS=1` set to
+//! rewrite the golden files, and review the diff.
+
+use std::path::{Path, PathBuf};
+
+use dotnet_bindgen_core::*;
+
+use crate::abi_summary;
+use crate::ast;
+use crate::codegen::{self, CodegenOptions};
+use crate::csproj;
+use crate::data::BindgenData;
+use crate::error::BindgenError;
+use crate::platform::NativePlatform;
+
+fn int(width: u8, signed: bool) -> BindgenTypeDescriptor {
+    BindgenTypeDescriptor::Int { width, signed }
+}
+
+fn arg(name: &str, ty: BindgenTypeDescriptor) -> BindgenFunctionArgumentDescriptor {
+    BindgenFunctionArgumentDescriptor {
+        name: name.to_string(),
+        ty,
+        direction: None,
+        encoding: None,
+        default: None,
+    }
+}
+
+fn function(
+    name: &str,
+    arguments: Vec<BindgenFunctionArgumentDescriptor>,
+    return_ty: BindgenTypeDescriptor,
+) -> BindgenFunctionDescriptor {
+    BindgenFunctionDescriptor {
+        real_name: name.to_string(),
+        thunk_name: format!("__bindgen_thunk_{}", name),
+        owner: None,
+        receiver: None,
+        blocking: false,
+        no_gc_transition: false,
+        calling_convention: BindgenCallingConvention::C,
+        arguments,
+        return_ty,
+        return_ownership: None,
+        free_thunk_name: None,
+        clone_thunk_name: None,
+        buffer_len_arg: None,
+        fill_buffer_arg: None,
+        returns_dictionary: false,
+        return_encoding: None,
+        module: None,
+        no_thunk: false,
+        reports_invalid_args: false,
+    }
+}
+
+fn point() -> BindgenStructDescriptor {
+    BindgenStructDescriptor {
+        name: "Point".to_string(),
+        cs_name: None,
+        size: Some(8),
+        align: Some(4),
+        fields: vec![
+            BindgenStructFieldDescriptor {
+                name: "x".to_string(),
+                ty: int(32, true),
+            },
+            BindgenStructFieldDescriptor {
+                name: "y".to_string(),
+                ty: int(32, true),
+            },
+        ],
+        fixed_string: false,
+    }
+}
+
+/// Collapses every run of whitespace into a single space, so that only changes to the tokens of
+/// the generated code fail the comparison.
+fn normalize_whitespace(source: &str) -> String {
+    source.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn golden_path(file_name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("golden")
+        .join(file_name)
+}
+
+fn check_golden(name: &str, descriptors: Vec<BindgenExportDescriptor>) {
+    check_golden_with_options(name, descriptors, &CodegenOptions::default());
+}
+
+fn check_golden_with_options(name: &str, descriptors: Vec<BindgenExportDescriptor>, options: &CodegenOptions) {
+    check_golden_file(&format!("{}.cs", name), &render(descriptors, options));
+}
+
+fn render(descriptors: Vec<BindgenExportDescriptor>, options: &CodegenOptions) -> String {
+    let data = BindgenData {
+        source_file: PathBuf::from("libgolden.so"),
+        descriptors,
+        native_binding_version: None,
+        pointer_width: 8,
+    };
+
+    let mut rendered = Vec::new();
+    codegen::form_ast_from_data(&data, options)
+        .expect("Failed to generate the bindings")
+        .render(&mut rendered)
+        .expect("Failed to render the generated bindings");
+    String::from_utf8(rendered).expect("Generated bindings weren't valid UTF-8")
+}
+
+/// Compares generated output against the golden file of the given name, or rewrites it when
+/// blessing.
+fn check_golden_file(file_name: &str, rendered: &str) {
+    let path = golden_path(file_name);
+    if std::env::var_os("DOTNET_BINDGEN_BLESS").is_some() {
+        std::fs::write(&path, rendered).expect("Failed to write golden file");
+        return;
+    }
+
+    let golden = std::fs::read_to_string(&path)
+        .unwrap_or_else(|_| panic!("Missing golden file {}, run with DOTNET_BINDGEN_BLESS=1 to create it", path.display()));
+
+    assert!(
+        normalize_whitespace(rendered) == normalize_whitespace(&golden),
+        "Generated bindings differ from {}, run with DOTNET_BINDGEN_BLESS=1 to update it if intended.\nGenerated:\n{}",
+        path.display(),
+        rendered,
+    );
+}
+
+#[test]
+fn int_args() {
+    check_golden("int_args", vec![
+        BindgenExportDescriptor::Function(function(
+            "add",
+            vec![arg("a", int(32, true)), arg("b", int(64, false))],
+            int(64, false),
+        )),
+    ]);
+}
+
+#[test]
+fn bool_args() {
+    check_golden("bool_args", vec![
+        BindgenExportDescriptor::Function(function(
+            "negate",
+            vec![arg("value", BindgenTypeDescriptor::Bool)],
+            BindgenTypeDescriptor::Bool,
+        )),
+    ]);
+}
+
+#[test]
+fn slice_args() {
+    check_golden("slice_args", vec![
+        BindgenExportDescriptor::Function(function(
+            "sum",
+            vec![arg("values", BindgenTypeDescriptor::Slice { elem_type: Box::new(int(32, true)) })],
+            int(32, true),
+        )),
+        BindgenExportDescriptor::Function(function(
+            "fill",
+            vec![arg("buffer", BindgenTypeDescriptor::MutSlice { elem_type: Box::new(int(8, false)) })],
+            BindgenTypeDescriptor::Void,
+        )),
+        BindgenExportDescriptor::Function(function(
+            "count",
+            vec![arg("values", BindgenTypeDescriptor::OptionSlice { elem_type: Box::new(int(32, true)) })],
+            int(64, false),
+        )),
+    ]);
+}
+
+#[test]
+fn struct_args() {
+    let mut length = function(
+        "length",
+        Vec::new(),
+        BindgenTypeDescriptor::Float { width: 32 },
+    );
+    length.owner = Some("Point".to_string());
+    length.receiver = Some(BindgenReceiver::Ref);
+
+    check_golden("struct_args", vec![
+        BindgenExportDescriptor::Struct(point()),
+        BindgenExportDescriptor::Function(function(
+            "manhattan",
+            vec![arg("a", BindgenTypeDescriptor::Struct(point())), arg("b", BindgenTypeDescriptor::Struct(point()))],
+            int(32, true),
+        )),
+        BindgenExportDescriptor::Function(length),
+    ]);
+}
+
+#[test]
+fn string_array_return() {
+    let mut names = function(
+        "names",
+        Vec::new(),
+        BindgenTypeDescriptor::OwnedSlice { elem_type: Box::new(BindgenTypeDescriptor::OwnedStr) },
+    );
+    names.free_thunk_name = Some("__bindgen_free_names".to_string());
+    names.return_ownership = Some(BindgenReturnOwnership::Owned);
+
+    check_golden("string_array_return", vec![BindgenExportDescriptor::Function(names)]);
+}
+
+#[test]
+fn fixed_buffer_fields() {
+    let waveform = BindgenStructDescriptor {
+        name: "Waveform".to_string(),
+        cs_name: None,
+        size: Some(20),
+        align: Some(4),
+        fields: vec![
+            BindgenStructFieldDescriptor {
+                name: "samples".to_string(),
+                ty: BindgenTypeDescriptor::Array { elem_type: Box::new(int(16, false)), len: 8 },
+            },
+            BindgenStructFieldDescriptor {
+                name: "rate".to_string(),
+                ty: int(32, false),
+            },
+        ],
+        fixed_string: false,
+    };
+
+    check_golden("fixed_buffer_fields", vec![BindgenExportDescriptor::Struct(waveform)]);
+}
+
+#[test]
+fn rust_buffer_args() {
+    check_golden("rust_buffer_args", vec![
+        BindgenExportDescriptor::Function(function(
+            "consume",
+            vec![arg("buffer", BindgenTypeDescriptor::OwnedSlice { elem_type: Box::new(int(8, false)) })],
+            int(64, false),
+        )),
+    ]);
+}
+
+/// By-value structs are passed with the platform C ABI on both sides of the P/Invoke, so the
+/// bindings are the same for linux-x64 and win-x64. This covers both register-sized and
+/// stack-spilled structs, in enough numbers to run out of argument registers on either.
+/// Slices, strings and bools interleaved, each of which declares its own generated locals, so
+/// their numbering has to keep shifting past those of the arguments before.
+#[test]
+fn many_complex_args() {
+    let slice = |elem_type| BindgenTypeDescriptor::Slice { elem_type: Box::new(elem_type) };
+    let mut_slice = |elem_type| BindgenTypeDescriptor::MutSlice { elem_type: Box::new(elem_type) };
+
+    let mut many_complex_args = function(
+        "many_complex_args",
+        vec![
+            arg("a", slice(int(8, false))),
+            arg("flag_a", BindgenTypeDescriptor::Bool),
+            arg("b", mut_slice(int(32, true))),
+            arg("name", BindgenTypeDescriptor::Str),
+            arg("flag_b", BindgenTypeDescriptor::Bool),
+            arg("c", slice(int(64, false))),
+            arg("d", mut_slice(int(8, false))),
+        ],
+        BindgenTypeDescriptor::Bool,
+    );
+    many_complex_args.reports_invalid_args = true;
+
+    let descriptors = vec![BindgenExportDescriptor::Function(many_complex_args)];
+    let rendered = render(descriptors.clone(), &CodegenOptions::default());
+
+    // Every generated local is declared once, either as `Type _genN;` or in a `fixed` statement.
+    let mut declared: Vec<_> = rendered
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.starts_with("return "))
+        .filter_map(|line| {
+            let declaration = line.strip_prefix("fixed (").unwrap_or(line);
+            let ident = declaration.split_whitespace().nth(1)?;
+            ident.trim_end_matches(';').strip_prefix("_gen")?.parse::<u32>().ok()
+        })
+        .collect();
+    let count = declared.len();
+    declared.sort_unstable();
+    declared.dedup();
+    assert_eq!(declared.len(), count, "Generated idents collided between arguments:\n{}", rendered);
+    assert_eq!(count, 15);
+
+    check_golden("many_complex_args", descriptors);
+}
+
+#[test]
+fn many_struct_args() {
+    let transform = BindgenStructDescriptor {
+        name: "Transform".to_string(),
+        cs_name: None,
+        size: Some(32),
+        align: Some(8),
+        fields: ["x", "y", "z", "w"]
+            .iter()
+            .map(|name| BindgenStructFieldDescriptor {
+                name: name.to_string(),
+                ty: BindgenTypeDescriptor::Float { width: 64 },
+            })
+            .collect(),
+        fixed_string: false,
+    };
+
+    let many_args = |name: &str, ty: &BindgenStructDescriptor| {
+        let mut arguments = ["a", "b", "c", "d", "e", "f"]
+            .iter()
+            .map(|arg_name| arg(arg_name, BindgenTypeDescriptor::Struct(ty.clone())))
+            .collect::<Vec<_>>();
+        arguments.push(arg("scale", int(32, true)));
+        BindgenExportDescriptor::Function(function(name, arguments, BindgenTypeDescriptor::Float { width: 64 }))
+    };
+
+    check_golden("many_struct_args", vec![
+        BindgenExportDescriptor::Struct(point()),
+        BindgenExportDescriptor::Struct(transform.clone()),
+        many_args("sum_points", &point()),
+        many_args("sum_transforms", &transform),
+    ]);
+}
+
+#[test]
+fn function_pointer_fields() {
+    let fn_ptr = |args: Vec<BindgenTypeDescriptor>, return_ty| BindgenTypeDescriptor::FnPtr {
+        args,
+        return_ty: Box::new(return_ty),
+    };
+    let vtable = BindgenStructDescriptor {
+        name: "PluginVTable".to_string(),
+        cs_name: None,
+        size: Some(16),
+        align: Some(8),
+        fields: vec![
+            BindgenStructFieldDescriptor {
+                name: "on_load".to_string(),
+                ty: fn_ptr(vec![int(32, false)], int(32, true)),
+            },
+            BindgenStructFieldDescriptor {
+                name: "on_event".to_string(),
+                ty: fn_ptr(vec![int(64, false), BindgenTypeDescriptor::Float { width: 32 }], BindgenTypeDescriptor::Void),
+            },
+        ],
+        fixed_string: false,
+    };
+
+    let options = CodegenOptions {
+        function_pointers: true,
+        ..CodegenOptions::default()
+    };
+    check_golden_with_options("function_pointer_fields", vec![
+        BindgenExportDescriptor::Struct(vtable.clone()),
+        BindgenExportDescriptor::Function(function(
+            "register_plugin",
+            vec![arg("vtable", BindgenTypeDescriptor::Struct(vtable)), arg("version", int(32, false))],
+            BindgenTypeDescriptor::Void,
+        )),
+    ], &options);
+}
+
+/// `--function-pointers` can be given along with `--csharp-only`, which `--lang-version` can't be,
+/// eg when generating the bindings from JSON.
+#[test]
+fn function_pointer_args() {
+    let matches = crate::cli()
+        .get_matches_from_safe([
+            "dotnet-bindgen-cli",
+            "--descriptors", "test_lib.json",
+            "--csharp-only",
+            "--function-pointers",
+            "--source-output-dir", "out",
+        ])
+        .expect("--function-pointers should be accepted with --csharp-only");
+
+    let options = CodegenOptions {
+        function_pointers: crate::function_pointers_from_args(&matches),
+        ..CodegenOptions::default()
+    };
+    check_golden_with_options("function_pointer_args", vec![
+        BindgenExportDescriptor::Function(function(
+            "set_callback",
+            vec![arg("callback", BindgenTypeDescriptor::FnPtr {
+                args: vec![int(32, false), BindgenTypeDescriptor::Float { width: 64 }],
+                return_ty: Box::new(int(32, true)),
+            })],
+            BindgenTypeDescriptor::Void,
+        )),
+    ], &options);
+}
+
+/// Structs needing padding between and after their fields, where C#'s default sequential layout has
+/// to agree with `repr(C)` for the generated size and alignment assertions to hold.
+#[test]
+fn padded_struct_layouts() {
+    let field = |name: &str, ty| BindgenStructFieldDescriptor {
+        name: name.to_string(),
+        ty,
+    };
+    let padded = BindgenStructDescriptor {
+        name: "Padded".to_string(),
+        cs_name: None,
+        size: Some(24),
+        align: Some(8),
+        fields: vec![
+            field("a", int(8, false)),
+            field("b", int(32, false)),
+            field("c", int(16, false)),
+            field("d", int(64, false)),
+        ],
+        fixed_string: false,
+    };
+    let nested = BindgenStructDescriptor {
+        name: "Nested".to_string(),
+        cs_name: None,
+        size: Some(32),
+        align: Some(8),
+        fields: vec![
+            field("tag", int(8, false)),
+            field("inner", BindgenTypeDescriptor::Struct(padded.clone())),
+        ],
+        fixed_string: false,
+    };
+    let trailing = BindgenStructDescriptor {
+        name: "Trailing".to_string(),
+        cs_name: None,
+        size: Some(16),
+        align: Some(8),
+        fields: vec![
+            field("value", BindgenTypeDescriptor::Float { width: 64 }),
+            field("flag", int(8, false)),
+        ],
+        fixed_string: false,
+    };
+
+    check_golden("padded_struct_layouts", vec![
+        BindgenExportDescriptor::Struct(padded),
+        BindgenExportDescriptor::Struct(nested),
+        BindgenExportDescriptor::Struct(trailing),
+    ]);
+}
+
+#[test]
+fn int128_round_trip() {
+    check_golden("int128_round_trip", vec![
+        BindgenExportDescriptor::Function(function(
+            "negate_i128",
+            vec![arg("value", int(128, true))],
+            int(128, true),
+        )),
+        BindgenExportDescriptor::Function(function(
+            "widening_mul",
+            vec![arg("a", int(64, false)), arg("b", int(64, false))],
+            int(128, false),
+        )),
+    ]);
+}
+
+#[test]
+fn opaque_pointers() {
+    let scene_ptr = || BindgenTypeDescriptor::Ptr {
+        name: "test_lib::Scene".to_string(),
+        mutable: true,
+        pointee: Some(Box::new(BindgenTypeDescriptor::Opaque { name: "Scene".to_string() })),
+    };
+
+    check_golden("opaque_pointers", vec![
+        BindgenExportDescriptor::Function(function("scene_new", vec![], scene_ptr())),
+        BindgenExportDescriptor::Function(function(
+            "scene_tick",
+            vec![arg("scene", scene_ptr()), arg("delta", int(32, false))],
+            int(32, false),
+        )),
+        BindgenExportDescriptor::Function(function(
+            "scene_free",
+            vec![arg("scene", scene_ptr())],
+            BindgenTypeDescriptor::Void,
+        )),
+    ]);
+}
+
+#[test]
+fn library_import_unsafe_classes() {
+    let slice = || BindgenTypeDescriptor::Slice { elem_type: Box::new(int(32, true)) };
+    let scene_ptr = BindgenTypeDescriptor::Ptr {
+        name: "test_lib::Scene".to_string(),
+        mutable: true,
+        pointee: Some(Box::new(BindgenTypeDescriptor::Opaque { name: "Scene".to_string() })),
+    };
+
+    // Only TopLevelMethods has a member using pointers, the slices being handled by marshallers
+    let mut mean = function("mean", vec![arg("values", slice())], BindgenTypeDescriptor::Float { width: 64 });
+    mean.module = Some("Stats".to_string());
+
+    let options = CodegenOptions {
+        custom_marshallers: true,
+        ..CodegenOptions::default()
+    };
+    check_golden_with_options("library_import_unsafe_classes", vec![
+        BindgenExportDescriptor::Function(function("sum", vec![arg("values", slice())], int(32, true))),
+        BindgenExportDescriptor::Function(function(
+            "scene_tick",
+            vec![arg("scene", scene_ptr), arg("delta", int(32, false))],
+            int(32, false),
+        )),
+        BindgenExportDescriptor::Function(mean),
+    ], &options);
+}
+
+#[test]
+fn borrowed_returns() {
+    let mut version = function("version", Vec::new(), BindgenTypeDescriptor::Str);
+    version.return_ownership = Some(BindgenReturnOwnership::Borrowed);
+
+    let mut primes = function(
+        "primes",
+        Vec::new(),
+        BindgenTypeDescriptor::Slice { elem_type: Box::new(int(32, false)) },
+    );
+    primes.return_ownership = Some(BindgenReturnOwnership::Borrowed);
+
+    check_golden("borrowed_returns", vec![
+        BindgenExportDescriptor::Function(version),
+        BindgenExportDescriptor::Function(primes),
+    ]);
+}
+
+#[test]
+fn record_structs() {
+    let mut length = function(
+        "length",
+        Vec::new(),
+        BindgenTypeDescriptor::Float { width: 32 },
+    );
+    length.owner = Some("Point".to_string());
+    length.receiver = Some(BindgenReceiver::Ref);
+
+    // Holds structs rather than scalars, so stays a plain struct
+    let field = |name: &str| BindgenStructFieldDescriptor {
+        name: name.to_string(),
+        ty: BindgenTypeDescriptor::Struct(point()),
+    };
+    let segment = BindgenStructDescriptor {
+        name: "Segment".to_string(),
+        cs_name: None,
+        size: Some(16),
+        align: Some(4),
+        fields: vec![field("start"), field("end")],
+        fixed_string: false,
+    };
+
+    let options = CodegenOptions {
+        record_structs: true,
+        ..CodegenOptions::default()
+    };
+    check_golden_with_options("record_structs", vec![
+        BindgenExportDescriptor::Struct(point()),
+        BindgenExportDescriptor::Struct(segment),
+        BindgenExportDescriptor::Function(length),
+    ], &options);
+}
+
+#[test]
+fn stream_overloads() {
+    let bytes = || BindgenTypeDescriptor::Slice { elem_type: Box::new(int(8, false)) };
+
+    let options = CodegenOptions {
+        stream_overloads: true,
+        ..CodegenOptions::default()
+    };
+    check_golden_with_options("stream_overloads", vec![
+        BindgenExportDescriptor::Function(function(
+            "checksum",
+            vec![arg("data", bytes()), arg("seed", int(32, false))],
+            int(32, false),
+        )),
+        BindgenExportDescriptor::Function(function(
+            "copy_into",
+            vec![arg("source", bytes()), arg("key", bytes())],
+            BindgenTypeDescriptor::Void,
+        )),
+    ], &options);
+}
+
+#[test]
+fn buffer_struct_returns() {
+    let field = |name: &str, ty| BindgenStructFieldDescriptor {
+        name: name.to_string(),
+        ty,
+    };
+    let struct_ty = |name: &str, fields| BindgenTypeDescriptor::Struct(BindgenStructDescriptor {
+        name: name.to_string(),
+        cs_name: None,
+        size: None,
+        align: None,
+        fields,
+        fixed_string: false,
+    });
+
+    let histogram = struct_ty("Histogram", vec![
+        field("buckets", BindgenTypeDescriptor::OwnedSlice { elem_type: Box::new(int(32, false)) }),
+        field("label", BindgenTypeDescriptor::OwnedStr),
+        field("total", int(64, false)),
+    ]);
+    let header = struct_ty("Header", vec![
+        field("name", BindgenTypeDescriptor::Str),
+        field("magic", int(32, false)),
+    ]);
+
+    let mut build_histogram = function(
+        "build_histogram",
+        vec![arg("values", BindgenTypeDescriptor::Slice { elem_type: Box::new(int(8, false)) })],
+        histogram.clone(),
+    );
+    build_histogram.return_ownership = Some(BindgenReturnOwnership::Owned);
+    build_histogram.free_thunk_name = Some("__bindgen_free_build_histogram".to_string());
+
+    let mut default_header = function("default_header", Vec::new(), header.clone());
+    default_header.return_ownership = Some(BindgenReturnOwnership::Borrowed);
+
+    let export = |ty| match ty {
+        BindgenTypeDescriptor::Struct(s) => BindgenExportDescriptor::Struct(s),
+        _ => unreachable!(),
+    };
+    check_golden("buffer_struct_returns", vec![
+        export(histogram),
+        export(header),
+        BindgenExportDescriptor::Function(build_histogram),
+        BindgenExportDescriptor::Function(default_header),
+    ]);
+}
+
+#[test]
+fn no_copy_slices() {
+    let u32_slice = || Box::new(int(32, false));
+
+    let mut squares = function(
+        "squares",
+        vec![arg("count", int(32, false))],
+        BindgenTypeDescriptor::OwnedSlice { elem_type: u32_slice() },
+    );
+    squares.return_ownership = Some(BindgenReturnOwnership::Owned);
+    squares.free_thunk_name = Some("__bindgen_free_squares".to_string());
+
+    let mut primes = function("primes", Vec::new(), BindgenTypeDescriptor::Slice { elem_type: u32_slice() });
+    primes.return_ownership = Some(BindgenReturnOwnership::Borrowed);
+
+    let options = CodegenOptions {
+        no_copy_slices: true,
+        ..CodegenOptions::default()
+    };
+    check_golden_with_options("no_copy_slices", vec![
+        BindgenExportDescriptor::Function(squares),
+        BindgenExportDescriptor::Function(primes),
+    ], &options);
+}
+
+#[test]
+fn default_arguments() {
+    let with_default = |name: &str, ty, default| BindgenFunctionArgumentDescriptor {
+        default: Some(default),
+        ..arg(name, ty)
+    };
+
+    let blend = function(
+        "blend",
+        vec![
+            arg("from", BindgenTypeDescriptor::Float { width: 32 }),
+            arg("to", BindgenTypeDescriptor::Float { width: 32 }),
+            with_default("amount", BindgenTypeDescriptor::Float { width: 32 }, BindgenDefaultValue::Float("0.5".to_string())),
+            with_default("clamp", BindgenTypeDescriptor::Bool, BindgenDefaultValue::Bool(true)),
+        ],
+        BindgenTypeDescriptor::Float { width: 32 },
+    );
+
+    let seek = function(
+        "seek",
+        vec![
+            with_default("offset", int(64, true), BindgenDefaultValue::Int(-1)),
+            with_default("limit", int(64, false), BindgenDefaultValue::UInt(u64::MAX)),
+            with_default("label", BindgenTypeDescriptor::Str, BindgenDefaultValue::Str("start".to_string())),
+        ],
+        BindgenTypeDescriptor::Void,
+    );
+
+    check_golden("default_arguments", vec![
+        BindgenExportDescriptor::Function(blend),
+        BindgenExportDescriptor::Function(seek),
+    ]);
+}
+
+#[test]
+fn escaped_strings() {
+    let options = CodegenOptions {
+        file_header: vec![
+            "Quotes \"like this\", a back\\slash and caf\u{e9} \u{1F980}".to_string(),
+            "A stray */ must not close the comment\nand an embedded newline starts a new line".to_string(),
+        ],
+        ..CodegenOptions::default()
+    };
+
+    let greet = function(
+        "greet",
+        vec![BindgenFunctionArgumentDescriptor {
+            default: Some(BindgenDefaultValue::Str("say \"h\u{e9}llo\" \\ */\r\n\t\u{1F980}".to_string())),
+            ..arg("greeting", BindgenTypeDescriptor::Str)
+        }],
+        BindgenTypeDescriptor::Void,
+    );
+
+    check_golden_with_options("escaped_strings", vec![BindgenExportDescriptor::Function(greet)], &options);
+}
+
+#[test]
+fn debug_only_validation() {
+    let options = CodegenOptions {
+        debug_only_validation: true,
+        stream_overloads: true,
+        ..CodegenOptions::default()
+    };
+    check_golden_with_options("debug_only_validation", vec![
+        BindgenExportDescriptor::Function(function(
+            "tag",
+            vec![
+                arg("label", BindgenTypeDescriptor::Str),
+                arg("data", BindgenTypeDescriptor::Slice { elem_type: Box::new(int(8, false)) }),
+                arg("seed", int(32, false)),
+            ],
+            int(32, false),
+        )),
+        BindgenExportDescriptor::Function(function(
+            "scale",
+            vec![
+                arg("values", BindgenTypeDescriptor::MutSlice { elem_type: Box::new(BindgenTypeDescriptor::Float { width: 32 }) }),
+                arg("factor", BindgenTypeDescriptor::Float { width: 32 }),
+            ],
+            BindgenTypeDescriptor::Void,
+        )),
+        BindgenExportDescriptor::Function(function("add", vec![arg("a", int(32, true)), arg("b", int(32, true))], int(32, true))),
+    ], &options);
+}
+
+/// The bool is folded into the native call, but the encoded name is used twice, and the copied
+/// string can't be moved past the call freeing the native one.
+#[test]
+fn inline_temporaries() {
+    let mut greet = function(
+        "greet",
+        vec![arg("name", BindgenTypeDescriptor::Str), arg("shout", BindgenTypeDescriptor::Bool)],
+        BindgenTypeDescriptor::OwnedStr,
+    );
+    greet.free_thunk_name = Some("__bindgen_free_greet".to_string());
+    greet.return_ownership = Some(BindgenReturnOwnership::Owned);
+
+    let options = CodegenOptions {
+        inline_temporaries: 2,
+        ..CodegenOptions::default()
+    };
+    check_golden_with_options("inline_temporaries", vec![BindgenExportDescriptor::Function(greet)], &options);
+}
+
+#[test]
+fn tuple_returns() {
+    let i32_slice = || BindgenTypeDescriptor::Slice { elem_type: Box::new(int(32, true)) };
+
+    let mut split_header = function(
+        "split_header",
+        vec![arg("values", i32_slice())],
+        BindgenTypeDescriptor::Tuple { elements: vec![i32_slice(), int(32, true)] },
+    );
+    split_header.return_ownership = Some(BindgenReturnOwnership::Borrowed);
+
+    let mut take_line = function(
+        "take_line",
+        vec![arg("text", BindgenTypeDescriptor::Str)],
+        BindgenTypeDescriptor::Tuple { elements: vec![BindgenTypeDescriptor::OwnedStr, int(64, false)] },
+    );
+    take_line.return_ownership = Some(BindgenReturnOwnership::Owned);
+    take_line.free_thunk_name = Some("__bindgen_free_take_line".to_string());
+
+    check_golden("tuple_returns", vec![
+        BindgenExportDescriptor::Function(split_header),
+        BindgenExportDescriptor::Function(take_line),
+    ]);
+}
+
+#[test]
+fn fill_buffer_overload() {
+    let byte_buffer = || BindgenTypeDescriptor::MutSlice { elem_type: Box::new(int(8, false)) };
+
+    // usize as described on a 32 bit target
+    let fill_buffer = BindgenFunctionDescriptor {
+        fill_buffer_arg: Some("buffer".to_string()),
+        ..function(
+            "fill_buffer",
+            vec![arg("seed", int(8, false)), arg("buffer", byte_buffer())],
+            int(32, false),
+        )
+    };
+
+    // The same shape, but not marked, so gets no overload
+    let read_into = function("read_into", vec![arg("buffer", byte_buffer())], int(64, false));
+
+    check_golden("fill_buffer_overload", vec![
+        BindgenExportDescriptor::Function(fill_buffer),
+        BindgenExportDescriptor::Function(read_into),
+    ]);
+}
+
+#[test]
+fn invalid_utf8_reporting() {
+    let mut char_count = function("char_count", vec![arg("text", BindgenTypeDescriptor::Str)], int(64, false));
+    char_count.reports_invalid_args = true;
+
+    let mut log = function("log", vec![arg("message", BindgenTypeDescriptor::Str)], BindgenTypeDescriptor::Void);
+    log.reports_invalid_args = true;
+
+    check_golden("invalid_utf8_reporting", vec![
+        BindgenExportDescriptor::Function(char_count),
+        BindgenExportDescriptor::Function(log),
+    ]);
+}
+
+/// Slices are pinned rather than copied by the marshaller, so an explicit direction is only
+/// recorded in the descriptor, and the P/Invoke parameters carry no `[In]`/`[Out]` attributes.
+#[test]
+fn argument_directions() {
+    let with_direction = |name: &str, ty, direction| BindgenFunctionArgumentDescriptor {
+        direction: Some(direction),
+        ..arg(name, ty)
+    };
+    let bytes = || Box::new(int(8, false));
+
+    let copy_buffer = function(
+        "copy_buffer",
+        vec![
+            with_direction("src", BindgenTypeDescriptor::Slice { elem_type: bytes() }, BindgenArgumentDirection::In),
+            with_direction("dst", BindgenTypeDescriptor::MutSlice { elem_type: bytes() }, BindgenArgumentDirection::Out),
+            with_direction("scratch", BindgenTypeDescriptor::MutSlice { elem_type: bytes() }, BindgenArgumentDirection::InOut),
+        ],
+        BindgenTypeDescriptor::Void,
+    );
+
+    check_golden("argument_directions", vec![BindgenExportDescriptor::Function(copy_buffer)]);
+}
+
+#[test]
+fn entry_points() {
+    // The P/Invoke for a thunk is named after it, so needs no EntryPoint.
+    let thunked = function("thunked", vec![arg("value", int(32, true))], int(32, true));
+
+    // A foreign function's P/Invoke is renamed into the __bindgen namespace, so names the symbol.
+    let mut foreign = function("foreign", vec![arg("value", int(32, true))], int(32, true));
+    foreign.thunk_name = "foreign".to_string();
+    foreign.no_thunk = true;
+
+    check_golden("entry_points", vec![
+        BindgenExportDescriptor::Function(thunked),
+        BindgenExportDescriptor::Function(foreign),
+    ]);
+}
+
+#[test]
+fn static_accessors() {
+    // Primitives are read straight from the export, structs are still marshalled, and both share
+    // the one library handle.
+    check_golden("static_accessors", vec![
+        BindgenExportDescriptor::Struct(point()),
+        BindgenExportDescriptor::Static(BindgenStaticDescriptor {
+            name: "CALL_COUNT".to_string(),
+            ty: int(32, false),
+            mutable: true,
+        }),
+        BindgenExportDescriptor::Static(BindgenStaticDescriptor {
+            name: "ORIGIN".to_string(),
+            ty: BindgenTypeDescriptor::Struct(point()),
+            mutable: false,
+        }),
+    ]);
+}
+
+#[test]
+fn readonly_struct_receivers() {
+    // `this` can't be passed by ref in a readonly struct, so a `&mut self` method takes it by `in`.
+    let mut translate = function("translate", vec![arg("dx", int(32, true))], BindgenTypeDescriptor::Void);
+    translate.owner = Some("Point".to_string());
+    translate.receiver = Some(BindgenReceiver::RefMut);
+
+    let options = CodegenOptions {
+        readonly_structs: true,
+        ..CodegenOptions::default()
+    };
+    check_golden_with_options("readonly_struct_receivers", vec![
+        BindgenExportDescriptor::Struct(point()),
+        BindgenExportDescriptor::Function(translate),
+    ], &options);
+}
+
+#[test]
+fn unbound_method_owner() {
+    let mut length = function("length", Vec::new(), BindgenTypeDescriptor::Float { width: 32 });
+    length.owner = Some("Point".to_string());
+    length.receiver = Some(BindgenReceiver::Ref);
+
+    let data = BindgenData {
+        source_file: PathBuf::from("libgolden.so"),
+        descriptors: vec![BindgenExportDescriptor::Function(length)],
+        native_binding_version: None,
+        pointer_width: 8,
+    };
+
+    let err = codegen::form_ast_from_data(&data, &CodegenOptions::default()).err();
+    assert!(matches!(
+        err,
+        Some(BindgenError::UnboundMethodOwner { ref owner, ref method }) if owner == "Point" && method == "length"
+    ));
+}
+
+fn enumeration(name: &str, repr: BindgenTypeDescriptor, variants: &[(&str, i64)]) -> BindgenEnumDescriptor {
+    BindgenEnumDescriptor {
+        name: name.to_string(),
+        repr: Box::new(repr),
+        variants: variants
+            .iter()
+            .map(|(name, value)| BindgenEnumVariantDescriptor { name: name.to_string(), value: *value })
+            .collect(),
+    }
+}
+
+/// Enums are blittable, so slices of them are pinned and returned `Vec`s copied out like any
+/// other integer. Each is declared with the C# type matching its repr.
+#[test]
+fn enum_slices() {
+    let color = enumeration("Color", int(8, false), &[("Red", 0), ("Green", 1), ("Blue", 5)]);
+    let priority = enumeration("Priority", int(16, true), &[("Low", -100), ("Normal", 0), ("High", 1000)]);
+    // 1 << 63, which arrives wrapped around
+    let mask = enumeration("Mask", int(64, false), &[("Empty", 0), ("TopBit", i64::MIN)]);
+
+    // The thunks check every discriminant, reporting undefined ones to be thrown.
+    let count_color = BindgenFunctionDescriptor {
+        reports_invalid_args: true,
+        ..function(
+            "count_color",
+            vec![
+                arg("colors", BindgenTypeDescriptor::Slice { elem_type: Box::new(BindgenTypeDescriptor::Enum(color.clone())) }),
+                arg("color", BindgenTypeDescriptor::Enum(color.clone())),
+            ],
+            int(32, false),
+        )
+    };
+    let reverse_colors = BindgenFunctionDescriptor {
+        reports_invalid_args: true,
+        ..function(
+            "reverse_colors",
+            vec![arg("colors", BindgenTypeDescriptor::MutSlice { elem_type: Box::new(BindgenTypeDescriptor::Enum(color.clone())) })],
+            BindgenTypeDescriptor::Void,
+        )
+    };
+    let primary_colors = BindgenFunctionDescriptor {
+        return_ownership: Some(BindgenReturnOwnership::Owned),
+        free_thunk_name: Some("__bindgen_free_primary_colors".to_string()),
+        ..function(
+            "primary_colors",
+            Vec::new(),
+            BindgenTypeDescriptor::OwnedSlice { elem_type: Box::new(BindgenTypeDescriptor::Enum(color.clone())) },
+        )
+    };
+    let is_urgent = BindgenFunctionDescriptor {
+        reports_invalid_args: true,
+        ..function(
+            "is_urgent",
+            vec![
+                arg("priority", BindgenTypeDescriptor::Enum(priority.clone())),
+                arg("mask", BindgenTypeDescriptor::Enum(mask.clone())),
+            ],
+            BindgenTypeDescriptor::Bool,
+        )
+    };
+
+    check_golden("enum_slices", vec![
+        BindgenExportDescriptor::Enum(color),
+        BindgenExportDescriptor::Enum(priority),
+        BindgenExportDescriptor::Enum(mask),
+        BindgenExportDescriptor::Function(count_color),
+        BindgenExportDescriptor::Function(reverse_colors),
+        BindgenExportDescriptor::Function(primary_colors),
+        BindgenExportDescriptor::Function(is_urgent),
+    ]);
+}
+
+#[test]
+fn enum_discriminant_out_of_range() {
+    let form = |enumeration: BindgenEnumDescriptor| {
+        let data = BindgenData {
+            source_file: PathBuf::from("libgolden.so"),
+            descriptors: vec![BindgenExportDescriptor::Enum(enumeration)],
+            native_binding_version: None,
+            pointer_width: 8,
+        };
+        codegen::form_ast_from_data(&data, &CodegenOptions::default()).map(|_| ())
+    };
+
+    assert!(matches!(
+        form(enumeration("Color", int(8, false), &[("Red", 0), ("Ultraviolet", 256)])),
+        Err(BindgenError::Other(msg)) if msg.contains("discriminant")
+    ));
+    assert!(matches!(
+        form(enumeration("Priority", int(16, true), &[("Lowest", -32769)])),
+        Err(BindgenError::Other(msg)) if msg.contains("discriminant")
+    ));
+    assert!(form(enumeration("Priority", int(16, true), &[("Lowest", -32768)])).is_ok());
+}
+
+fn overaligned(align: u64) -> BindgenStructDescriptor {
+    BindgenStructDescriptor {
+        name: format!("Lanes{}", align),
+        cs_name: None,
+        size: Some(align),
+        align: Some(align),
+        fields: vec![BindgenStructFieldDescriptor {
+            name: "lanes".to_string(),
+            ty: BindgenTypeDescriptor::Array { elem_type: Box::new(int(8, false)), len: align },
+        }],
+        fixed_string: false,
+    }
+}
+
+/// Slices of structs aligned to more than the runtime aligns array elements to, which is 8 bytes
+/// on 64 bit targets and 4 on 32 bit ones, are refused.
+#[test]
+fn overaligned_slices() {
+    let form = |elem: BindgenStructDescriptor, pointer_width| {
+        let sum = function(
+            "sum",
+            vec![arg("values", BindgenTypeDescriptor::Slice { elem_type: Box::new(BindgenTypeDescriptor::Struct(elem.clone())) })],
+            int(32, false),
+        );
+        let data = BindgenData {
+            source_file: PathBuf::from("libgolden.so"),
+            descriptors: vec![BindgenExportDescriptor::Struct(elem), BindgenExportDescriptor::Function(sum)],
+            native_binding_version: None,
+            pointer_width,
+        };
+        match codegen::form_ast_from_data(&data, &CodegenOptions::default()) {
+            Err(BindgenError::OveralignedSlice { align, max_align, .. }) => Some((align, max_align)),
+            Err(e) => panic!("Unexpected error: {}", e),
+            Ok(_) => None,
+        }
+    };
+
+    for align in [16, 32, 64] {
+        assert_eq!(form(overaligned(align), 8), Some((align, 8)));
+        assert_eq!(form(overaligned(align), 4), Some((align, 4)));
+    }
+    assert_eq!(form(overaligned(8), 8), None);
+    assert_eq!(form(overaligned(8), 4), Some((8, 4)));
+}
+
+/// Overaligned structs can still be passed by value, with a warning, but get no alignment check
+/// as .NET never aligns a struct to more than 8 bytes.
+#[test]
+fn overaligned_by_value_structs() {
+    let structs: Vec<_> = [16, 32, 64].iter().map(|&align| overaligned(align)).collect();
+    let functions = structs.iter().map(|s| {
+        let name = format!("first_lane_{}", s.align.unwrap());
+        function(&name, vec![arg("value", BindgenTypeDescriptor::Struct(s.clone()))], int(8, false))
+    });
+
+    check_golden(
+        "overaligned_by_value_structs",
+        structs
+            .iter()
+            .cloned()
+            .map(BindgenExportDescriptor::Struct)
+            .chain(functions.map(BindgenExportDescriptor::Function))
+            .collect(),
+    );
+}
+
+#[test]
+fn module_name_clash() {
+    let clash = |module: &str| {
+        let function = BindgenFunctionDescriptor {
+            module: Some(module.to_string()),
+            ..function("copy", Vec::new(), BindgenTypeDescriptor::Void)
+        };
+        let data = BindgenData {
+            source_file: PathBuf::from("libgolden.so"),
+            descriptors: vec![BindgenExportDescriptor::Struct(point()), BindgenExportDescriptor::Function(function)],
+            native_binding_version: None,
+            pointer_width: 8,
+        };
+        match codegen::form_ast_from_data(&data, &CodegenOptions::default()) {
+            Err(BindgenError::ModuleNameClash { name, .. }) => Some(name),
+            _ => None,
+        }
+    };
+
+    assert_eq!(clash("Interop.Marshal").as_deref(), Some("Marshal"));
+    assert_eq!(clash("Span").as_deref(), Some("Span"));
+    assert_eq!(clash("Point").as_deref(), Some("Point"));
+    assert_eq!(clash("Math"), None);
+}
+
+#[test]
+fn skip_locals_init() {
+    let options = CodegenOptions {
+        skip_locals_init: true,
+        ..CodegenOptions::default()
+    };
+    check_golden_with_options("skip_locals_init", vec![
+        BindgenExportDescriptor::Function(function(
+            "sum",
+            vec![arg("values", BindgenTypeDescriptor::Slice { elem_type: Box::new(int(32, true)) })],
+            int(64, true),
+        )),
+    ], &options);
+
+    // The module attribute can't be compiled for netstandard2.0, so nor can the project target it
+    let binary = csproj::NativeBinary::new(NativePlatform::LinuxX64, PathBuf::from("libgolden.so"));
+    let proj = csproj::ProjFileBuilder::new(csproj::NativeBinarySet::new(vec![binary]))
+        .feature(csproj::Feature::SkipLocalsInit)
+        .build()
+        .expect("Failed to build the project file");
+    check_golden_file("skip_locals_init.csproj", &proj.render_proj_xml());
+}
+
+#[test]
+fn every_struct_has_a_layout() {
+    let dir = golden_path("");
+    for entry in std::fs::read_dir(&dir).expect("Failed to read the golden files") {
+        let path = entry.expect("Failed to read the golden files").path();
+        if path.extension() != Some("cs".as_ref()) {
+            continue;
+        }
+
+        let source = std::fs::read_to_string(&path).expect("Failed to read golden file");
+        let lines: Vec<_> = source.lines().map(str::trim).collect();
+        for (idx, line) in lines.iter().enumerate() {
+            let declares_struct = line.split_whitespace().take_while(|&w| w != ":").any(|w| w == "struct");
+            let has_layout = lines[..idx]
+                .iter()
+                .rev()
+                .take_while(|l| l.starts_with('['))
+                .any(|l| l.starts_with("[StructLayout("));
+
+            assert!(
+                !declares_struct || has_layout,
+                "{} declares a struct without a StructLayout: {}",
+                path.display(),
+                line,
+            );
+        }
+    }
+}
+
+#[test]
+fn fixed_string_next_to_math_module() {
+    let name = BindgenStructDescriptor {
+        name: "Name".to_string(),
+        cs_name: None,
+        size: Some(33),
+        align: Some(1),
+        fields: vec![
+            BindgenStructFieldDescriptor {
+                name: "bytes".to_string(),
+                ty: BindgenTypeDescriptor::Array { elem_type: Box::new(int(8, false)), len: 32 },
+            },
+            BindgenStructFieldDescriptor {
+                name: "len".to_string(),
+                ty: int(8, false),
+            },
+        ],
+        fixed_string: true,
+    };
+
+    let square = BindgenFunctionDescriptor {
+        module: Some("Math".to_string()),
+        ..function("square", vec![arg("x", int(32, true))], int(32, true))
+    };
+
+    check_golden("fixed_string_next_to_math_module", vec![
+        BindgenExportDescriptor::Struct(name),
+        BindgenExportDescriptor::Function(square),
+    ]);
+}
+
+#[test]
+fn struct_equality() {
+    let sample = BindgenStructDescriptor {
+        name: "Sample".to_string(),
+        cs_name: None,
+        size: Some(16),
+        align: Some(8),
+        fields: vec![
+            BindgenStructFieldDescriptor {
+                name: "value".to_string(),
+                ty: BindgenTypeDescriptor::Float { width: 64 },
+            },
+            BindgenStructFieldDescriptor {
+                name: "count".to_string(),
+                ty: int(32, true),
+            },
+        ],
+        fixed_string: false,
+    };
+
+    let options = CodegenOptions {
+        struct_equality: true,
+        ..CodegenOptions::default()
+    };
+    check_golden_with_options("struct_equality", vec![
+        BindgenExportDescriptor::Struct(sample.clone()),
+        BindgenExportDescriptor::Function(function(
+            "mean",
+            vec![arg("sample", BindgenTypeDescriptor::Struct(sample))],
+            BindgenTypeDescriptor::Float { width: 64 },
+        )),
+    ], &options);
+}
+
+/// The free functions in TopLevelMethods are declared on the interface, while those in a module
+/// and the methods of a bound struct aren't.
+#[test]
+fn emit_interface() {
+    let options = CodegenOptions {
+        emit_interface: true,
+        ..CodegenOptions::default()
+    };
+
+    let length = BindgenFunctionDescriptor {
+        owner: Some("Point".to_string()),
+        receiver: Some(BindgenReceiver::Ref),
+        ..function("length", Vec::new(), BindgenTypeDescriptor::Float { width: 64 })
+    };
+    let square = BindgenFunctionDescriptor {
+        module: Some("Math".to_string()),
+        ..function("square", vec![arg("x", int(32, true))], int(32, true))
+    };
+
+    check_golden_with_options("emit_interface", vec![
+        BindgenExportDescriptor::Struct(point()),
+        BindgenExportDescriptor::Function(function(
+            "sum",
+            vec![arg("values", BindgenTypeDescriptor::Slice { elem_type: Box::new(int(32, true)) })],
+            int(64, true),
+        )),
+        BindgenExportDescriptor::Function(function("reset", Vec::new(), BindgenTypeDescriptor::Void)),
+        BindgenExportDescriptor::Function(length),
+        BindgenExportDescriptor::Function(square),
+    ], &options);
+}
+
+#[test]
+fn slice_views() {
+    let options = CodegenOptions {
+        slice_views: true,
+        ..CodegenOptions::default()
+    };
+
+    check_golden_with_options("slice_views", vec![
+        BindgenExportDescriptor::Function(function(
+            "sum",
+            vec![arg("values", BindgenTypeDescriptor::Slice { elem_type: Box::new(int(32, true)) })],
+            int(64, true),
+        )),
+        BindgenExportDescriptor::Function(function(
+            "fill",
+            vec![
+                arg("buffer", BindgenTypeDescriptor::MutSlice { elem_type: Box::new(int(8, false)) }),
+                arg("value", int(8, false)),
+            ],
+            BindgenTypeDescriptor::Void,
+        )),
+    ], &options);
+}
+
+/// The pragmas wrap the whole file with the given warnings, and are left out entirely when there
+/// are none.
+#[test]
+fn disabled_warnings() {
+    let descriptors = || vec![
+        BindgenExportDescriptor::Function(function("add", vec![arg("a", int(32, true)), arg("b", int(32, true))], int(32, true))),
+    ];
+
+    check_golden_with_options("disabled_warnings", descriptors(), &CodegenOptions {
+        disabled_warnings: vec!["CS0649".to_string(), "IDE1006".to_string()],
+        ..CodegenOptions::default()
+    });
+    check_golden_with_options("no_disabled_warnings", descriptors(), &CodegenOptions {
+        disabled_warnings: Vec::new(),
+        ..CodegenOptions::default()
+    });
+}
+
+#[test]
+fn namespace_prefix() {
+    let options = CodegenOptions {
+        namespace_prefix: Some("Acme.Native".to_string()),
+        ..CodegenOptions::default()
+    };
+
+    check_golden_with_options("namespace_prefix", vec![
+        BindgenExportDescriptor::Struct(point()),
+        BindgenExportDescriptor::Function(function(
+            "manhattan",
+            vec![arg("point", BindgenTypeDescriptor::Struct(point()))],
+            int(32, true),
+        )),
+    ], &options);
+}
+
+/// Only the P/Invoke of the marked function is `[SuppressGCTransition]`.
+#[test]
+fn no_gc_transition() {
+    let now = BindgenFunctionDescriptor {
+        no_gc_transition: true,
+        ..function("now_ticks", Vec::new(), int(64, false))
+    };
+
+    let descriptors = vec![
+        BindgenExportDescriptor::Function(now),
+        BindgenExportDescriptor::Function(function("flush", Vec::new(), BindgenTypeDescriptor::Void)),
+    ];
+    assert!(codegen::uses_suppress_gc_transition(&descriptors));
+    check_golden("no_gc_transition", descriptors);
+
+    // The attribute can't be compiled for netstandard2.0, so nor can the project target it
+    let binary = csproj::NativeBinary::new(NativePlatform::LinuxX64, PathBuf::from("libgolden.so"));
+    let proj = csproj::ProjFileBuilder::new(csproj::NativeBinarySet::new(vec![binary]))
+        .feature(csproj::Feature::SuppressGCTransition)
+        .build()
+        .expect("Failed to build the project file");
+    check_golden_file("no_gc_transition.csproj", &proj.render_proj_xml());
+}
+
+/// Both the plain forwarding wrappers and those marshalling their arguments are marked.
+#[test]
+fn debugger_step_through() {
+    let options = CodegenOptions {
+        debugger_step_through: true,
+        ..CodegenOptions::default()
+    };
+
+    check_golden_with_options("debugger_step_through", vec![
+        BindgenExportDescriptor::Function(function("add", vec![arg("a", int(32, true)), arg("b", int(32, true))], int(32, true))),
+        BindgenExportDescriptor::Function(function(
+            "sum",
+            vec![arg("values", BindgenTypeDescriptor::Slice { elem_type: Box::new(int(32, true)) })],
+            int(64, true),
+        )),
+    ], &options);
+}
+
+/// Every generated type is internal, including the helpers and module classes, while their
+/// members stay public.
+#[test]
+fn internal_visibility() {
+    let options = CodegenOptions {
+        type_visibility: ast::Visibility::Internal,
+        ..CodegenOptions::default()
+    };
+
+    let square = BindgenFunctionDescriptor {
+        module: Some("Math".to_string()),
+        ..function("square", vec![arg("x", int(32, true))], int(32, true))
+    };
+
+    check_golden_with_options("internal_visibility", vec![
+        BindgenExportDescriptor::Struct(point()),
+        BindgenExportDescriptor::Enum(enumeration("Color", int(8, false), &[("Red", 0), ("Green", 1)])),
+        BindgenExportDescriptor::Function(function(
+            "sum",
+            vec![arg("values", BindgenTypeDescriptor::Slice { elem_type: Box::new(int(32, true)) })],
+            int(64, true),
+        )),
+        BindgenExportDescriptor::Function(square),
+    ], &options);
+}
+
+/// Golden files are compared ignoring whitespace, so the layout is checked separately: minifying
+/// only drops indentation and blank lines. The file header keeps its block comment layout.
+#[test]
+fn minify() {
+    let descriptors = vec![
+        BindgenExportDescriptor::Struct(point()),
+        BindgenExportDescriptor::Function(function(
+            "sum",
+            vec![arg("values", BindgenTypeDescriptor::Slice { elem_type: Box::new(int(32, true)) })],
+            int(64, true),
+        )),
+    ];
+    let options = CodegenOptions {
+        minify: true,
+        ..CodegenOptions::default()
+    };
+
+    let minified = render(descriptors.clone(), &options);
+    assert!(
+        minified
+            .lines()
+            .filter(|line| !line.starts_with(" *"))
+            .all(|line| !line.is_empty() && !line.starts_with(char::is_whitespace)),
+        "Minified bindings still have blank or indented lines:\n{}",
+        minified,
+    );
+    assert_eq!(
+        normalize_whitespace(&minified),
+        normalize_whitespace(&render(descriptors.clone(), &CodegenOptions::default())),
+    );
+
+    check_golden_with_options("minify", descriptors, &options);
+}
+
+/// Only blocking functions get an `...Async` overload, and of those not the ones taking a Span or
+/// instance methods, which are warned about instead.
+#[test]
+fn async_wrappers() {
+    let options = CodegenOptions {
+        async_wrappers: true,
+        ..CodegenOptions::default()
+    };
+
+    let blocking = |f: BindgenFunctionDescriptor| BindgenFunctionDescriptor { blocking: true, ..f };
+
+    check_golden_with_options("async_wrappers", vec![
+        BindgenExportDescriptor::Struct(point()),
+        BindgenExportDescriptor::Function(blocking(function(
+            "checksum",
+            vec![arg("data", BindgenTypeDescriptor::Slice { elem_type: Box::new(int(8, false)) })],
+            int(32, false),
+        ))),
+        BindgenExportDescriptor::Function(blocking(function("sync_all", Vec::new(), BindgenTypeDescriptor::Void))),
+        BindgenExportDescriptor::Function(blocking(function(
+            "read_into",
+            vec![arg("buffer", BindgenTypeDescriptor::UninitSlice { elem_type: Box::new(int(8, false)) })],
+            int(64, false),
+        ))),
+        BindgenExportDescriptor::Function(blocking(BindgenFunctionDescriptor {
+            owner: Some("Point".to_string()),
+            receiver: Some(BindgenReceiver::Ref),
+            ..function("slow_length", Vec::new(), BindgenTypeDescriptor::Float { width: 64 })
+        })),
+        BindgenExportDescriptor::Function(function("add", vec![arg("a", int(32, true)), arg("b", int(32, true))], int(32, true))),
+    ], &options);
+}
+
+/// The enter hook fires first, before the arguments are null checked, encoded, or pinned. The exit
+/// hook fires last, once a returned string has been decoded and freed, so isn't reached when the
+/// call throws, eg on invalid UTF-8. Only blittable values are handed to the hooks.
+#[test]
+fn trace_hooks() {
+    let options = CodegenOptions {
+        trace_hooks: true,
+        ..CodegenOptions::default()
+    };
+
+    let repeat = BindgenFunctionDescriptor {
+        return_ownership: Some(BindgenReturnOwnership::Owned),
+        free_thunk_name: Some("__bindgen_free_repeat".to_string()),
+        reports_invalid_args: true,
+        ..function(
+            "repeat",
+            vec![
+                arg("text", BindgenTypeDescriptor::Str),
+                arg("counts", BindgenTypeDescriptor::Slice { elem_type: Box::new(int(32, false)) }),
+            ],
+            BindgenTypeDescriptor::OwnedStr,
+        )
+    };
+    let length = BindgenFunctionDescriptor {
+        owner: Some("Point".to_string()),
+        receiver: Some(BindgenReceiver::Ref),
+        ..function("length", Vec::new(), BindgenTypeDescriptor::Float { width: 64 })
+    };
+
+    check_golden_with_options("trace_hooks", vec![
+        BindgenExportDescriptor::Struct(point()),
+        BindgenExportDescriptor::Function(repeat),
+        BindgenExportDescriptor::Function(length),
+        BindgenExportDescriptor::Function(function("reset", Vec::new(), BindgenTypeDescriptor::Void)),
+    ], &options);
+}
+
+/// Every variant named by `variant_name`, which the ABI summary golden must cover.
+const DESCRIPTOR_VARIANTS: [&str; 24] = [
+    "Void", "Int", "Bool", "Slice", "MutSlice", "OptionSlice", "UninitSlice", "Ref", "MutRef", "Str",
+    "Float", "Array", "OwnedSlice", "OwnedStr", "CString", "Arc", "RawPtr", "Ptr", "Opaque",
+    "Duration", "FnPtr", "Tuple", "Struct", "Enum",
+];
+
+/// The name of the given descriptor's variant, matched exhaustively so that a new variant can't be
+/// added without being named here, and so added to `DESCRIPTOR_VARIANTS`.
+fn variant_name(ty: &BindgenTypeDescriptor) -> &'static str {
+    use BindgenTypeDescriptor as Desc;
+
+    match ty {
+        Desc::Void => "Void",
+        Desc::Int { .. } => "Int",
+        Desc::Bool => "Bool",
+        Desc::Slice { .. } => "Slice",
+        Desc::MutSlice { .. } => "MutSlice",
+        Desc::OptionSlice { .. } => "OptionSlice",
+        Desc::UninitSlice { .. } => "UninitSlice",
+        Desc::Ref { .. } => "Ref",
+        Desc::MutRef { .. } => "MutRef",
+        Desc::Str => "Str",
+        Desc::Float { .. } => "Float",
+        Desc::Array { .. } => "Array",
+        Desc::OwnedSlice { .. } => "OwnedSlice",
+        Desc::OwnedStr => "OwnedStr",
+        Desc::CString => "CString",
+        Desc::Arc { .. } => "Arc",
+        Desc::RawPtr => "RawPtr",
+        Desc::Ptr { .. } => "Ptr",
+        Desc::Opaque { .. } => "Opaque",
+        Desc::Duration => "Duration",
+        Desc::FnPtr { .. } => "FnPtr",
+        Desc::Tuple { .. } => "Tuple",
+        Desc::Struct(_) => "Struct",
+        Desc::Enum(_) => "Enum",
+    }
+}
+
+/// Every descriptor variant used by the given type, including those nested in it.
+fn used_variants(ty: &BindgenTypeDescriptor, used: &mut std::collections::BTreeSet<&'static str>) {
+    use BindgenTypeDescriptor as Desc;
+
+    used.insert(variant_name(ty));
+    match ty {
+        Desc::Slice { elem_type }
+        | Desc::MutSlice { elem_type }
+        | Desc::OptionSlice { elem_type }
+        | Desc::UninitSlice { elem_type }
+        | Desc::Array { elem_type, .. }
+        | Desc::OwnedSlice { elem_type } => used_variants(elem_type, used),
+        Desc::Ref { target } | Desc::MutRef { target } => used_variants(target, used),
+        Desc::Ptr { pointee: Some(pointee), .. } => used_variants(pointee, used),
+        Desc::FnPtr { args, return_ty } => {
+            args.iter().for_each(|arg| used_variants(arg, used));
+            used_variants(return_ty, used);
+        }
+        Desc::Tuple { elements } => elements.iter().for_each(|elem| used_variants(elem, used)),
+        _ => {}
+    }
+}
+
+/// The summary gives the AbiType each argument and return value crosses the boundary as, which
+/// has to be kept in step with the `BindgenAbiConvert` impls by hand, so every descriptor variant
+/// is summarised here.
+#[test]
+fn abi_summary() {
+    let slice = |elem_type| BindgenTypeDescriptor::Slice { elem_type: Box::new(elem_type) };
+    let point_ty = || BindgenTypeDescriptor::Struct(point());
+    let color = enumeration("Color", int(8, false), &[("Red", 0), ("Green", 1)]);
+
+    let scalars = function(
+        "scalars",
+        vec![
+            arg("a", int(32, true)),
+            arg("b", BindgenTypeDescriptor::Float { width: 64 }),
+            arg("flag", BindgenTypeDescriptor::Bool),
+            arg("color", BindgenTypeDescriptor::Enum(color)),
+        ],
+        int(128, true),
+    );
+    let slices = function(
+        "slices",
+        vec![
+            arg("values", slice(int(32, false))),
+            arg("out", BindgenTypeDescriptor::MutSlice { elem_type: Box::new(int(8, false)) }),
+            arg("maybe", BindgenTypeDescriptor::OptionSlice { elem_type: Box::new(int(16, false)) }),
+            arg("scratch", BindgenTypeDescriptor::UninitSlice { elem_type: Box::new(int(8, false)) }),
+            arg("matrix", BindgenTypeDescriptor::Array { elem_type: Box::new(BindgenTypeDescriptor::Float { width: 32 }), len: 4 }),
+        ],
+        BindgenTypeDescriptor::OwnedSlice { elem_type: Box::new(point_ty()) },
+    );
+    let pointers = function(
+        "pointers",
+        vec![
+            arg("origin", BindgenTypeDescriptor::Ref { target: Box::new(point_ty()) }),
+            arg("target", BindgenTypeDescriptor::MutRef { target: Box::new(point_ty()) }),
+            arg("user_data", BindgenTypeDescriptor::RawPtr),
+            arg("scene", BindgenTypeDescriptor::Ptr {
+                name: "Scene".to_string(),
+                mutable: true,
+                pointee: Some(Box::new(BindgenTypeDescriptor::Opaque { name: "Scene".to_string() })),
+            }),
+            arg("callback", BindgenTypeDescriptor::FnPtr {
+                args: vec![int(64, false)],
+                return_ty: Box::new(BindgenTypeDescriptor::Void),
+            }),
+        ],
+        BindgenTypeDescriptor::Arc { name: "test_lib::Engine".to_string() },
+    );
+    let strings = function(
+        "strings",
+        vec![arg("name", BindgenTypeDescriptor::Str)],
+        BindgenTypeDescriptor::OwnedSlice { elem_type: Box::new(BindgenTypeDescriptor::OwnedStr) },
+    );
+    let c_string = function("c_string", Vec::new(), BindgenTypeDescriptor::CString);
+    let timeout = function("timeout", vec![arg("after", BindgenTypeDescriptor::Duration)], BindgenTypeDescriptor::Duration);
+    let split = function(
+        "split",
+        vec![arg("text", BindgenTypeDescriptor::Str)],
+        BindgenTypeDescriptor::Tuple { elements: vec![slice(int(32, true)), BindgenTypeDescriptor::OwnedStr, int(128, false)] },
+    );
+    let translate = BindgenFunctionDescriptor {
+        owner: Some("Point".to_string()),
+        receiver: Some(BindgenReceiver::RefMut),
+        calling_convention: BindgenCallingConvention::System,
+        ..function("translate", vec![arg("dx", int(32, true))], BindgenTypeDescriptor::Void)
+    };
+
+    let functions = vec![scalars, slices, pointers, strings, c_string, timeout, split, translate];
+
+    let mut used = std::collections::BTreeSet::new();
+    for f in &functions {
+        f.arguments.iter().for_each(|arg| used_variants(&arg.ty, &mut used));
+        used_variants(&f.return_ty, &mut used);
+    }
+    let unused: Vec<_> = DESCRIPTOR_VARIANTS.iter().filter(|name| !used.contains(*name)).collect();
+    assert!(unused.is_empty(), "The ABI summary golden doesn't cover {:?}", unused);
+
+    let data = BindgenData {
+        source_file: PathBuf::from("libgolden.so"),
+        descriptors: functions.into_iter().map(BindgenExportDescriptor::Function).collect(),
+        native_binding_version: None,
+        pointer_width: 8,
+    };
+
+    let mut summary = Vec::new();
+    abi_summary::write_abi_summary(&data, &mut summary).expect("Failed to write the ABI summary");
+    check_golden_file(
+        "abi_summary.txt",
+        &String::from_utf8(summary).expect("The ABI summary wasn't valid UTF-8"),
+    );
+}
@@ -1,16 +1,26 @@
+use std::io::Write;
 use std::path::{Path, PathBuf};
 
-use clap::{App, Arg};
+use clap::{App, AppSettings, Arg, SubCommand};
 use heck::CamelCase;
+use rayon::prelude::*;
 
+mod abi_summary;
 mod ast;
 mod platform;
 mod csproj;
 mod codegen;
 mod data;
+mod diagnostics;
+mod diff;
+mod error;
 mod path_ext;
 
+#[cfg(test)]
+mod golden_tests;
+
 use data::BindgenData;
+use error::BindgenError;
 use path_ext::BinBaseName;
 use platform::NativePlatform;
 
@@ -29,7 +39,7 @@ impl SourceBinarySpec {
     ///     nativePlatform:path/to/binary.so
     /// 
     /// Where the platform of the binary is omitted, the platform this tool is currently running on is assumed.
-    fn from_bin_arg(arg: &str) -> Result<Self, &'static str> {
+    fn from_bin_arg(arg: &str) -> Result<Self, BindgenError> {
         let platform;
         let binary_path;
 
@@ -49,7 +59,22 @@ impl SourceBinarySpec {
         Self::new(platform, &binary_path)
     }
 
-    fn new(platform: platform::NativePlatform, bin_path: &Path) -> Result<Self, &'static str> {
+    /// Creates a SourceBinarySpec from the --descriptors argument, for generating bindings without
+    /// the binary itself.
+    fn from_descriptors_arg(arg: &str) -> Result<Self, BindgenError> {
+        let json_path = Path::new(arg)
+            .canonicalize()
+            .map_err(|_| "Failed to canonicalize the descriptors path - does it exist?")?;
+
+        Ok(Self {
+            platform: NativePlatform::host_platform(),
+            base_name: json_path.bin_base_name(),
+            bindgen_data: BindgenData::load_json(&json_path)?,
+            bin_path: json_path,
+        })
+    }
+
+    fn new(platform: platform::NativePlatform, bin_path: &Path) -> Result<Self, BindgenError> {
         let bin_path = bin_path.to_owned();
         let base_name = bin_path.bin_base_name();
         let bindgen_data = BindgenData::load(&bin_path)?;
@@ -63,6 +88,105 @@ impl SourceBinarySpec {
     }
 }
 
+/// Loads each of the given --bin arguments, in the same order.
+///
+/// Loading a binary means reading and parsing the whole file, which for a run over many platforms
+/// dominates everything else, so they're all loaded in parallel.
+fn load_source_binaries(bin_args: &[&str]) -> Result<Vec<SourceBinarySpec>, BindgenError> {
+    bin_args
+        .par_iter()
+        .map(|arg| SourceBinarySpec::from_bin_arg(arg))
+        .collect()
+}
+
+/// How the generated C# source file is encoded.
+///
+/// The source is always UTF-8, as any non-ASCII in string literals is escaped, but some tools only
+/// recognise UTF-8 files that start with a byte order mark.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum OutputEncoding {
+    Utf8,
+    Utf8Bom,
+}
+
+impl OutputEncoding {
+    const UTF8_BOM: &'static [u8] = b"\xEF\xBB\xBF";
+
+    fn from_args(matches: &clap::ArgMatches) -> Self {
+        match matches.value_of("output-encoding") {
+            Some("utf8-bom") => OutputEncoding::Utf8Bom,
+            _ => OutputEncoding::Utf8,
+        }
+    }
+}
+
+/// Settings of the generated project, rather than of the bindings themselves.
+struct ProjSettings {
+    /// The C# language version, left up to the SDK's default if unset
+    lang_version: Option<String>,
+
+    /// Empty to pick the oldest target frameworks which have everything the bindings use
+    target_frameworks: Vec<String>,
+}
+
+impl ProjSettings {
+    fn from_args(matches: &clap::ArgMatches) -> Self {
+        Self {
+            lang_version: matches.value_of("lang-version").map(str::to_string),
+            target_frameworks: matches
+                .values_of("target-framework")
+                .into_iter()
+                .flatten()
+                .map(str::to_string)
+                .collect(),
+        }
+    }
+}
+
+/// The names of the generated files, where given on the command line rather than derived from
+/// the library's name.
+struct OutputFilenames {
+    bindings: Option<String>,
+    project: Option<String>,
+}
+
+impl OutputFilenames {
+    fn from_args(matches: &clap::ArgMatches) -> Result<Self, &'static str> {
+        let validate = |arg, extension: &str, error| match matches.value_of(arg) {
+            None => Ok(None),
+            Some(name) => {
+                let path = Path::new(name);
+                let is_bare_filename = path.file_name() == Some(name.as_ref());
+                if is_bare_filename && path.extension() == Some(extension.as_ref()) {
+                    Ok(Some(name.to_string()))
+                } else {
+                    Err(error)
+                }
+            }
+        };
+
+        Ok(Self {
+            bindings: validate(
+                "bindings-filename",
+                "cs",
+                "The --bindings-filename must be a file name, without a directory, ending in .cs",
+            )?,
+            project: validate(
+                "project-filename",
+                "csproj",
+                "The --project-filename must be a file name, without a directory, ending in .csproj",
+            )?,
+        })
+    }
+
+    fn bindings(&self, base_name: &str) -> String {
+        self.bindings.clone().unwrap_or_else(|| format!("{}Bindings.cs", base_name.to_camel_case()))
+    }
+
+    fn project(&self, base_name: &str) -> String {
+        self.project.clone().unwrap_or_else(|| format!("{}Bindings.csproj", base_name.to_camel_case()))
+    }
+}
 
 /// Takes any number of source binary specs, and generates a bindings project.
 /// All binaries given must contain the same binding metadata, and target different platforms.
@@ -74,24 +198,47 @@ impl SourceBinarySpec {
 ///
 /// source_output_dir:
 ///     The root directory to write the source code of the generated project to.
+///
+/// codegen_options:
+///     Options controlling the shape of the generated C# source.
+///
+/// proj_settings:
+///     The C# language version and target frameworks to set in the generated project.
+///
+/// output_encoding:
+///     Whether the bindings source file starts with a UTF-8 byte order mark.
+///
+/// csharp_only:
+///     Only write the bindings source file, for including in an existing project, rather than a
+///     complete project.
+///
+/// output_filenames:
+///     Overrides for the names of the bindings source and project files.
 fn generate_bindings(
     input_binaries: Vec<SourceBinarySpec>,
-    source_output_dir: &Path
-) -> Result<(), &'static str> {
+    source_output_dir: &Path,
+    codegen_options: &codegen::CodegenOptions,
+    proj_settings: &ProjSettings,
+    output_encoding: OutputEncoding,
+    csharp_only: bool,
+    output_filenames: &OutputFilenames,
+) -> Result<(), BindgenError> {
     let base_name;
     // Basic validation of the given source binaries.
     match input_binaries.first() {
-        None => return Err("Must have at least one binary to generate bindings for"),
+        None => return Err("Must have at least one binary to generate bindings for".into()),
         Some(f) => {
             base_name = f.base_name.clone();
 
-            if input_binaries.iter().any(|b| b.base_name != base_name) {
-                return Err("The given source binaries have different base names")
-            }
-
-            if input_binaries.iter()
-                .any(|b| b.bindgen_data.descriptors != f.bindgen_data.descriptors) {
-                return Err("The given source binaries expose different descriptors")
+            if let Some(other) = input_binaries.iter()
+                .find(|b| b.bindgen_data.descriptors != f.bindgen_data.descriptors) {
+                eprintln!(
+                    "{} differs from {}:",
+                    other.bin_path.display(),
+                    f.bin_path.display(),
+                );
+                print_descriptor_diff(&f.bindgen_data, &other.bindgen_data, &mut std::io::stderr());
+                return Err("The given source binaries expose different descriptors".into())
             }
         }
     }
@@ -99,7 +246,7 @@ fn generate_bindings(
     // Ensure the output directory exists + is an empty directory
     if source_output_dir.exists() {
         if !source_output_dir.is_dir() {
-            return Err("The given source-output-dir is not a directory")
+            return Err("The given source-output-dir is not a directory".into())
         }
     } else {
         std::fs::create_dir_all(source_output_dir)
@@ -111,10 +258,72 @@ fn generate_bindings(
         .map_err(|_| "Failed to open the source output directory")?
         .any(|_| true)
     {
-        return Err("The given source-output-dir is not empty")
+        return Err("The given source-output-dir is not empty".into())
     }
 
-    // Generate + write the project file
+    if !csharp_only {
+        write_proj_file(
+            &input_binaries,
+            source_output_dir,
+            &output_filenames.project(&base_name),
+            proj_settings,
+            &proj_features(codegen_options, &input_binaries[0].bindgen_data),
+            !codegen_options.static_link,
+        )?;
+    }
+
+    // Generate binding source ast from one set of extracted data
+    // Write out a bindings source file from that ast
+    let bindings_filename = output_filenames.bindings(&base_name);
+    let bindings_filepath = source_output_dir.join(bindings_filename);
+    let mut bindings_file = std::fs::File::create(&bindings_filepath)
+        .map_err(|_| "Failed to open bindings file for writing")?;
+    let ast_root = codegen::form_ast_from_data(
+        &input_binaries.first().unwrap().bindgen_data,
+        codegen_options,
+    )?;
+    if output_encoding == OutputEncoding::Utf8Bom {
+        bindings_file.write_all(OutputEncoding::UTF8_BOM)
+            .map_err(|_| "Failed to write bindings C# ast to file")?;
+    }
+    ast_root.render(&mut bindings_file)
+        .map_err(|_| "Failed to write bindings C# ast to file")?;
+
+    Ok(())
+}
+
+/// Writes the items which were added, removed, or changed between two sets of extracted data.
+fn print_descriptor_diff(old: &BindgenData, new: &BindgenData, out: &mut dyn Write) {
+    let changes = diff::diff_descriptors(&old.descriptors, &new.descriptors);
+
+    // Failing to write a diff is never worth failing over, so the results are ignored.
+    if changes.is_empty() {
+        let _ = writeln!(out, "No differences");
+    }
+    for change in changes {
+        let _ = writeln!(out, "{}", change);
+    }
+}
+
+/// Writes the descriptors extracted from a binary as a pretty-printed JSON array.
+///
+/// The JSON mirrors the descriptor types in dotnet-bindgen-core, so changes along with
+/// `BINDGEN_ABI_VERSION`.
+fn print_descriptors_json(data: &BindgenData, out: &mut dyn Write) -> Result<(), &'static str> {
+    serde_json::to_writer_pretty(&mut *out, &data.descriptors)
+        .map_err(|_| "Failed to write the descriptors as JSON")?;
+    writeln!(out).map_err(|_| "Failed to write the descriptors as JSON")
+}
+
+/// Generates + writes the project file, which bundles the native binaries alongside the bindings.
+fn write_proj_file(
+    input_binaries: &[SourceBinarySpec],
+    source_output_dir: &Path,
+    proj_filename: &str,
+    proj_settings: &ProjSettings,
+    features: &[csproj::Feature],
+    bundle_native_binaries: bool,
+) -> Result<(), &'static str> {
     let binary_set = csproj::NativeBinarySet::new(
         input_binaries.iter().map(|b| csproj::NativeBinary::new(
             b.platform,
@@ -122,38 +331,229 @@ fn generate_bindings(
         ))
     );
 
-    let proj = csproj::ProjFile {
-        target_framework: "netstandard2.0".to_owned(),
-        allow_unsafe: true,
-        binary_set
-    };
+    let mut proj = csproj::ProjFileBuilder::new(binary_set)
+        .allow_unsafe(true)
+        .bundle_native_binaries(bundle_native_binaries);
+
+    for &feature in features {
+        proj = proj.feature(feature);
+    }
+
+    if let Some((first, rest)) = proj_settings.target_frameworks.split_first() {
+        proj = proj.target_framework(first);
+        for target_framework in rest {
+            proj = proj.additional_target_framework(target_framework);
+        }
+    }
+
+    if let Some(lang_version) = &proj_settings.lang_version {
+        proj = proj.lang_version(lang_version);
+    }
+
+    let proj = proj.build()?;
 
-    let proj_filename = format!("{}Bindings.csproj", base_name.to_camel_case());
     let proj_filepath = source_output_dir.join(proj_filename);
     let proj_content = proj.render_proj_xml();
 
     std::fs::write(proj_filepath, proj_content)
-        .map_err(|_| "Failed to write csproj file")?;
+        .map_err(|_| "Failed to write csproj file")
+}
 
-    // Generate binding source ast from one set of extracted data
-    // Write out a bindings source file from that ast
-    let bindings_filename = format!("{}Bindings.cs", base_name.to_camel_case());
-    let bindings_filepath = source_output_dir.join(bindings_filename);
-    let mut bindings_file = std::fs::File::create(&bindings_filepath).expect(&format!(
-        "Can't open {} for writing",
-        bindings_filepath.to_str().unwrap()
-    ));
-    let ast_root = codegen::form_ast_from_data(&input_binaries.first().unwrap().bindgen_data);
-    ast_root.render(&mut bindings_file)
-        .map_err(|_| "Failed to write bindings C# ast to file")?;
+/// The features used by the bindings generated with the given options, which the project's target
+/// frameworks must have.
+fn proj_features(options: &codegen::CodegenOptions, data: &BindgenData) -> Vec<csproj::Feature> {
+    let mut features = Vec::new();
+    if options.custom_marshallers {
+        features.push(csproj::Feature::LibraryImport);
+    }
+    if options.record_structs {
+        features.push(csproj::Feature::RecordStructs);
+    }
+    if codegen::uses_int128(&data.descriptors) {
+        features.push(csproj::Feature::Int128);
+    }
+    if !options.platform_dll_names.is_empty() {
+        features.push(csproj::Feature::DllImportResolver);
+    }
+    if options.skip_locals_init {
+        features.push(csproj::Feature::SkipLocalsInit);
+    }
+    if codegen::uses_static_accessors(&data.descriptors) {
+        features.push(csproj::Feature::StaticAccessors);
+    }
+    if codegen::uses_suppress_gc_transition(&data.descriptors) {
+        features.push(csproj::Feature::SuppressGCTransition);
+    }
+    features
+}
 
-    Ok(())
+/// Builds the lines of the generated file's header comment from the command line arguments.
+///
+/// An explicit --header-file or --header-text replaces the default header entirely.
+fn file_header_from_args(matches: &clap::ArgMatches) -> Result<Vec<String>, &'static str> {
+    let text = if let Some(path) = matches.value_of("header-file") {
+        std::fs::read_to_string(path).map_err(|_| "Failed to read the given header file")?
+    } else if let Some(text) = matches.value_of("header-text") {
+        text.replace("\\n", "\n")
+    } else {
+        return Ok(codegen::CodegenOptions::default().file_header);
+    };
+
+    if text.contains("*/") {
+        return Err("The file header must not contain a \"*/\" sequence");
+    }
+
+    Ok(text.lines().map(|l| l.trim_end().to_string()).collect())
 }
 
-fn main() -> Result<(), &'static str> {
-    let matches = App::new("dotnet-bindgen-cli tool")
+/// Parses the comma separated --disable-warnings list, falling back to the default set.
+fn disabled_warnings_from_args(matches: &clap::ArgMatches) -> Result<Vec<String>, &'static str> {
+    let ids = match matches.value_of("disable-warnings") {
+        Some(ids) => ids,
+        None => return Ok(codegen::CodegenOptions::default().disabled_warnings),
+    };
+
+    let ids: Vec<String> = ids
+        .split(',')
+        .map(|id| id.trim().to_string())
+        .filter(|id| !id.is_empty())
+        .collect();
+
+    if ids.iter().any(|id| !id.chars().all(|c| c.is_ascii_alphanumeric())) {
+        return Err("Warning IDs given to --disable-warnings must be alphanumeric");
+    }
+
+    Ok(ids)
+}
+
+/// The library name to load on each OS whose binary is named differently from the first.
+///
+/// The generated bindings can only tell OSes apart at runtime, so binaries for the same OS, eg
+/// linux-x64 and linux-musl-x64, must share a name.
+fn platform_dll_names(binaries: &[SourceBinarySpec]) -> Result<Vec<(String, String)>, &'static str> {
+    let mut names: Vec<(String, String)> = Vec::new();
+    for binary in binaries {
+        let os = binary.platform.os_platform_name();
+        match names.iter().find(|(other_os, _)| other_os == os) {
+            Some((_, name)) if *name != binary.base_name => {
+                return Err("The binaries given for the same OS have different base names");
+            }
+            Some(_) => (),
+            None => names.push((os.to_string(), binary.base_name.clone())),
+        }
+    }
+
+    // The first binary's name goes in the DllImport attributes, so needs no resolving.
+    names.retain(|(_, name)| *name != binaries[0].base_name);
+
+    Ok(names)
+}
+
+/// Validates the --dll-name-override library name, if one was given.
+///
+/// Library names commonly contain dashes and dots, so they're allowed alongside the usual
+/// identifier characters.
+fn dll_name_override_from_args(matches: &clap::ArgMatches) -> Result<Option<String>, &'static str> {
+    let name = match matches.value_of("dll-name-override") {
+        Some(name) => name,
+        None => return Ok(None),
+    };
+
+    if name.is_empty() {
+        return Err("The --dll-name-override library name must not be empty");
+    }
+
+    let is_valid_char = |c: char| c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.';
+    if !name.chars().all(is_valid_char) {
+        return Err("The --dll-name-override library name may only contain letters, digits, '_', '-' and '.'");
+    }
+
+    Ok(Some(name.to_string()))
+}
+
+/// Whether --link-mode static was given, in which case there's no library file to name.
+fn static_link_from_args(matches: &clap::ArgMatches) -> Result<bool, &'static str> {
+    let static_link = matches.value_of("link-mode") == Some("static");
+    if static_link && matches.is_present("dll-name-override") {
+        return Err("--dll-name-override can't be given with --link-mode static, which imports from the executable itself");
+    }
+
+    Ok(static_link)
+}
+
+fn type_visibility_from_args(matches: &clap::ArgMatches) -> ast::Visibility {
+    match matches.value_of("visibility") {
+        Some("internal") => ast::Visibility::Internal,
+        _ => ast::Visibility::Public,
+    }
+}
+
+/// Validates the --namespace-prefix, if one was given, as a dot separated list of identifiers.
+fn namespace_prefix_from_args(matches: &clap::ArgMatches) -> Result<Option<String>, &'static str> {
+    let prefix = match matches.value_of("namespace-prefix") {
+        Some(prefix) => prefix,
+        None => return Ok(None),
+    };
+
+    let is_identifier = |part: &str| {
+        let mut chars = part.chars();
+        match chars.next() {
+            Some(c) if c.is_ascii_alphabetic() || c == '_' => {
+                chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+            }
+            _ => false,
+        }
+    };
+
+    if !prefix.split('.').all(is_identifier) {
+        return Err("The --namespace-prefix must be a dot separated list of identifiers");
+    }
+
+    Ok(Some(prefix.to_string()))
+}
+
+/// Parses each --map-type into a mapping from a Rust type name to a C# type.
+fn type_mapper_from_args(matches: &clap::ArgMatches) -> Result<codegen::TypeMapper, &'static str> {
+    let mut mapper = codegen::TypeMapper::default();
+
+    for mapping in matches.values_of("map-type").into_iter().flatten() {
+        let (rust_name, cs_path) = match mapping.split_once('=') {
+            Some((rust_name, cs_path)) if !rust_name.is_empty() && !cs_path.is_empty() => (rust_name, cs_path),
+            _ => return Err("Each --map-type must be of the form RustName=CSharpType"),
+        };
+
+        mapper.map(rust_name.trim(), cs_path.trim());
+    }
+
+    Ok(mapper)
+}
+
+/// Whether the given C# LangVersion has function pointers, which arrived in C# 9.
+fn lang_version_has_function_pointers(lang_version: &str) -> bool {
+    match lang_version.to_ascii_lowercase().as_str() {
+        "latest" | "latestmajor" | "preview" => true,
+        version => matches!(
+            version.split('.').next().map(str::parse::<u32>),
+            Some(Ok(major)) if major >= 9
+        ),
+    }
+}
+
+/// Whether function pointers are bound as `delegate* unmanaged`, as asked for explicitly, or implied
+/// by the project's C# LangVersion.
+fn function_pointers_from_args(matches: &clap::ArgMatches) -> bool {
+    matches.is_present("function-pointers") || matches!(
+        matches.value_of("lang-version"),
+        Some(version) if lang_version_has_function_pointers(version)
+    )
+}
+
+/// The command line arguments, and the subcommands taking their own.
+fn cli() -> App<'static, 'static> {
+    App::new("dotnet-bindgen-cli tool")
         .author("Joe Roberts")
         .about("Extract binding data from annotated binaries + generate dotnet bindings")
+        .setting(AppSettings::SubcommandsNegateReqs)
         .arg(Arg::with_name("source-output-dir")
             .required(true)
             .long("source-output-dir")
@@ -162,20 +562,297 @@ fn main() -> Result<(), &'static str> {
     NB: This directory must be empty!"#)
             .takes_value(true))
         .arg(Arg::with_name("bin")
-            .required(true)
+            .required_unless("descriptors")
             .long("bin")
             .value_name("Bin or Plat:Bin")
-            .help("The path to the binary to process")
+            .help(r#"The path to the binary to process.
+    May be given more than once, for builds of the same library for different platforms."#)
+            .takes_value(true)
+            .multiple(true)
+            .number_of_values(1))
+        .arg(Arg::with_name("header-file")
+            .long("header-file")
+            .value_name("File")
+            .help("A file whose contents replace the header comment of the generated source")
+            .takes_value(true)
+            .conflicts_with("header-text"))
+        .arg(Arg::with_name("header-text")
+            .long("header-text")
+            .value_name("Text")
+            .help(r#"Text which replaces the header comment of the generated source.
+    A literal "\n" starts a new line."#)
+            .takes_value(true))
+        .arg(Arg::with_name("struct-equality")
+            .long("struct-equality")
+            .help("Generate IEquatable<T>, GetHashCode, and ==/!= operators for bound structs"))
+        .arg(Arg::with_name("readonly-structs")
+            .long("readonly-structs")
+            .help("Generate bound structs as readonly structs, constructed with a fieldwise constructor"))
+        .arg(Arg::with_name("record-structs")
+            .long("record-structs")
+            .help("Generate bound structs of only integers, floats, and enums as readonly record structs. Needs C# 10"))
+        .arg(Arg::with_name("slice-views")
+            .long("slice-views")
+            .help("Marshal slices through a stack-only ref struct, so pinned pointers can't escape"))
+        .arg(Arg::with_name("async-wrappers")
+            .long("async-wrappers")
+            .help("Generate Task returning ...Async overloads for functions marked #[dotnet_bindgen(blocking)]"))
+        .arg(Arg::with_name("stream-overloads")
+            .long("stream-overloads")
+            .help("Generate ...FromStream methods taking a System.IO.Stream in place of each &[u8] argument"))
+        .arg(Arg::with_name("disable-warnings")
+            .long("disable-warnings")
+            .value_name("IDs")
+            .help(r#"A comma separated list of warning IDs to disable throughout the generated source.
+    Replaces the default set of analyzer rules the generated code trips. An empty list disables none."#)
+            .takes_value(true))
+        .arg(Arg::with_name("dll-name-override")
+            .long("dll-name-override")
+            .value_name("Name")
+            .help("The library name to use in DllImport attributes, for when the binary is renamed after building")
+            .takes_value(true))
+        .arg(Arg::with_name("link-mode")
+            .long("link-mode")
+            .value_name("Mode")
+            .help(r#"How the native library is linked into the .NET app. A static library, eg for NativeAOT or iOS, is imported from the
+    executable as __Internal, and isn't copied alongside the assembly."#)
+            .possible_values(&["dynamic", "static"])
+            .default_value("dynamic")
+            .takes_value(true))
+        .arg(Arg::with_name("namespace-prefix")
+            .long("namespace-prefix")
+            .value_name("Namespace")
+            .help("A namespace, eg Acme.Native, to prepend to the generated namespace")
+            .takes_value(true))
+        .arg(Arg::with_name("lang-version")
+            .long("lang-version")
+            .value_name("Version")
+            .help("The C# LangVersion to set in the generated project. From 9, function pointer arguments are bound as delegate* unmanaged")
+            .takes_value(true)
+            .conflicts_with("csharp-only"))
+        .arg(Arg::with_name("function-pointers")
+            .long("function-pointers")
+            .help(r#"Bind function pointer arguments and struct fields as delegate* unmanaged, rather than as an IntPtr.
+    Needs C# 9 or later. Implied by --lang-version 9 or later, so is only needed with --csharp-only."#))
+        .arg(Arg::with_name("target-framework")
+            .long("target-framework")
+            .value_name("Framework")
+            .help(r#"A target framework of the generated project, eg net6.0. May be given more than once.
+    Defaults to the oldest frameworks with everything the bindings use, eg net7.0 for --custom-marshallers."#)
+            .takes_value(true)
+            .multiple(true)
+            .number_of_values(1)
+            .conflicts_with("csharp-only"))
+        .arg(Arg::with_name("visibility")
+            .long("visibility")
+            .value_name("Visibility")
+            .help("The access modifier of the generated types, eg internal to keep them out of the assembly's public API")
+            .possible_values(&["public", "internal"])
+            .default_value("public")
+            .takes_value(true))
+        .arg(Arg::with_name("output-encoding")
+            .long("output-encoding")
+            .value_name("Encoding")
+            .help("The encoding of the generated C# source, which is UTF-8 with or without a byte order mark")
+            .possible_values(&["utf8", "utf8-bom"])
+            .default_value("utf8")
+            .takes_value(true))
+        .arg(Arg::with_name("inline-temporaries")
+            .long("inline-temporaries")
+            .value_name("Level")
+            .help(r#"Fold temporaries the wrapper methods use only once into the statement using them. Level 1 folds values without side
+    effects, and level 2 also folds method calls and allocations"#)
+            .possible_values(&["0", "1", "2"])
+            .default_value("0")
             .takes_value(true))
-        .get_matches();
+        .arg(Arg::with_name("pretty")
+            .long("pretty")
+            .help("Indent the generated source, and separate its members with blank lines. This is the default")
+            .overrides_with("minify"))
+        .arg(Arg::with_name("minify")
+            .long("minify")
+            .help("Generate source without indentation or blank lines between members, eg for embedding")
+            .overrides_with("pretty"))
+        .arg(Arg::with_name("descriptors")
+            .long("descriptors")
+            .value_name("JSON")
+            .help(r#"Generate the bindings from descriptors written by dump-descriptors, rather than from a binary.
+    The library is named after the file, eg test_lib.json for libtest_lib.so. Needs --csharp-only, as there's no binary to bundle."#)
+            .takes_value(true)
+            .conflicts_with("bin")
+            .requires("csharp-only"))
+        .arg(Arg::with_name("bindings-filename")
+            .long("bindings-filename")
+            .value_name("File.cs")
+            .help("The name of the generated bindings source file, rather than one derived from the library's name")
+            .takes_value(true))
+        .arg(Arg::with_name("project-filename")
+            .long("project-filename")
+            .value_name("File.csproj")
+            .help("The name of the generated project file, rather than one derived from the library's name")
+            .takes_value(true)
+            .conflicts_with("csharp-only"))
+        .arg(Arg::with_name("csharp-only")
+            .long("csharp-only")
+            .alias("no-csproj")
+            .help(r#"Only generate the bindings source file, for including in an existing project.
+    The project must allow unsafe blocks, and reference System.Memory if targeting netstandard2.0."#))
+        .arg(Arg::with_name("emit-abi-summary")
+            .long("emit-abi-summary")
+            .value_name("File")
+            .help("Also write a summary of each thunk's symbol and the AbiType of its arguments, for debugging the bindings")
+            .takes_value(true))
+        .arg(Arg::with_name("warn-as-error")
+            .long("warn-as-error")
+            .help("Exit with an error if any warnings were raised while generating the bindings, eg in CI"))
+        .arg(Arg::with_name("emit-interface")
+            .long("emit-interface")
+            .help("Generate an interface declaring the bound free functions, and a class implementing it"))
+        .arg(Arg::with_name("owned-buffers")
+            .long("owned-buffers")
+            .help("Return Rust allocated slices in a disposable class exposing a Span, rather than copying them"))
+        .arg(Arg::with_name("no-copy-slices")
+            .long("no-copy-slices")
+            .help(r#"Return slices as a disposable NativeSlice<T>, whose AsSpan() views the native memory without a copy.
+    Spans over an owned slice dangle once it is disposed. Takes precedence over --owned-buffers."#))
+        .arg(Arg::with_name("debugger-step-through")
+            .long("debugger-step-through")
+            .help("Mark the generated wrapper methods with [DebuggerStepThrough], so the debugger steps over them"))
+        .arg(Arg::with_name("debug-only-validation")
+            .long("debug-only-validation")
+            .help(r#"Move the wrapper methods' argument null checks into [Conditional("DEBUG")] validators.
+    Release builds of the calling code skip the checks."#))
+        .arg(Arg::with_name("skip-locals-init")
+            .long("skip-locals-init")
+            .help(r#"Mark the generated wrapper methods and module with [SkipLocalsInit], so the JIT doesn't zero their temporaries.
+    Needs net5.0 or later, so the generated project targets it, and a project which allows unsafe blocks."#))
+        .arg(Arg::with_name("trace-hooks")
+            .long("trace-hooks")
+            .help("Call partial OnNativeCallEnter/OnNativeCallExit hooks around each native call, eg to log them"))
+        .arg(Arg::with_name("custom-marshallers")
+            .long("custom-marshallers")
+            .help(r#"Declare the P/Invokes with [LibraryImport], marshalling slices and strings through generated custom marshallers.
+    Needs net7.0 or later, which the generated project then targets."#))
+        .arg(Arg::with_name("map-type")
+            .long("map-type")
+            .value_name("RustName=CSharpType")
+            .help(r#"Bind a Rust struct or enum as an existing C# type with the same layout, eg Point=Acme.Geometry.Point.
+    The C# type isn't generated. May be given more than once."#)
+            .takes_value(true)
+            .multiple(true)
+            .number_of_values(1))
+        .subcommand(SubCommand::with_name("diff")
+            .about("Print the functions, structs, enums, and statics that differ between two builds of a library")
+            .arg(Arg::with_name("old")
+                .required(true)
+                .value_name("Old Bin")
+                .help("The path to the old build of the binary"))
+            .arg(Arg::with_name("new")
+                .required(true)
+                .value_name("New Bin")
+                .help("The path to the new build of the binary")))
+        .subcommand(SubCommand::with_name("dump-descriptors")
+            .about("Print the descriptors extracted from a binary as JSON, eg for generating bindings for other languages")
+            .arg(Arg::with_name("bin")
+                .required(true)
+                .value_name("Bin")
+                .help("The path to the binary to process")))
+}
+
+fn main() -> Result<(), BindgenError> {
+    let matches = cli().get_matches();
+
+    if let Some(diff_matches) = matches.subcommand_matches("diff") {
+        let load = |arg| {
+            let path = Path::new(diff_matches.value_of(arg).unwrap())
+                .canonicalize()
+                .map_err(|_| "Failed to canonicalize a binary path - do they all exist?")?;
+            BindgenData::load(&path)
+        };
+
+        print_descriptor_diff(&load("old")?, &load("new")?, &mut std::io::stdout());
+        return Ok(());
+    }
+
+    if let Some(dump_matches) = matches.subcommand_matches("dump-descriptors") {
+        let path = Path::new(dump_matches.value_of("bin").unwrap())
+            .canonicalize()
+            .map_err(|_| "Failed to canonicalize the binary path - does it exist?")?;
+
+        print_descriptors_json(&BindgenData::load(&path)?, &mut std::io::stdout())?;
+        return Ok(());
+    }
 
-    let source_binaries = vec![
-        SourceBinarySpec::from_bin_arg(matches.value_of("bin").unwrap())?,
-    ];
+    let source_binaries = match matches.value_of("descriptors") {
+        Some(json) => vec![SourceBinarySpec::from_descriptors_arg(json)?],
+        None => {
+            let bin_args: Vec<&str> = matches.values_of("bin").unwrap().collect();
+            load_source_binaries(&bin_args)?
+        }
+    };
+
+    // Rendered now, as the binaries are consumed by generating the bindings, but only written once
+    // they have been, so that a failed run doesn't leave a summary of bindings that don't exist.
+    let abi_summary = match (matches.value_of("emit-abi-summary"), source_binaries.first()) {
+        (Some(path), Some(binary)) => {
+            let mut summary = Vec::new();
+            abi_summary::write_abi_summary(&binary.bindgen_data, &mut summary)
+                .map_err(|_| "Failed to write the ABI summary")?;
+            Some((path, summary))
+        }
+        _ => None,
+    };
 
     let source_output_dir = Path::new(matches.value_of("source-output-dir").unwrap());
 
-    generate_bindings(source_binaries, &source_output_dir)?;
+    let static_link = static_link_from_args(&matches)?;
+    let codegen_options = codegen::CodegenOptions {
+        file_header: file_header_from_args(&matches)?,
+        struct_equality: matches.is_present("struct-equality"),
+        readonly_structs: matches.is_present("readonly-structs"),
+        record_structs: matches.is_present("record-structs"),
+        slice_views: matches.is_present("slice-views"),
+        async_wrappers: matches.is_present("async-wrappers"),
+        stream_overloads: matches.is_present("stream-overloads"),
+        disabled_warnings: disabled_warnings_from_args(&matches)?,
+        dll_name_override: dll_name_override_from_args(&matches)?,
+        static_link,
+        // A statically linked library is part of the executable, so there's no file to resolve.
+        platform_dll_names: if static_link { Vec::new() } else { platform_dll_names(&source_binaries)? },
+        namespace_prefix: namespace_prefix_from_args(&matches)?,
+        emit_interface: matches.is_present("emit-interface"),
+        owned_buffers: matches.is_present("owned-buffers"),
+        no_copy_slices: matches.is_present("no-copy-slices"),
+        debugger_step_through: matches.is_present("debugger-step-through"),
+        debug_only_validation: matches.is_present("debug-only-validation"),
+        skip_locals_init: matches.is_present("skip-locals-init"),
+        trace_hooks: matches.is_present("trace-hooks"),
+        custom_marshallers: matches.is_present("custom-marshallers"),
+        type_mapper: type_mapper_from_args(&matches)?,
+        type_visibility: type_visibility_from_args(&matches),
+        minify: matches.is_present("minify"),
+        inline_temporaries: matches.value_of("inline-temporaries").and_then(|level| level.parse().ok()).unwrap_or(0),
+        function_pointers: function_pointers_from_args(&matches),
+    };
+
+    generate_bindings(
+        source_binaries,
+        source_output_dir,
+        &codegen_options,
+        &ProjSettings::from_args(&matches),
+        OutputEncoding::from_args(&matches),
+        matches.is_present("csharp-only"),
+        &OutputFilenames::from_args(&matches)?,
+    )?;
+
+    if let Some((path, summary)) = abi_summary {
+        std::fs::write(path, summary).map_err(|_| "Failed to write the ABI summary")?;
+    }
+
+    // Every warning has been printed by now, so they can all be seen before failing.
+    if matches.is_present("warn-as-error") && diagnostics::warning_count() > 0 {
+        return Err(BindgenError::WarningsAsErrors { count: diagnostics::warning_count() });
+    }
 
     Ok(())
 }
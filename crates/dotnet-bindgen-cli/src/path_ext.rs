@@ -9,11 +9,13 @@ impl BinBaseName for Path {
         let ext = self.extension().and_then(|e| e.to_str());
         let stem = self
             .file_stem()
-            .expect("Expect a native binary path to have a filename".into())
+            .expect("Expect a native binary path to have a filename")
             .to_str()
-            .expect("Expect a native binary filename to be valid unicode".into());
+            .expect("Expect a native binary filename to be valid unicode");
 
-        if stem.starts_with("lib") && ext == Some("so") {
+        let has_lib_prefix = matches!(ext, Some("so") | Some("a") | Some("rlib"));
+
+        if stem.starts_with("lib") && has_lib_prefix {
             stem.chars().skip(3).collect::<String>()
         } else {
             stem.into()
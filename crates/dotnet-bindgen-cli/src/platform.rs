@@ -11,7 +11,7 @@ pub enum NativePlatform {
 
 impl NativePlatform {
     /// The string representing this RID that dotnet understands
-    pub fn to_dotnet_rid_string(&self) -> &'static str {
+    pub fn to_dotnet_rid_string(self) -> &'static str {
         match self {
             NativePlatform::WinX64 => "win-x64",
             NativePlatform::LinuxX64 => "linux-x64",
@@ -20,6 +20,15 @@ impl NativePlatform {
         }
     }
 
+    /// The name of the `OSPlatform` property for the OS this RID runs on
+    pub fn os_platform_name(self) -> &'static str {
+        match self {
+            NativePlatform::WinX64 => "Windows",
+            NativePlatform::LinuxX64 | NativePlatform::LinuxMuslX64 => "Linux",
+            NativePlatform::OsxX64 => "OSX",
+        }
+    }
+
     pub fn host_platform() -> Self {
         // TODO
         NativePlatform::LinuxX64
@@ -0,0 +1,64 @@
+/*
+ * This is a generated file, do not modify by hand.
+ * Generated by dotnet-bindgen-cli v0.1.0
+ */
+
+using System;
+using System.Runtime.InteropServices;
+
+#pragma warning disable CA1051, CA1060, CA1707, CA1815, CA5392
+
+namespace GoldenBindings
+{
+    [StructLayout(LayoutKind.Sequential)]
+    public struct SliceAbi
+    {
+        public IntPtr Ptr;
+        public UInt64 Len;
+    }
+
+    public static class TopLevelMethods
+    {
+        public const UInt64 BindingVersion = 0x0B7D2ECB7EDCFC26UL;
+
+        [DllImport("golden", CallingConvention = CallingConvention.Cdecl)]
+        private static extern void __bindgen_thunk_copy_buffer(SliceAbi src, SliceAbi dst, SliceAbi scratch);
+
+        public static void CopyBuffer(Byte[] src, Byte[] dst, Byte[] scratch)
+        {
+            if (src is null) throw new ArgumentNullException(nameof(src));
+            if (dst is null) throw new ArgumentNullException(nameof(dst));
+            if (scratch is null) throw new ArgumentNullException(nameof(scratch));
+            SliceAbi _gen0;
+            (_gen0).Len = (UInt64)((src).Length);
+            unsafe
+            {
+                fixed (Byte* _gen1 = src)
+                {
+                    (_gen0).Ptr = (IntPtr)(_gen1);
+                    SliceAbi _gen2;
+                    (_gen2).Len = (UInt64)((dst).Length);
+                    unsafe
+                    {
+                        fixed (Byte* _gen3 = dst)
+                        {
+                            (_gen2).Ptr = (IntPtr)(_gen3);
+                            SliceAbi _gen4;
+                            (_gen4).Len = (UInt64)((scratch).Length);
+                            unsafe
+                            {
+                                fixed (Byte* _gen5 = scratch)
+                                {
+                                    (_gen4).Ptr = (IntPtr)(_gen5);
+                                    __bindgen_thunk_copy_buffer(_gen0, _gen2, _gen4);
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#pragma warning restore CA1051, CA1060, CA1707, CA1815, CA5392
@@ -0,0 +1,124 @@
+/*
+ * This is a generated file, do not modify by hand.
+ * Generated by dotnet-bindgen-cli v0.1.0
+ */
+
+using System;
+using System.Runtime.InteropServices;
+
+#pragma warning disable CA1051, CA1060, CA1707, CA1815, CA5392
+
+namespace GoldenBindings
+{
+    [StructLayout(LayoutKind.Sequential)]
+    public struct Point
+    {
+        public Int32 X;
+        public Int32 Y;
+
+        static Point()
+        {
+            TopLevelMethods.EnsureValidated();
+        }
+
+        [DllImport("golden", CallingConvention = CallingConvention.Cdecl)]
+        private static extern Double __bindgen_thunk_slow_length(in Point self);
+
+        public Double SlowLength()
+        {
+            return __bindgen_thunk_slow_length(this);
+        }
+    }
+
+    [StructLayout(LayoutKind.Sequential)]
+    public struct SliceAbi
+    {
+        public IntPtr Ptr;
+        public UInt64 Len;
+    }
+
+    public static class TopLevelMethods
+    {
+        public const UInt64 BindingVersion = 0xDB785694CA842266UL;
+
+        static TopLevelMethods()
+        {
+            if (Marshal.SizeOf<Point>() != 8) throw new InvalidOperationException("The size of Point in C# doesn't match its size of 8 bytes in Rust");
+            if (Marshal.OffsetOf<PointAlignmentProbe>("Value").ToInt64() != 4) throw new InvalidOperationException("The alignment of Point in C# doesn't match its alignment of 4 bytes in Rust");
+        }
+
+        [DllImport("golden", CallingConvention = CallingConvention.Cdecl)]
+        private static extern UInt32 __bindgen_thunk_checksum(SliceAbi data);
+
+        public static UInt32 Checksum(Byte[] data)
+        {
+            if (data is null) throw new ArgumentNullException(nameof(data));
+            SliceAbi _gen0;
+            (_gen0).Len = (UInt64)((data).Length);
+            unsafe
+            {
+                fixed (Byte* _gen1 = data)
+                {
+                    (_gen0).Ptr = (IntPtr)(_gen1);
+                    return __bindgen_thunk_checksum(_gen0);
+                }
+            }
+        }
+
+        public static System.Threading.Tasks.Task<UInt32> ChecksumAsync(Byte[] data)
+        {
+            return System.Threading.Tasks.Task.Run(() => Checksum(data));
+        }
+
+        [DllImport("golden", CallingConvention = CallingConvention.Cdecl)]
+        private static extern void __bindgen_thunk_sync_all();
+
+        public static void SyncAll()
+        {
+            __bindgen_thunk_sync_all();
+        }
+
+        public static System.Threading.Tasks.Task SyncAllAsync()
+        {
+            return System.Threading.Tasks.Task.Run(() => SyncAll());
+        }
+
+        [DllImport("golden", CallingConvention = CallingConvention.Cdecl)]
+        private static extern UInt64 __bindgen_thunk_read_into(SliceAbi buffer);
+
+        public static UInt64 ReadInto(Span<Byte> buffer)
+        {
+            SliceAbi _gen0;
+            (_gen0).Len = (UInt64)((buffer).Length);
+            unsafe
+            {
+                fixed (Byte* _gen1 = buffer)
+                {
+                    (_gen0).Ptr = (IntPtr)(_gen1);
+                    return __bindgen_thunk_read_into(_gen0);
+                }
+            }
+        }
+
+        [DllImport("golden", CallingConvention = CallingConvention.Cdecl)]
+        private static extern Int32 __bindgen_thunk_add(Int32 a, Int32 b);
+
+        public static Int32 Add(Int32 a, Int32 b)
+        {
+            return __bindgen_thunk_add(a, b);
+        }
+
+        public static void EnsureValidated()
+        {
+        }
+
+        [StructLayout(LayoutKind.Sequential)]
+        private struct PointAlignmentProbe
+        {
+            public Byte Pad;
+            public Point Value;
+        }
+    }
+}
+
+#pragma warning restore CA1051, CA1060, CA1707, CA1815, CA5392
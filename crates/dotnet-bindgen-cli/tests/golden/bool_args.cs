@@ -0,0 +1,36 @@
+/*
+ * This is a generated file, do not modify by hand.
+ * Generated by dotnet-bindgen-cli v0.1.0
+ */
+
+using System;
+using System.Runtime.InteropServices;
+
+#pragma warning disable CA1051, CA1060, CA1707, CA1815, CA5392
+
+namespace GoldenBindings
+{
+    [StructLayout(LayoutKind.Sequential)]
+    public struct SliceAbi
+    {
+        public IntPtr Ptr;
+        public UInt64 Len;
+    }
+
+    public static class TopLevelMethods
+    {
+        public const UInt64 BindingVersion = 0x2A88166D1F10FB7FUL;
+
+        [DllImport("golden", CallingConvention = CallingConvention.Cdecl)]
+        private static extern Byte __bindgen_thunk_negate(Byte value);
+
+        public static Byte Negate(bool value)
+        {
+            Byte _gen0;
+            _gen0 = ( (value) ? (1) : (0) );
+            return __bindgen_thunk_negate(_gen0);
+        }
+    }
+}
+
+#pragma warning restore CA1051, CA1060, CA1707, CA1815, CA5392
@@ -0,0 +1,56 @@
+/*
+ * This is a generated file, do not modify by hand.
+ * Generated by dotnet-bindgen-cli v0.1.0
+ */
+
+using System;
+using System.Runtime.InteropServices;
+
+#pragma warning disable CA1051, CA1060, CA1707, CA1815, CA5392
+
+namespace GoldenBindings
+{
+    [StructLayout(LayoutKind.Sequential)]
+    public struct SliceAbi
+    {
+        public IntPtr Ptr;
+        public UInt64 Len;
+    }
+
+    public static class TopLevelMethods
+    {
+        public const UInt64 BindingVersion = 0xA4DDE03761BE3164UL;
+
+        [DllImport("golden", CallingConvention = CallingConvention.Cdecl)]
+        private static extern SliceAbi __bindgen_thunk_version();
+
+        public static string Version()
+        {
+            SliceAbi _gen0;
+            _gen0 = __bindgen_thunk_version();
+            unsafe
+            {
+                string _gen1;
+                _gen1 = System.Text.Encoding.UTF8.GetString((Byte*)((_gen0).Ptr), checked((Int32)((_gen0).Len)));
+                return _gen1;
+            }
+        }
+
+        [DllImport("golden", CallingConvention = CallingConvention.Cdecl)]
+        private static extern SliceAbi __bindgen_thunk_primes();
+
+        public static UInt32[] Primes()
+        {
+            SliceAbi _gen0;
+            _gen0 = __bindgen_thunk_primes();
+            unsafe
+            {
+                UInt32[] _gen1;
+                _gen1 = new Span<UInt32>((void*)((_gen0).Ptr), checked((Int32)((_gen0).Len))).ToArray();
+                return _gen1;
+            }
+        }
+    }
+}
+
+#pragma warning restore CA1051, CA1060, CA1707, CA1815, CA5392
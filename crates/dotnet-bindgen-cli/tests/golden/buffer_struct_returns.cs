@@ -0,0 +1,111 @@
+/*
+ * This is a generated file, do not modify by hand.
+ * Generated by dotnet-bindgen-cli v0.1.0
+ */
+
+using System;
+using System.Runtime.InteropServices;
+
+#pragma warning disable CA1051, CA1060, CA1707, CA1815, CA5392
+
+namespace GoldenBindings
+{
+    [StructLayout(LayoutKind.Sequential)]
+    public struct HistogramAbi
+    {
+        public OwnedSliceAbi Buckets;
+        public OwnedSliceAbi Label;
+        public UInt64 Total;
+    }
+
+    [StructLayout(LayoutKind.Sequential)]
+    public struct Histogram
+    {
+        public UInt32[] Buckets;
+        public string Label;
+        public UInt64 Total;
+    }
+
+    [StructLayout(LayoutKind.Sequential)]
+    public struct HeaderAbi
+    {
+        public SliceAbi Name;
+        public UInt32 Magic;
+    }
+
+    [StructLayout(LayoutKind.Sequential)]
+    public struct Header
+    {
+        public string Name;
+        public UInt32 Magic;
+    }
+
+    [StructLayout(LayoutKind.Sequential)]
+    public struct SliceAbi
+    {
+        public IntPtr Ptr;
+        public UInt64 Len;
+    }
+
+    [StructLayout(LayoutKind.Sequential)]
+    public struct OwnedSliceAbi
+    {
+        public IntPtr Ptr;
+        public UInt64 Len;
+        public UInt64 Cap;
+    }
+
+    public static class TopLevelMethods
+    {
+        public const UInt64 BindingVersion = 0xFDD1BF13E5A1CE50UL;
+
+        [DllImport("golden", CallingConvention = CallingConvention.Cdecl)]
+        private static extern HistogramAbi __bindgen_thunk_build_histogram(SliceAbi values);
+
+        public static Histogram BuildHistogram(Byte[] values)
+        {
+            if (values is null) throw new ArgumentNullException(nameof(values));
+            SliceAbi _gen0;
+            (_gen0).Len = (UInt64)((values).Length);
+            unsafe
+            {
+                fixed (Byte* _gen1 = values)
+                {
+                    (_gen0).Ptr = (IntPtr)(_gen1);
+                    HistogramAbi _gen2;
+                    _gen2 = __bindgen_thunk_build_histogram(_gen0);
+                    unsafe
+                    {
+                        Histogram _gen3;
+                        (_gen3).Buckets = new Span<UInt32>((void*)(((_gen2).Buckets).Ptr), checked((Int32)(((_gen2).Buckets).Len))).ToArray();
+                        (_gen3).Label = System.Text.Encoding.UTF8.GetString((Byte*)(((_gen2).Label).Ptr), checked((Int32)(((_gen2).Label).Len)));
+                        (_gen3).Total = (_gen2).Total;
+                        __bindgen_free_build_histogram(_gen2);
+                        return _gen3;
+                    }
+                }
+            }
+        }
+
+        [DllImport("golden", CallingConvention = CallingConvention.Cdecl)]
+        private static extern void __bindgen_free_build_histogram(HistogramAbi value);
+
+        [DllImport("golden", CallingConvention = CallingConvention.Cdecl)]
+        private static extern HeaderAbi __bindgen_thunk_default_header();
+
+        public static Header DefaultHeader()
+        {
+            HeaderAbi _gen0;
+            _gen0 = __bindgen_thunk_default_header();
+            unsafe
+            {
+                Header _gen1;
+                (_gen1).Name = System.Text.Encoding.UTF8.GetString((Byte*)(((_gen0).Name).Ptr), checked((Int32)(((_gen0).Name).Len)));
+                (_gen1).Magic = (_gen0).Magic;
+                return _gen1;
+            }
+        }
+    }
+}
+
+#pragma warning restore CA1051, CA1060, CA1707, CA1815, CA5392
@@ -0,0 +1,166 @@
+/*
+ * This is a generated file, do not modify by hand.
+ * Generated by dotnet-bindgen-cli v0.1.0
+ */
+
+using System;
+using System.Runtime.InteropServices;
+
+#pragma warning disable CA1051, CA1060, CA1707, CA1815, CA5392
+
+namespace GoldenBindings
+{
+    [StructLayout(LayoutKind.Sequential)]
+    public struct SliceAbi
+    {
+        public IntPtr Ptr;
+        public UInt64 Len;
+    }
+
+    internal static class PooledStream
+    {
+        public static Byte[] ReadToEnd(System.IO.Stream stream, ref Int32 len)
+        {
+            Byte[] buf;
+            buf = System.Buffers.ArrayPool<Byte>.Shared.Rent(4096);
+            bool done;
+            done = false;
+            try
+            {
+                Int32 read;
+                read = 1;
+                while (read != 0)
+                {
+                    read = stream.Read(buf, len, (buf).Length - len);
+                    len = len + read;
+                    if (len == (buf).Length)
+                    {
+                        Byte[] grown;
+                        grown = System.Buffers.ArrayPool<Byte>.Shared.Rent(checked((buf).Length * 2));
+                        System.Buffer.BlockCopy(buf, 0, grown, 0, len);
+                        System.Buffers.ArrayPool<Byte>.Shared.Return(buf);
+                        buf = grown;
+                    }
+                }
+                done = true;
+            }
+            finally
+            {
+                if (!(done))
+                {
+                    System.Buffers.ArrayPool<Byte>.Shared.Return(buf);
+                }
+            }
+            return buf;
+        }
+    }
+
+    public static class TopLevelMethods
+    {
+        public const UInt64 BindingVersion = 0x1EE1B55A5A9A7DB2UL;
+
+        [DllImport("golden", CallingConvention = CallingConvention.Cdecl)]
+        private static extern UInt32 __bindgen_thunk_tag(SliceAbi label, SliceAbi data, UInt32 seed);
+
+        public static UInt32 Tag(string label, Byte[] data, UInt32 seed)
+        {
+            ValidateTagArguments(data);
+            return Tag(label, new Span<Byte>(data), seed);
+        }
+
+        [System.Diagnostics.Conditional("DEBUG")]
+        private static void ValidateTagArguments(Byte[] data)
+        {
+            if (data is null) throw new ArgumentNullException(nameof(data));
+        }
+
+        [System.Diagnostics.Conditional("DEBUG")]
+        private static void ValidateTagArguments(System.IO.Stream data)
+        {
+            if (data is null) throw new ArgumentNullException(nameof(data));
+        }
+
+        private static UInt32 Tag(string label, Span<Byte> data, UInt32 seed)
+        {
+            ValidateTagArguments(label);
+            Byte[] _gen1;
+            _gen1 = new System.Text.UTF8Encoding(false, true).GetBytes(label);
+            SliceAbi _gen0;
+            (_gen0).Len = (UInt64)((_gen1).Length);
+            unsafe
+            {
+                fixed (Byte* _gen2 = _gen1)
+                {
+                    (_gen0).Ptr = (IntPtr)(_gen2);
+                    SliceAbi _gen3;
+                    (_gen3).Len = (UInt64)((data).Length);
+                    unsafe
+                    {
+                        fixed (Byte* _gen4 = data)
+                        {
+                            (_gen3).Ptr = (IntPtr)(_gen4);
+                            return __bindgen_thunk_tag(_gen0, _gen3, seed);
+                        }
+                    }
+                }
+            }
+        }
+
+        [System.Diagnostics.Conditional("DEBUG")]
+        private static void ValidateTagArguments(string label)
+        {
+            if (label is null) throw new ArgumentNullException(nameof(label));
+        }
+
+        public static UInt32 TagFromStream(string label, System.IO.Stream data, UInt32 seed)
+        {
+            ValidateTagArguments(data);
+            Int32 _gen0;
+            _gen0 = 0;
+            Byte[] _gen1;
+            _gen1 = PooledStream.ReadToEnd(data, ref _gen0);
+            try
+            {
+                return Tag(label, new Span<Byte>(_gen1, 0, _gen0), seed);
+            }
+            finally
+            {
+                System.Buffers.ArrayPool<Byte>.Shared.Return(_gen1);
+            }
+        }
+
+        [DllImport("golden", CallingConvention = CallingConvention.Cdecl)]
+        private static extern void __bindgen_thunk_scale(SliceAbi values, Single factor);
+
+        public static void Scale(Single[] values, Single factor)
+        {
+            ValidateScaleArguments(values);
+            SliceAbi _gen0;
+            (_gen0).Len = (UInt64)((values).Length);
+            unsafe
+            {
+                fixed (Single* _gen1 = values)
+                {
+                    (_gen0).Ptr = (IntPtr)(_gen1);
+                    __bindgen_thunk_scale(_gen0, factor);
+                }
+            }
+        }
+
+        [System.Diagnostics.Conditional("DEBUG")]
+        private static void ValidateScaleArguments(Single[] values)
+        {
+            if (values is null) throw new ArgumentNullException(nameof(values));
+        }
+
+        [DllImport("golden", CallingConvention = CallingConvention.Cdecl)]
+        private static extern Int32 __bindgen_thunk_add(Int32 a, Int32 b);
+
+        public static Int32 Add(Int32 a, Int32 b)
+        {
+            return __bindgen_thunk_add(a, b);
+        }
+    }
+}
+
+#pragma warning restore CA1051, CA1060, CA1707, CA1815, CA5392
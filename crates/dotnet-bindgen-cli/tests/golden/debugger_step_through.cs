@@ -0,0 +1,54 @@
+/*
+ * This is a generated file, do not modify by hand.
+ * Generated by dotnet-bindgen-cli v0.1.0
+ */
+
+using System;
+using System.Runtime.InteropServices;
+
+#pragma warning disable CA1051, CA1060, CA1707, CA1815, CA5392
+
+namespace GoldenBindings
+{
+    [StructLayout(LayoutKind.Sequential)]
+    public struct SliceAbi
+    {
+        public IntPtr Ptr;
+        public UInt64 Len;
+    }
+
+    public static class TopLevelMethods
+    {
+        public const UInt64 BindingVersion = 0xCE66DBA370B24004UL;
+
+        [DllImport("golden", CallingConvention = CallingConvention.Cdecl)]
+        private static extern Int32 __bindgen_thunk_add(Int32 a, Int32 b);
+
+        [System.Diagnostics.DebuggerStepThrough]
+        public static Int32 Add(Int32 a, Int32 b)
+        {
+            return __bindgen_thunk_add(a, b);
+        }
+
+        [DllImport("golden", CallingConvention = CallingConvention.Cdecl)]
+        private static extern Int64 __bindgen_thunk_sum(SliceAbi values);
+
+        [System.Diagnostics.DebuggerStepThrough]
+        public static Int64 Sum(Int32[] values)
+        {
+            if (values is null) throw new ArgumentNullException(nameof(values));
+            SliceAbi _gen0;
+            (_gen0).Len = (UInt64)((values).Length);
+            unsafe
+            {
+                fixed (Int32* _gen1 = values)
+                {
+                    (_gen0).Ptr = (IntPtr)(_gen1);
+                    return __bindgen_thunk_sum(_gen0);
+                }
+            }
+        }
+    }
+}
+
+#pragma warning restore CA1051, CA1060, CA1707, CA1815, CA5392
@@ -0,0 +1,81 @@
+/*
+ * This is a generated file, do not modify by hand.
+ * Generated by dotnet-bindgen-cli v0.1.0
+ */
+
+using System;
+using System.Runtime.InteropServices;
+
+#pragma warning disable CA1051, CA1060, CA1707, CA1815, CA5392
+
+namespace GoldenBindings
+{
+    [StructLayout(LayoutKind.Sequential)]
+    public struct SliceAbi
+    {
+        public IntPtr Ptr;
+        public UInt64 Len;
+    }
+
+    public static class TopLevelMethods
+    {
+        public const UInt64 BindingVersion = 0xA8F7D7D2D57C145AUL;
+
+        [DllImport("golden", CallingConvention = CallingConvention.Cdecl)]
+        private static extern Single __bindgen_thunk_blend(Single from, Single to, Single amount, Byte clamp);
+
+        public static Single Blend(Single from, Single to, Single amount, bool clamp)
+        {
+            Byte _gen0;
+            _gen0 = ( (clamp) ? (1) : (0) );
+            return __bindgen_thunk_blend(from, to, amount, _gen0);
+        }
+
+        public static Single Blend(Single from, Single to)
+        {
+            return Blend(from, to, 0.5f, true);
+        }
+
+        public static Single Blend(Single from, Single to, Single amount)
+        {
+            return Blend(from, to, amount, true);
+        }
+
+        [DllImport("golden", CallingConvention = CallingConvention.Cdecl)]
+        private static extern void __bindgen_thunk_seek(Int64 offset, UInt64 limit, SliceAbi label);
+
+        public static void Seek(Int64 offset, UInt64 limit, string label)
+        {
+            if (label is null) throw new ArgumentNullException(nameof(label));
+            Byte[] _gen1;
+            _gen1 = new System.Text.UTF8Encoding(false, true).GetBytes(label);
+            SliceAbi _gen0;
+            (_gen0).Len = (UInt64)((_gen1).Length);
+            unsafe
+            {
+                fixed (Byte* _gen2 = _gen1)
+                {
+                    (_gen0).Ptr = (IntPtr)(_gen2);
+                    __bindgen_thunk_seek(offset, limit, _gen0);
+                }
+            }
+        }
+
+        public static void Seek()
+        {
+            Seek(-1, 18446744073709551615, "start");
+        }
+
+        public static void Seek(Int64 offset)
+        {
+            Seek(offset, 18446744073709551615, "start");
+        }
+
+        public static void Seek(Int64 offset, UInt64 limit)
+        {
+            Seek(offset, limit, "start");
+        }
+    }
+}
+
+#pragma warning restore CA1051, CA1060, CA1707, CA1815, CA5392
@@ -0,0 +1,34 @@
+/*
+ * This is a generated file, do not modify by hand.
+ * Generated by dotnet-bindgen-cli v0.1.0
+ */
+
+using System;
+using System.Runtime.InteropServices;
+
+#pragma warning disable CS0649, IDE1006
+
+namespace GoldenBindings
+{
+    [StructLayout(LayoutKind.Sequential)]
+    public struct SliceAbi
+    {
+        public IntPtr Ptr;
+        public UInt64 Len;
+    }
+
+    public static class TopLevelMethods
+    {
+        public const UInt64 BindingVersion = 0x681343E69E0314B3UL;
+
+        [DllImport("golden", CallingConvention = CallingConvention.Cdecl)]
+        private static extern Int32 __bindgen_thunk_add(Int32 a, Int32 b);
+
+        public static Int32 Add(Int32 a, Int32 b)
+        {
+            return __bindgen_thunk_add(a, b);
+        }
+    }
+}
+
+#pragma warning restore CS0649, IDE1006
@@ -0,0 +1,42 @@
+/*
+ * This is a generated file, do not modify by hand.
+ * Generated by dotnet-bindgen-cli v0.1.0
+ */
+
+using System;
+using System.Runtime.InteropServices;
+
+#pragma warning disable CA1051, CA1060, CA1707, CA1815, CA5392
+
+namespace GoldenBindings
+{
+    [StructLayout(LayoutKind.Sequential)]
+    public struct SliceAbi
+    {
+        public IntPtr Ptr;
+        public UInt64 Len;
+    }
+
+    public static class TopLevelMethods
+    {
+        public const UInt64 BindingVersion = 0xBA916FF2711A15D2UL;
+
+        [DllImport("golden", CallingConvention = CallingConvention.Cdecl)]
+        private static extern Int32 __bindgen_thunk_thunked(Int32 value);
+
+        public static Int32 Thunked(Int32 value)
+        {
+            return __bindgen_thunk_thunked(value);
+        }
+
+        [DllImport("golden", EntryPoint = "foreign", CallingConvention = CallingConvention.Cdecl)]
+        private static extern Int32 __bindgen_extern_foreign(Int32 value);
+
+        public static Int32 Foreign(Int32 value)
+        {
+            return __bindgen_extern_foreign(value);
+        }
+    }
+}
+
+#pragma warning restore CA1051, CA1060, CA1707, CA1815, CA5392
@@ -0,0 +1,130 @@
+/*
+ * This is a generated file, do not modify by hand.
+ * Generated by dotnet-bindgen-cli v0.1.0
+ */
+
+using System;
+using System.Runtime.InteropServices;
+
+#pragma warning disable CA1051, CA1060, CA1707, CA1815, CA5392
+
+namespace GoldenBindings
+{
+    public enum Color : byte
+    {
+        Red = 0,
+        Green = 1,
+        Blue = 5,
+    }
+
+    public enum Priority : short
+    {
+        Low = -100,
+        Normal = 0,
+        High = 1000,
+    }
+
+    public enum Mask : ulong
+    {
+        Empty = 0,
+        TopBit = 9223372036854775808,
+    }
+
+    [StructLayout(LayoutKind.Sequential)]
+    public struct SliceAbi
+    {
+        public IntPtr Ptr;
+        public UInt64 Len;
+    }
+
+    [StructLayout(LayoutKind.Sequential)]
+    public struct OwnedSliceAbi
+    {
+        public IntPtr Ptr;
+        public UInt64 Len;
+        public UInt64 Cap;
+    }
+
+    public static class TopLevelMethods
+    {
+        public const UInt64 BindingVersion = 0x49DB82FA35DE4FC6UL;
+
+        [DllImport("golden", CallingConvention = CallingConvention.Cdecl)]
+        private static extern UInt32 __bindgen_thunk_count_color(SliceAbi colors, Color color, ref Byte invalidArg);
+
+        public static UInt32 CountColor(Color[] colors, Color color)
+        {
+            if (colors is null) throw new ArgumentNullException(nameof(colors));
+            SliceAbi _gen0;
+            (_gen0).Len = (UInt64)((colors).Length);
+            unsafe
+            {
+                fixed (Color* _gen1 = colors)
+                {
+                    (_gen0).Ptr = (IntPtr)(_gen1);
+                    Byte _gen2;
+                    _gen2 = 0;
+                    UInt32 _gen3;
+                    _gen3 = __bindgen_thunk_count_color(_gen0, color, ref _gen2);
+                    if (_gen2 == 2) throw new ArgumentException("An enum argument was not one of its defined values");
+                    return _gen3;
+                }
+            }
+        }
+
+        [DllImport("golden", CallingConvention = CallingConvention.Cdecl)]
+        private static extern void __bindgen_thunk_reverse_colors(SliceAbi colors, ref Byte invalidArg);
+
+        public static void ReverseColors(Color[] colors)
+        {
+            if (colors is null) throw new ArgumentNullException(nameof(colors));
+            SliceAbi _gen0;
+            (_gen0).Len = (UInt64)((colors).Length);
+            unsafe
+            {
+                fixed (Color* _gen1 = colors)
+                {
+                    (_gen0).Ptr = (IntPtr)(_gen1);
+                    Byte _gen2;
+                    _gen2 = 0;
+                    __bindgen_thunk_reverse_colors(_gen0, ref _gen2);
+                    if (_gen2 == 2) throw new ArgumentException("An enum argument was not one of its defined values");
+                }
+            }
+        }
+
+        [DllImport("golden", CallingConvention = CallingConvention.Cdecl)]
+        private static extern OwnedSliceAbi __bindgen_thunk_primary_colors();
+
+        public static Color[] PrimaryColors()
+        {
+            OwnedSliceAbi _gen0;
+            _gen0 = __bindgen_thunk_primary_colors();
+            unsafe
+            {
+                Color[] _gen1;
+                _gen1 = new Span<Color>((void*)((_gen0).Ptr), checked((Int32)((_gen0).Len))).ToArray();
+                __bindgen_free_primary_colors(_gen0);
+                return _gen1;
+            }
+        }
+
+        [DllImport("golden", CallingConvention = CallingConvention.Cdecl)]
+        private static extern void __bindgen_free_primary_colors(OwnedSliceAbi value);
+
+        [DllImport("golden", CallingConvention = CallingConvention.Cdecl)]
+        private static extern Byte __bindgen_thunk_is_urgent(Priority priority, Mask mask, ref Byte invalidArg);
+
+        public static Byte IsUrgent(Priority priority, Mask mask)
+        {
+            Byte _gen0;
+            _gen0 = 0;
+            Byte _gen1;
+            _gen1 = __bindgen_thunk_is_urgent(priority, mask, ref _gen0);
+            if (_gen0 == 2) throw new ArgumentException("An enum argument was not one of its defined values");
+            return _gen1;
+        }
+    }
+}
+
+#pragma warning restore CA1051, CA1060, CA1707, CA1815, CA5392
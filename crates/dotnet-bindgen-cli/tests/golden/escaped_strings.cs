@@ -0,0 +1,52 @@
+/*
+ * Quotes "like this", a back\slash and café 🦀
+ * A stray * / must not close the comment
+ * and an embedded newline starts a new line
+ */
+
+using System;
+using System.Runtime.InteropServices;
+
+#pragma warning disable CA1051, CA1060, CA1707, CA1815, CA5392
+
+namespace GoldenBindings
+{
+    [StructLayout(LayoutKind.Sequential)]
+    public struct SliceAbi
+    {
+        public IntPtr Ptr;
+        public UInt64 Len;
+    }
+
+    public static class TopLevelMethods
+    {
+        public const UInt64 BindingVersion = 0xCC1D73240128178BUL;
+
+        [DllImport("golden", CallingConvention = CallingConvention.Cdecl)]
+        private static extern void __bindgen_thunk_greet(SliceAbi greeting);
+
+        public static void Greet(string greeting)
+        {
+            if (greeting is null) throw new ArgumentNullException(nameof(greeting));
+            Byte[] _gen1;
+            _gen1 = new System.Text.UTF8Encoding(false, true).GetBytes(greeting);
+            SliceAbi _gen0;
+            (_gen0).Len = (UInt64)((_gen1).Length);
+            unsafe
+            {
+                fixed (Byte* _gen2 = _gen1)
+                {
+                    (_gen0).Ptr = (IntPtr)(_gen2);
+                    __bindgen_thunk_greet(_gen0);
+                }
+            }
+        }
+
+        public static void Greet()
+        {
+            Greet("say \"h\u00E9llo\" \\ */\r\n\t\uD83E\uDD80");
+        }
+    }
+}
+
+#pragma warning restore CA1051, CA1060, CA1707, CA1815, CA5392
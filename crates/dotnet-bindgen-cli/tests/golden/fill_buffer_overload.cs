@@ -0,0 +1,75 @@
+/*
+ * This is a generated file, do not modify by hand.
+ * Generated by dotnet-bindgen-cli v0.1.0
+ */
+
+using System;
+using System.Runtime.InteropServices;
+
+#pragma warning disable CA1051, CA1060, CA1707, CA1815, CA5392
+
+namespace GoldenBindings
+{
+    [StructLayout(LayoutKind.Sequential)]
+    public struct SliceAbi
+    {
+        public IntPtr Ptr;
+        public UInt64 Len;
+    }
+
+    public static class TopLevelMethods
+    {
+        public const UInt64 BindingVersion = 0x6A6AF7C7E6579B1BUL;
+
+        [DllImport("golden", CallingConvention = CallingConvention.Cdecl)]
+        private static extern UInt32 __bindgen_thunk_fill_buffer(Byte seed, SliceAbi buffer);
+
+        public static UInt32 FillBuffer(Byte seed, Byte[] buffer)
+        {
+            if (buffer is null) throw new ArgumentNullException(nameof(buffer));
+            SliceAbi _gen0;
+            (_gen0).Len = (UInt64)((buffer).Length);
+            unsafe
+            {
+                fixed (Byte* _gen1 = buffer)
+                {
+                    (_gen0).Ptr = (IntPtr)(_gen1);
+                    return __bindgen_thunk_fill_buffer(seed, _gen0);
+                }
+            }
+        }
+
+        public static Byte[] FillBuffer(Byte seed)
+        {
+            Byte[] _gen0;
+            _gen0 = new Byte[0];
+            UInt32 _gen1;
+            _gen1 = FillBuffer(seed, _gen0);
+            _gen0 = new Byte[_gen1];
+            UInt32 _gen2;
+            _gen2 = FillBuffer(seed, _gen0);
+            if (_gen2 != _gen1) throw new InvalidOperationException("fill_buffer reported a different buffer size on the second call");
+            return _gen0;
+        }
+
+        [DllImport("golden", CallingConvention = CallingConvention.Cdecl)]
+        private static extern UInt64 __bindgen_thunk_read_into(SliceAbi buffer);
+
+        public static UInt64 ReadInto(Byte[] buffer)
+        {
+            if (buffer is null) throw new ArgumentNullException(nameof(buffer));
+            SliceAbi _gen0;
+            (_gen0).Len = (UInt64)((buffer).Length);
+            unsafe
+            {
+                fixed (Byte* _gen1 = buffer)
+                {
+                    (_gen0).Ptr = (IntPtr)(_gen1);
+                    return __bindgen_thunk_read_into(_gen0);
+                }
+            }
+        }
+    }
+}
+
+#pragma warning restore CA1051, CA1060, CA1707, CA1815, CA5392
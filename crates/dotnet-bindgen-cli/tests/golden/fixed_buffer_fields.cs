@@ -0,0 +1,60 @@
+/*
+ * This is a generated file, do not modify by hand.
+ * Generated by dotnet-bindgen-cli v0.1.0
+ */
+
+using System;
+using System.Runtime.InteropServices;
+
+#pragma warning disable CA1051, CA1060, CA1707, CA1815, CA5392
+
+namespace GoldenBindings
+{
+    [StructLayout(LayoutKind.Sequential)]
+    public struct Waveform
+    {
+        public unsafe fixed UInt16 Samples[8];
+        public UInt32 Rate;
+
+        #if NETCOREAPP2_1_OR_GREATER || NETSTANDARD2_1_OR_GREATER
+        public unsafe Span<UInt16> SamplesSpan
+        {
+            get
+            {
+                return System.Runtime.InteropServices.MemoryMarshal.CreateSpan(ref Samples[0], 8);
+            }
+        }
+        #endif
+    }
+
+    [StructLayout(LayoutKind.Sequential)]
+    public struct SliceAbi
+    {
+        public IntPtr Ptr;
+        public UInt64 Len;
+    }
+
+    public static class TopLevelMethods
+    {
+        public const UInt64 BindingVersion = 0xC5C6348C901AE091UL;
+
+        static TopLevelMethods()
+        {
+            if (Marshal.SizeOf<Waveform>() != 20) throw new InvalidOperationException("The size of Waveform in C# doesn't match its size of 20 bytes in Rust");
+            if (Marshal.OffsetOf<WaveformAlignmentProbe>("Value").ToInt64() != 4) throw new InvalidOperationException("The alignment of Waveform in C# doesn't match its alignment of 4 bytes in Rust");
+        }
+
+        public static void EnsureValidated()
+        {
+        }
+
+        [StructLayout(LayoutKind.Sequential)]
+        private struct WaveformAlignmentProbe
+        {
+            public Byte Pad;
+            public Waveform Value;
+        }
+    }
+}
+
+#pragma warning restore CA1051, CA1060, CA1707, CA1815, CA5392
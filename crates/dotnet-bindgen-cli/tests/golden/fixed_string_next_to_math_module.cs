@@ -0,0 +1,87 @@
+/*
+ * This is a generated file, do not modify by hand.
+ * Generated by dotnet-bindgen-cli v0.1.0
+ */
+
+using System;
+using System.Runtime.InteropServices;
+
+#pragma warning disable CA1051, CA1060, CA1707, CA1815, CA5392
+
+namespace GoldenBindings
+{
+    [StructLayout(LayoutKind.Sequential)]
+    public struct Name
+    {
+        public unsafe fixed Byte Bytes[32];
+        public Byte Len;
+
+        public unsafe string Value
+        {
+            get
+            {
+                fixed (Byte* _gen0 = Bytes)
+                {
+                    return System.Text.Encoding.UTF8.GetString(_gen0, System.Math.Min(Len, 32));
+                }
+            }
+        }
+
+        #if NETCOREAPP2_1_OR_GREATER || NETSTANDARD2_1_OR_GREATER
+        public unsafe Span<Byte> BytesSpan
+        {
+            get
+            {
+                return System.Runtime.InteropServices.MemoryMarshal.CreateSpan(ref Bytes[0], 32);
+            }
+        }
+        #endif
+    }
+
+    [StructLayout(LayoutKind.Sequential)]
+    public struct SliceAbi
+    {
+        public IntPtr Ptr;
+        public UInt64 Len;
+    }
+
+    public static class Math
+    {
+        static Math()
+        {
+            TopLevelMethods.EnsureValidated();
+        }
+
+        [DllImport("golden", CallingConvention = CallingConvention.Cdecl)]
+        private static extern Int32 __bindgen_thunk_square(Int32 x);
+
+        public static Int32 Square(Int32 x)
+        {
+            return __bindgen_thunk_square(x);
+        }
+    }
+
+    public static class TopLevelMethods
+    {
+        public const UInt64 BindingVersion = 0x95FA1FEB6458887AUL;
+
+        static TopLevelMethods()
+        {
+            if (Marshal.SizeOf<Name>() != 33) throw new InvalidOperationException("The size of Name in C# doesn't match its size of 33 bytes in Rust");
+            if (Marshal.OffsetOf<NameAlignmentProbe>("Value").ToInt64() != 1) throw new InvalidOperationException("The alignment of Name in C# doesn't match its alignment of 1 bytes in Rust");
+        }
+
+        public static void EnsureValidated()
+        {
+        }
+
+        [StructLayout(LayoutKind.Sequential)]
+        private struct NameAlignmentProbe
+        {
+            public Byte Pad;
+            public Name Value;
+        }
+    }
+}
+
+#pragma warning restore CA1051, CA1060, CA1707, CA1815, CA5392
@@ -0,0 +1,34 @@
+/*
+ * This is a generated file, do not modify by hand.
+ * Generated by dotnet-bindgen-cli v0.1.0
+ */
+
+using System;
+using System.Runtime.InteropServices;
+
+#pragma warning disable CA1051, CA1060, CA1707, CA1815, CA5392
+
+namespace GoldenBindings
+{
+    [StructLayout(LayoutKind.Sequential)]
+    public struct SliceAbi
+    {
+        public IntPtr Ptr;
+        public UInt64 Len;
+    }
+
+    public static class TopLevelMethods
+    {
+        public const UInt64 BindingVersion = 0xB3F7D34FD628AE93UL;
+
+        [DllImport("golden", CallingConvention = CallingConvention.Cdecl)]
+        private static extern unsafe void __bindgen_thunk_set_callback(delegate* unmanaged[Cdecl]<UInt32, Double, Int32> callback);
+
+        public static unsafe void SetCallback(delegate* unmanaged[Cdecl]<UInt32, Double, Int32> callback)
+        {
+            __bindgen_thunk_set_callback(callback);
+        }
+    }
+}
+
+#pragma warning restore CA1051, CA1060, CA1707, CA1815, CA5392
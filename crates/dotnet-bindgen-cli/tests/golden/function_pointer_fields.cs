@@ -0,0 +1,58 @@
+/*
+ * This is a generated file, do not modify by hand.
+ * Generated by dotnet-bindgen-cli v0.1.0
+ */
+
+using System;
+using System.Runtime.InteropServices;
+
+#pragma warning disable CA1051, CA1060, CA1707, CA1815, CA5392
+
+namespace GoldenBindings
+{
+    [StructLayout(LayoutKind.Sequential)]
+    public struct PluginVTable
+    {
+        public unsafe delegate* unmanaged[Cdecl]<UInt32, Int32> OnLoad;
+        public unsafe delegate* unmanaged[Cdecl]<UInt64, Single, void> OnEvent;
+    }
+
+    [StructLayout(LayoutKind.Sequential)]
+    public struct SliceAbi
+    {
+        public IntPtr Ptr;
+        public UInt64 Len;
+    }
+
+    public static class TopLevelMethods
+    {
+        public const UInt64 BindingVersion = 0xD7D6FF6E6CF7D9A0UL;
+
+        static TopLevelMethods()
+        {
+            if (Marshal.SizeOf<PluginVTable>() != 16) throw new InvalidOperationException("The size of PluginVTable in C# doesn't match its size of 16 bytes in Rust");
+            if (Marshal.OffsetOf<PluginVTableAlignmentProbe>("Value").ToInt64() != 8) throw new InvalidOperationException("The alignment of PluginVTable in C# doesn't match its alignment of 8 bytes in Rust");
+        }
+
+        [DllImport("golden", CallingConvention = CallingConvention.Cdecl)]
+        private static extern void __bindgen_thunk_register_plugin(PluginVTable vtable, UInt32 version);
+
+        public static void RegisterPlugin(PluginVTable vtable, UInt32 version)
+        {
+            __bindgen_thunk_register_plugin(vtable, version);
+        }
+
+        public static void EnsureValidated()
+        {
+        }
+
+        [StructLayout(LayoutKind.Sequential)]
+        private struct PluginVTableAlignmentProbe
+        {
+            public Byte Pad;
+            public PluginVTable Value;
+        }
+    }
+}
+
+#pragma warning restore CA1051, CA1060, CA1707, CA1815, CA5392
@@ -0,0 +1,65 @@
+/*
+ * This is a generated file, do not modify by hand.
+ * Generated by dotnet-bindgen-cli v0.1.0
+ */
+
+using System;
+using System.Runtime.InteropServices;
+
+#pragma warning disable CA1051, CA1060, CA1707, CA1815, CA5392
+
+namespace GoldenBindings
+{
+    [StructLayout(LayoutKind.Sequential)]
+    public struct SliceAbi
+    {
+        public IntPtr Ptr;
+        public UInt64 Len;
+    }
+
+    [StructLayout(LayoutKind.Sequential)]
+    public struct OwnedSliceAbi
+    {
+        public IntPtr Ptr;
+        public UInt64 Len;
+        public UInt64 Cap;
+    }
+
+    public static class TopLevelMethods
+    {
+        public const UInt64 BindingVersion = 0xD07AA8F43C595BE0UL;
+
+        [DllImport("golden", CallingConvention = CallingConvention.Cdecl)]
+        private static extern OwnedSliceAbi __bindgen_thunk_greet(SliceAbi name, Byte shout);
+
+        public static string Greet(string name, bool shout)
+        {
+            if (name is null) throw new ArgumentNullException(nameof(name));
+            Byte[] _gen1;
+            _gen1 = new System.Text.UTF8Encoding(false, true).GetBytes(name);
+            SliceAbi _gen0;
+            (_gen0).Len = (UInt64)((_gen1).Length);
+            unsafe
+            {
+                fixed (Byte* _gen2 = _gen1)
+                {
+                    (_gen0).Ptr = (IntPtr)(_gen2);
+                    OwnedSliceAbi _gen4;
+                    _gen4 = __bindgen_thunk_greet(_gen0, (Byte)(( (shout) ? (1) : (0) )));
+                    unsafe
+                    {
+                        string _gen5;
+                        _gen5 = System.Text.Encoding.UTF8.GetString((Byte*)((_gen4).Ptr), checked((Int32)((_gen4).Len)));
+                        __bindgen_free_greet(_gen4);
+                        return _gen5;
+                    }
+                }
+            }
+        }
+
+        [DllImport("golden", CallingConvention = CallingConvention.Cdecl)]
+        private static extern void __bindgen_free_greet(OwnedSliceAbi value);
+    }
+}
+
+#pragma warning restore CA1051, CA1060, CA1707, CA1815, CA5392
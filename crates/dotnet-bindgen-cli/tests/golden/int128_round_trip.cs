@@ -0,0 +1,87 @@
+/*
+ * This is a generated file, do not modify by hand.
+ * Generated by dotnet-bindgen-cli v0.1.0
+ */
+
+using System;
+using System.Runtime.InteropServices;
+
+#pragma warning disable CA1051, CA1060, CA1707, CA1815, CA5392
+
+#if !NET7_0_OR_GREATER
+#error These bindings use Int128/UInt128, which need net7.0 or later
+#endif
+
+namespace GoldenBindings
+{
+    [StructLayout(LayoutKind.Sequential)]
+    public struct SliceAbi
+    {
+        public IntPtr Ptr;
+        public UInt64 Len;
+    }
+
+    [StructLayout(LayoutKind.Sequential)]
+    public struct Int128Abi
+    {
+        public UInt64 Lo;
+        public UInt64 Hi;
+
+        public static Int128Abi FromInt128(Int128 value)
+        {
+            unchecked
+            {
+                Int128Abi _gen0;
+                (_gen0).Lo = (UInt64)(value);
+                (_gen0).Hi = (UInt64)(value >> 64);
+                return _gen0;
+            }
+        }
+
+        public Int128 ToInt128()
+        {
+            return new Int128((this).Hi, (this).Lo);
+        }
+
+        public static Int128Abi FromUInt128(UInt128 value)
+        {
+            unchecked
+            {
+                Int128Abi _gen0;
+                (_gen0).Lo = (UInt64)(value);
+                (_gen0).Hi = (UInt64)(value >> 64);
+                return _gen0;
+            }
+        }
+
+        public UInt128 ToUInt128()
+        {
+            return new UInt128((this).Hi, (this).Lo);
+        }
+    }
+
+    public static class TopLevelMethods
+    {
+        public const UInt64 BindingVersion = 0x33BF5E718010A8A3UL;
+
+        [DllImport("golden", CallingConvention = CallingConvention.Cdecl)]
+        private static extern Int128Abi __bindgen_thunk_negate_i128(Int128Abi value);
+
+        public static Int128 NegateI128(Int128 value)
+        {
+            Int128Abi _gen0;
+            _gen0 = Int128Abi.FromInt128(value);
+            return __bindgen_thunk_negate_i128(_gen0).ToInt128();
+        }
+
+        [DllImport("golden", CallingConvention = CallingConvention.Cdecl)]
+        private static extern Int128Abi __bindgen_thunk_widening_mul(UInt64 a, UInt64 b);
+
+        public static UInt128 WideningMul(UInt64 a, UInt64 b)
+        {
+            return __bindgen_thunk_widening_mul(a, b).ToUInt128();
+        }
+    }
+}
+
+#pragma warning restore CA1051, CA1060, CA1707, CA1815, CA5392
@@ -0,0 +1,90 @@
+/*
+ * This is a generated file, do not modify by hand.
+ * Generated by dotnet-bindgen-cli v0.1.0
+ */
+
+using System;
+using System.Runtime.InteropServices;
+
+#pragma warning disable CA1051, CA1060, CA1707, CA1815, CA5392
+
+namespace GoldenBindings
+{
+    [StructLayout(LayoutKind.Sequential)]
+    internal struct Point
+    {
+        public Int32 X;
+        public Int32 Y;
+    }
+
+    internal enum Color : byte
+    {
+        Red = 0,
+        Green = 1,
+    }
+
+    [StructLayout(LayoutKind.Sequential)]
+    internal struct SliceAbi
+    {
+        public IntPtr Ptr;
+        public UInt64 Len;
+    }
+
+    internal static class Math
+    {
+        static Math()
+        {
+            TopLevelMethods.EnsureValidated();
+        }
+
+        [DllImport("golden", CallingConvention = CallingConvention.Cdecl)]
+        private static extern Int32 __bindgen_thunk_square(Int32 x);
+
+        public static Int32 Square(Int32 x)
+        {
+            return __bindgen_thunk_square(x);
+        }
+    }
+
+    internal static class TopLevelMethods
+    {
+        public const UInt64 BindingVersion = 0x50CD5337FBCE90CAUL;
+
+        static TopLevelMethods()
+        {
+            if (Marshal.SizeOf<Point>() != 8) throw new InvalidOperationException("The size of Point in C# doesn't match its size of 8 bytes in Rust");
+            if (Marshal.OffsetOf<PointAlignmentProbe>("Value").ToInt64() != 4) throw new InvalidOperationException("The alignment of Point in C# doesn't match its alignment of 4 bytes in Rust");
+        }
+
+        [DllImport("golden", CallingConvention = CallingConvention.Cdecl)]
+        private static extern Int64 __bindgen_thunk_sum(SliceAbi values);
+
+        public static Int64 Sum(Int32[] values)
+        {
+            if (values is null) throw new ArgumentNullException(nameof(values));
+            SliceAbi _gen0;
+            (_gen0).Len = (UInt64)((values).Length);
+            unsafe
+            {
+                fixed (Int32* _gen1 = values)
+                {
+                    (_gen0).Ptr = (IntPtr)(_gen1);
+                    return __bindgen_thunk_sum(_gen0);
+                }
+            }
+        }
+
+        public static void EnsureValidated()
+        {
+        }
+
+        [StructLayout(LayoutKind.Sequential)]
+        private struct PointAlignmentProbe
+        {
+            public Byte Pad;
+            public Point Value;
+        }
+    }
+}
+
+#pragma warning restore CA1051, CA1060, CA1707, CA1815, CA5392
@@ -0,0 +1,74 @@
+/*
+ * This is a generated file, do not modify by hand.
+ * Generated by dotnet-bindgen-cli v0.1.0
+ */
+
+using System;
+using System.Runtime.InteropServices;
+
+#pragma warning disable CA1051, CA1060, CA1707, CA1815, CA5392
+
+namespace GoldenBindings
+{
+    [StructLayout(LayoutKind.Sequential)]
+    public struct SliceAbi
+    {
+        public IntPtr Ptr;
+        public UInt64 Len;
+    }
+
+    public static class TopLevelMethods
+    {
+        public const UInt64 BindingVersion = 0x2B436455F3E9DF8EUL;
+
+        [DllImport("golden", CallingConvention = CallingConvention.Cdecl)]
+        private static extern UInt64 __bindgen_thunk_char_count(SliceAbi text, ref Byte invalidArg);
+
+        public static UInt64 CharCount(string text)
+        {
+            if (text is null) throw new ArgumentNullException(nameof(text));
+            Byte[] _gen1;
+            _gen1 = new System.Text.UTF8Encoding(false, true).GetBytes(text);
+            SliceAbi _gen0;
+            (_gen0).Len = (UInt64)((_gen1).Length);
+            unsafe
+            {
+                fixed (Byte* _gen2 = _gen1)
+                {
+                    (_gen0).Ptr = (IntPtr)(_gen2);
+                    Byte _gen3;
+                    _gen3 = 0;
+                    UInt64 _gen4;
+                    _gen4 = __bindgen_thunk_char_count(_gen0, ref _gen3);
+                    if (_gen3 == 1) throw new ArgumentException("A string argument was not valid UTF-8");
+                    return _gen4;
+                }
+            }
+        }
+
+        [DllImport("golden", CallingConvention = CallingConvention.Cdecl)]
+        private static extern void __bindgen_thunk_log(SliceAbi message, ref Byte invalidArg);
+
+        public static void Log(string message)
+        {
+            if (message is null) throw new ArgumentNullException(nameof(message));
+            Byte[] _gen1;
+            _gen1 = new System.Text.UTF8Encoding(false, true).GetBytes(message);
+            SliceAbi _gen0;
+            (_gen0).Len = (UInt64)((_gen1).Length);
+            unsafe
+            {
+                fixed (Byte* _gen2 = _gen1)
+                {
+                    (_gen0).Ptr = (IntPtr)(_gen2);
+                    Byte _gen3;
+                    _gen3 = 0;
+                    __bindgen_thunk_log(_gen0, ref _gen3);
+                    if (_gen3 == 1) throw new ArgumentException("A string argument was not valid UTF-8");
+                }
+            }
+        }
+    }
+}
+
+#pragma warning restore CA1051, CA1060, CA1707, CA1815, CA5392
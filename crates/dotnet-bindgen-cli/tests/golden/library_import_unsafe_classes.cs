@@ -0,0 +1,95 @@
+/*
+ * This is a generated file, do not modify by hand.
+ * Generated by dotnet-bindgen-cli v0.1.0
+ */
+
+using System;
+using System.Runtime.InteropServices;
+
+#pragma warning disable CA1051, CA1060, CA1707, CA1815, CA5392
+
+namespace GoldenBindings
+{
+    [StructLayout(LayoutKind.Sequential)]
+    public struct Scene
+    {
+    }
+
+    [StructLayout(LayoutKind.Sequential)]
+    public struct SliceAbi
+    {
+        public IntPtr Ptr;
+        public UInt64 Len;
+    }
+
+    [System.Runtime.InteropServices.Marshalling.CustomMarshaller(typeof(Int32[]), System.Runtime.InteropServices.Marshalling.MarshalMode.ManagedToUnmanagedIn, typeof(Int32SliceMarshaller.ManagedToUnmanagedIn))]
+    public static class Int32SliceMarshaller
+    {
+        [StructLayout(LayoutKind.Sequential)]
+        public ref struct ManagedToUnmanagedIn
+        {
+            private Int32[] array;
+
+            public void FromManaged(Int32[] managed)
+            {
+                (this).array = managed;
+            }
+
+            public ref Int32 GetPinnableReference()
+            {
+                return ref MemoryMarshal.GetArrayDataReference((this).array);
+            }
+
+            public unsafe SliceAbi ToUnmanaged()
+            {
+                SliceAbi _gen0;
+                (_gen0).Ptr = (IntPtr)(System.Runtime.CompilerServices.Unsafe.AsPointer(ref this.GetPinnableReference()));
+                (_gen0).Len = (UInt64)(((this).array).Length);
+                return _gen0;
+            }
+
+            public void Free()
+            {
+            }
+        }
+    }
+
+    public static partial class Stats
+    {
+        [LibraryImport("golden")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(System.Runtime.CompilerServices.CallConvCdecl) })]
+        private static partial Double __bindgen_thunk_mean([System.Runtime.InteropServices.Marshalling.MarshalUsing(typeof(Int32SliceMarshaller))] Int32[] values);
+
+        public static Double Mean(Int32[] values)
+        {
+            if (values is null) throw new ArgumentNullException(nameof(values));
+            return __bindgen_thunk_mean(values);
+        }
+    }
+
+    public static unsafe partial class TopLevelMethods
+    {
+        public const UInt64 BindingVersion = 0xF0DADC620EF06902UL;
+
+        [LibraryImport("golden")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(System.Runtime.CompilerServices.CallConvCdecl) })]
+        private static partial Int32 __bindgen_thunk_sum([System.Runtime.InteropServices.Marshalling.MarshalUsing(typeof(Int32SliceMarshaller))] Int32[] values);
+
+        public static Int32 Sum(Int32[] values)
+        {
+            if (values is null) throw new ArgumentNullException(nameof(values));
+            return __bindgen_thunk_sum(values);
+        }
+
+        [LibraryImport("golden")]
+        [UnmanagedCallConv(CallConvs = new[] { typeof(System.Runtime.CompilerServices.CallConvCdecl) })]
+        private static partial unsafe UInt32 __bindgen_thunk_scene_tick(Scene* scene, UInt32 delta);
+
+        public static unsafe UInt32 SceneTick(Scene* scene, UInt32 delta)
+        {
+            return __bindgen_thunk_scene_tick(scene, delta);
+        }
+    }
+}
+
+#pragma warning restore CA1051, CA1060, CA1707, CA1815, CA5392
@@ -0,0 +1,95 @@
+/*
+ * This is a generated file, do not modify by hand.
+ * Generated by dotnet-bindgen-cli v0.1.0
+ */
+
+using System;
+using System.Runtime.InteropServices;
+
+#pragma warning disable CA1051, CA1060, CA1707, CA1815, CA5392
+
+namespace GoldenBindings
+{
+    [StructLayout(LayoutKind.Sequential)]
+    public struct SliceAbi
+    {
+        public IntPtr Ptr;
+        public UInt64 Len;
+    }
+
+    public static class TopLevelMethods
+    {
+        public const UInt64 BindingVersion = 0x7345E84D633D6C14UL;
+
+        [DllImport("golden", CallingConvention = CallingConvention.Cdecl)]
+        private static extern Byte __bindgen_thunk_many_complex_args(SliceAbi a, Byte flag_a, SliceAbi b, SliceAbi name, Byte flag_b, SliceAbi c, SliceAbi d, ref Byte invalidArg);
+
+        public static Byte ManyComplexArgs(Byte[] a, bool flagA, Int32[] b, string name, bool flagB, UInt64[] c, Byte[] d)
+        {
+            if (a is null) throw new ArgumentNullException(nameof(a));
+            if (b is null) throw new ArgumentNullException(nameof(b));
+            if (name is null) throw new ArgumentNullException(nameof(name));
+            if (c is null) throw new ArgumentNullException(nameof(c));
+            if (d is null) throw new ArgumentNullException(nameof(d));
+            SliceAbi _gen0;
+            (_gen0).Len = (UInt64)((a).Length);
+            unsafe
+            {
+                fixed (Byte* _gen1 = a)
+                {
+                    (_gen0).Ptr = (IntPtr)(_gen1);
+                    Byte _gen2;
+                    _gen2 = ( (flagA) ? (1) : (0) );
+                    SliceAbi _gen3;
+                    (_gen3).Len = (UInt64)((b).Length);
+                    unsafe
+                    {
+                        fixed (Int32* _gen4 = b)
+                        {
+                            (_gen3).Ptr = (IntPtr)(_gen4);
+                            Byte[] _gen6;
+                            _gen6 = new System.Text.UTF8Encoding(false, true).GetBytes(name);
+                            SliceAbi _gen5;
+                            (_gen5).Len = (UInt64)((_gen6).Length);
+                            unsafe
+                            {
+                                fixed (Byte* _gen7 = _gen6)
+                                {
+                                    (_gen5).Ptr = (IntPtr)(_gen7);
+                                    Byte _gen8;
+                                    _gen8 = ( (flagB) ? (1) : (0) );
+                                    SliceAbi _gen9;
+                                    (_gen9).Len = (UInt64)((c).Length);
+                                    unsafe
+                                    {
+                                        fixed (UInt64* _gen10 = c)
+                                        {
+                                            (_gen9).Ptr = (IntPtr)(_gen10);
+                                            SliceAbi _gen11;
+                                            (_gen11).Len = (UInt64)((d).Length);
+                                            unsafe
+                                            {
+                                                fixed (Byte* _gen12 = d)
+                                                {
+                                                    (_gen11).Ptr = (IntPtr)(_gen12);
+                                                    Byte _gen13;
+                                                    _gen13 = 0;
+                                                    Byte _gen14;
+                                                    _gen14 = __bindgen_thunk_many_complex_args(_gen0, _gen2, _gen3, _gen5, _gen8, _gen9, _gen11, ref _gen13);
+                                                    if (_gen13 == 1) throw new ArgumentException("A string argument was not valid UTF-8");
+                                                    return _gen14;
+                                                }
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#pragma warning restore CA1051, CA1060, CA1707, CA1815, CA5392
@@ -0,0 +1,84 @@
+/*
+ * This is a generated file, do not modify by hand.
+ * Generated by dotnet-bindgen-cli v0.1.0
+ */
+
+using System;
+using System.Runtime.InteropServices;
+
+#pragma warning disable CA1051, CA1060, CA1707, CA1815, CA5392
+
+namespace GoldenBindings
+{
+    [StructLayout(LayoutKind.Sequential)]
+    public struct Point
+    {
+        public Int32 X;
+        public Int32 Y;
+    }
+
+    [StructLayout(LayoutKind.Sequential)]
+    public struct Transform
+    {
+        public Double X;
+        public Double Y;
+        public Double Z;
+        public Double W;
+    }
+
+    [StructLayout(LayoutKind.Sequential)]
+    public struct SliceAbi
+    {
+        public IntPtr Ptr;
+        public UInt64 Len;
+    }
+
+    public static class TopLevelMethods
+    {
+        public const UInt64 BindingVersion = 0x0692662854EB196AUL;
+
+        static TopLevelMethods()
+        {
+            if (Marshal.SizeOf<Point>() != 8) throw new InvalidOperationException("The size of Point in C# doesn't match its size of 8 bytes in Rust");
+            if (Marshal.OffsetOf<PointAlignmentProbe>("Value").ToInt64() != 4) throw new InvalidOperationException("The alignment of Point in C# doesn't match its alignment of 4 bytes in Rust");
+            if (Marshal.SizeOf<Transform>() != 32) throw new InvalidOperationException("The size of Transform in C# doesn't match its size of 32 bytes in Rust");
+            if (Marshal.OffsetOf<TransformAlignmentProbe>("Value").ToInt64() != 8) throw new InvalidOperationException("The alignment of Transform in C# doesn't match its alignment of 8 bytes in Rust");
+        }
+
+        [DllImport("golden", CallingConvention = CallingConvention.Cdecl)]
+        private static extern Double __bindgen_thunk_sum_points(Point a, Point b, Point c, Point d, Point e, Point f, Int32 scale);
+
+        public static Double SumPoints(Point a, Point b, Point c, Point d, Point e, Point f, Int32 scale)
+        {
+            return __bindgen_thunk_sum_points(a, b, c, d, e, f, scale);
+        }
+
+        [DllImport("golden", CallingConvention = CallingConvention.Cdecl)]
+        private static extern Double __bindgen_thunk_sum_transforms(Transform a, Transform b, Transform c, Transform d, Transform e, Transform f, Int32 scale);
+
+        public static Double SumTransforms(Transform a, Transform b, Transform c, Transform d, Transform e, Transform f, Int32 scale)
+        {
+            return __bindgen_thunk_sum_transforms(a, b, c, d, e, f, scale);
+        }
+
+        public static void EnsureValidated()
+        {
+        }
+
+        [StructLayout(LayoutKind.Sequential)]
+        private struct PointAlignmentProbe
+        {
+            public Byte Pad;
+            public Point Value;
+        }
+
+        [StructLayout(LayoutKind.Sequential)]
+        private struct TransformAlignmentProbe
+        {
+            public Byte Pad;
+            public Transform Value;
+        }
+    }
+}
+
+#pragma warning restore CA1051, CA1060, CA1707, CA1815, CA5392
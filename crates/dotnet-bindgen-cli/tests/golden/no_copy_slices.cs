@@ -0,0 +1,98 @@
+/*
+ * This is a generated file, do not modify by hand.
+ * Generated by dotnet-bindgen-cli v0.1.0
+ */
+
+using System;
+using System.Runtime.InteropServices;
+
+#pragma warning disable CA1051, CA1060, CA1707, CA1815, CA5392
+
+namespace GoldenBindings
+{
+    [StructLayout(LayoutKind.Sequential)]
+    public struct SliceAbi
+    {
+        public IntPtr Ptr;
+        public UInt64 Len;
+    }
+
+    [StructLayout(LayoutKind.Sequential)]
+    public struct OwnedSliceAbi
+    {
+        public IntPtr Ptr;
+        public UInt64 Len;
+        public UInt64 Cap;
+    }
+
+    public sealed class NativeSlice<T> : IDisposable
+    {
+        private OwnedSliceAbi abi;
+        private readonly Action<OwnedSliceAbi> free;
+
+        public NativeSlice(OwnedSliceAbi abi, Action<OwnedSliceAbi> free)
+        {
+            (this).abi = abi;
+            (this).free = free;
+        }
+
+        public NativeSlice(SliceAbi abi)
+        {
+            ((this).abi).Ptr = (abi).Ptr;
+            ((this).abi).Len = (abi).Len;
+        }
+
+        ~NativeSlice()
+        {
+            Free();
+        }
+
+        public unsafe Span<T> AsSpan()
+        {
+            return new Span<T>((void*)(((this).abi).Ptr), checked((Int32)(((this).abi).Len)));
+        }
+
+        public void Dispose()
+        {
+            Free();
+            GC.SuppressFinalize(this);
+        }
+
+        private void Free()
+        {
+            OwnedSliceAbi _gen0;
+            _gen0 = (this).abi;
+            (this).abi = new OwnedSliceAbi();
+            if ((this).free != null)
+            {
+                this.free(_gen0);
+            }
+        }
+    }
+
+    public static class TopLevelMethods
+    {
+        public const UInt64 BindingVersion = 0x2284A0BC654A593EUL;
+
+        [DllImport("golden", CallingConvention = CallingConvention.Cdecl)]
+        private static extern OwnedSliceAbi __bindgen_thunk_squares(UInt32 count);
+
+        public static NativeSlice<UInt32> Squares(UInt32 count)
+        {
+            return new NativeSlice<UInt32>(__bindgen_thunk_squares(count), __bindgen_free_squares);
+        }
+
+        [DllImport("golden", CallingConvention = CallingConvention.Cdecl)]
+        private static extern void __bindgen_free_squares(OwnedSliceAbi value);
+
+        [DllImport("golden", CallingConvention = CallingConvention.Cdecl)]
+        private static extern SliceAbi __bindgen_thunk_primes();
+
+        public static NativeSlice<UInt32> Primes()
+        {
+            return new NativeSlice<UInt32>(__bindgen_thunk_primes());
+        }
+    }
+}
+
+#pragma warning restore CA1051, CA1060, CA1707, CA1815, CA5392
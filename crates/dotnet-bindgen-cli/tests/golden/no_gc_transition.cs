@@ -0,0 +1,43 @@
+/*
+ * This is a generated file, do not modify by hand.
+ * Generated by dotnet-bindgen-cli v0.1.0
+ */
+
+using System;
+using System.Runtime.InteropServices;
+
+#pragma warning disable CA1051, CA1060, CA1707, CA1815, CA5392
+
+namespace GoldenBindings
+{
+    [StructLayout(LayoutKind.Sequential)]
+    public struct SliceAbi
+    {
+        public IntPtr Ptr;
+        public UInt64 Len;
+    }
+
+    public static class TopLevelMethods
+    {
+        public const UInt64 BindingVersion = 0x1866A9C0A7D7D227UL;
+
+        [DllImport("golden", CallingConvention = CallingConvention.Cdecl)]
+        [SuppressGCTransition]
+        private static extern UInt64 __bindgen_thunk_now_ticks();
+
+        public static UInt64 NowTicks()
+        {
+            return __bindgen_thunk_now_ticks();
+        }
+
+        [DllImport("golden", CallingConvention = CallingConvention.Cdecl)]
+        private static extern void __bindgen_thunk_flush();
+
+        public static void Flush()
+        {
+            __bindgen_thunk_flush();
+        }
+    }
+}
+
+#pragma warning restore CA1051, CA1060, CA1707, CA1815, CA5392
@@ -0,0 +1,55 @@
+/*
+ * This is a generated file, do not modify by hand.
+ * Generated by dotnet-bindgen-cli v0.1.0
+ */
+
+using System;
+using System.Runtime.InteropServices;
+
+#pragma warning disable CA1051, CA1060, CA1707, CA1815, CA5392
+
+namespace GoldenBindings
+{
+    [StructLayout(LayoutKind.Sequential)]
+    public struct Scene
+    {
+    }
+
+    [StructLayout(LayoutKind.Sequential)]
+    public struct SliceAbi
+    {
+        public IntPtr Ptr;
+        public UInt64 Len;
+    }
+
+    public static class TopLevelMethods
+    {
+        public const UInt64 BindingVersion = 0x40B791E96C53944CUL;
+
+        [DllImport("golden", CallingConvention = CallingConvention.Cdecl)]
+        private static extern unsafe Scene* __bindgen_thunk_scene_new();
+
+        public static unsafe Scene* SceneNew()
+        {
+            return __bindgen_thunk_scene_new();
+        }
+
+        [DllImport("golden", CallingConvention = CallingConvention.Cdecl)]
+        private static extern unsafe UInt32 __bindgen_thunk_scene_tick(Scene* scene, UInt32 delta);
+
+        public static unsafe UInt32 SceneTick(Scene* scene, UInt32 delta)
+        {
+            return __bindgen_thunk_scene_tick(scene, delta);
+        }
+
+        [DllImport("golden", CallingConvention = CallingConvention.Cdecl)]
+        private static extern unsafe void __bindgen_thunk_scene_free(Scene* scene);
+
+        public static unsafe void SceneFree(Scene* scene)
+        {
+            __bindgen_thunk_scene_free(scene);
+        }
+    }
+}
+
+#pragma warning restore CA1051, CA1060, CA1707, CA1815, CA5392
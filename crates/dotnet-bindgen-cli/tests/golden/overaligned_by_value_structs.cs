@@ -0,0 +1,109 @@
+/*
+ * This is a generated file, do not modify by hand.
+ * Generated by dotnet-bindgen-cli v0.1.0
+ */
+
+using System;
+using System.Runtime.InteropServices;
+
+#pragma warning disable CA1051, CA1060, CA1707, CA1815, CA5392
+
+namespace GoldenBindings
+{
+    [StructLayout(LayoutKind.Sequential)]
+    public struct Lanes16
+    {
+        public unsafe fixed Byte Lanes[16];
+
+        #if NETCOREAPP2_1_OR_GREATER || NETSTANDARD2_1_OR_GREATER
+        public unsafe Span<Byte> LanesSpan
+        {
+            get
+            {
+                return System.Runtime.InteropServices.MemoryMarshal.CreateSpan(ref Lanes[0], 16);
+            }
+        }
+        #endif
+    }
+
+    [StructLayout(LayoutKind.Sequential)]
+    public struct Lanes32
+    {
+        public unsafe fixed Byte Lanes[32];
+
+        #if NETCOREAPP2_1_OR_GREATER || NETSTANDARD2_1_OR_GREATER
+        public unsafe Span<Byte> LanesSpan
+        {
+            get
+            {
+                return System.Runtime.InteropServices.MemoryMarshal.CreateSpan(ref Lanes[0], 32);
+            }
+        }
+        #endif
+    }
+
+    [StructLayout(LayoutKind.Sequential)]
+    public struct Lanes64
+    {
+        public unsafe fixed Byte Lanes[64];
+
+        #if NETCOREAPP2_1_OR_GREATER || NETSTANDARD2_1_OR_GREATER
+        public unsafe Span<Byte> LanesSpan
+        {
+            get
+            {
+                return System.Runtime.InteropServices.MemoryMarshal.CreateSpan(ref Lanes[0], 64);
+            }
+        }
+        #endif
+    }
+
+    [StructLayout(LayoutKind.Sequential)]
+    public struct SliceAbi
+    {
+        public IntPtr Ptr;
+        public UInt64 Len;
+    }
+
+    public static class TopLevelMethods
+    {
+        public const UInt64 BindingVersion = 0x784749D156F4FD3CUL;
+
+        static TopLevelMethods()
+        {
+            if (Marshal.SizeOf<Lanes16>() != 16) throw new InvalidOperationException("The size of Lanes16 in C# doesn't match its size of 16 bytes in Rust");
+            if (Marshal.SizeOf<Lanes32>() != 32) throw new InvalidOperationException("The size of Lanes32 in C# doesn't match its size of 32 bytes in Rust");
+            if (Marshal.SizeOf<Lanes64>() != 64) throw new InvalidOperationException("The size of Lanes64 in C# doesn't match its size of 64 bytes in Rust");
+        }
+
+        [DllImport("golden", CallingConvention = CallingConvention.Cdecl)]
+        private static extern Byte __bindgen_thunk_first_lane_16(Lanes16 value);
+
+        public static Byte FirstLane16(Lanes16 value)
+        {
+            return __bindgen_thunk_first_lane_16(value);
+        }
+
+        [DllImport("golden", CallingConvention = CallingConvention.Cdecl)]
+        private static extern Byte __bindgen_thunk_first_lane_32(Lanes32 value);
+
+        public static Byte FirstLane32(Lanes32 value)
+        {
+            return __bindgen_thunk_first_lane_32(value);
+        }
+
+        [DllImport("golden", CallingConvention = CallingConvention.Cdecl)]
+        private static extern Byte __bindgen_thunk_first_lane_64(Lanes64 value);
+
+        public static Byte FirstLane64(Lanes64 value)
+        {
+            return __bindgen_thunk_first_lane_64(value);
+        }
+
+        public static void EnsureValidated()
+        {
+        }
+    }
+}
+
+#pragma warning restore CA1051, CA1060, CA1707, CA1815, CA5392
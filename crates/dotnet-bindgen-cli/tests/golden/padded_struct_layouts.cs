@@ -0,0 +1,84 @@
+/*
+ * This is a generated file, do not modify by hand.
+ * Generated by dotnet-bindgen-cli v0.1.0
+ */
+
+using System;
+using System.Runtime.InteropServices;
+
+#pragma warning disable CA1051, CA1060, CA1707, CA1815, CA5392
+
+namespace GoldenBindings
+{
+    [StructLayout(LayoutKind.Sequential)]
+    public struct Padded
+    {
+        public Byte A;
+        public UInt32 B;
+        public UInt16 C;
+        public UInt64 D;
+    }
+
+    [StructLayout(LayoutKind.Sequential)]
+    public struct Nested
+    {
+        public Byte Tag;
+        public Padded Inner;
+    }
+
+    [StructLayout(LayoutKind.Sequential)]
+    public struct Trailing
+    {
+        public Double Value;
+        public Byte Flag;
+    }
+
+    [StructLayout(LayoutKind.Sequential)]
+    public struct SliceAbi
+    {
+        public IntPtr Ptr;
+        public UInt64 Len;
+    }
+
+    public static class TopLevelMethods
+    {
+        public const UInt64 BindingVersion = 0xDB260F799A8D19F1UL;
+
+        static TopLevelMethods()
+        {
+            if (Marshal.SizeOf<Padded>() != 24) throw new InvalidOperationException("The size of Padded in C# doesn't match its size of 24 bytes in Rust");
+            if (Marshal.OffsetOf<PaddedAlignmentProbe>("Value").ToInt64() != 8) throw new InvalidOperationException("The alignment of Padded in C# doesn't match its alignment of 8 bytes in Rust");
+            if (Marshal.SizeOf<Nested>() != 32) throw new InvalidOperationException("The size of Nested in C# doesn't match its size of 32 bytes in Rust");
+            if (Marshal.OffsetOf<NestedAlignmentProbe>("Value").ToInt64() != 8) throw new InvalidOperationException("The alignment of Nested in C# doesn't match its alignment of 8 bytes in Rust");
+            if (Marshal.SizeOf<Trailing>() != 16) throw new InvalidOperationException("The size of Trailing in C# doesn't match its size of 16 bytes in Rust");
+            if (Marshal.OffsetOf<TrailingAlignmentProbe>("Value").ToInt64() != 8) throw new InvalidOperationException("The alignment of Trailing in C# doesn't match its alignment of 8 bytes in Rust");
+        }
+
+        public static void EnsureValidated()
+        {
+        }
+
+        [StructLayout(LayoutKind.Sequential)]
+        private struct PaddedAlignmentProbe
+        {
+            public Byte Pad;
+            public Padded Value;
+        }
+
+        [StructLayout(LayoutKind.Sequential)]
+        private struct NestedAlignmentProbe
+        {
+            public Byte Pad;
+            public Nested Value;
+        }
+
+        [StructLayout(LayoutKind.Sequential)]
+        private struct TrailingAlignmentProbe
+        {
+            public Byte Pad;
+            public Trailing Value;
+        }
+    }
+}
+
+#pragma warning restore CA1051, CA1060, CA1707, CA1815, CA5392
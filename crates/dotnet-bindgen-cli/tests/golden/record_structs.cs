@@ -0,0 +1,76 @@
+/*
+ * This is a generated file, do not modify by hand.
+ * Generated by dotnet-bindgen-cli v0.1.0
+ */
+
+using System;
+using System.Runtime.InteropServices;
+
+#pragma warning disable CA1051, CA1060, CA1707, CA1815, CA5392
+
+namespace GoldenBindings
+{
+    [StructLayout(LayoutKind.Sequential)]
+    public readonly record struct Point(Int32 X, Int32 Y)
+    {
+        static Point()
+        {
+            TopLevelMethods.EnsureValidated();
+        }
+
+        [DllImport("golden", CallingConvention = CallingConvention.Cdecl)]
+        private static extern Single __bindgen_thunk_length(in Point self);
+
+        public Single Length()
+        {
+            return __bindgen_thunk_length(this);
+        }
+    }
+
+    [StructLayout(LayoutKind.Sequential)]
+    public struct Segment
+    {
+        public Point Start;
+        public Point End;
+    }
+
+    [StructLayout(LayoutKind.Sequential)]
+    public struct SliceAbi
+    {
+        public IntPtr Ptr;
+        public UInt64 Len;
+    }
+
+    public static class TopLevelMethods
+    {
+        public const UInt64 BindingVersion = 0x4D93B681C27CD7B2UL;
+
+        static TopLevelMethods()
+        {
+            if (Marshal.SizeOf<Point>() != 8) throw new InvalidOperationException("The size of Point in C# doesn't match its size of 8 bytes in Rust");
+            if (Marshal.OffsetOf<PointAlignmentProbe>("Value").ToInt64() != 4) throw new InvalidOperationException("The alignment of Point in C# doesn't match its alignment of 4 bytes in Rust");
+            if (Marshal.SizeOf<Segment>() != 16) throw new InvalidOperationException("The size of Segment in C# doesn't match its size of 16 bytes in Rust");
+            if (Marshal.OffsetOf<SegmentAlignmentProbe>("Value").ToInt64() != 4) throw new InvalidOperationException("The alignment of Segment in C# doesn't match its alignment of 4 bytes in Rust");
+        }
+
+        public static void EnsureValidated()
+        {
+        }
+
+        [StructLayout(LayoutKind.Sequential)]
+        private struct PointAlignmentProbe
+        {
+            public Byte Pad;
+            public Point Value;
+        }
+
+        [StructLayout(LayoutKind.Sequential)]
+        private struct SegmentAlignmentProbe
+        {
+            public Byte Pad;
+            public Segment Value;
+        }
+    }
+}
+
+#pragma warning restore CA1051, CA1060, CA1707, CA1815, CA5392
@@ -0,0 +1,93 @@
+/*
+ * This is a generated file, do not modify by hand.
+ * Generated by dotnet-bindgen-cli v0.1.0
+ */
+
+using System;
+using System.Runtime.InteropServices;
+
+#pragma warning disable CA1051, CA1060, CA1707, CA1815, CA5392
+
+namespace GoldenBindings
+{
+    [StructLayout(LayoutKind.Sequential)]
+    public struct SliceAbi
+    {
+        public IntPtr Ptr;
+        public UInt64 Len;
+    }
+
+    [StructLayout(LayoutKind.Sequential)]
+    public struct OwnedSliceAbi
+    {
+        public IntPtr Ptr;
+        public UInt64 Len;
+        public UInt64 Cap;
+    }
+
+    public sealed class RustBuffer : IDisposable
+    {
+        private OwnedSliceAbi abi;
+
+        public RustBuffer(Int32 length)
+        {
+            ((this).abi).Ptr = __bindgen_alloc(checked((UInt64)(length)));
+            ((this).abi).Len = (UInt64)(length);
+            ((this).abi).Cap = (UInt64)(length);
+        }
+
+        ~RustBuffer()
+        {
+            Free();
+        }
+
+        public unsafe Span<Byte> AsSpan()
+        {
+            return new Span<Byte>((void*)(((this).abi).Ptr), checked((Int32)(((this).abi).Len)));
+        }
+
+        public OwnedSliceAbi Transfer()
+        {
+            OwnedSliceAbi _gen0;
+            _gen0 = (this).abi;
+            (this).abi = new OwnedSliceAbi();
+            GC.SuppressFinalize(this);
+            return _gen0;
+        }
+
+        public void Dispose()
+        {
+            Free();
+            GC.SuppressFinalize(this);
+        }
+
+        private void Free()
+        {
+            __bindgen_dealloc(((this).abi).Ptr, ((this).abi).Cap);
+            (this).abi = new OwnedSliceAbi();
+        }
+
+        [DllImport("golden", CallingConvention = CallingConvention.Cdecl)]
+        private static extern IntPtr __bindgen_alloc(UInt64 len);
+
+        [DllImport("golden", CallingConvention = CallingConvention.Cdecl)]
+        private static extern void __bindgen_dealloc(IntPtr ptr, UInt64 len);
+    }
+
+    public static class TopLevelMethods
+    {
+        public const UInt64 BindingVersion = 0xB022A352B92FE295UL;
+
+        [DllImport("golden", CallingConvention = CallingConvention.Cdecl)]
+        private static extern UInt64 __bindgen_thunk_consume(OwnedSliceAbi buffer);
+
+        public static UInt64 Consume(RustBuffer buffer)
+        {
+            OwnedSliceAbi _gen0;
+            _gen0 = buffer.Transfer();
+            return __bindgen_thunk_consume(_gen0);
+        }
+    }
+}
+
+#pragma warning restore CA1051, CA1060, CA1707, CA1815, CA5392
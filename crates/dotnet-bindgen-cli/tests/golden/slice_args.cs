@@ -0,0 +1,79 @@
+/*
+ * This is a generated file, do not modify by hand.
+ * Generated by dotnet-bindgen-cli v0.1.0
+ */
+
+using System;
+using System.Runtime.InteropServices;
+
+#pragma warning disable CA1051, CA1060, CA1707, CA1815, CA5392
+
+namespace GoldenBindings
+{
+    [StructLayout(LayoutKind.Sequential)]
+    public struct SliceAbi
+    {
+        public IntPtr Ptr;
+        public UInt64 Len;
+    }
+
+    public static class TopLevelMethods
+    {
+        public const UInt64 BindingVersion = 0x9F782A81754BD4CFUL;
+
+        [DllImport("golden", CallingConvention = CallingConvention.Cdecl)]
+        private static extern Int32 __bindgen_thunk_sum(SliceAbi values);
+
+        public static Int32 Sum(Int32[] values)
+        {
+            if (values is null) throw new ArgumentNullException(nameof(values));
+            SliceAbi _gen0;
+            (_gen0).Len = (UInt64)((values).Length);
+            unsafe
+            {
+                fixed (Int32* _gen1 = values)
+                {
+                    (_gen0).Ptr = (IntPtr)(_gen1);
+                    return __bindgen_thunk_sum(_gen0);
+                }
+            }
+        }
+
+        [DllImport("golden", CallingConvention = CallingConvention.Cdecl)]
+        private static extern void __bindgen_thunk_fill(SliceAbi buffer);
+
+        public static void Fill(Byte[] buffer)
+        {
+            if (buffer is null) throw new ArgumentNullException(nameof(buffer));
+            SliceAbi _gen0;
+            (_gen0).Len = (UInt64)((buffer).Length);
+            unsafe
+            {
+                fixed (Byte* _gen1 = buffer)
+                {
+                    (_gen0).Ptr = (IntPtr)(_gen1);
+                    __bindgen_thunk_fill(_gen0);
+                }
+            }
+        }
+
+        [DllImport("golden", CallingConvention = CallingConvention.Cdecl)]
+        private static extern UInt64 __bindgen_thunk_count(SliceAbi values);
+
+        public static UInt64 Count(Int32[] values)
+        {
+            SliceAbi _gen0;
+            (_gen0).Len = (UInt64)(( (values == null) ? (0) : ((values).Length) ));
+            unsafe
+            {
+                fixed (Int32* _gen1 = values)
+                {
+                    (_gen0).Ptr = ( (values != null && _gen1 == null) ? (new IntPtr(1)) : ((IntPtr)(_gen1)) );
+                    return __bindgen_thunk_count(_gen0);
+                }
+            }
+        }
+    }
+}
+
+#pragma warning restore CA1051, CA1060, CA1707, CA1815, CA5392
@@ -0,0 +1,81 @@
+/*
+ * This is a generated file, do not modify by hand.
+ * Generated by dotnet-bindgen-cli v0.1.0
+ */
+
+using System;
+using System.Runtime.InteropServices;
+
+#pragma warning disable CA1051, CA1060, CA1707, CA1815, CA5392
+
+namespace GoldenBindings
+{
+    [StructLayout(LayoutKind.Sequential)]
+    public struct SliceAbi
+    {
+        public IntPtr Ptr;
+        public UInt64 Len;
+    }
+
+    [StructLayout(LayoutKind.Sequential)]
+    public readonly ref struct SliceView
+    {
+        public readonly IntPtr Ptr;
+        public readonly Int32 Length;
+
+        public SliceView(IntPtr ptr, Int32 length)
+        {
+            (this).Ptr = ptr;
+            (this).Length = length;
+        }
+
+        public SliceAbi ToAbi()
+        {
+            SliceAbi _gen0;
+            (_gen0).Ptr = (this).Ptr;
+            (_gen0).Len = (UInt64)((this).Length);
+            return _gen0;
+        }
+    }
+
+    public static class TopLevelMethods
+    {
+        public const UInt64 BindingVersion = 0x2D0210B3AC9C052AUL;
+
+        [DllImport("golden", CallingConvention = CallingConvention.Cdecl)]
+        private static extern Int64 __bindgen_thunk_sum(SliceAbi values);
+
+        public static Int64 Sum(Int32[] values)
+        {
+            if (values is null) throw new ArgumentNullException(nameof(values));
+            unsafe
+            {
+                fixed (Int32* _gen1 = values)
+                {
+                    SliceAbi _gen0;
+                    _gen0 = new SliceView((IntPtr)(_gen1), (values).Length).ToAbi();
+                    return __bindgen_thunk_sum(_gen0);
+                }
+            }
+        }
+
+        [DllImport("golden", CallingConvention = CallingConvention.Cdecl)]
+        private static extern void __bindgen_thunk_fill(SliceAbi buffer, Byte value);
+
+        public static void Fill(Byte[] buffer, Byte value)
+        {
+            if (buffer is null) throw new ArgumentNullException(nameof(buffer));
+            unsafe
+            {
+                fixed (Byte* _gen1 = buffer)
+                {
+                    SliceAbi _gen0;
+                    _gen0 = new SliceView((IntPtr)(_gen1), (buffer).Length).ToAbi();
+                    __bindgen_thunk_fill(_gen0, value);
+                }
+            }
+        }
+    }
+}
+
+#pragma warning restore CA1051, CA1060, CA1707, CA1815, CA5392
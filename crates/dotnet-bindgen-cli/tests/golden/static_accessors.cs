@@ -0,0 +1,68 @@
+/*
+ * This is a generated file, do not modify by hand.
+ * Generated by dotnet-bindgen-cli v0.1.0
+ */
+
+using System;
+using System.Runtime.InteropServices;
+
+#pragma warning disable CA1051, CA1060, CA1707, CA1815, CA5392
+
+namespace GoldenBindings
+{
+    [StructLayout(LayoutKind.Sequential)]
+    public struct Point
+    {
+        public Int32 X;
+        public Int32 Y;
+    }
+
+    [StructLayout(LayoutKind.Sequential)]
+    public struct SliceAbi
+    {
+        public IntPtr Ptr;
+        public UInt64 Len;
+    }
+
+    public static class TopLevelMethods
+    {
+        private static readonly IntPtr libraryHandle = NativeLibrary.Load("golden", System.Reflection.Assembly.GetExecutingAssembly(), null);
+        public const UInt64 BindingVersion = 0x6DA43BF9FC19BF7FUL;
+
+        static TopLevelMethods()
+        {
+            if (Marshal.SizeOf<Point>() != 8) throw new InvalidOperationException("The size of Point in C# doesn't match its size of 8 bytes in Rust");
+            if (Marshal.OffsetOf<PointAlignmentProbe>("Value").ToInt64() != 4) throw new InvalidOperationException("The alignment of Point in C# doesn't match its alignment of 4 bytes in Rust");
+        }
+
+        public static UInt32 ReadCallCount()
+        {
+            IntPtr _gen0;
+            _gen0 = NativeLibrary.GetExport(libraryHandle, "CALL_COUNT");
+            unsafe
+            {
+                return *((UInt32*)(_gen0));
+            }
+        }
+
+        public static Point GetOrigin()
+        {
+            IntPtr _gen0;
+            _gen0 = NativeLibrary.GetExport(libraryHandle, "ORIGIN");
+            return Marshal.PtrToStructure<Point>(_gen0);
+        }
+
+        public static void EnsureValidated()
+        {
+        }
+
+        [StructLayout(LayoutKind.Sequential)]
+        private struct PointAlignmentProbe
+        {
+            public Byte Pad;
+            public Point Value;
+        }
+    }
+}
+
+#pragma warning restore CA1051, CA1060, CA1707, CA1815, CA5392
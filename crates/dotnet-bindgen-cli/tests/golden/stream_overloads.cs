@@ -0,0 +1,166 @@
+/*
+ * This is a generated file, do not modify by hand.
+ * Generated by dotnet-bindgen-cli v0.1.0
+ */
+
+using System;
+using System.Runtime.InteropServices;
+
+#pragma warning disable CA1051, CA1060, CA1707, CA1815, CA5392
+
+namespace GoldenBindings
+{
+    [StructLayout(LayoutKind.Sequential)]
+    public struct SliceAbi
+    {
+        public IntPtr Ptr;
+        public UInt64 Len;
+    }
+
+    internal static class PooledStream
+    {
+        public static Byte[] ReadToEnd(System.IO.Stream stream, ref Int32 len)
+        {
+            Byte[] buf;
+            buf = System.Buffers.ArrayPool<Byte>.Shared.Rent(4096);
+            bool done;
+            done = false;
+            try
+            {
+                Int32 read;
+                read = 1;
+                while (read != 0)
+                {
+                    read = stream.Read(buf, len, (buf).Length - len);
+                    len = len + read;
+                    if (len == (buf).Length)
+                    {
+                        Byte[] grown;
+                        grown = System.Buffers.ArrayPool<Byte>.Shared.Rent(checked((buf).Length * 2));
+                        System.Buffer.BlockCopy(buf, 0, grown, 0, len);
+                        System.Buffers.ArrayPool<Byte>.Shared.Return(buf);
+                        buf = grown;
+                    }
+                }
+                done = true;
+            }
+            finally
+            {
+                if (!(done))
+                {
+                    System.Buffers.ArrayPool<Byte>.Shared.Return(buf);
+                }
+            }
+            return buf;
+        }
+    }
+
+    public static class TopLevelMethods
+    {
+        public const UInt64 BindingVersion = 0x2C395AD2121E8213UL;
+
+        [DllImport("golden", CallingConvention = CallingConvention.Cdecl)]
+        private static extern UInt32 __bindgen_thunk_checksum(SliceAbi data, UInt32 seed);
+
+        public static UInt32 Checksum(Byte[] data, UInt32 seed)
+        {
+            if (data is null) throw new ArgumentNullException(nameof(data));
+            return Checksum(new Span<Byte>(data), seed);
+        }
+
+        private static UInt32 Checksum(Span<Byte> data, UInt32 seed)
+        {
+            SliceAbi _gen0;
+            (_gen0).Len = (UInt64)((data).Length);
+            unsafe
+            {
+                fixed (Byte* _gen1 = data)
+                {
+                    (_gen0).Ptr = (IntPtr)(_gen1);
+                    return __bindgen_thunk_checksum(_gen0, seed);
+                }
+            }
+        }
+
+        public static UInt32 ChecksumFromStream(System.IO.Stream data, UInt32 seed)
+        {
+            if (data is null) throw new ArgumentNullException(nameof(data));
+            Int32 _gen0;
+            _gen0 = 0;
+            Byte[] _gen1;
+            _gen1 = PooledStream.ReadToEnd(data, ref _gen0);
+            try
+            {
+                return Checksum(new Span<Byte>(_gen1, 0, _gen0), seed);
+            }
+            finally
+            {
+                System.Buffers.ArrayPool<Byte>.Shared.Return(_gen1);
+            }
+        }
+
+        [DllImport("golden", CallingConvention = CallingConvention.Cdecl)]
+        private static extern void __bindgen_thunk_copy_into(SliceAbi source, SliceAbi key);
+
+        public static void CopyInto(Byte[] source, Byte[] key)
+        {
+            if (source is null) throw new ArgumentNullException(nameof(source));
+            if (key is null) throw new ArgumentNullException(nameof(key));
+            CopyInto(new Span<Byte>(source), new Span<Byte>(key));
+        }
+
+        private static void CopyInto(Span<Byte> source, Span<Byte> key)
+        {
+            SliceAbi _gen0;
+            (_gen0).Len = (UInt64)((source).Length);
+            unsafe
+            {
+                fixed (Byte* _gen1 = source)
+                {
+                    (_gen0).Ptr = (IntPtr)(_gen1);
+                    SliceAbi _gen2;
+                    (_gen2).Len = (UInt64)((key).Length);
+                    unsafe
+                    {
+                        fixed (Byte* _gen3 = key)
+                        {
+                            (_gen2).Ptr = (IntPtr)(_gen3);
+                            __bindgen_thunk_copy_into(_gen0, _gen2);
+                        }
+                    }
+                }
+            }
+        }
+
+        public static void CopyIntoFromStream(System.IO.Stream source, System.IO.Stream key)
+        {
+            if (source is null) throw new ArgumentNullException(nameof(source));
+            if (key is null) throw new ArgumentNullException(nameof(key));
+            Int32 _gen0;
+            _gen0 = 0;
+            Byte[] _gen1;
+            _gen1 = PooledStream.ReadToEnd(source, ref _gen0);
+            try
+            {
+                Int32 _gen2;
+                _gen2 = 0;
+                Byte[] _gen3;
+                _gen3 = PooledStream.ReadToEnd(key, ref _gen2);
+                try
+                {
+                    CopyInto(new Span<Byte>(_gen1, 0, _gen0), new Span<Byte>(_gen3, 0, _gen2));
+                }
+                finally
+                {
+                    System.Buffers.ArrayPool<Byte>.Shared.Return(_gen3);
+                }
+            }
+            finally
+            {
+                System.Buffers.ArrayPool<Byte>.Shared.Return(_gen1);
+            }
+        }
+    }
+}
+
+#pragma warning restore CA1051, CA1060, CA1707, CA1815, CA5392
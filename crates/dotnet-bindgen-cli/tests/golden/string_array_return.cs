@@ -0,0 +1,53 @@
+/*
+ * This is a generated file, do not modify by hand.
+ * Generated by dotnet-bindgen-cli v0.1.0
+ */
+
+using System;
+using System.Runtime.InteropServices;
+
+#pragma warning disable CA1051, CA1060, CA1707, CA1815, CA5392
+
+namespace GoldenBindings
+{
+    [StructLayout(LayoutKind.Sequential)]
+    public struct SliceAbi
+    {
+        public IntPtr Ptr;
+        public UInt64 Len;
+    }
+
+    [StructLayout(LayoutKind.Sequential)]
+    public struct OwnedSliceAbi
+    {
+        public IntPtr Ptr;
+        public UInt64 Len;
+        public UInt64 Cap;
+    }
+
+    public static class TopLevelMethods
+    {
+        public const UInt64 BindingVersion = 0xAC95379C7B816EBBUL;
+
+        [DllImport("golden", CallingConvention = CallingConvention.Cdecl)]
+        private static extern OwnedSliceAbi __bindgen_thunk_names();
+
+        public static string[] Names()
+        {
+            OwnedSliceAbi _gen0;
+            _gen0 = __bindgen_thunk_names();
+            unsafe
+            {
+                string[] _gen1;
+                _gen1 = Array.ConvertAll(new Span<OwnedSliceAbi>((void*)((_gen0).Ptr), checked((Int32)((_gen0).Len))).ToArray(), s => System.Text.Encoding.UTF8.GetString((Byte*)((s).Ptr), checked((Int32)((s).Len))));
+                __bindgen_free_names(_gen0);
+                return _gen1;
+            }
+        }
+
+        [DllImport("golden", CallingConvention = CallingConvention.Cdecl)]
+        private static extern void __bindgen_free_names(OwnedSliceAbi value);
+    }
+}
+
+#pragma warning restore CA1051, CA1060, CA1707, CA1815, CA5392
@@ -0,0 +1,90 @@
+/*
+ * This is a generated file, do not modify by hand.
+ * Generated by dotnet-bindgen-cli v0.1.0
+ */
+
+using System;
+using System.Runtime.InteropServices;
+
+#pragma warning disable CA1051, CA1060, CA1707, CA1815, CA5392
+
+namespace GoldenBindings
+{
+    [StructLayout(LayoutKind.Sequential)]
+    public struct Sample : IEquatable<Sample>
+    {
+        public Double Value;
+        public Int32 Count;
+
+        public bool Equals(Sample other)
+        {
+            return Value.Equals((other).Value) && Count.Equals((other).Count);
+        }
+
+        public override bool Equals(object obj)
+        {
+            return obj is Sample && Equals((Sample)(obj));
+        }
+
+        public override Int32 GetHashCode()
+        {
+            unchecked
+            {
+                Int32 _gen0;
+                _gen0 = 17;
+                _gen0 = _gen0 * 31 + Value.GetHashCode();
+                _gen0 = _gen0 * 31 + Count.GetHashCode();
+                return _gen0;
+            }
+        }
+
+        public static bool operator ==(Sample left, Sample right)
+        {
+            return left.Equals(right);
+        }
+
+        public static bool operator !=(Sample left, Sample right)
+        {
+            return !(left.Equals(right));
+        }
+    }
+
+    [StructLayout(LayoutKind.Sequential)]
+    public struct SliceAbi
+    {
+        public IntPtr Ptr;
+        public UInt64 Len;
+    }
+
+    public static class TopLevelMethods
+    {
+        public const UInt64 BindingVersion = 0xA4F4D1EDEDF72BC1UL;
+
+        static TopLevelMethods()
+        {
+            if (Marshal.SizeOf<Sample>() != 16) throw new InvalidOperationException("The size of Sample in C# doesn't match its size of 16 bytes in Rust");
+            if (Marshal.OffsetOf<SampleAlignmentProbe>("Value").ToInt64() != 8) throw new InvalidOperationException("The alignment of Sample in C# doesn't match its alignment of 8 bytes in Rust");
+        }
+
+        [DllImport("golden", CallingConvention = CallingConvention.Cdecl)]
+        private static extern Double __bindgen_thunk_mean(Sample sample);
+
+        public static Double Mean(Sample sample)
+        {
+            return __bindgen_thunk_mean(sample);
+        }
+
+        public static void EnsureValidated()
+        {
+        }
+
+        [StructLayout(LayoutKind.Sequential)]
+        private struct SampleAlignmentProbe
+        {
+            public Byte Pad;
+            public Sample Value;
+        }
+    }
+}
+
+#pragma warning restore CA1051, CA1060, CA1707, CA1815, CA5392
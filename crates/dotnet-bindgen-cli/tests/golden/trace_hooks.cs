@@ -0,0 +1,143 @@
+/*
+ * This is a generated file, do not modify by hand.
+ * Generated by dotnet-bindgen-cli v0.1.0
+ */
+
+using System;
+using System.Runtime.InteropServices;
+
+#pragma warning disable CA1051, CA1060, CA1707, CA1815, CA5392
+
+namespace GoldenBindings
+{
+    [StructLayout(LayoutKind.Sequential)]
+    public partial struct Point
+    {
+        public Int32 X;
+        public Int32 Y;
+
+        static Point()
+        {
+            TopLevelMethods.EnsureValidated();
+        }
+
+        [DllImport("golden", CallingConvention = CallingConvention.Cdecl)]
+        private static extern Double __bindgen_thunk_length(in Point self);
+
+        public Double Length()
+        {
+            OnNativeCallEnter("Length", new object[] { });
+            Double _gen0;
+            _gen0 = __bindgen_thunk_length(this);
+            OnNativeCallExit("Length", _gen0);
+            return _gen0;
+        }
+
+        static partial void OnNativeCallEnter(string method, object[] args);
+
+        static partial void OnNativeCallExit(string method, object result);
+    }
+
+    [StructLayout(LayoutKind.Sequential)]
+    public struct SliceAbi
+    {
+        public IntPtr Ptr;
+        public UInt64 Len;
+    }
+
+    [StructLayout(LayoutKind.Sequential)]
+    public struct OwnedSliceAbi
+    {
+        public IntPtr Ptr;
+        public UInt64 Len;
+        public UInt64 Cap;
+    }
+
+    public static partial class TopLevelMethods
+    {
+        public const UInt64 BindingVersion = 0x04C2AA656CD17A14UL;
+
+        static TopLevelMethods()
+        {
+            if (Marshal.SizeOf<Point>() != 8) throw new InvalidOperationException("The size of Point in C# doesn't match its size of 8 bytes in Rust");
+            if (Marshal.OffsetOf<PointAlignmentProbe>("Value").ToInt64() != 4) throw new InvalidOperationException("The alignment of Point in C# doesn't match its alignment of 4 bytes in Rust");
+        }
+
+        [DllImport("golden", CallingConvention = CallingConvention.Cdecl)]
+        private static extern OwnedSliceAbi __bindgen_thunk_repeat(SliceAbi text, SliceAbi counts, ref Byte invalidArg);
+
+        public static string Repeat(string text, UInt32[] counts)
+        {
+            OnNativeCallEnter("Repeat", new object[] { null, null });
+            if (text is null) throw new ArgumentNullException(nameof(text));
+            if (counts is null) throw new ArgumentNullException(nameof(counts));
+            Byte[] _gen1;
+            _gen1 = new System.Text.UTF8Encoding(false, true).GetBytes(text);
+            SliceAbi _gen0;
+            (_gen0).Len = (UInt64)((_gen1).Length);
+            unsafe
+            {
+                fixed (Byte* _gen2 = _gen1)
+                {
+                    (_gen0).Ptr = (IntPtr)(_gen2);
+                    SliceAbi _gen3;
+                    (_gen3).Len = (UInt64)((counts).Length);
+                    unsafe
+                    {
+                        fixed (UInt32* _gen4 = counts)
+                        {
+                            (_gen3).Ptr = (IntPtr)(_gen4);
+                            Byte _gen5;
+                            _gen5 = 0;
+                            OwnedSliceAbi _gen6;
+                            _gen6 = __bindgen_thunk_repeat(_gen0, _gen3, ref _gen5);
+                            if (_gen5 == 1) throw new ArgumentException("A string argument was not valid UTF-8");
+                            OwnedSliceAbi _gen7;
+                            _gen7 = _gen6;
+                            unsafe
+                            {
+                                string _gen8;
+                                _gen8 = System.Text.Encoding.UTF8.GetString((Byte*)((_gen7).Ptr), checked((Int32)((_gen7).Len)));
+                                __bindgen_free_repeat(_gen7);
+                                string _gen9;
+                                _gen9 = _gen8;
+                                OnNativeCallExit("Repeat", null);
+                                return _gen9;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        [DllImport("golden", CallingConvention = CallingConvention.Cdecl)]
+        private static extern void __bindgen_free_repeat(OwnedSliceAbi value);
+
+        [DllImport("golden", CallingConvention = CallingConvention.Cdecl)]
+        private static extern void __bindgen_thunk_reset();
+
+        public static void Reset()
+        {
+            OnNativeCallEnter("Reset", new object[] { });
+            __bindgen_thunk_reset();
+            OnNativeCallExit("Reset", null);
+        }
+
+        static partial void OnNativeCallEnter(string method, object[] args);
+
+        static partial void OnNativeCallExit(string method, object result);
+
+        public static void EnsureValidated()
+        {
+        }
+
+        [StructLayout(LayoutKind.Sequential)]
+        private struct PointAlignmentProbe
+        {
+            public Byte Pad;
+            public Point Value;
+        }
+    }
+}
+
+#pragma warning restore CA1051, CA1060, CA1707, CA1815, CA5392
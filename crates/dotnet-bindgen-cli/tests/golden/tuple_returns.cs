@@ -0,0 +1,106 @@
+/*
+ * This is a generated file, do not modify by hand.
+ * Generated by dotnet-bindgen-cli v0.1.0
+ */
+
+using System;
+using System.Runtime.InteropServices;
+
+#pragma warning disable CA1051, CA1060, CA1707, CA1815, CA5392
+
+namespace GoldenBindings
+{
+    [StructLayout(LayoutKind.Sequential)]
+    public struct SliceAbi
+    {
+        public IntPtr Ptr;
+        public UInt64 Len;
+    }
+
+    [StructLayout(LayoutKind.Sequential)]
+    public struct OwnedSliceAbi
+    {
+        public IntPtr Ptr;
+        public UInt64 Len;
+        public UInt64 Cap;
+    }
+
+    [StructLayout(LayoutKind.Sequential)]
+    public struct SliceAbiInt32TupleAbi
+    {
+        public SliceAbi Item1;
+        public Int32 Item2;
+    }
+
+    [StructLayout(LayoutKind.Sequential)]
+    public struct OwnedSliceAbiUInt64TupleAbi
+    {
+        public OwnedSliceAbi Item1;
+        public UInt64 Item2;
+    }
+
+    public static class TopLevelMethods
+    {
+        public const UInt64 BindingVersion = 0x9DF1CD7BCE4E3BA1UL;
+
+        [DllImport("golden", CallingConvention = CallingConvention.Cdecl)]
+        private static extern SliceAbiInt32TupleAbi __bindgen_thunk_split_header(SliceAbi values);
+
+        public static (Int32[], Int32) SplitHeader(Int32[] values)
+        {
+            if (values is null) throw new ArgumentNullException(nameof(values));
+            SliceAbi _gen0;
+            (_gen0).Len = (UInt64)((values).Length);
+            unsafe
+            {
+                fixed (Int32* _gen1 = values)
+                {
+                    (_gen0).Ptr = (IntPtr)(_gen1);
+                    SliceAbiInt32TupleAbi _gen2;
+                    _gen2 = __bindgen_thunk_split_header(_gen0);
+                    unsafe
+                    {
+                        (Int32[], Int32) _gen3;
+                        (_gen3).Item1 = new Span<Int32>((void*)(((_gen2).Item1).Ptr), checked((Int32)(((_gen2).Item1).Len))).ToArray();
+                        (_gen3).Item2 = (_gen2).Item2;
+                        return _gen3;
+                    }
+                }
+            }
+        }
+
+        [DllImport("golden", CallingConvention = CallingConvention.Cdecl)]
+        private static extern OwnedSliceAbiUInt64TupleAbi __bindgen_thunk_take_line(SliceAbi text);
+
+        public static (string, UInt64) TakeLine(string text)
+        {
+            if (text is null) throw new ArgumentNullException(nameof(text));
+            Byte[] _gen1;
+            _gen1 = new System.Text.UTF8Encoding(false, true).GetBytes(text);
+            SliceAbi _gen0;
+            (_gen0).Len = (UInt64)((_gen1).Length);
+            unsafe
+            {
+                fixed (Byte* _gen2 = _gen1)
+                {
+                    (_gen0).Ptr = (IntPtr)(_gen2);
+                    OwnedSliceAbiUInt64TupleAbi _gen3;
+                    _gen3 = __bindgen_thunk_take_line(_gen0);
+                    unsafe
+                    {
+                        (string, UInt64) _gen4;
+                        (_gen4).Item1 = System.Text.Encoding.UTF8.GetString((Byte*)(((_gen3).Item1).Ptr), checked((Int32)(((_gen3).Item1).Len)));
+                        (_gen4).Item2 = (_gen3).Item2;
+                        __bindgen_free_take_line(_gen3);
+                        return _gen4;
+                    }
+                }
+            }
+        }
+
+        [DllImport("golden", CallingConvention = CallingConvention.Cdecl)]
+        private static extern void __bindgen_free_take_line(OwnedSliceAbiUInt64TupleAbi value);
+    }
+}
+
+#pragma warning restore CA1051, CA1060, CA1707, CA1815, CA5392
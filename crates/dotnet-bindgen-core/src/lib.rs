@@ -1,6 +1,152 @@
 //! Core types, methods, and constants to be shared between all components of the bindgen pipeline.
 //!
 //! This component is intended to be fairly minimal, to reduce the impact of having it included in client code.
+//! It only depends on `alloc`, so that `#[dotnet_bindgen]` can be used in `no_std` crates.
+//!
+//! The descriptor types implement `serde::Serialize` and `serde::Deserialize` when the optional
+//! `serde` feature is enabled.
+//!
+//! The optional `std` feature lets describe functions catch their own panics, which otherwise
+//! can't be caught by the generator loading them.
+
+#![no_std]
+
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+
+use alloc::borrow::Cow;
+use alloc::alloc::Layout;
+use alloc::boxed::Box;
+use alloc::ffi::CString;
+use alloc::string::String;
+#[cfg(target_has_atomic = "ptr")]
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::ffi::c_char;
+use core::fmt::Write;
+use core::future::Future;
+use core::mem::{ManuallyDrop, MaybeUninit};
+use core::pin::Pin;
+use core::sync::atomic::{AtomicPtr, Ordering};
+use core::time::Duration;
+
+/// Re-exports used by the code `#[dotnet_bindgen]` generates, which can't rely on the std prelude.
+#[doc(hidden)]
+pub mod __private {
+    pub use alloc::boxed::Box;
+    pub use alloc::string::ToString;
+    pub use alloc::vec;
+
+    /// Fails to compile unless `T` is `Copy`, which slice elements must be to be safely rebuilt from
+    /// the raw parts handed over by the caller.
+    pub const fn assert_copy<T: Copy>() {}
+
+    /// Fails to compile unless `T` is `FfiStable`, which the arguments and return type of a
+    /// function declared in an `extern` block must be, as there's no thunk to convert them.
+    pub const fn assert_ffi_stable<T: super::FfiStable>() {}
+
+    /// Implemented for the types a function can return without saying who owns the returned
+    /// value, which are those with nothing to free.
+    #[diagnostic::on_unimplemented(
+        message = "`{Self}` isn't Copy, so it's ambiguous whether C# owns it once returned",
+        note = "mark the function #[dotnet_bindgen(returns_owned)] or #[dotnet_bindgen(returns_borrowed)]"
+    )]
+    pub trait UnannotatedReturn {}
+
+    impl<T: Copy> UnannotatedReturn for T {}
+
+    /// Fails to compile unless `T` can be returned without a `returns_owned` or `returns_borrowed`
+    /// annotation.
+    pub const fn assert_unannotated_return<T: UnannotatedReturn>() {}
+
+    /// Fails to compile unless `T` can be returned at all, rather than only taken as an argument.
+    pub const fn assert_returnable<T: super::BindgenAbiReturn>() {}
+
+    /// Calls the body of a describe function, returning None if it panics.
+    ///
+    /// The binary carries its own copy of std, so a panic unwinding out of a describe function is
+    /// a foreign exception to the generator, which can't catch it and aborts. Without `std` there's
+    /// no way to catch it here either, so the panic is left to the binary's panic handler.
+    pub fn catch_describe_panic<F>(describe: F) -> Option<super::BindgenExportDescriptor>
+    where
+        F: FnOnce() -> super::BindgenExportDescriptor + core::panic::UnwindSafe,
+    {
+        #[cfg(feature = "std")]
+        return std::panic::catch_unwind(describe).ok();
+
+        #[cfg(not(feature = "std"))]
+        return Some(describe());
+    }
+
+    /// Drives the future returned by a `#[dotnet_bindgen(block_on)]` function to completion, on
+    /// the executor registered with `set_block_on`.
+    ///
+    /// Panics, aborting the process as the panic can't unwind into C#, if none was registered.
+    pub fn block_on<F: core::future::Future>(future: F) -> F::Output {
+        let block_on = super::BLOCK_ON.load(super::Ordering::Acquire);
+        assert!(
+            !block_on.is_null(),
+            "A #[dotnet_bindgen(block_on)] function was called before an executor was registered with set_block_on",
+        );
+
+        // Only ever stored from a BlockOn by set_block_on.
+        let block_on = unsafe { core::mem::transmute::<*mut (), super::BlockOn>(block_on) };
+
+        let mut output = None;
+        block_on(Box::pin(async {
+            output = Some(future.await);
+        }));
+        output.expect("The executor registered with set_block_on returned before the future completed")
+    }
+
+    /// Whether the given bytes are valid UTF-8, which may be a null pointer if empty.
+    pub(crate) fn is_valid_utf8(ptr: *const u8, len: usize) -> bool {
+        if len == 0 {
+            return true;
+        }
+
+        let bytes = unsafe { core::slice::from_raw_parts(ptr, len) };
+        core::str::from_utf8(bytes).is_ok()
+    }
+
+    /// Fails to compile when an argument of type `T` has to be checked for valid UTF-8, but the
+    /// macro couldn't tell so from how its type was written, eg because it's behind a type
+    /// alias, and so didn't give the thunk a way to report invalid bytes.
+    pub struct AssertNoUtf8Check<T>(core::marker::PhantomData<T>);
+
+    impl<T: super::BindgenAbiConvert> AssertNoUtf8Check<T> {
+        pub const OK: () = assert!(
+            !T::VALIDATES_UTF8,
+            "string arguments must be written as &str or Cow<str>, so their bytes can be checked",
+        );
+    }
+
+    /// The same as `AssertNoUtf8Check`, for arguments whose enum discriminants have to be checked.
+    pub struct AssertNoDiscriminantCheck<T>(core::marker::PhantomData<T>);
+
+    impl<T: super::BindgenAbiConvert> AssertNoDiscriminantCheck<T> {
+        pub const OK: () = assert!(
+            !T::CHECKS_DISCRIMINANTS,
+            "enum arguments must be written as the enum's name, or a slice of it, so their discriminants can be checked",
+        );
+    }
+}
+
+/// Runs a future to completion, blocking the calling thread until it does, eg
+/// `|future| futures::executor::block_on(future)`.
+pub type BlockOn = for<'a> fn(Pin<Box<dyn Future<Output = ()> + 'a>>);
+
+static BLOCK_ON: AtomicPtr<()> = AtomicPtr::new(core::ptr::null_mut());
+
+/// Registers the executor which `#[dotnet_bindgen(block_on)]` functions block on their futures
+/// with, replacing any registered before.
+///
+/// This must be called before any of them are, eg from a `#[dotnet_bindgen]` init function which
+/// the C# calls first, as there's no executor to fall back to.
+pub fn set_block_on(block_on: BlockOn) {
+    BLOCK_ON.store(block_on as *mut (), Ordering::Release);
+}
 
 /// Marker trait for types that are trivially stable ABI types
 pub trait FfiStable {}
@@ -9,92 +155,854 @@ macro_rules! trivially_ffi_stable {
     ($($ty:ident),*) => { $( impl FfiStable for $ty {})* }
 }
 
-trivially_ffi_stable!(i8, i16, i32, i64, u8, u16, u32, u64);
+trivially_ffi_stable!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize, f32, f64);
+
+// All reference types to FfiStable types are also FfiStable
+impl<T: FfiStable> FfiStable for &T {}
+impl<T: FfiStable> FfiStable for &mut T {}
+
+// Raw pointers are never dereferenced across the boundary, so are FfiStable whatever they point
+// to, eg an opaque type which is only ever handled by pointer.
+impl<T> FfiStable for *const T {}
+impl<T> FfiStable for *mut T {}
+
+/// Marker trait for the types a `#[repr(C)]` struct can hold while staying FfiStable.
+///
+/// Arrays can't be passed by value through an `extern` function, so aren't FfiStable themselves,
+/// but are fine as struct fields.
+pub trait FfiStableField {}
+
+impl<T: FfiStable> FfiStableField for T {}
+impl<T: FfiStableField, const N: usize> FfiStableField for [T; N] {}
+
+/// Defines how to translate a non-trivial type to/from a stable ABI type
+pub trait BindgenAbiConvert {
+    type AbiType: FfiStable;
+
+    /// Set for strings, whose `from_abi_type` needs valid UTF-8. The thunks check arguments of
+    /// these types with `is_valid_utf8` in debug builds, reporting invalid bytes to C# (see
+    /// `BindgenFunctionDescriptor::reports_invalid_args`) before anything is converted.
+    const VALIDATES_UTF8: bool = false;
+
+    /// Set for C-like enums and slices of them, whose `from_abi_type` needs every discriminant to
+    /// be one the enum defines. The thunks check these in every build, as there's no cheaper way
+    /// to convert them.
+    const CHECKS_DISCRIMINANTS: bool = false;
+
+    fn from_abi_type(abi_value: Self::AbiType) -> Self;
+
+    /// Whether the value can be converted, for types setting `VALIDATES_UTF8`.
+    fn is_valid_utf8(_abi_value: &Self::AbiType) -> bool {
+        true
+    }
+
+    /// Whether the value can be converted, for types setting `CHECKS_DISCRIMINANTS`.
+    fn has_valid_discriminants(_abi_value: &Self::AbiType) -> bool {
+        true
+    }
+}
+
+/// Defines how to translate a value back to its stable ABI type, to be returned.
+///
+/// This is every type but those which can only be borrowed on the way in, eg a `Cow`.
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` can only be taken as an argument, so can't be returned across the ffi boundary",
+    label = "can't be returned",
+    note = "return an owned value, eg a String or Vec, with #[dotnet_bindgen(returns_owned)] instead"
+)]
+pub trait BindgenAbiReturn: BindgenAbiConvert {
+    fn to_abi_type(self) -> Self::AbiType;
+}
+
+/// Types which are already FfiStable need no marshalling across the boundary
+///
+/// Any self-respecting optimiser will realise that these methods do nothing, and
+/// completely optimise them out of any release build.
+impl<T: FfiStable> BindgenAbiConvert for T {
+    type AbiType = T;
+
+    fn from_abi_type(abi_value: Self::AbiType) -> Self {
+        abi_value
+    }
+}
+
+impl<T: FfiStable> BindgenAbiReturn for T {
+    fn to_abi_type(self) -> Self::AbiType {
+        self
+    }
+}
+
+/// The element types of slices and Vecs crossing the boundary, which C# holds as `Repr`s.
+///
+/// This is every FfiStable type, plus the C-like enums `#[dotnet_bindgen]` implements it for, which
+/// are held as their repr integer as C# can put any value of it in an enum.
+///
+/// # Safety
+///
+/// `Self` must have the same layout as `Repr`, and `is_valid_repr` must only return true for
+/// `Repr`s which are also valid values of `Self`.
+pub unsafe trait BindgenSliceElem {
+    type Repr: FfiStable;
+
+    /// Set when not every `Repr` is a valid `Self`, so the elements must be checked with
+    /// `is_valid_repr` before a slice of them is formed.
+    const CHECKS_DISCRIMINANTS: bool = false;
+
+    fn is_valid_repr(_repr: &Self::Repr) -> bool {
+        true
+    }
+}
+
+unsafe impl<T: FfiStable> BindgenSliceElem for T {
+    type Repr = T;
+}
+
+/// Whether every element of a slice is a valid `T`, which may be a null pointer if empty.
+fn has_valid_elems<T: BindgenSliceElem>(abi_value: &SliceAbi<T::Repr>) -> bool {
+    if !T::CHECKS_DISCRIMINANTS || abi_value.len == 0 {
+        return true;
+    }
+
+    let reprs = unsafe { core::slice::from_raw_parts(abi_value.ptr, abi_value.len as usize) };
+    reprs.iter().all(T::is_valid_repr)
+}
+
+/// Explicitly map booleans to uint8s to cross the ffi boundary.
+///
+/// The C99 standard only says that the representation of a bool must be large enough to hold 0
+/// or 1. In practice this almost always means they are represented by a uint8_t by C compilers.
+/// The Rust specification explicitly doens't say how it might be represented.
+impl BindgenAbiConvert for bool {
+    type AbiType = u8;
+
+    fn from_abi_type(abi_value: Self::AbiType) -> Self {
+        abi_value != 0
+    }
+}
+
+impl BindgenAbiReturn for bool {
+    fn to_abi_type(self) -> Self::AbiType {
+       if self { 1 } else { 0 }
+    }
+}
+
+impl BindgenTypeDescribe for bool {
+    fn describe() -> BindgenTypeDescriptor {
+        BindgenTypeDescriptor::Bool
+    }
+}
+
+/// FfiStable representation of a fixed size array.
+///
+/// Arrays can't be passed by value through an `extern` function, but a struct wrapping one can.
+#[repr(C)]
+pub struct ArrayAbi<T: FfiStable, const N: usize> {
+    elems: [T; N],
+}
+
+impl<T: FfiStable, const N: usize> FfiStable for ArrayAbi<T, N> {}
+
+impl<T: FfiStable, const N: usize> BindgenAbiConvert for [T; N] {
+    type AbiType = ArrayAbi<T, N>;
+
+    fn from_abi_type(abi_value: Self::AbiType) -> Self {
+        abi_value.elems
+    }
+}
+
+impl<T: FfiStable, const N: usize> BindgenAbiReturn for [T; N] {
+    fn to_abi_type(self) -> Self::AbiType {
+        ArrayAbi { elems: self }
+    }
+}
+
+impl<T: FfiStable + BindgenTypeDescribe, const N: usize> BindgenTypeDescribe for [T; N] {
+    fn describe() -> BindgenTypeDescriptor {
+        let elem_type = Box::new(<T as BindgenTypeDescribe>::describe());
+        BindgenTypeDescriptor::Array { elem_type, len: N as u64 }
+    }
+}
+
+/// FfiStable representation of a slice type
+///
+/// This representation is written to look very similar to the actual underlying
+/// representation of a slice type, such that the conversion functions are likely
+/// to optimise away to nothing. This has been confirmed on rustc 1.37.0 in release
+/// mode on an intel 2500k cpu.
+#[repr(C)]
+pub struct SliceAbi<T: FfiStable> {
+    ptr: *const T,
+    len: u64,
+}
+
+impl<T: FfiStable> FfiStable for SliceAbi<T> {}
+
+impl<T: BindgenSliceElem> BindgenAbiConvert for &[T] {
+    type AbiType = SliceAbi<T::Repr>;
+
+    const CHECKS_DISCRIMINANTS: bool = T::CHECKS_DISCRIMINANTS;
+
+    fn from_abi_type(abi_value: Self::AbiType) -> Self {
+        // An empty C# array is pinned as a null pointer, which from_raw_parts doesn't accept.
+        if abi_value.len == 0 {
+            return &[];
+        }
+
+        assert!(has_valid_elems::<T>(&abi_value), "An enum passed across the ffi boundary had an undefined discriminant");
+        unsafe { core::slice::from_raw_parts(abi_value.ptr as *const T, abi_value.len as usize) }
+    }
+
+    fn has_valid_discriminants(abi_value: &Self::AbiType) -> bool {
+        has_valid_elems::<T>(abi_value)
+    }
+}
+
+impl<T: BindgenSliceElem> BindgenAbiReturn for &[T] {
+    fn to_abi_type(self) -> Self::AbiType {
+        let ptr = self.as_ptr() as *const T::Repr;
+        let len = self.len() as u64;
+        Self::AbiType { ptr, len }
+    }
+}
+
+/// A `SliceAbi` can be held by a struct returned with `#[dotnet_bindgen(returns_borrowed)]`, which C#
+/// copies into an array. It doesn't borrow the slice, so the slice must outlive the C# call.
+impl<T: FfiStable> From<&[T]> for SliceAbi<T> {
+    fn from(slice: &[T]) -> Self {
+        SliceAbi { ptr: slice.as_ptr(), len: slice.len() as u64 }
+    }
+}
+
+impl<T: FfiStable + BindgenTypeDescribe> BindgenTypeDescribe for SliceAbi<T> {
+    fn describe() -> BindgenTypeDescriptor {
+        <&[T]>::describe()
+    }
+}
+
+/// Optional slices share the representation of slices, with `None` passed as a null pointer.
+///
+/// The caller must pass a non-null (but possibly dangling) pointer for an empty slice, which
+/// `Some(&[])` always does.
+impl<T: BindgenSliceElem> BindgenAbiConvert for Option<&[T]> {
+    type AbiType = SliceAbi<T::Repr>;
+
+    const CHECKS_DISCRIMINANTS: bool = T::CHECKS_DISCRIMINANTS;
+
+    fn from_abi_type(abi_value: Self::AbiType) -> Self {
+        if abi_value.ptr.is_null() {
+            return None;
+        }
+
+        Some(<&[T]>::from_abi_type(abi_value))
+    }
+
+    fn has_valid_discriminants(abi_value: &Self::AbiType) -> bool {
+        has_valid_elems::<T>(abi_value)
+    }
+}
+
+impl<T: BindgenSliceElem> BindgenAbiReturn for Option<&[T]> {
+    fn to_abi_type(self) -> Self::AbiType {
+        match self {
+            Some(slice) => slice.to_abi_type(),
+            None => SliceAbi {
+                ptr: core::ptr::null(),
+                len: 0,
+            },
+        }
+    }
+}
+
+/// Mutable slices share the representation of immutable ones, the pointer just happens to be
+/// written through.
+impl<T: BindgenSliceElem> BindgenAbiConvert for &mut [T] {
+    type AbiType = SliceAbi<T::Repr>;
+
+    const CHECKS_DISCRIMINANTS: bool = T::CHECKS_DISCRIMINANTS;
+
+    fn from_abi_type(abi_value: Self::AbiType) -> Self {
+        if abi_value.len == 0 {
+            return &mut [];
+        }
+
+        assert!(has_valid_elems::<T>(&abi_value), "An enum passed across the ffi boundary had an undefined discriminant");
+        unsafe { core::slice::from_raw_parts_mut(abi_value.ptr as *mut T, abi_value.len as usize) }
+    }
+
+    fn has_valid_discriminants(abi_value: &Self::AbiType) -> bool {
+        has_valid_elems::<T>(abi_value)
+    }
+}
+
+impl<T: BindgenSliceElem> BindgenAbiReturn for &mut [T] {
+    fn to_abi_type(self) -> Self::AbiType {
+        let ptr = self.as_ptr() as *const T::Repr;
+        let len = self.len() as u64;
+        Self::AbiType { ptr, len }
+    }
+}
+
+/// Output buffers that the native side fully writes before reading, so that the caller needn't
+/// initialize them first.
+///
+/// These share the representation of mutable slices. The elements must be treated as write-only
+/// until they have been written, as the caller makes no promises about their contents.
+impl<T: FfiStable> BindgenAbiConvert for &mut [MaybeUninit<T>] {
+    type AbiType = SliceAbi<T>;
+
+    fn from_abi_type(abi_value: Self::AbiType) -> Self {
+        if abi_value.len == 0 {
+            return &mut [];
+        }
+
+        let ptr = abi_value.ptr as *mut MaybeUninit<T>;
+        unsafe { core::slice::from_raw_parts_mut(ptr, abi_value.len as usize) }
+    }
+}
+
+impl<T: FfiStable> BindgenAbiReturn for &mut [MaybeUninit<T>] {
+    fn to_abi_type(self) -> Self::AbiType {
+        let ptr = self.as_ptr() as *const T;
+        let len = self.len() as u64;
+        Self::AbiType { ptr, len }
+    }
+}
+
+/// FfiStable representation of a buffer allocated by Rust, which must be handed back to the
+/// function's free thunk once the caller is done with it.
+///
+/// Dropping one frees the buffer, so a struct holding one is freed along with it by the free thunk.
+#[repr(C)]
+pub struct OwnedSliceAbi<T: FfiStable> {
+    ptr: *mut T,
+    len: u64,
+    cap: u64,
+}
+
+impl<T: FfiStable> FfiStable for OwnedSliceAbi<T> {}
+
+impl<T: FfiStable> Drop for OwnedSliceAbi<T> {
+    fn drop(&mut self) {
+        let abi_value = OwnedSliceAbi {
+            ptr: self.ptr,
+            len: self.len,
+            cap: self.cap,
+        };
+        drop(Vec::<T>::from_abi_type(abi_value));
+    }
+}
+
+/// An `OwnedSliceAbi` can be held by a struct returned with `#[dotnet_bindgen(returns_owned)]`,
+/// which C# copies into an array before freeing.
+impl<T: FfiStable> From<Vec<T>> for OwnedSliceAbi<T> {
+    fn from(vec: Vec<T>) -> Self {
+        <Vec<T> as BindgenAbiReturn>::to_abi_type(vec)
+    }
+}
+
+impl<T: FfiStable + BindgenTypeDescribe> BindgenTypeDescribe for OwnedSliceAbi<T> {
+    fn describe() -> BindgenTypeDescriptor {
+        <Vec<T>>::describe()
+    }
+}
+
+/// Vecs are mostly only returned, as C# memory can't be handed over for Rust to free. The
+/// exception is a `Vec<u8>` argument, taking a buffer C# allocated with `__bindgen_alloc`.
+///
+/// Converting back from the AbiType reclaims the allocation, which is how the free thunk drops it.
+/// Only a Vec of FfiStable elements can be taken as an argument, as the elements aren't checked.
+impl<T: BindgenSliceElem> BindgenAbiConvert for Vec<T> {
+    type AbiType = OwnedSliceAbi<T::Repr>;
+
+    const CHECKS_DISCRIMINANTS: bool = T::CHECKS_DISCRIMINANTS;
+
+    fn from_abi_type(abi_value: Self::AbiType) -> Self {
+        // The allocation is reclaimed here, rather than by the AbiType's own Drop.
+        let abi_value = ManuallyDrop::new(abi_value);
+
+        // Nothing was allocated, and a zeroed AbiType (eg one that was already freed) has a null
+        // pointer that from_raw_parts doesn't accept.
+        if abi_value.cap == 0 {
+            return Vec::new();
+        }
+
+        unsafe {
+            Vec::from_raw_parts(abi_value.ptr as *mut T, abi_value.len as usize, abi_value.cap as usize)
+        }
+    }
+}
+
+impl<T: BindgenSliceElem> BindgenAbiReturn for Vec<T> {
+    fn to_abi_type(self) -> Self::AbiType {
+        let mut vec = ManuallyDrop::new(self);
+        Self::AbiType {
+            ptr: vec.as_mut_ptr() as *mut T::Repr,
+            len: vec.len() as u64,
+            cap: vec.capacity() as u64,
+        }
+    }
+}
+
+impl<T: BindgenSliceElem + BindgenTypeDescribe> BindgenTypeDescribe for Vec<T> {
+    fn describe() -> BindgenTypeDescriptor {
+        let elem_type = Box::new(<T as BindgenTypeDescribe>::describe());
+        BindgenTypeDescriptor::OwnedSlice { elem_type }
+    }
+}
+
+/// bools aren't FfiStable, so a Vec of them is returned as a Vec<u8> of 0s and 1s.
+impl BindgenAbiConvert for Vec<bool> {
+    type AbiType = OwnedSliceAbi<u8>;
+
+    fn from_abi_type(abi_value: Self::AbiType) -> Self {
+        Vec::<u8>::from_abi_type(abi_value)
+            .into_iter()
+            .map(bool::from_abi_type)
+            .collect()
+    }
+}
+
+impl BindgenAbiReturn for Vec<bool> {
+    fn to_abi_type(self) -> Self::AbiType {
+        self.into_iter()
+            .map(bool::to_abi_type)
+            .collect::<Vec<u8>>()
+            .to_abi_type()
+    }
+}
+
+impl BindgenTypeDescribe for Vec<bool> {
+    fn describe() -> BindgenTypeDescriptor {
+        BindgenTypeDescriptor::OwnedSlice {
+            elem_type: Box::new(BindgenTypeDescriptor::Bool),
+        }
+    }
+}
+
+/// Owned strings are returned as their UTF-8 bytes, in the same way as a Vec<u8>.
+///
+/// As for `&str`, debug builds validate the bytes of one coming the other way.
+impl BindgenAbiConvert for String {
+    type AbiType = OwnedSliceAbi<u8>;
+
+    const VALIDATES_UTF8: bool = true;
+
+    fn from_abi_type(abi_value: Self::AbiType) -> Self {
+        let bytes = Vec::from_abi_type(abi_value);
+
+        if cfg!(debug_assertions) {
+            String::from_utf8(bytes).expect("A string passed across the ffi boundary was not valid UTF-8")
+        } else {
+            unsafe { String::from_utf8_unchecked(bytes) }
+        }
+    }
+
+    fn is_valid_utf8(abi_value: &Self::AbiType) -> bool {
+        __private::is_valid_utf8(abi_value.ptr, abi_value.len as usize)
+    }
+}
+
+impl BindgenAbiReturn for String {
+    fn to_abi_type(self) -> Self::AbiType {
+        self.into_bytes().to_abi_type()
+    }
+}
+
+impl BindgenTypeDescribe for String {
+    fn describe() -> BindgenTypeDescriptor {
+        BindgenTypeDescriptor::OwnedStr
+    }
+}
+
+/// The string counterpart of `SliceAbi`, for a struct returned with
+/// `#[dotnet_bindgen(returns_borrowed)]`, which C# decodes into a string.
+#[repr(transparent)]
+pub struct StrAbi(SliceAbi<u8>);
+
+impl FfiStable for StrAbi {}
+
+impl From<&str> for StrAbi {
+    fn from(s: &str) -> Self {
+        StrAbi(s.to_abi_type())
+    }
+}
+
+impl BindgenTypeDescribe for StrAbi {
+    fn describe() -> BindgenTypeDescriptor {
+        BindgenTypeDescriptor::Str
+    }
+}
+
+/// The string counterpart of `OwnedSliceAbi`, for a struct returned with
+/// `#[dotnet_bindgen(returns_owned)]`, which C# decodes into a string before freeing.
+#[repr(transparent)]
+pub struct OwnedStrAbi(OwnedSliceAbi<u8>);
+
+impl FfiStable for OwnedStrAbi {}
+
+impl From<String> for OwnedStrAbi {
+    fn from(s: String) -> Self {
+        OwnedStrAbi(s.to_abi_type())
+    }
+}
+
+impl BindgenTypeDescribe for OwnedStrAbi {
+    fn describe() -> BindgenTypeDescriptor {
+        BindgenTypeDescriptor::OwnedStr
+    }
+}
+
+/// A Vec of strings is returned as an owned slice of owned strings.
+///
+/// Converting back from the AbiType reclaims the outer buffer along with each of the strings, so
+/// the free thunk drops all of them.
+impl BindgenAbiConvert for Vec<String> {
+    type AbiType = OwnedSliceAbi<OwnedSliceAbi<u8>>;
+
+    fn from_abi_type(abi_value: Self::AbiType) -> Self {
+        Vec::<OwnedSliceAbi<u8>>::from_abi_type(abi_value)
+            .into_iter()
+            .map(String::from_abi_type)
+            .collect()
+    }
+}
+
+impl BindgenAbiReturn for Vec<String> {
+    fn to_abi_type(self) -> Self::AbiType {
+        self.into_iter()
+            .map(String::to_abi_type)
+            .collect::<Vec<_>>()
+            .to_abi_type()
+    }
+}
+
+impl BindgenTypeDescribe for Vec<String> {
+    fn describe() -> BindgenTypeDescriptor {
+        BindgenTypeDescriptor::OwnedSlice {
+            elem_type: Box::new(BindgenTypeDescriptor::OwnedStr),
+        }
+    }
+}
+
+/// C strings are returned as the pointer from `CString::into_raw`, for C# to read up to the NUL.
+impl BindgenAbiConvert for CString {
+    type AbiType = *mut c_char;
+
+    // Only ever called by the generated thunks, on a pointer from `to_abi_type`.
+    #[allow(clippy::not_unsafe_ptr_arg_deref)]
+    fn from_abi_type(abi_value: Self::AbiType) -> Self {
+        // A null pointer was never handed out by `to_abi_type`, so there's nothing to reclaim.
+        if abi_value.is_null() {
+            return CString::default();
+        }
+
+        unsafe { CString::from_raw(abi_value) }
+    }
+}
+
+impl BindgenAbiReturn for CString {
+    fn to_abi_type(self) -> Self::AbiType {
+        self.into_raw()
+    }
+}
+
+impl BindgenTypeDescribe for CString {
+    fn describe() -> BindgenTypeDescriptor {
+        BindgenTypeDescriptor::CString
+    }
+}
+
+/// Reference counted values, which C# holds on to through a SafeHandle.
+pub trait BindgenHandle: BindgenAbiConvert {
+    /// Takes another reference to the value, without giving up the given one.
+    fn clone_abi_type(abi_value: &Self::AbiType) -> Self::AbiType;
+}
+
+/// Arcs cross the boundary as the pointer from `Arc::into_raw`, which owns one strong reference.
+///
+/// The value may be dropped from the .NET finalizer thread, so must be `Send + Sync`.
+#[cfg(target_has_atomic = "ptr")]
+impl<T: Send + Sync> BindgenAbiConvert for Arc<T> {
+    type AbiType = *const ();
+
+    fn from_abi_type(abi_value: Self::AbiType) -> Self {
+        unsafe { Arc::from_raw(abi_value as *const T) }
+    }
+}
+
+#[cfg(target_has_atomic = "ptr")]
+impl<T: Send + Sync> BindgenAbiReturn for Arc<T> {
+    fn to_abi_type(self) -> Self::AbiType {
+        Arc::into_raw(self) as *const ()
+    }
+}
+
+#[cfg(target_has_atomic = "ptr")]
+impl<T: Send + Sync> BindgenHandle for Arc<T> {
+    fn clone_abi_type(abi_value: &Self::AbiType) -> Self::AbiType {
+        let arc = ManuallyDrop::new(Self::from_abi_type(*abi_value));
+        Arc::clone(&arc).to_abi_type()
+    }
+}
+
+#[cfg(target_has_atomic = "ptr")]
+impl<T> BindgenTypeDescribe for Arc<T> {
+    fn describe() -> BindgenTypeDescriptor {
+        BindgenTypeDescriptor::Arc {
+            name: String::from(core::any::type_name::<T>()),
+        }
+    }
+}
+
+/// Strings cross the boundary as a slice of UTF-8 bytes.
+///
+/// The generated C# encodes strings with a strict encoder, which throws rather than producing
+/// invalid UTF-8. Since a mistake there would otherwise be silent UB, debug builds validate the
+/// bytes again on the way in. The thunks check them first and report bad bytes to C# (see
+/// `BindgenFunctionDescriptor::reports_invalid_args`), so the panic here is only reached by other
+/// callers of `from_abi_type`.
+impl BindgenAbiConvert for &str {
+    type AbiType = SliceAbi<u8>;
+
+    const VALIDATES_UTF8: bool = true;
+
+    fn from_abi_type(abi_value: Self::AbiType) -> Self {
+        let bytes = <&[u8]>::from_abi_type(abi_value);
+
+        if cfg!(debug_assertions) {
+            core::str::from_utf8(bytes).expect("A string passed across the ffi boundary was not valid UTF-8")
+        } else {
+            unsafe { core::str::from_utf8_unchecked(bytes) }
+        }
+    }
+
+    fn is_valid_utf8(abi_value: &Self::AbiType) -> bool {
+        __private::is_valid_utf8(abi_value.ptr, abi_value.len as usize)
+    }
+}
+
+impl BindgenAbiReturn for &str {
+    fn to_abi_type(self) -> Self::AbiType {
+        self.as_bytes().to_abi_type()
+    }
+}
+
+impl BindgenTypeDescribe for &str {
+    fn describe() -> BindgenTypeDescriptor {
+        BindgenTypeDescriptor::Str
+    }
+}
 
-// All reference types and pointer types to FfiStable types are also FfiStable
-impl<'a, T: FfiStable> FfiStable for &'a T {}
-impl<'a, T: FfiStable> FfiStable for &'a mut T {}
-impl<T: FfiStable> FfiStable for *const T {}
-impl<T: FfiStable> FfiStable for *mut T {}
+/// Cows are always borrowed on the way in, so look just like the slice they borrow on the wire.
+///
+/// They can't be returned, so don't implement `BindgenAbiReturn`, as an owned Cow's buffer would be
+/// freed before the caller could read it.
+impl<T: FfiStable + Clone> BindgenAbiConvert for Cow<'_, [T]> {
+    type AbiType = SliceAbi<T>;
 
-/// Defines how to translate a non-trivial type to/from a stable ABI type
-pub trait BindgenAbiConvert {
-    type AbiType: FfiStable;
+    fn from_abi_type(abi_value: Self::AbiType) -> Self {
+        Cow::Borrowed(<&[T]>::from_abi_type(abi_value))
+    }
+}
 
-    fn from_abi_type(abi_value: Self::AbiType) -> Self;
-    fn to_abi_type(self) -> Self::AbiType;
+impl<T: FfiStable + Clone + BindgenTypeDescribe> BindgenTypeDescribe for Cow<'_, [T]> {
+    fn describe() -> BindgenTypeDescriptor {
+        <&[T]>::describe()
+    }
 }
 
-/// Types which are already FfiStable need no marshalling across the boundary
-///
-/// Any self-respecting optimiser will realise that these methods do nothing, and
-/// completely optimise them out of any release build.
-impl<T: FfiStable> BindgenAbiConvert for T {
-    type AbiType = T;
+/// See the `Cow<[T]>` impl, this is the same for strings.
+impl BindgenAbiConvert for Cow<'_, str> {
+    type AbiType = SliceAbi<u8>;
+
+    const VALIDATES_UTF8: bool = true;
 
     fn from_abi_type(abi_value: Self::AbiType) -> Self {
-        abi_value
+        Cow::Borrowed(<&str>::from_abi_type(abi_value))
     }
 
-    fn to_abi_type(self) -> Self::AbiType {
-        self
+    fn is_valid_utf8(abi_value: &Self::AbiType) -> bool {
+        <&str>::is_valid_utf8(abi_value)
     }
 }
 
-/// Explicitly map booleans to uint8s to cross the ffi boundary.
+impl BindgenTypeDescribe for Cow<'_, str> {
+    fn describe() -> BindgenTypeDescriptor {
+        BindgenTypeDescriptor::Str
+    }
+}
+
+/// FfiStable representation of a Duration, split the same way as Duration itself
+#[repr(C)]
+pub struct DurationAbi {
+    secs: u64,
+    nanos: u32,
+}
+
+impl FfiStable for DurationAbi {}
+
+/// Durations become a TimeSpan in the generated C#.
 ///
-/// The C99 standard only says that the representation of a bool must be large enough to hold 0 or
-/// 1. In practice this almost always means they are represented by a uint8_t by C compilers. The Rust
-/// specification explicitly doens't say how it might be represented.
-impl BindgenAbiConvert for bool {
-    type AbiType = u8;
+/// A TimeSpan only has a resolution of 100ns, so anything finer is truncated on the way out.
+impl BindgenAbiConvert for Duration {
+    type AbiType = DurationAbi;
 
     fn from_abi_type(abi_value: Self::AbiType) -> Self {
-        abi_value != 0
+        Duration::new(abi_value.secs, abi_value.nanos)
     }
+}
 
+impl BindgenAbiReturn for Duration {
     fn to_abi_type(self) -> Self::AbiType {
-       if self { 1 } else { 0 }
+        Self::AbiType {
+            secs: self.as_secs(),
+            nanos: self.subsec_nanos(),
+        }
     }
 }
 
-impl BindgenTypeDescribe for bool {
+impl BindgenTypeDescribe for Duration {
     fn describe() -> BindgenTypeDescriptor {
-        BindgenTypeDescriptor::Bool
+        BindgenTypeDescriptor::Duration
     }
 }
 
-/// FfiStable representation of a slice type
+/// FfiStable representation of an `i128` or `u128`, as its low and high halves.
 ///
-/// This representation is written to look very similar to the actual underlying
-/// representation of a slice type, such that the conversion functions are likely
-/// to optimise away to nothing. This has been confirmed on rustc 1.37.0 in release
-/// mode on an intel 2500k cpu.
+/// Rust passes and returns 128-bit integers differently from how .NET passes an `Int128`, eg
+/// returning them in xmm0 on win-x64. A plain struct of two u64s is passed and returned the same
+/// way by both, in a pair of registers on SysV and behind a pointer on win-x64.
 #[repr(C)]
-pub struct SliceAbi<T: FfiStable> {
-    ptr: *const T,
-    len: u64,
+pub struct Int128Abi {
+    lo: u64,
+    hi: u64,
 }
 
-impl<T: FfiStable> FfiStable for SliceAbi<T> {}
+impl FfiStable for Int128Abi {}
 
-impl<T: FfiStable> BindgenAbiConvert for &[T] {
-    type AbiType = SliceAbi<T>;
+/// 128-bit integers become an Int128 or UInt128 in the generated C#, which needs net7.0.
+macro_rules! int128_abi_convert {
+    ($($ty:ty => $signed:expr,)*) => {
+        $(
+            impl BindgenAbiConvert for $ty {
+                type AbiType = Int128Abi;
 
-    fn from_abi_type(abi_value: Self::AbiType) -> Self {
-        unsafe { std::slice::from_raw_parts(abi_value.ptr, abi_value.len as usize) }
-    }
+                fn from_abi_type(abi_value: Self::AbiType) -> Self {
+                    ((u128::from(abi_value.hi) << 64) | u128::from(abi_value.lo)) as $ty
+                }
+            }
 
-    fn to_abi_type(self) -> Self::AbiType {
-        let ptr = self.as_ptr();
-        let len = self.len() as u64;
-        Self::AbiType { ptr, len }
+            impl BindgenAbiReturn for $ty {
+                fn to_abi_type(self) -> Self::AbiType {
+                    Self::AbiType {
+                        lo: self as u64,
+                        hi: (self as u128 >> 64) as u64,
+                    }
+                }
+            }
+
+            impl BindgenTypeDescribe for $ty {
+                fn describe() -> BindgenTypeDescriptor {
+                    BindgenTypeDescriptor::Int { width: 128, signed: $signed }
+                }
+            }
+        )*
+    };
+}
+
+int128_abi_convert![
+    i128 => true,
+    u128 => false,
+];
+
+/// The unit type only crosses the boundary as the return type of a function pointer.
+impl FfiStable for () {}
+
+impl BindgenTypeDescribe for () {
+    fn describe() -> BindgenTypeDescriptor {
+        BindgenTypeDescriptor::Void
     }
 }
 
+/// FfiStable representation of a tuple, a struct holding each element's AbiType in order.
+///
+/// The fields are named after those of the C# `ValueTuple` the tuple is copied into.
+macro_rules! tuple_abi_convert {
+    ($($abi:ident { $($field:ident: $elem:ident),* },)*) => {
+        $(
+            #[repr(C)]
+            pub struct $abi<$($elem: FfiStable),*> {
+                $($field: $elem),*
+            }
+
+            impl<$($elem: FfiStable),*> FfiStable for $abi<$($elem),*> {}
+
+            /// Tuples can only be returned, with any slices or strings they hold copied out by C#.
+            impl<$($elem: BindgenAbiConvert),*> BindgenAbiConvert for ($($elem,)*) {
+                type AbiType = $abi<$($elem::AbiType),*>;
+
+                fn from_abi_type(abi_value: Self::AbiType) -> Self {
+                    ($($elem::from_abi_type(abi_value.$field),)*)
+                }
+            }
+
+            impl<$($elem: BindgenAbiReturn),*> BindgenAbiReturn for ($($elem,)*) {
+                fn to_abi_type(self) -> Self::AbiType {
+                    let ($($field,)*) = self;
+                    Self::AbiType {
+                        $($field: $field.to_abi_type()),*
+                    }
+                }
+            }
+
+            impl<$($elem: BindgenTypeDescribe),*> BindgenTypeDescribe for ($($elem,)*) {
+                fn describe() -> BindgenTypeDescriptor {
+                    BindgenTypeDescriptor::Tuple {
+                        elements: alloc::vec![$(<$elem as BindgenTypeDescribe>::describe()),*],
+                    }
+                }
+            }
+        )*
+    };
+}
+
+tuple_abi_convert![
+    TupleAbi2 { item1: A, item2: B },
+    TupleAbi3 { item1: A, item2: B, item3: C },
+    TupleAbi4 { item1: A, item2: B, item3: C, item4: D },
+];
+
+/// `extern "C"` function pointers taking and returning FfiStable types are just a pointer.
+macro_rules! ffi_stable_fn_ptr {
+    ($($arg:ident),*) => {
+        impl<R: FfiStable, $($arg: FfiStable),*> FfiStable for extern "C" fn($($arg),*) -> R {}
+
+        impl<R, $($arg),*> BindgenTypeDescribe for extern "C" fn($($arg),*) -> R
+        where
+            R: FfiStable + BindgenTypeDescribe,
+            $($arg: FfiStable + BindgenTypeDescribe),*
+        {
+            fn describe() -> BindgenTypeDescriptor {
+                BindgenTypeDescriptor::FnPtr {
+                    args: alloc::vec![$(<$arg as BindgenTypeDescribe>::describe()),*],
+                    return_ty: Box::new(<R as BindgenTypeDescribe>::describe()),
+                }
+            }
+        }
+    };
+}
+
+ffi_stable_fn_ptr!();
+ffi_stable_fn_ptr!(A);
+ffi_stable_fn_ptr!(A, B);
+ffi_stable_fn_ptr!(A, B, C);
+ffi_stable_fn_ptr!(A, B, C, D);
+ffi_stable_fn_ptr!(A, B, C, D, E);
+ffi_stable_fn_ptr!(A, B, C, D, E, F);
 
 #[repr(C)]
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum BindgenTypeDescriptor {
     Void,
     Int {
@@ -105,18 +1013,172 @@ pub enum BindgenTypeDescriptor {
     Slice {
         elem_type: Box<BindgenTypeDescriptor>,
     },
+    MutSlice {
+        elem_type: Box<BindgenTypeDescriptor>,
+    },
+    /// An `Option<&[T]>`, with `None` passed as a null pointer
+    OptionSlice {
+        elem_type: Box<BindgenTypeDescriptor>,
+    },
+    /// A `&mut [MaybeUninit<T>]`, which is only ever written through
+    UninitSlice {
+        elem_type: Box<BindgenTypeDescriptor>,
+    },
+    /// A `&T`, passed as a pointer to the caller's value
+    Ref {
+        target: Box<BindgenTypeDescriptor>,
+    },
+    /// A `&mut T`, passed as a pointer to the caller's value
+    MutRef {
+        target: Box<BindgenTypeDescriptor>,
+    },
+    /// A UTF-8 string slice
+    Str,
+    /// An IEEE 754 floating point number
+    Float {
+        width: u8,
+    },
+    /// A fixed size array, eg `[f32; 4]`
+    Array {
+        elem_type: Box<BindgenTypeDescriptor>,
+        len: u64,
+    },
+    /// A `Vec<T>`, which can only be returned
+    OwnedSlice {
+        elem_type: Box<BindgenTypeDescriptor>,
+    },
+    /// A `String`, which can only be returned
+    OwnedStr,
+    /// A `CString`, which can only be returned, as a pointer to its NUL-terminated bytes
+    CString,
+    /// An `Arc<T>`, given its Rust type name, eg `my_crate::Engine`
+    Arc {
+        name: String,
+    },
+    /// An opaque pointer from a `#[dotnet_bindgen(raw_ptr)]` argument, passed through untouched
+    RawPtr,
+    /// A `*const T` or `*mut T`, given the Rust type name of `T`
+    ///
+    /// `T` itself is only described when it's an `Opaque` type, as it's commonly the struct
+    /// holding the pointer.
+    Ptr {
+        name: String,
+        mutable: bool,
+        pointee: Option<Box<BindgenTypeDescriptor>>,
+    },
+    /// A type declared with `#[dotnet_bindgen(opaque)]`, which is only ever handled by pointer
+    Opaque {
+        name: String,
+    },
+    /// A `std::time::Duration`
+    Duration,
+    /// An `extern "C" fn(...) -> ...` function pointer
+    FnPtr {
+        args: Vec<BindgenTypeDescriptor>,
+        return_ty: Box<BindgenTypeDescriptor>,
+    },
+    /// A tuple of 2 to 4 elements, which can only be returned
+    Tuple {
+        elements: Vec<BindgenTypeDescriptor>,
+    },
     Struct(BindgenStructDescriptor),
+    Enum(BindgenEnumDescriptor),
+}
+
+/// An explicit marshalling direction for an argument, as given by
+/// `#[dotnet_bindgen(direction = "...")]`
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BindgenArgumentDirection {
+    In,
+    Out,
+    InOut,
+}
+
+/// The encoding a C# string is converted to for an argument, as given by
+/// `#[dotnet_bindgen(encoding = "...")]`
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BindgenStringEncoding {
+    /// For `&str` or `&[u8]` arguments
+    Utf8,
+    /// For `&[u16]` arguments
+    Utf16,
+    /// The system's ANSI code page, for `&[u8]` arguments
+    Ansi,
+}
+
+/// Who owns a value once a function has returned it, as given by `#[dotnet_bindgen(returns_owned)]`
+/// or `#[dotnet_bindgen(returns_borrowed)]`
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BindgenReturnOwnership {
+    /// C# is handed the value, and frees it through the function's free thunk
+    Owned,
+    /// The value stays owned by Rust, and C# only copies it out, eg a `&'static str`
+    Borrowed,
 }
 
 #[repr(C)]
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BindgenFunctionArgumentDescriptor {
     pub name: String,
     pub ty: BindgenTypeDescriptor,
+    /// None if the argument wasn't annotated. The C# bindings pin slices in place rather than
+    /// copying them, so this doesn't change their marshalling, but other generators may use it.
+    pub direction: Option<BindgenArgumentDirection>,
+    /// Set if the argument is passed a C# string in the given encoding
+    pub encoding: Option<BindgenStringEncoding>,
+    /// Set by `#[dotnet_bindgen(default(...))]`, the value an overload omitting the argument passes
+    pub default: Option<BindgenDefaultValue>,
+}
+
+/// The default value of an argument, as given by `#[dotnet_bindgen(default(name = ...))]`
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BindgenDefaultValue {
+    Bool(bool),
+    /// For signed integer arguments
+    Int(i64),
+    /// For unsigned integer arguments
+    UInt(u64),
+    /// For floating point arguments, the literal's digits as written, eg `1.5` or `-0.25`
+    Float(String),
+    /// For `&str` arguments
+    Str(String),
+}
+
+/// How a method declared in a #[dotnet_bindgen] impl block takes `self`
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BindgenReceiver {
+    /// `self`
+    Value,
+    /// `&self`
+    Ref,
+    /// `&mut self`
+    RefMut,
+}
+
+/// The calling convention of a generated thunk, as given by `#[dotnet_bindgen(abi = "...")]`
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BindgenCallingConvention {
+    /// `extern "C"`, the default
+    C,
+    /// `extern "system"`, which is stdcall on 32 bit Windows and the same as "C" everywhere else
+    System,
 }
 
 #[repr(C)]
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BindgenFunctionDescriptor {
     /// The original name of the function that the #[dotnet_bindgen] attribute was placed on
     pub real_name: String,
@@ -124,12 +1186,78 @@ pub struct BindgenFunctionDescriptor {
     /// The no_mangle'd name of the generated thunk
     pub thunk_name: String,
 
+    /// For functions declared in a #[dotnet_bindgen] impl block, the name of the struct the block
+    /// is for.
+    ///
+    /// The impl block is expanded separately from the struct, so it is these that reference the
+    /// struct rather than the other way around.
+    pub owner: Option<String>,
+
+    /// How the function takes `self`, if at all. Always None for free functions.
+    pub receiver: Option<BindgenReceiver>,
+
+    /// Set by `#[dotnet_bindgen(blocking)]` on functions which may block for a long time
+    pub blocking: bool,
+
+    /// Set by `#[dotnet_bindgen(no_gc_transition)]` on very short functions, which are called
+    /// without switching the calling thread into preemptive GC mode.
+    ///
+    /// Such functions must not block, allocate on the managed heap, or call back into managed code,
+    /// otherwise the GC can stall or the runtime can corrupt itself.
+    pub no_gc_transition: bool,
+
+    pub calling_convention: BindgenCallingConvention,
+
     pub arguments: Vec<BindgenFunctionArgumentDescriptor>,
     pub return_ty: BindgenTypeDescriptor,
+
+    /// None if the return wasn't annotated, which is only allowed for `Copy` return types
+    pub return_ownership: Option<BindgenReturnOwnership>,
+
+    /// The no_mangle'd name of the thunk which frees a value returned by this function, for
+    /// functions which return anything not borrowed.
+    pub free_thunk_name: Option<String>,
+
+    /// The no_mangle'd name of the thunk which takes another reference to a value returned by this
+    /// function, for functions which return an `Arc`
+    pub clone_thunk_name: Option<String>,
+
+    /// For a function returning a pointer to a byte buffer, the name of the argument the buffer's
+    /// length is written to
+    pub buffer_len_arg: Option<String>,
+
+    /// Set by `#[dotnet_bindgen(fill_buffer)]` on a `&mut [u8]` argument of a function returning
+    /// the `usize` size of buffer it needs, which then also gets an overload allocating the buffer.
+    pub fill_buffer_arg: Option<String>,
+
+    /// Set by `#[dotnet_bindgen(returns_dictionary)]` on functions returning a `Vec` of two field
+    /// structs, which are bound as returning a `Dictionary` keyed on the first field.
+    pub returns_dictionary: bool,
+
+    /// Set by `#[dotnet_bindgen(return_encoding = "...")]` on functions returning a `CString`, to
+    /// decode it as something other than UTF-8.
+    pub return_encoding: Option<BindgenStringEncoding>,
+
+    /// Set by `#[dotnet_bindgen(module = "...")]` on free functions, to bind them in nested static
+    /// classes following the dotted path, eg `Math.Trig`, rather than in TopLevelMethods.
+    pub module: Option<String>,
+
+    /// Set for functions declared in a #[dotnet_bindgen] `extern` block, which have no generated
+    /// thunk. The `thunk_name` is then the symbol of the foreign function itself.
+    pub no_thunk: bool,
+
+    /// Set when the function takes a `&str` or `Cow<str>`, or a type which might be a C-like enum
+    /// or a slice of one. Its thunk then takes a trailing `&mut u8`, which it sets without calling
+    /// the function if an argument can't be converted, leaving the returned value uninitialized.
+    ///
+    /// It's set to 1 if a string argument isn't valid UTF-8, which is only checked in debug builds,
+    /// and to 2 if an enum argument, or an element of an enum slice, has an undefined discriminant.
+    pub reports_invalid_args: bool,
 }
 
 #[repr(C)]
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BindgenStructFieldDescriptor {
     /// The name as it appears in the original struct definition
     pub name: String,
@@ -140,20 +1268,78 @@ pub struct BindgenStructFieldDescriptor {
 
 #[repr(C)]
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BindgenStructDescriptor {
     /// The original name of the struct that received the #[dotnet_bindgen] attribute
     pub name: String,
 
+    /// The name to give the struct in C#, if overridden with `#[dotnet_bindgen(cs_name = "...")]`
+    pub cs_name: Option<String>,
+
+    /// The size of the struct in bytes, as laid out by rustc for the described binary
+    pub size: Option<u64>,
+
+    /// The alignment of the struct in bytes, eg 64 for a `#[repr(C, align(64))]` struct
+    pub align: Option<u64>,
+
     /// An ordered set of the fields that appear in this struct.
-    pub fields: Vec<BindgenStructFieldDescriptor>
+    pub fields: Vec<BindgenStructFieldDescriptor>,
+
+    /// Whether the struct is an inline string of the form `{ bytes: [u8; N], len: u8 }`, from
+    /// `#[dotnet_bindgen(fixed_string)]`
+    pub fixed_string: bool,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BindgenEnumVariantDescriptor {
+    /// The name as it appears in the original enum definition
+    pub name: String,
+
+    /// The discriminant of the variant. Discriminants of u64 enums above i64::MAX wrap around.
+    pub value: i64,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BindgenEnumDescriptor {
+    /// The original name of the C-like enum that received the #[dotnet_bindgen] attribute
+    pub name: String,
+
+    /// The integer type the enum is represented as, from its `#[repr(...)]` attribute
+    pub repr: Box<BindgenTypeDescriptor>,
+
+    /// The variants of the enum, in declaration order
+    pub variants: Vec<BindgenEnumVariantDescriptor>,
 }
 
+#[repr(C)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BindgenStaticDescriptor {
+    /// The no_mangle'd name of the static, which is also its symbol name
+    pub name: String,
+
+    /// The type of the static being described
+    pub ty: BindgenTypeDescriptor,
+
+    /// Whether this is a `static mut`
+    pub mutable: bool,
+}
 
 #[repr(C)]
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+// Each is only built once, by its describe function, so there's nothing to gain from boxing the
+// larger variants.
+#[allow(clippy::large_enum_variant)]
 pub enum BindgenExportDescriptor {
     Function(BindgenFunctionDescriptor),
     Struct(BindgenStructDescriptor),
+    Static(BindgenStaticDescriptor),
+    Enum(BindgenEnumDescriptor),
 }
 
 
@@ -166,6 +1352,14 @@ pub enum BindgenExportDescriptor {
 /// to find out what the type eventually became.
 pub trait BindgenTypeDescribe {
     fn describe() -> BindgenTypeDescriptor;
+
+    /// The descriptor of this type as the target of a raw pointer, if it should be bound as one.
+    ///
+    /// Pointees generally aren't described, as they're commonly the struct holding the pointer, so
+    /// this is only given by opaque types.
+    fn describe_pointee() -> Option<BindgenTypeDescriptor> {
+        None
+    }
 }
 
 macro_rules! simple_describe {
@@ -194,14 +1388,243 @@ simple_describe![
     u16 => Int { width: 16, signed: false },
     u32 => Int { width: 32, signed: false },
     u64 => Int { width: 64, signed: false },
+    isize => Int { width: (core::mem::size_of::<isize>() * 8) as u8, signed: true },
+    usize => Int { width: (core::mem::size_of::<usize>() * 8) as u8, signed: false },
+    f32 => Float { width: 32 },
+    f64 => Float { width: 64 },
 ];
 
-impl<'a, T: FfiStable + BindgenTypeDescribe> BindgenTypeDescribe for &'a [T] {
+impl<T: FfiStable + BindgenTypeDescribe> BindgenTypeDescribe for &T {
+    fn describe() -> BindgenTypeDescriptor {
+        let target = Box::new(<T as BindgenTypeDescribe>::describe());
+        BindgenTypeDescriptor::Ref { target }
+    }
+}
+
+impl<T: FfiStable + BindgenTypeDescribe> BindgenTypeDescribe for &mut T {
+    fn describe() -> BindgenTypeDescriptor {
+        let target = Box::new(<T as BindgenTypeDescribe>::describe());
+        BindgenTypeDescriptor::MutRef { target }
+    }
+}
+
+impl<T: BindgenTypeDescribe> BindgenTypeDescribe for *const T {
+    fn describe() -> BindgenTypeDescriptor {
+        BindgenTypeDescriptor::Ptr {
+            name: String::from(core::any::type_name::<T>()),
+            mutable: false,
+            pointee: <T as BindgenTypeDescribe>::describe_pointee().map(Box::new),
+        }
+    }
+}
+
+impl<T: BindgenTypeDescribe> BindgenTypeDescribe for *mut T {
+    fn describe() -> BindgenTypeDescriptor {
+        BindgenTypeDescriptor::Ptr {
+            name: String::from(core::any::type_name::<T>()),
+            mutable: true,
+            pointee: <T as BindgenTypeDescribe>::describe_pointee().map(Box::new),
+        }
+    }
+}
+
+impl<T: BindgenSliceElem + BindgenTypeDescribe> BindgenTypeDescribe for &[T] {
     fn describe() -> BindgenTypeDescriptor {
         let elem_type = Box::new(<T as BindgenTypeDescribe>::describe());
         BindgenTypeDescriptor::Slice { elem_type }
     }
 }
 
+impl<T: BindgenSliceElem + BindgenTypeDescribe> BindgenTypeDescribe for Option<&[T]> {
+    fn describe() -> BindgenTypeDescriptor {
+        let elem_type = Box::new(<T as BindgenTypeDescribe>::describe());
+        BindgenTypeDescriptor::OptionSlice { elem_type }
+    }
+}
+
+impl<T: BindgenSliceElem + BindgenTypeDescribe> BindgenTypeDescribe for &mut [T] {
+    fn describe() -> BindgenTypeDescriptor {
+        let elem_type = Box::new(<T as BindgenTypeDescribe>::describe());
+        BindgenTypeDescriptor::MutSlice { elem_type }
+    }
+}
+
+impl<T: FfiStable + BindgenTypeDescribe> BindgenTypeDescribe for &mut [MaybeUninit<T>] {
+    fn describe() -> BindgenTypeDescriptor {
+        let elem_type = Box::new(<T as BindgenTypeDescribe>::describe());
+        BindgenTypeDescriptor::UninitSlice { elem_type }
+    }
+}
+
+/// Bumped whenever the descriptor types above change in a way that isn't compatible with older
+/// versions of the generator.
+pub const BINDGEN_ABI_VERSION: u32 = 34;
+
+/// The exported symbol holding the BINDGEN_ABI_VERSION a binary was built against.
+///
+/// Binaries built before versioning was introduced won't have this symbol at all, which the
+/// generator treats as version 0.
+pub const BINDGEN_ABI_VERSION_SYMBOL: &str = "__bindgen_abi_version";
+
+#[used]
+#[no_mangle]
+#[doc(hidden)]
+#[allow(non_upper_case_globals)]
+pub static __bindgen_abi_version: u32 = BINDGEN_ABI_VERSION;
+
+/// The exported function returning the `binding_version` of every descriptor in a binary.
+///
+/// Generated bindings compare it against the version they were generated from when first used.
+pub const BINDGEN_VERSION_SYMBOL: &str = "__bindgen_version";
+
+/// A describe function, linked into a list at load time so that the binary can enumerate its own
+/// descriptors.
+#[doc(hidden)]
+pub struct BindgenRegistration {
+    describe: fn() -> Option<BindgenExportDescriptor>,
+    next: AtomicPtr<BindgenRegistration>,
+}
+
+impl BindgenRegistration {
+    pub const fn new(describe: fn() -> Option<BindgenExportDescriptor>) -> Self {
+        Self {
+            describe,
+            next: AtomicPtr::new(core::ptr::null_mut()),
+        }
+    }
+}
+
+static REGISTRATIONS: AtomicPtr<BindgenRegistration> = AtomicPtr::new(core::ptr::null_mut());
+
+/// Adds a describe function to the list walked by `__bindgen_version`.
+///
+/// Called from a static constructor generated alongside each describe function.
+#[doc(hidden)]
+pub fn register(registration: &'static BindgenRegistration) {
+    let new_head = registration as *const BindgenRegistration as *mut BindgenRegistration;
+    let mut head = REGISTRATIONS.load(Ordering::Acquire);
+    loop {
+        registration.next.store(head, Ordering::Relaxed);
+        match REGISTRATIONS.compare_exchange_weak(head, new_head, Ordering::AcqRel, Ordering::Acquire) {
+            Ok(_) => return,
+            Err(current) => head = current,
+        }
+    }
+}
+
+/// FNV-1a, fed with the Debug representation of a descriptor.
+struct DescriptorHasher(u64);
+
+impl Write for DescriptorHasher {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        for byte in s.bytes() {
+            self.0 ^= byte as u64;
+            self.0 = self.0.wrapping_mul(0x100_0000_01b3);
+        }
+        Ok(())
+    }
+}
+
+/// A hash of the given descriptors, which changes whenever any of them do.
+///
+/// The order the descriptors are given in doesn't matter.
+pub fn binding_version<'a>(descriptors: impl IntoIterator<Item = &'a BindgenExportDescriptor>) -> u64 {
+    descriptors
+        .into_iter()
+        .map(|descriptor| {
+            let mut hasher = DescriptorHasher(0xcbf2_9ce4_8422_2325);
+            let _ = write!(hasher, "{:?}", descriptor);
+            hasher.0
+        })
+        .fold(0, u64::wrapping_add)
+}
+
+#[no_mangle]
+#[doc(hidden)]
+pub extern "C" fn __bindgen_version() -> u64 {
+    let mut descriptors = Vec::new();
+    let mut registration = REGISTRATIONS.load(Ordering::Acquire) as *const BindgenRegistration;
+    while let Some(current) = unsafe { registration.as_ref() } {
+        // A describe function that panics is skipped by the generator too.
+        descriptors.extend((current.describe)());
+        registration = current.next.load(Ordering::Relaxed);
+    }
+
+    binding_version(&descriptors)
+}
+
+/// The exported functions allocating and freeing byte buffers with the Rust allocator, which C#
+/// fills in and hands over to functions taking a `Vec<u8>` without a copy.
+pub const BINDGEN_ALLOC_SYMBOL: &str = "__bindgen_alloc";
+pub const BINDGEN_DEALLOC_SYMBOL: &str = "__bindgen_dealloc";
+
+/// Allocates `len` bytes exactly as a `Vec<u8>` with a capacity of `len` would, so that the buffer
+/// can be reclaimed as one.
+///
+/// Nothing is allocated for an empty buffer, which is described by a null pointer.
+#[no_mangle]
+#[doc(hidden)]
+pub extern "C" fn __bindgen_alloc(len: u64) -> *mut u8 {
+    if len == 0 {
+        return core::ptr::null_mut();
+    }
+
+    let layout = Layout::array::<u8>(len as usize).expect("Can't allocate a buffer this large");
+    let ptr = unsafe { alloc::alloc::alloc(layout) };
+    if ptr.is_null() {
+        alloc::alloc::handle_alloc_error(layout);
+    }
+    ptr
+}
+
+/// Frees a buffer from `__bindgen_alloc` which was never handed over to Rust.
+///
+/// # Safety
+///
+/// `ptr` and `len` must be exactly as given to and returned from `__bindgen_alloc`, and the
+/// buffer must not have been freed already.
+#[no_mangle]
+#[doc(hidden)]
+pub unsafe extern "C" fn __bindgen_dealloc(ptr: *mut u8, len: u64) {
+    if len != 0 {
+        drop(Vec::from_raw_parts(ptr, 0, len as usize));
+    }
+}
+
 /// The generator discovers descriptors by scanning the binary for symbols that start with this prefix.
-pub const BINDGEN_DESCRIBE_PREFIX: &'static str = "__bindgen_describe";
+pub const BINDGEN_DESCRIBE_PREFIX: &str = "__bindgen_describe";
+
+/// The linker section holding a pointer to every describe function.
+///
+/// Each describe function is referenced from a `#[used]` static in this section, so that neither
+/// the compiler nor the linker can decide it is dead and discard it.
+pub const BINDGEN_SECTION_NAME: &str = ".bindgen";
+
+/// The equivalent of BINDGEN_SECTION_NAME for Mach-O binaries, which require a segment name too.
+pub const BINDGEN_MACHO_SECTION_NAME: &str = "__DATA,__bindgen";
+
+/// The longest section name that survives being linked into a PE binary, which silently truncates
+/// anything longer.
+pub const MAX_SECTION_NAME_LEN: usize = 8;
+
+const _: () = assert!(BINDGEN_SECTION_NAME.len() <= MAX_SECTION_NAME_LEN);
+
+/// The longest segment or section name a Mach-O binary can hold, each of which is a fixed size
+/// field.
+pub const MAX_MACHO_NAME_LEN: usize = 16;
+
+/// Whether both the segment and section parts of a Mach-O `segment,section` name fit in their
+/// fields.
+const fn macho_name_fits(name: &str) -> bool {
+    let bytes = name.as_bytes();
+    let mut idx = 0;
+    while idx < bytes.len() {
+        if bytes[idx] == b',' {
+            return idx <= MAX_MACHO_NAME_LEN && bytes.len() - idx - 1 <= MAX_MACHO_NAME_LEN;
+        }
+        idx += 1;
+    }
+    false
+}
+
+const _: () = assert!(macho_name_fits(BINDGEN_MACHO_SECTION_NAME));
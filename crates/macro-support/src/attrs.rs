@@ -0,0 +1,239 @@
+//! Parsing of the options given to `#[dotnet_bindgen(...)]` attributes.
+//!
+//! Options are consumed by whichever item they are attached to, and anything left over once that
+//! item has taken all the options it understands is reported as an error, along with the options
+//! it would have understood.
+//!
+//! Several `#[dotnet_bindgen(...)]` attributes on the same item are merged, as if all their options
+//! had been given in one.
+//!
+//! The options are parsed here rather than as a `syn::Meta`, which only takes literals as values,
+//! so that negative numbers can be given, eg `#[dotnet_bindgen(default(offset = -1))]`.
+
+use proc_macro2::{Span, TokenStream};
+use syn::ext::IdentExt;
+use syn::parse::{Parse, ParseStream, Parser};
+use syn::punctuated::Punctuated;
+
+use crate::Diagnostic;
+
+/// The value half of a single option
+pub enum BindgenAttrValue {
+    /// A bare flag, eg `#[dotnet_bindgen(raw_ptr)]`
+    Flag,
+
+    /// A key/value pair, eg `#[dotnet_bindgen(direction = "in")]`
+    Lit(syn::Lit),
+
+    /// A key/value pair whose value is negated, eg `#[dotnet_bindgen(default(offset = -1))]`
+    NegatedLit(syn::Lit),
+
+    /// A nested set of options, eg `#[dotnet_bindgen(returns_buffer(len_out = "len"))]`
+    List(BindgenAttrs),
+}
+
+pub struct BindgenAttr {
+    pub key: String,
+    pub span: Span,
+    pub value: BindgenAttrValue,
+}
+
+impl Parse for BindgenAttr {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        // Keywords are accepted as keys, as they are by syn::Meta
+        let key = input.call(syn::Ident::parse_any)?;
+
+        let value = if input.peek(syn::token::Paren) {
+            let content;
+            syn::parenthesized!(content in input);
+            BindgenAttrValue::List(BindgenAttrs::parse_options(&content)?)
+        } else if input.parse::<Option<syn::Token![=]>>()?.is_some() {
+            match input.parse::<Option<syn::Token![-]>>()? {
+                Some(_) => BindgenAttrValue::NegatedLit(input.parse()?),
+                None => BindgenAttrValue::Lit(input.parse()?),
+            }
+        } else {
+            BindgenAttrValue::Flag
+        };
+
+        Ok(Self {
+            key: key.to_string(),
+            span: key.span(),
+            value,
+        })
+    }
+}
+
+#[derive(Default)]
+pub struct BindgenAttrs {
+    attrs: Vec<BindgenAttr>,
+
+    /// Every key the item has asked for, whether or not it was given, to suggest in place of any
+    /// unknown options.
+    known_keys: Vec<String>,
+}
+
+impl BindgenAttrs {
+    /// Parses the arguments of the attribute that invoked the macro itself.
+    pub fn parse_args(tokens: TokenStream) -> Result<Self, Diagnostic> {
+        Ok(Self::parse_options.parse2(tokens)?)
+    }
+
+    /// Removes any `#[dotnet_bindgen(...)]` attributes from the given set, returning the options
+    /// they contained.
+    ///
+    /// Such helper attributes must be stripped from the item before it is emitted, as rustc would
+    /// otherwise try to expand them as macros in their own right.
+    pub fn take_from(attrs: &mut Vec<syn::Attribute>) -> Result<Self, Diagnostic> {
+        let mut parsed = Self::default();
+
+        let mut error = None;
+        attrs.retain(|attr| {
+            if !attr.path.is_ident("dotnet_bindgen") {
+                return true;
+            }
+
+            // A bare #[dotnet_bindgen] has no options
+            let result = if attr.tokens.is_empty() {
+                Ok(Self::default())
+            } else {
+                attr.parse_args_with(Self::parse_options).map_err(Diagnostic::from)
+            };
+
+            match result {
+                Ok(more) => parsed.attrs.extend(more.attrs),
+                Err(e) => {
+                    error.get_or_insert(e);
+                }
+            }
+
+            false
+        });
+
+        match error {
+            Some(e) => Err(e),
+            None => Ok(parsed),
+        }
+    }
+
+    /// Parses a comma separated list of options.
+    fn parse_options(input: ParseStream) -> syn::Result<Self> {
+        let attrs = Punctuated::<BindgenAttr, syn::Token![,]>::parse_terminated(input)?;
+
+        Ok(Self {
+            attrs: attrs.into_iter().collect(),
+            known_keys: Vec::new(),
+        })
+    }
+
+    /// Adds the options from another attribute on the same item.
+    pub fn extend(&mut self, other: BindgenAttrs) {
+        self.attrs.extend(other.attrs);
+    }
+
+    fn take(&mut self, key: &str) -> Result<Option<BindgenAttr>, Diagnostic> {
+        if !self.known_keys.iter().any(|k| k == key) {
+            self.known_keys.push(key.to_string());
+        }
+
+        let idx = match self.attrs.iter().position(|a| a.key == key) {
+            Some(idx) => idx,
+            None => return Ok(None),
+        };
+        let attr = self.attrs.remove(idx);
+
+        // Eg from two stacked attributes, which would otherwise have to silently pick one
+        if let Some(duplicate) = self.attrs.iter().find(|a| a.key == key) {
+            return Err(Diagnostic::span_error(
+                duplicate.span,
+                format!("The dotnet_bindgen option {} is given more than once", key),
+            ));
+        }
+
+        Ok(Some(attr))
+    }
+
+    /// Takes the option `key = "value"`, if present.
+    pub fn take_str(&mut self, key: &str) -> Result<Option<syn::LitStr>, Diagnostic> {
+        match self.take(key)? {
+            None => Ok(None),
+            Some(BindgenAttr {
+                value: BindgenAttrValue::Lit(syn::Lit::Str(s)),
+                ..
+            }) => Ok(Some(s)),
+            Some(attr) => Err(Diagnostic::span_error(
+                attr.span,
+                format!("Expected the dotnet_bindgen option {} to be of the form {} = \"...\"", key, key),
+            )),
+        }
+    }
+
+    /// Takes the option `key = <literal>` or `key = -<literal>`, if present, whatever kind of
+    /// literal it is, along with whether it was negated.
+    pub fn take_lit(&mut self, key: &str) -> Result<Option<(bool, syn::Lit)>, Diagnostic> {
+        match self.take(key)? {
+            None => Ok(None),
+            Some(BindgenAttr {
+                value: BindgenAttrValue::Lit(lit),
+                ..
+            }) => Ok(Some((false, lit))),
+            Some(BindgenAttr {
+                value: BindgenAttrValue::NegatedLit(lit),
+                ..
+            }) => Ok(Some((true, lit))),
+            Some(attr) => Err(Diagnostic::span_error(
+                attr.span,
+                format!("Expected the dotnet_bindgen option {} to be of the form {} = <literal>", key, key),
+            )),
+        }
+    }
+
+    /// Takes the bare flag `key`, returning whether it was present.
+    pub fn take_flag(&mut self, key: &str) -> Result<bool, Diagnostic> {
+        match self.take(key)? {
+            None => Ok(false),
+            Some(BindgenAttr {
+                value: BindgenAttrValue::Flag,
+                ..
+            }) => Ok(true),
+            Some(attr) => Err(Diagnostic::span_error(
+                attr.span,
+                format!("The dotnet_bindgen option {} doesn't take a value", key),
+            )),
+        }
+    }
+
+    /// Takes the nested options `key(...)`, if present.
+    pub fn take_list(&mut self, key: &str) -> Result<Option<BindgenAttrs>, Diagnostic> {
+        match self.take(key)? {
+            None => Ok(None),
+            Some(BindgenAttr {
+                value: BindgenAttrValue::List(attrs),
+                ..
+            }) => Ok(Some(attrs)),
+            Some(attr) => Err(Diagnostic::span_error(
+                attr.span,
+                format!("Expected the dotnet_bindgen option {} to be of the form {}(...)", key, key),
+            )),
+        }
+    }
+
+    /// Errors if any options were given that haven't been consumed by the item.
+    pub fn finish(self) -> Result<(), Diagnostic> {
+        let expected = match self.known_keys.as_slice() {
+            [] => "this item takes no options".to_string(),
+            keys => format!("expected one of: {}", keys.join(", ")),
+        };
+
+        let errors = self
+            .attrs
+            .into_iter()
+            .map(|attr| Diagnostic::span_error(
+                attr.span,
+                format!("Unknown or misplaced dotnet_bindgen option: {}, {}", attr.key, expected),
+            ))
+            .collect();
+
+        Diagnostic::from_vec(errors)
+    }
+}
@@ -2,23 +2,87 @@ use proc_macro2::TokenStream;
 use quote::{format_ident, quote, quote_spanned, ToTokens};
 use syn::spanned::Spanned;
 
+mod attrs;
 mod error;
 pub use crate::error::Diagnostic;
 
+use crate::attrs::BindgenAttrs;
+
 use dotnet_bindgen_core::*;
 
+/// A `#[used]` static in the bindgen link section which references the given describe function.
+///
+/// Nothing in the program itself calls a describe function, so without this aggressive dead code
+/// elimination (eg `--gc-sections`, `opt-level = "z"`) is free to drop them.
+fn describe_registration(
+    descriptor_name: &proc_macro2::Ident,
+    owner: Option<&proc_macro2::Ident>,
+) -> TokenStream {
+    let static_name = format_ident!("{}_REGISTRATION", descriptor_name.to_string().to_uppercase());
+    let list_entry_name = format_ident!("{}_LIST_ENTRY", descriptor_name.to_string().to_uppercase());
+    let constructor_name = format_ident!("{}_CONSTRUCTOR", descriptor_name.to_string().to_uppercase());
+    let descriptor_path = match owner {
+        Some(owner) => quote! { #owner::#descriptor_name },
+        None => quote! { #descriptor_name },
+    };
+
+    // The list entry is added by a static constructor, run when the binary is loaded, so that
+    // `__bindgen_version` can find every descriptor in the binary.
+    quote! {
+        #[used]
+        #[doc(hidden)]
+        #[allow(non_upper_case_globals)]
+        #[cfg_attr(target_vendor = "apple", link_section = #BINDGEN_MACHO_SECTION_NAME)]
+        #[cfg_attr(not(target_vendor = "apple"), link_section = #BINDGEN_SECTION_NAME)]
+        static #static_name: fn() -> ::core::option::Option<::dotnet_bindgen::core::BindgenExportDescriptor> = #descriptor_path;
+
+        #[doc(hidden)]
+        #[allow(non_upper_case_globals)]
+        static #list_entry_name: ::dotnet_bindgen::core::BindgenRegistration =
+            ::dotnet_bindgen::core::BindgenRegistration::new(#descriptor_path);
+
+        #[used]
+        #[doc(hidden)]
+        #[allow(non_upper_case_globals)]
+        #[cfg_attr(target_vendor = "apple", link_section = "__DATA,__mod_init_func")]
+        #[cfg_attr(windows, link_section = ".CRT$XCU")]
+        #[cfg_attr(not(any(target_vendor = "apple", windows)), link_section = ".init_array")]
+        static #constructor_name: extern "C" fn() = {
+            extern "C" fn register() {
+                ::dotnet_bindgen::core::register(&#list_entry_name);
+            }
+            register
+        };
+    }
+}
+
 struct ExportedFunctionArg {
     name: proc_macro2::Ident,
     ty: syn::Type,
+    direction: Option<BindgenArgumentDirection>,
+
+    /// Set by `#[dotnet_bindgen(raw_ptr)]`, in which case `ty` is a raw pointer that crosses the
+    /// boundary as a `*mut c_void` without going through `BindgenAbiConvert`.
+    raw_ptr: bool,
+
+    /// Set by `#[dotnet_bindgen(encoding = "...")]`
+    encoding: Option<BindgenStringEncoding>,
+
+    /// Set by `#[dotnet_bindgen(fill_buffer)]`, marking the `&mut [u8]` a fill-buffer style
+    /// function writes into.
+    fill_buffer: bool,
+
+    /// Set by `#[dotnet_bindgen(default(...))]` on the function
+    default: Option<BindgenDefaultValue>,
 }
 
 impl std::fmt::Debug for ExportedFunctionArg {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let ty_string = format!("syn::Type({})", self.ty.to_token_stream().to_string());
+        let ty_string = format!("syn::Type({})", self.ty.to_token_stream());
         write!(
             f,
-            "ExportedFunctionArg {{ name: {}, ty: {} }}",
-            self.name, ty_string
+            "ExportedFunctionArg {{ name: {}, ty: {}, direction: {:?}, raw_ptr: {}, encoding: {:?}, fill_buffer: {}, default: {:?} }}",
+            self.name, ty_string, self.direction, self.raw_ptr, self.encoding, self.fill_buffer, self.default
         )
     }
 }
@@ -27,19 +91,63 @@ struct ExportedFunction {
     name: proc_macro2::Ident,
     arguments: Vec<ExportedFunctionArg>,
     return_ty: Option<syn::Type>,
+
+    /// The struct whose impl block this function was declared in, if any
+    owner: Option<proc_macro2::Ident>,
+
+    receiver: Option<BindgenReceiver>,
+
+    blocking: bool,
+
+    no_gc_transition: bool,
+
+    calling_convention: BindgenCallingConvention,
+
+    /// Set by `#[dotnet_bindgen(returns_buffer(len_out = "..."))]`, in which case the function
+    /// returns a `*const u8` and writes the buffer's length through this `*mut usize` argument.
+    buffer_len_arg: Option<proc_macro2::Ident>,
+
+    /// The argument marked `#[dotnet_bindgen(fill_buffer)]`, if any.
+    fill_buffer_arg: Option<proc_macro2::Ident>,
+
+    /// Set by `#[dotnet_bindgen(returns_dictionary)]` on functions returning a `Vec` of key/value
+    /// pairs, which are then bound as returning a C# `Dictionary`.
+    returns_dictionary: bool,
+
+    /// Set by `#[dotnet_bindgen(return_encoding = "...")]`, the encoding a returned `CString` is
+    /// decoded from.
+    return_encoding: Option<BindgenStringEncoding>,
+
+    /// Set by `#[dotnet_bindgen(returns_owned)]` or `#[dotnet_bindgen(returns_borrowed)]`, which
+    /// functions returning anything but a `Copy` type must give.
+    return_ownership: Option<BindgenReturnOwnership>,
+
+    /// Set by `#[dotnet_bindgen(module = "...")]` on free functions, a dotted path of the nested
+    /// static classes the function is bound in.
+    module: Option<String>,
+
+    /// Declared in an `extern` block, so there's no Rust body to generate a thunk around, and C#
+    /// calls the foreign symbol directly.
+    foreign: bool,
+
+    /// Set by `#[dotnet_bindgen(block_on)]` on async functions, whose thunk blocks on the returned
+    /// future with the executor registered through `set_block_on`.
+    block_on: bool,
 }
 
 impl std::fmt::Debug for ExportedFunction {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let return_ty_string = match &self.return_ty {
-            Some(t) => format!("Some(syn::Type({}))", t.to_token_stream().to_string()),
+            Some(t) => format!("Some(syn::Type({}))", t.to_token_stream()),
             None => "None".to_string(),
         };
 
         write!(
             f,
-            "ExportedFunction {{ name: {}, arguments: {:?}, return_ty: {:?} }}",
-            self.name, self.arguments, return_ty_string
+            "ExportedFunction {{ name: {}, arguments: {:?}, return_ty: {:?}, owner: {:?}, receiver: {:?}, blocking: {}, no_gc_transition: {}, calling_convention: {:?}, buffer_len_arg: {:?}, fill_buffer_arg: {:?}, returns_dictionary: {}, return_encoding: {:?}, return_ownership: {:?}, module: {:?}, foreign: {}, block_on: {} }}",
+            self.name, self.arguments, return_ty_string, self.owner, self.receiver, self.blocking,
+            self.no_gc_transition, self.calling_convention, self.buffer_len_arg, self.fill_buffer_arg, self.returns_dictionary,
+            self.return_encoding, self.return_ownership, self.module, self.foreign, self.block_on
         )
     }
 }
@@ -53,55 +161,315 @@ impl ToTokens for ExportedFunction {
         for arg in &self.arguments {
             let name = &arg.name;
             let ty = &arg.ty;
-            thunk_args.push(
-                quote! {
-                    #name: <#ty as ::dotnet_bindgen::core::BindgenAbiConvert>::AbiType
-                }
-                .to_token_stream(),
-            );
 
-            arg_conversions.push(quote! {
-                let #name = <#ty as ::dotnet_bindgen::core::BindgenAbiConvert>::from_abi_type(#name);
-            });
+            if let Some(elem_ty) = slice_elem_type(ty) {
+                let elem_span = elem_ty.span();
+                arg_conversions.push(quote_spanned! {elem_span=>
+                    ::dotnet_bindgen::core::__private::assert_copy::<#elem_ty>();
+                });
+            }
+
+            if arg.raw_ptr {
+                thunk_args.push(quote! { #name: *mut ::core::ffi::c_void });
+                arg_conversions.push(quote! { let #name = #name as #ty; });
+            } else {
+                thunk_args.push(
+                    quote! {
+                        #name: <#ty as ::dotnet_bindgen::core::BindgenAbiConvert>::AbiType
+                    }
+                    .to_token_stream(),
+                );
+
+                arg_conversions.push(quote! {
+                    let #name = <#ty as ::dotnet_bindgen::core::BindgenAbiConvert>::from_abi_type(#name);
+                });
+            }
 
             let name_string = name.to_string();
+            let direction = match arg.direction {
+                None => quote! { None },
+                Some(BindgenArgumentDirection::In) => quote! {
+                    Some(::dotnet_bindgen::core::BindgenArgumentDirection::In)
+                },
+                Some(BindgenArgumentDirection::Out) => quote! {
+                    Some(::dotnet_bindgen::core::BindgenArgumentDirection::Out)
+                },
+                Some(BindgenArgumentDirection::InOut) => quote! {
+                    Some(::dotnet_bindgen::core::BindgenArgumentDirection::InOut)
+                },
+            };
+            let ty_descriptor = if arg.raw_ptr {
+                quote! { ::dotnet_bindgen::core::BindgenTypeDescriptor::RawPtr }
+            } else {
+                quote! { <#ty as ::dotnet_bindgen::core::BindgenTypeDescribe>::describe() }
+            };
+            let encoding = encoding_frag(arg.encoding);
+            let default = default_frag(arg.default.as_ref());
             arg_descriptors.push(quote! {
                 ::dotnet_bindgen::core::BindgenFunctionArgumentDescriptor {
-                    name: #name_string.to_string(),
-                    ty: <#ty as ::dotnet_bindgen::core::BindgenTypeDescribe>::describe(),
+                    name: ::dotnet_bindgen::core::__private::ToString::to_string(#name_string),
+                    ty: #ty_descriptor,
+                    direction: #direction,
+                    encoding: #encoding,
+                    default: #default,
                 }
             })
         }
 
-        let arg_names = self.arguments.iter().map(|a| a.name.clone());
+        // String and enum arguments are checked before anything is converted, so that invalid
+        // UTF-8 or an undefined discriminant is reported to C# rather than panicking in
+        // `from_abi_type`. The function isn't called, and the returned value is left uninitialized,
+        // which C# never reads after the flag is set.
+        //
+        // Whether the thunk takes the flag has to be decided from how the types are written, but
+        // which types are checked is up to `BindgenAbiConvert::VALIDATES_UTF8` and
+        // `CHECKS_DISCRIMINANTS`, and any other argument of such a type fails to compile rather
+        // than going unchecked. A struct can't be told apart from an enum by name, so those get
+        // the flag too, and their checks optimise away.
+        let (utf8_args, other_args): (Vec<_>, Vec<_>) = self.arguments
+            .iter()
+            .filter(|arg| !arg.raw_ptr)
+            .partition(|arg| is_str_type(&arg.ty));
+        let (enum_args, other_args): (Vec<_>, Vec<_>) = other_args
+            .into_iter()
+            .partition(|arg| may_be_enum_type(&arg.ty));
+        let reports_invalid_args = !self.foreign && (!utf8_args.is_empty() || !enum_args.is_empty());
+        if reports_invalid_args {
+            let early_return = match &self.return_ty {
+                Some(_) => quote! { ::core::mem::MaybeUninit::uninit() },
+                None => quote! {},
+            };
+            let utf8_checks = utf8_args.iter().map(|arg| {
+                let (name, ty) = (&arg.name, &arg.ty);
+                quote! {
+                    && <#ty as ::dotnet_bindgen::core::BindgenAbiConvert>::is_valid_utf8(&#name)
+                }
+            });
+            let discriminant_checks = enum_args.iter().map(|arg| {
+                let (name, ty) = (&arg.name, &arg.ty);
+                quote! {
+                    && <#ty as ::dotnet_bindgen::core::BindgenAbiConvert>::has_valid_discriminants(&#name)
+                }
+            });
+            thunk_args.push(quote! { __bindgen_invalid_arg: &mut u8 });
+            if !enum_args.is_empty() {
+                arg_conversions.insert(0, quote! {
+                    if !(true #(#discriminant_checks)*) {
+                        *__bindgen_invalid_arg = 2;
+                        return #early_return;
+                    }
+                });
+            }
+            if !utf8_args.is_empty() {
+                arg_conversions.insert(0, quote! {
+                    if cfg!(debug_assertions) && !(true #(#utf8_checks)*) {
+                        *__bindgen_invalid_arg = 1;
+                        return #early_return;
+                    }
+                });
+            }
+        }
+        if !self.foreign {
+            for arg in &enum_args {
+                let ty = &arg.ty;
+                arg_conversions.insert(0, quote_spanned! {ty.span()=>
+                    let () = ::dotnet_bindgen::core::__private::AssertNoUtf8Check::<#ty>::OK;
+                });
+            }
+            for arg in other_args {
+                let ty = &arg.ty;
+                arg_conversions.insert(0, quote_spanned! {ty.span()=>
+                    let () = ::dotnet_bindgen::core::__private::AssertNoUtf8Check::<#ty>::OK;
+                    let () = ::dotnet_bindgen::core::__private::AssertNoDiscriminantCheck::<#ty>::OK;
+                });
+            }
+        }
+
+        let mut call_args: Vec<TokenStream> = self.arguments
+            .iter()
+            .map(|a| a.name.to_token_stream())
+            .collect();
+
+        // Receivers need no conversion, so Self must be FfiStable, which rules out C-like enums as
+        // their discriminants would go unchecked.
+        if let Some(receiver) = self.receiver {
+            if !self.foreign {
+                arg_conversions.insert(0, quote! {
+                    ::dotnet_bindgen::core::__private::assert_ffi_stable::<Self>();
+                });
+            }
+
+            let this_ty = match receiver {
+                BindgenReceiver::Value => quote! { Self },
+                BindgenReceiver::Ref => quote! { &Self },
+                BindgenReceiver::RefMut => quote! { &mut Self },
+            };
+            thunk_args.insert(0, quote! { this: #this_ty });
+            call_args.insert(0, quote! { this });
+        }
 
         let real_name = &self.name;
-        let thunk_name = format_ident!("__bindgen_thunk_{}", self.name);
-        let descriptor_name = format_ident!("{}_func_{}", BINDGEN_DESCRIBE_PREFIX, self.name);
+        let (thunk_name, free_thunk_name, clone_thunk_name, descriptor_name, call_path) = match &self.owner {
+            None if self.foreign => (
+                self.name.clone(),
+                format_ident!("__bindgen_free_{}", self.name),
+                format_ident!("__bindgen_clone_{}", self.name),
+                format_ident!("{}_func_{}", BINDGEN_DESCRIBE_PREFIX, self.name),
+                quote! { #real_name },
+            ),
+            Some(owner) => (
+                format_ident!("__bindgen_thunk_{}_{}", owner, self.name),
+                format_ident!("__bindgen_free_{}_{}", owner, self.name),
+                format_ident!("__bindgen_clone_{}_{}", owner, self.name),
+                format_ident!("{}_func_{}_{}", BINDGEN_DESCRIBE_PREFIX, owner, self.name),
+                quote! { Self::#real_name },
+            ),
+            None => (
+                format_ident!("__bindgen_thunk_{}", self.name),
+                format_ident!("__bindgen_free_{}", self.name),
+                format_ident!("__bindgen_clone_{}", self.name),
+                format_ident!("{}_func_{}", BINDGEN_DESCRIBE_PREFIX, self.name),
+                quote! { #real_name },
+            ),
+        };
         let real_name_string = real_name.to_string();
         let thunk_name_string = thunk_name.to_string();
 
+        let abi = match self.calling_convention {
+            BindgenCallingConvention::C => quote! { "C" },
+            BindgenCallingConvention::System => quote! { "system" },
+        };
+
+        // Foreign functions are called as is, so can only take and return FfiStable types. They
+        // are also referenced here, as nothing on the Rust side might use them otherwise.
+        let ffi_stable_checks: Vec<_> = if self.foreign {
+            self.arguments
+                .iter()
+                .filter(|arg| !arg.raw_ptr)
+                .map(|arg| &arg.ty)
+                .chain(self.return_ty.as_ref())
+                .map(|ty| quote_spanned! {ty.span()=>
+                    ::dotnet_bindgen::core::__private::assert_ffi_stable::<#ty>();
+                })
+                .chain(std::iter::once(quote! { let _ = #real_name; }))
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        // Whether C# should free a returned value can't be told from its type, so anything which
+        // might need freeing must say.
+        let ownership_check = match &self.return_ty {
+            Some(ty) if self.return_ownership.is_none() && !self.foreign => quote_spanned! {ty.span()=>
+                ::dotnet_bindgen::core::__private::assert_unannotated_return::<#ty>();
+            },
+            _ => quote! {},
+        };
+
+        // Some types, eg a Cow, can only be borrowed on the way in, so mustn't be returned.
+        let returnable_check = match &self.return_ty {
+            Some(ty) if self.buffer_len_arg.is_none() && !self.foreign => quote_spanned! {ty.span()=>
+                ::dotnet_bindgen::core::__private::assert_returnable::<#ty>();
+            },
+            _ => quote! {},
+        };
+
+        let mut call = quote! { #call_path(#(#call_args),*) };
+        if self.block_on {
+            call = quote! { ::dotnet_bindgen::core::__private::block_on(#call) };
+        }
+
         let thunk = match &self.return_ty {
+            _ if self.foreign => quote! {},
+            // The returned pointer is FfiStable as is.
+            Some(ty) if self.buffer_len_arg.is_some() && reports_invalid_args => quote! {
+                #[no_mangle]
+                pub extern #abi fn #thunk_name(#(#thunk_args),*) -> ::core::mem::MaybeUninit<#ty> {
+                    #(#arg_conversions)*
+                    ::core::mem::MaybeUninit::new(#call)
+                }
+            },
+            Some(ty) if self.buffer_len_arg.is_some() => quote! {
+                #[no_mangle]
+                pub extern #abi fn #thunk_name(#(#thunk_args),*) -> #ty {
+                    #(#arg_conversions)*
+                    #call
+                }
+            },
+            // MaybeUninit has the same ABI as the value it wraps.
+            Some(ty) if reports_invalid_args => quote!{
+                #[no_mangle]
+                pub extern #abi fn #thunk_name(
+                    #(#thunk_args),*
+                ) -> ::core::mem::MaybeUninit<<#ty as ::dotnet_bindgen::core::BindgenAbiConvert>::AbiType> {
+                    #(#arg_conversions)*
+                    let ret = #call;
+                    ::core::mem::MaybeUninit::new(<#ty as ::dotnet_bindgen::core::BindgenAbiReturn>::to_abi_type(ret))
+                }
+            },
             Some(ty) => quote!{
                 #[no_mangle]
-                pub extern "C" fn #thunk_name(
+                pub extern #abi fn #thunk_name(
                     #(#thunk_args),*
                 ) -> <#ty as ::dotnet_bindgen::core::BindgenAbiConvert>::AbiType {
                     #(#arg_conversions)*
-                    let ret = #real_name(#(#arg_names),*);
-                    <#ty as ::dotnet_bindgen::core::BindgenAbiConvert>::to_abi_type(ret)
+                    let ret = #call;
+                    <#ty as ::dotnet_bindgen::core::BindgenAbiReturn>::to_abi_type(ret)
                 }
             },
             None => quote! {
                 #[no_mangle]
-                pub extern "C" fn #thunk_name(#(#thunk_args),*) {
+                pub extern #abi fn #thunk_name(#(#thunk_args),*) {
                     #(#arg_conversions)*
-                    #real_name(#(#arg_names),*);
+                    #call;
                 }
             }
         };
 
+        // Hands a returned value back to Rust to be dropped, which is how C# frees anything the
+        // function allocated. Borrowed values are never C#'s to free.
+        let borrowed = self.return_ownership == Some(BindgenReturnOwnership::Borrowed);
+        let (free_thunk, free_thunk_name_frag) = match &self.return_ty {
+            Some(ty) if self.buffer_len_arg.is_none() && !self.foreign && !borrowed => {
+                let free_thunk_name_string = free_thunk_name.to_string();
+                (
+                    quote! {
+                        #[no_mangle]
+                        pub extern #abi fn #free_thunk_name(
+                            value: <#ty as ::dotnet_bindgen::core::BindgenAbiConvert>::AbiType
+                        ) {
+                            let _ = <#ty as ::dotnet_bindgen::core::BindgenAbiConvert>::from_abi_type(value);
+                        }
+                    },
+                    quote! { Some(::dotnet_bindgen::core::__private::ToString::to_string(#free_thunk_name_string)) },
+                )
+            }
+            _ => (quote! {}, quote! { None }),
+        };
+
+        // Takes another reference to a returned Arc, for C# to hand over to functions taking one.
+        let (clone_thunk, clone_thunk_name_frag) = match &self.return_ty {
+            Some(ty) if is_arc(ty) && !self.foreign => {
+                let clone_thunk_name_string = clone_thunk_name.to_string();
+                (
+                    quote! {
+                        #[no_mangle]
+                        pub extern #abi fn #clone_thunk_name(
+                            value: <#ty as ::dotnet_bindgen::core::BindgenAbiConvert>::AbiType
+                        ) -> <#ty as ::dotnet_bindgen::core::BindgenAbiConvert>::AbiType {
+                            <#ty as ::dotnet_bindgen::core::BindgenHandle>::clone_abi_type(&value)
+                        }
+                    },
+                    quote! { Some(::dotnet_bindgen::core::__private::ToString::to_string(#clone_thunk_name_string)) },
+                )
+            }
+            _ => (quote! {}, quote! { None }),
+        };
+
         let return_ty_descriptor_frag = match &self.return_ty {
+            Some(_) if self.buffer_len_arg.is_some() => quote! {
+                ::dotnet_bindgen::core::BindgenTypeDescriptor::RawPtr
+            },
             Some(ty) => quote! {
                 <#ty as ::dotnet_bindgen::core::BindgenTypeDescribe>::describe()
             },
@@ -110,23 +478,127 @@ impl ToTokens for ExportedFunction {
             }
         };
 
+        let owner_frag = match &self.owner {
+            Some(owner) => {
+                let owner_string = owner.to_string();
+                quote! { Some(::dotnet_bindgen::core::__private::ToString::to_string(#owner_string)) }
+            }
+            None => quote! { None },
+        };
+
+        let receiver_frag = match self.receiver {
+            None => quote! { None },
+            Some(BindgenReceiver::Value) => quote! {
+                Some(::dotnet_bindgen::core::BindgenReceiver::Value)
+            },
+            Some(BindgenReceiver::Ref) => quote! {
+                Some(::dotnet_bindgen::core::BindgenReceiver::Ref)
+            },
+            Some(BindgenReceiver::RefMut) => quote! {
+                Some(::dotnet_bindgen::core::BindgenReceiver::RefMut)
+            },
+        };
+
+        let buffer_len_arg_frag = match &self.buffer_len_arg {
+            Some(arg) => {
+                let arg_string = arg.to_string();
+                quote! { Some(::dotnet_bindgen::core::__private::ToString::to_string(#arg_string)) }
+            }
+            None => quote! { None },
+        };
+
+        let fill_buffer_arg_frag = match &self.fill_buffer_arg {
+            Some(arg) => {
+                let arg_string = arg.to_string();
+                quote! { Some(::dotnet_bindgen::core::__private::ToString::to_string(#arg_string)) }
+            }
+            None => quote! { None },
+        };
+
+        let blocking = self.blocking;
+        let no_gc_transition = self.no_gc_transition;
+        let returns_dictionary = self.returns_dictionary;
+        let return_encoding = encoding_frag(self.return_encoding);
+        let return_ownership = match self.return_ownership {
+            None => quote! { None },
+            Some(BindgenReturnOwnership::Owned) => quote! {
+                Some(::dotnet_bindgen::core::BindgenReturnOwnership::Owned)
+            },
+            Some(BindgenReturnOwnership::Borrowed) => quote! {
+                Some(::dotnet_bindgen::core::BindgenReturnOwnership::Borrowed)
+            },
+        };
+        let foreign = self.foreign;
+        let module_frag = match &self.module {
+            Some(module) => quote! { Some(::dotnet_bindgen::core::__private::ToString::to_string(#module)) },
+            None => quote! { None },
+        };
+        let calling_convention = match self.calling_convention {
+            BindgenCallingConvention::C => quote! {
+                ::dotnet_bindgen::core::BindgenCallingConvention::C
+            },
+            BindgenCallingConvention::System => quote! {
+                ::dotnet_bindgen::core::BindgenCallingConvention::System
+            },
+        };
+
         let descriptor = quote! {
             #[no_mangle]
-            pub fn #descriptor_name() -> ::dotnet_bindgen::core::BindgenExportDescriptor {
-                ::dotnet_bindgen::core::BindgenExportDescriptor::Function(
+            pub fn #descriptor_name() -> ::core::option::Option<::dotnet_bindgen::core::BindgenExportDescriptor> {
+                #(#ffi_stable_checks)*
+                #ownership_check
+                #returnable_check
+                ::dotnet_bindgen::core::__private::catch_describe_panic(|| ::dotnet_bindgen::core::BindgenExportDescriptor::Function(
                     ::dotnet_bindgen::core::BindgenFunctionDescriptor {
-                        real_name: #real_name_string.to_string(),
-                        thunk_name: #thunk_name_string.to_string(),
-                        arguments: vec![#(#arg_descriptors),*],
+                        real_name: ::dotnet_bindgen::core::__private::ToString::to_string(#real_name_string),
+                        thunk_name: ::dotnet_bindgen::core::__private::ToString::to_string(#thunk_name_string),
+                        owner: #owner_frag,
+                        receiver: #receiver_frag,
+                        blocking: #blocking,
+                        no_gc_transition: #no_gc_transition,
+                        calling_convention: #calling_convention,
+                        arguments: ::dotnet_bindgen::core::__private::vec![#(#arg_descriptors),*],
                         return_ty: #return_ty_descriptor_frag,
+                        return_ownership: #return_ownership,
+                        free_thunk_name: #free_thunk_name_frag,
+                        clone_thunk_name: #clone_thunk_name_frag,
+                        buffer_len_arg: #buffer_len_arg_frag,
+                        fill_buffer_arg: #fill_buffer_arg_frag,
+                        returns_dictionary: #returns_dictionary,
+                        return_encoding: #return_encoding,
+                        module: #module_frag,
+                        no_thunk: #foreign,
+                        reports_invalid_args: #reports_invalid_args,
                     }
-                )
+                ))
             }
         };
 
+        let registration = describe_registration(&descriptor_name, self.owner.as_ref());
+
+        // Anything declared in an impl block is generated inside another impl block for the same
+        // type, so that `Self` means the same thing in the generated code.
+        let generated = match &self.owner {
+            Some(owner) => quote! {
+                #[allow(non_snake_case)]
+                impl #owner {
+                    #thunk
+                    #free_thunk
+                    #clone_thunk
+                    #descriptor
+                }
+            },
+            None => quote! {
+                #thunk
+                #free_thunk
+                #clone_thunk
+                #descriptor
+            },
+        };
+
         (quote! {
-            #thunk
-            #descriptor
+            #generated
+            #registration
         }).to_tokens(tokens);
     }
 }
@@ -139,7 +611,7 @@ struct ExportedStructField {
 
 impl std::fmt::Debug for ExportedStructField {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let ty_string = format!("syn::Type({})", self.ty.to_token_stream().to_string());
+        let ty_string = format!("syn::Type({})", self.ty.to_token_stream());
         write!(f, "ExportedStructField {{ name: {}, ty: {} }}", self.name, ty_string)
     }
 }
@@ -148,28 +620,52 @@ struct ExportedStruct {
     name: proc_macro2::Ident,
     fields: Vec<ExportedStructField>,
     span: proc_macro2::Span,
+
+    /// Overrides the name of the struct in the generated C#
+    cs_name: Option<String>,
+
+    /// Whether the struct is an inline `{ bytes: [u8; N], len: u8 }` string
+    fixed_string: bool,
 }
 
 impl std::fmt::Debug for ExportedStruct {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "ExportedStruct {{ name: {}, fields: {:?} }}", self.name, self.fields)
+        write!(
+            f,
+            "ExportedStruct {{ name: {}, fields: {:?}, cs_name: {:?}, fixed_string: {:?} }}",
+            self.name, self.fields, self.cs_name, self.fixed_string
+        )
+    }
+}
+
+/// The bound a field's type must satisfy for the struct holding it to be FfiStable.
+///
+/// Raw pointers are FfiStable whatever they point to, so rather than requiring the pointee to be
+/// FfiStable (which never resolves for a self-referential struct), it only needs to be described.
+fn field_stability_bound(ty: &syn::Type) -> TokenStream {
+    match ty {
+        syn::Type::Ptr(ptr) => {
+            let elem = &ptr.elem;
+            quote! { #elem: ::dotnet_bindgen::core::BindgenTypeDescribe }
+        }
+        _ => quote! { #ty: ::dotnet_bindgen::core::FfiStableField },
     }
 }
 
 impl ExportedStruct {
     /// For each member, produces an item of the form
-    ///     `struct Assert3 where String: FfiStable`
+    ///     `struct Assert3 where String: FfiStableField`
     /// to fail compilation with an appropriate error message with an appropriate span when the
     /// exported struct can not be FfiStable
     fn ffi_stable_member_assertions(&self) -> TokenStream {
         let mut assertions = Vec::new();
         for field in &self.fields {
             let assert_struct_ident = format_ident!("_AssertFfiStable_{}_{}", self.name, field.name);
-            let ty = &field.ty;
-            let ty_span = ty.span();
+            let ty_span = field.ty.span();
+            let bound = field_stability_bound(&field.ty);
             assertions.push(quote_spanned!{ty_span=>
                 #[allow(non_camel_case_types)]
-                struct #assert_struct_ident where #ty: ::dotnet_bindgen::core::FfiStable {}
+                struct #assert_struct_ident where #bound {}
             })
         }
 
@@ -185,9 +681,9 @@ impl ExportedStruct {
             where
         };
         for field in &self.fields {
-            let ty = &field.ty;
+            let bound = field_stability_bound(&field.ty);
             ffi_stable_impl = quote_spanned!{field.span=>
-                #ffi_stable_impl #ty: ::dotnet_bindgen::core::FfiStable,
+                #ffi_stable_impl #bound,
             }
         }
 
@@ -200,6 +696,12 @@ impl ExportedStruct {
     fn descriptor_impl(&self) -> TokenStream {
         let name = &self.name;
         let name_string = name.to_string();
+        let cs_name = match &self.cs_name {
+            Some(cs_name) => quote! { Some(::dotnet_bindgen::core::__private::ToString::to_string(#cs_name)) },
+            None => quote! { None },
+        };
+
+        let fixed_string = self.fixed_string;
 
         let mut field_descriptors = Vec::new();
 
@@ -209,7 +711,7 @@ impl ExportedStruct {
 
             field_descriptors.push(quote!{
                 ::dotnet_bindgen::core::BindgenStructFieldDescriptor {
-                    name: #field_name_string.to_string(),
+                    name: ::dotnet_bindgen::core::__private::ToString::to_string(#field_name_string),
                     ty: <#field_ty as ::dotnet_bindgen::core::BindgenTypeDescribe>::describe(),
                 }
             })
@@ -220,10 +722,14 @@ impl ExportedStruct {
                 fn describe() -> ::dotnet_bindgen::core::BindgenTypeDescriptor {
                     ::dotnet_bindgen::core::BindgenTypeDescriptor::Struct(
                         ::dotnet_bindgen::core::BindgenStructDescriptor {
-                            name: #name_string.to_string(),
-                            fields: vec![
+                            name: ::dotnet_bindgen::core::__private::ToString::to_string(#name_string),
+                            cs_name: #cs_name,
+                            size: Some(::core::mem::size_of::<#name>() as u64),
+                            align: Some(::core::mem::align_of::<#name>() as u64),
+                            fields: ::dotnet_bindgen::core::__private::vec![
                                 #(#field_descriptors),*
-                            ]
+                            ],
+                            fixed_string: #fixed_string,
                         }
                     )
                 }
@@ -231,22 +737,28 @@ impl ExportedStruct {
         }
     }
 
-    /// A #[no_mangle]'d function which returns a BindgenExportDescriptor::Struct
+    /// A #[no_mangle]'d function which returns a BindgenExportDescriptor::Struct, or None if describing
+    /// the struct panicked
     fn descriptor_func(&self) -> TokenStream {
         let struct_name = &self.name;
         let descriptor_name = format_ident!("{}_struct_{}", BINDGEN_DESCRIBE_PREFIX, self.name);
+        let registration = describe_registration(&descriptor_name, None);
 
         quote!{
+            #registration
+
             #[no_mangle]
             #[allow(non_snake_case)]
-            pub fn #descriptor_name() -> ::dotnet_bindgen::core::BindgenExportDescriptor {
-                let type_desc = <#struct_name as ::dotnet_bindgen::core::BindgenTypeDescribe>::describe();
-                ::dotnet_bindgen::core::BindgenExportDescriptor::Struct(
-                    match type_desc {
-                        ::dotnet_bindgen::core::BindgenTypeDescriptor::Struct(s) => s,
-                        _ => unreachable!(),
-                    }
-                )
+            pub fn #descriptor_name() -> ::core::option::Option<::dotnet_bindgen::core::BindgenExportDescriptor> {
+                ::dotnet_bindgen::core::__private::catch_describe_panic(|| {
+                    let type_desc = <#struct_name as ::dotnet_bindgen::core::BindgenTypeDescribe>::describe();
+                    ::dotnet_bindgen::core::BindgenExportDescriptor::Struct(
+                        match type_desc {
+                            ::dotnet_bindgen::core::BindgenTypeDescriptor::Struct(s) => s,
+                            _ => unreachable!(),
+                        }
+                    )
+                })
             }
         }
     }
@@ -268,10 +780,215 @@ impl ToTokens for ExportedStruct {
     }
 }
 
+struct ExportedStatic {
+    name: proc_macro2::Ident,
+    ty: syn::Type,
+    mutable: bool,
+}
+
+impl std::fmt::Debug for ExportedStatic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let ty_string = format!("syn::Type({})", self.ty.to_token_stream());
+        write!(
+            f,
+            "ExportedStatic {{ name: {}, ty: {}, mutable: {} }}",
+            self.name, ty_string, self.mutable
+        )
+    }
+}
+
+impl ToTokens for ExportedStatic {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let ty = &self.ty;
+        let ty_span = ty.span();
+        let assert_struct_ident = format_ident!("_AssertFfiStable_{}", self.name);
+        let name_string = self.name.to_string();
+        let mutable = self.mutable;
+
+        let descriptor_name = format_ident!("{}_static_{}", BINDGEN_DESCRIBE_PREFIX, self.name);
+        let registration = describe_registration(&descriptor_name, None);
+
+        let assertion = quote_spanned! {ty_span=>
+            #[allow(non_camel_case_types)]
+            struct #assert_struct_ident where #ty: ::dotnet_bindgen::core::FfiStable {}
+        };
+
+        (quote! {
+            #assertion
+            #registration
+
+            #[no_mangle]
+            #[allow(non_snake_case)]
+            pub fn #descriptor_name() -> ::core::option::Option<::dotnet_bindgen::core::BindgenExportDescriptor> {
+                ::dotnet_bindgen::core::__private::catch_describe_panic(|| ::dotnet_bindgen::core::BindgenExportDescriptor::Static(
+                    ::dotnet_bindgen::core::BindgenStaticDescriptor {
+                        name: ::dotnet_bindgen::core::__private::ToString::to_string(#name_string),
+                        ty: <#ty as ::dotnet_bindgen::core::BindgenTypeDescribe>::describe(),
+                        mutable: #mutable,
+                    }
+                ))
+            }
+        }).to_tokens(tokens);
+    }
+}
+
+struct ExportedEnum {
+    name: proc_macro2::Ident,
+    variants: Vec<proc_macro2::Ident>,
+
+    /// The integer type the enum crosses the boundary as, which is `c_int` for `#[repr(C)]`.
+    repr: TokenStream,
+
+    /// Whether the enum's repr is a signed integer. `#[repr(C)]` is a C int, so is signed.
+    signed: bool,
+}
+
+impl std::fmt::Debug for ExportedEnum {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "ExportedEnum {{ name: {}, variants: {:?}, repr: {}, signed: {} }}",
+            self.name, self.variants, self.repr, self.signed
+        )
+    }
+}
+
+impl ToTokens for ExportedEnum {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let name = &self.name;
+        let name_string = name.to_string();
+        let repr = &self.repr;
+        let signed = self.signed;
+        let variants = &self.variants;
+
+        let descriptor_name = format_ident!("{}_enum_{}", BINDGEN_DESCRIBE_PREFIX, self.name);
+        let registration = describe_registration(&descriptor_name, None);
+
+        let variant_descriptors = self.variants.iter().map(|variant| {
+            let variant_string = variant.to_string();
+            quote! {
+                ::dotnet_bindgen::core::BindgenEnumVariantDescriptor {
+                    name: ::dotnet_bindgen::core::__private::ToString::to_string(#variant_string),
+                    value: #name::#variant as i64,
+                }
+            }
+        });
+
+        (quote! {
+            // C# can put any value of the repr in an enum, so it crosses the boundary as the repr
+            // and the thunks check it's a defined discriminant before converting it.
+            const _: () = assert!(::core::mem::size_of::<#name>() == ::core::mem::size_of::<#repr>());
+
+            unsafe impl ::dotnet_bindgen::core::BindgenSliceElem for #name {
+                type Repr = #repr;
+
+                const CHECKS_DISCRIMINANTS: bool = true;
+
+                fn is_valid_repr(repr: &#repr) -> bool {
+                    false #(|| *repr == #name::#variants as #repr)*
+                }
+            }
+
+            impl ::dotnet_bindgen::core::BindgenAbiConvert for #name {
+                type AbiType = #repr;
+
+                const CHECKS_DISCRIMINANTS: bool = true;
+
+                fn from_abi_type(abi_value: #repr) -> Self {
+                    #(
+                        if abi_value == #name::#variants as #repr {
+                            return #name::#variants;
+                        }
+                    )*
+                    panic!("An enum passed across the ffi boundary had an undefined discriminant")
+                }
+
+                fn has_valid_discriminants(abi_value: &#repr) -> bool {
+                    <Self as ::dotnet_bindgen::core::BindgenSliceElem>::is_valid_repr(abi_value)
+                }
+            }
+
+            impl ::dotnet_bindgen::core::BindgenAbiReturn for #name {
+                fn to_abi_type(self) -> #repr {
+                    self as #repr
+                }
+            }
+
+            impl ::dotnet_bindgen::core::BindgenTypeDescribe for #name {
+                fn describe() -> ::dotnet_bindgen::core::BindgenTypeDescriptor {
+                    ::dotnet_bindgen::core::BindgenTypeDescriptor::Enum(
+                        ::dotnet_bindgen::core::BindgenEnumDescriptor {
+                            name: ::dotnet_bindgen::core::__private::ToString::to_string(#name_string),
+                            repr: ::dotnet_bindgen::core::__private::Box::new(::dotnet_bindgen::core::BindgenTypeDescriptor::Int {
+                                width: (::core::mem::size_of::<#name>() * 8) as u8,
+                                signed: #signed,
+                            }),
+                            variants: ::dotnet_bindgen::core::__private::vec![#(#variant_descriptors),*],
+                        }
+                    )
+                }
+            }
+
+            #registration
+
+            #[no_mangle]
+            #[allow(non_snake_case)]
+            pub fn #descriptor_name() -> ::core::option::Option<::dotnet_bindgen::core::BindgenExportDescriptor> {
+                ::dotnet_bindgen::core::__private::catch_describe_panic(|| {
+                    let type_desc = <#name as ::dotnet_bindgen::core::BindgenTypeDescribe>::describe();
+                    ::dotnet_bindgen::core::BindgenExportDescriptor::Enum(
+                        match type_desc {
+                            ::dotnet_bindgen::core::BindgenTypeDescriptor::Enum(e) => e,
+                            _ => unreachable!(),
+                        }
+                    )
+                })
+            }
+        }).to_tokens(tokens);
+    }
+}
+
+/// A struct marked `#[dotnet_bindgen(opaque)]`, which C# only ever sees behind a pointer.
+///
+/// Its layout is never described, so it needs no particular repr and isn't FfiStable itself.
+struct ExportedOpaque {
+    name: proc_macro2::Ident,
+}
+
+impl std::fmt::Debug for ExportedOpaque {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ExportedOpaque {{ name: {} }}", self.name)
+    }
+}
+
+impl ToTokens for ExportedOpaque {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let name = &self.name;
+        let name_string = name.to_string();
+
+        (quote! {
+            impl ::dotnet_bindgen::core::BindgenTypeDescribe for #name {
+                fn describe() -> ::dotnet_bindgen::core::BindgenTypeDescriptor {
+                    ::dotnet_bindgen::core::BindgenTypeDescriptor::Opaque {
+                        name: ::dotnet_bindgen::core::__private::ToString::to_string(#name_string),
+                    }
+                }
+
+                fn describe_pointee() -> ::core::option::Option<::dotnet_bindgen::core::BindgenTypeDescriptor> {
+                    ::core::option::Option::Some(<Self as ::dotnet_bindgen::core::BindgenTypeDescribe>::describe())
+                }
+            }
+        }).to_tokens(tokens);
+    }
+}
+
 #[derive(Debug)]
 enum Export {
     Func(ExportedFunction),
     Struct(ExportedStruct),
+    Static(ExportedStatic),
+    Enum(ExportedEnum),
+    Opaque(ExportedOpaque),
 }
 
 impl ToTokens for Export {
@@ -279,6 +996,9 @@ impl ToTokens for Export {
         match self {
             Export::Func(f) => f.to_tokens(tokens),
             Export::Struct(s) => s.to_tokens(tokens),
+            Export::Static(s) => s.to_tokens(tokens),
+            Export::Enum(e) => e.to_tokens(tokens),
+            Export::Opaque(o) => o.to_tokens(tokens),
         };
     }
 }
@@ -296,29 +1016,77 @@ impl ToTokens for Program {
 }
 
 trait MacroParse {
-    fn macro_parse(&self, program: &mut Program) -> Result<(), Diagnostic>;
+    /// Parses the item into the program, taking any options it understands from `opts`.
+    fn macro_parse(&mut self, program: &mut Program, opts: &mut BindgenAttrs) -> Result<(), Diagnostic>;
 }
 
-pub fn expand(_attrs: TokenStream, tokens: TokenStream) -> Result<TokenStream, Diagnostic> {
+pub fn expand(attrs: TokenStream, tokens: TokenStream) -> Result<TokenStream, Diagnostic> {
     let mut program = Program {
         exports: Vec::new(),
     };
 
-    let item = syn::parse2::<syn::Item>(tokens)?;
-    item.macro_parse(&mut program)?;
+    let mut opts = BindgenAttrs::parse_args(attrs)?;
+
+    let mut item = syn::parse2::<syn::Item>(tokens)?;
+
+    // Any further attributes stacked on the item haven't been expanded yet, so add their options
+    // here rather than letting each expand the item again.
+    if let Some(attrs) = item_attrs(&mut item) {
+        opts.extend(BindgenAttrs::take_from(attrs)?);
+    }
+
+    item.macro_parse(&mut program, &mut opts)?;
+    opts.finish()?;
 
     let mut tokens = proc_macro2::TokenStream::new();
     item.to_tokens(&mut tokens);
-    program.to_tokens(&mut tokens);
+    if !bindings_disabled() {
+        program.to_tokens(&mut tokens);
+    }
+
+    // Reading the variable through option_env! records it in the crate's dep-info, so that cargo
+    // rebuilds the crate when it is toggled.
+    let disable_var = DISABLE_ENV_VAR;
+    tokens.extend(quote! {
+        const _: ::core::option::Option<&str> = ::core::option_env!(#disable_var);
+    });
 
     Ok(tokens)
 }
 
+/// The attributes of an item of any kind `#[dotnet_bindgen]` can be placed on.
+fn item_attrs(item: &mut syn::Item) -> Option<&mut Vec<syn::Attribute>> {
+    match item {
+        syn::Item::Fn(f) => Some(&mut f.attrs),
+        syn::Item::Struct(s) => Some(&mut s.attrs),
+        syn::Item::Static(s) => Some(&mut s.attrs),
+        syn::Item::Enum(e) => Some(&mut e.attrs),
+        syn::Item::Impl(i) => Some(&mut i.attrs),
+        syn::Item::ForeignMod(m) => Some(&mut m.attrs),
+        _ => None,
+    }
+}
+
+/// Setting this environment variable while building turns `#[dotnet_bindgen]` into a no-op, which
+/// emits each item unchanged without any thunks or metadata.
+const DISABLE_ENV_VAR: &str = "DOTNET_BINDGEN_DISABLE";
+
+fn bindings_disabled() -> bool {
+    match std::env::var_os(DISABLE_ENV_VAR) {
+        Some(value) => !value.is_empty() && value != "0",
+        None => false,
+    }
+}
+
 impl MacroParse for syn::Item {
-    fn macro_parse(&self, program: &mut Program) -> Result<(), Diagnostic> {
+    fn macro_parse(&mut self, program: &mut Program, opts: &mut BindgenAttrs) -> Result<(), Diagnostic> {
         match self {
-            syn::Item::Fn(f) => f.macro_parse(program),
-            syn::Item::Struct(s) => s.macro_parse(program),
+            syn::Item::Fn(f) => f.macro_parse(program, opts),
+            syn::Item::Struct(s) => s.macro_parse(program, opts),
+            syn::Item::Static(s) => s.macro_parse(program, opts),
+            syn::Item::Enum(e) => e.macro_parse(program, opts),
+            syn::Item::Impl(i) => i.macro_parse(program, opts),
+            syn::Item::ForeignMod(m) => m.macro_parse(program, opts),
             _ => Err(Diagnostic::spanned_error(
                 self,
                 "Can't generate binding metadata for this",
@@ -328,62 +1096,970 @@ impl MacroParse for syn::Item {
 }
 
 impl MacroParse for syn::ItemFn {
-    fn macro_parse(&self, program: &mut Program) -> Result<(), Diagnostic> {
-        let mut arguments = Vec::new();
+    fn macro_parse(&mut self, program: &mut Program, opts: &mut BindgenAttrs) -> Result<(), Diagnostic> {
+        let (receiver, mut arguments, return_ty) = parse_signature(&mut self.sig)?;
 
-        for arg in self.sig.inputs.iter() {
-            arguments.push(match arg {
-                syn::FnArg::Receiver(r) => {
-                    bail_span!(r, "Can't generate binding metadata for methods")
-                }
-                syn::FnArg::Typed(pat_type) => {
-                    let name = parse_pat(&pat_type.pat)?;
-                    let ty = *pat_type.ty.clone();
-                    ExportedFunctionArg { name, ty }
-                }
-            });
+        if let Some(receiver) = receiver {
+            bail_span!(receiver, "Can't generate binding metadata for methods outside of an impl block")
         }
 
-        let name = self.sig.ident.clone();
-        let return_ty: Option<syn::Type> = match &self.sig.output {
-            syn::ReturnType::Default => None,
-            syn::ReturnType::Type(_arrow, ty) => Some(*ty.clone()),
-        };
+        let (blocking, no_gc_transition) = parse_blocking(opts, &self.sig.ident)?;
+        let block_on = parse_block_on(opts, &self.sig, no_gc_transition)?;
+        let buffer_len_arg = parse_returns_buffer(opts, &self.sig, &mut arguments, &return_ty)?;
+        parse_defaults(opts, &mut arguments, buffer_len_arg.as_ref())?;
+        let fill_buffer_arg = parse_fill_buffer(&self.sig, &arguments, &return_ty)?;
+        let returns_dictionary = parse_returns_dictionary(opts, &self.sig, &return_ty)?;
+        let return_encoding = parse_encoding(opts, "return_encoding")?;
+        let return_ownership = parse_return_ownership(opts, &self.sig, &return_ty)?;
+        let module = parse_module(opts)?;
 
         program.exports.push(Export::Func(ExportedFunction {
-            name,
+            name: self.sig.ident.clone(),
             arguments,
             return_ty,
+            owner: None,
+            receiver: None,
+            blocking,
+            no_gc_transition,
+            calling_convention: parse_abi(opts)?,
+            buffer_len_arg,
+            fill_buffer_arg,
+            returns_dictionary,
+            return_encoding,
+            return_ownership,
+            module,
+            foreign: false,
+            block_on,
         }));
 
         Ok(())
     }
 }
 
+impl MacroParse for syn::ItemForeignMod {
+    fn macro_parse(&mut self, program: &mut Program, _opts: &mut BindgenAttrs) -> Result<(), Diagnostic> {
+        let calling_convention = match self.abi.name.as_ref().map(|name| name.value()) {
+            None => BindgenCallingConvention::C,
+            Some(name) if name == "C" => BindgenCallingConvention::C,
+            Some(name) if name == "system" => BindgenCallingConvention::System,
+            Some(_) => bail_span!(self.abi, "Expected an extern \"C\" or extern \"system\" block"),
+        };
+
+        for item in self.items.iter_mut() {
+            let func = match item {
+                syn::ForeignItem::Fn(f) => f,
+                _ => continue,
+            };
+
+            if let Some(variadic) = &func.sig.variadic {
+                bail_span!(variadic, "Can't generate binding metadata for variadic functions");
+            }
+
+            // The bindings need the symbol name, which the attribute makes differ from the ident.
+            if let Some(attr) = func.attrs.iter().find(|attr| attr.path.is_ident("link_name")) {
+                bail_span!(attr, "Can't generate binding metadata for functions renamed with #[link_name]");
+            }
+
+            let mut func_opts = BindgenAttrs::take_from(&mut func.attrs)?;
+            let (blocking, no_gc_transition) = parse_blocking(&mut func_opts, &func.sig.ident)?;
+            let module = parse_module(&mut func_opts)?;
+            func_opts.finish()?;
+
+            let (_, arguments, return_ty) = parse_signature(&mut func.sig)?;
+            let fill_buffer_arg = parse_fill_buffer(&func.sig, &arguments, &return_ty)?;
+
+            program.exports.push(Export::Func(ExportedFunction {
+                name: func.sig.ident.clone(),
+                arguments,
+                return_ty,
+                owner: None,
+                receiver: None,
+                blocking,
+                no_gc_transition,
+                calling_convention,
+                buffer_len_arg: None,
+                fill_buffer_arg,
+                returns_dictionary: false,
+                return_encoding: None,
+                return_ownership: None,
+                module,
+                foreign: true,
+                block_on: false,
+            }));
+        }
+
+        Ok(())
+    }
+}
+
+impl MacroParse for syn::ItemImpl {
+    fn macro_parse(&mut self, program: &mut Program, _opts: &mut BindgenAttrs) -> Result<(), Diagnostic> {
+        if let Some((_, path, _)) = &self.trait_ {
+            bail_span!(path, "Can't generate binding metadata for trait impls");
+        }
+
+        if !self.generics.params.is_empty() {
+            bail_span!(self.generics, "Can't generate binding metadata for generic impl blocks");
+        }
+
+        let owner = match &*self.self_ty {
+            syn::Type::Path(p) if p.qself.is_none() => match p.path.get_ident() {
+                Some(ident) => ident.clone(),
+                None => bail_span!(p, "Expected the name of a #[dotnet_bindgen] struct"),
+            },
+            other => bail_span!(other, "Expected the name of a #[dotnet_bindgen] struct"),
+        };
+
+        for item in self.items.iter_mut() {
+            let method = match item {
+                syn::ImplItem::Method(m) => m,
+                _ => continue,
+            };
+
+            let mut method_opts = BindgenAttrs::take_from(&mut method.attrs)?;
+            let (blocking, no_gc_transition) = parse_blocking(&mut method_opts, &method.sig.ident)?;
+            let block_on = parse_block_on(&mut method_opts, &method.sig, no_gc_transition)?;
+            let calling_convention = parse_abi(&mut method_opts)?;
+
+            let (receiver, mut arguments, return_ty) = parse_signature(&mut method.sig)?;
+            check_not_self_borrow(return_ty.as_ref(), &owner)?;
+            let buffer_len_arg = parse_returns_buffer(
+                &mut method_opts,
+                &method.sig,
+                &mut arguments,
+                &return_ty,
+            )?;
+            parse_defaults(&mut method_opts, &mut arguments, buffer_len_arg.as_ref())?;
+            let fill_buffer_arg = parse_fill_buffer(&method.sig, &arguments, &return_ty)?;
+            let returns_dictionary =
+                parse_returns_dictionary(&mut method_opts, &method.sig, &return_ty)?;
+            let return_encoding = parse_encoding(&mut method_opts, "return_encoding")?;
+            let return_ownership = parse_return_ownership(&mut method_opts, &method.sig, &return_ty)?;
+            method_opts.finish()?;
+
+            let receiver = match receiver {
+                None => None,
+                Some(r) if r.reference.is_none() => Some(BindgenReceiver::Value),
+                Some(r) if r.mutability.is_some() => Some(BindgenReceiver::RefMut),
+                Some(_) => Some(BindgenReceiver::Ref),
+            };
+
+            program.exports.push(Export::Func(ExportedFunction {
+                name: method.sig.ident.clone(),
+                arguments,
+                return_ty,
+                owner: Some(owner.clone()),
+                receiver,
+                blocking,
+                no_gc_transition,
+                calling_convention,
+                buffer_len_arg,
+                fill_buffer_arg,
+                returns_dictionary,
+                return_encoding,
+                return_ownership,
+                module: None,
+                foreign: false,
+                block_on,
+            }));
+        }
+
+        Ok(())
+    }
+}
+
+/// Parses the receiver, arguments, and return type of a function signature, stripping any
+/// #[dotnet_bindgen(...)] options from the arguments along the way.
+#[allow(clippy::type_complexity)]
+fn parse_signature(
+    sig: &mut syn::Signature,
+) -> Result<(Option<syn::Receiver>, Vec<ExportedFunctionArg>, Option<syn::Type>), Diagnostic> {
+    let mut receiver = None;
+    let mut arguments = Vec::new();
+
+    for arg in sig.inputs.iter_mut() {
+        match arg {
+            syn::FnArg::Receiver(r) => receiver = Some(r.clone()),
+            syn::FnArg::Typed(pat_type) => {
+                if let syn::Pat::Ident(pat_ident) = &*pat_type.pat {
+                    if pat_ident.ident == "self" {
+                        bail_span!(pat_type, "Can't generate binding metadata for typed self arguments");
+                    }
+                }
+
+                let mut attrs = BindgenAttrs::take_from(&mut pat_type.attrs)?;
+                let direction = parse_direction(&mut attrs, &pat_type.ty)?;
+                let raw_ptr = attrs.take_flag("raw_ptr")?;
+                let encoding = parse_encoding(&mut attrs, "encoding")?;
+                let fill_buffer = attrs.take_flag("fill_buffer")?;
+                attrs.finish()?;
+
+                if raw_ptr && !matches!(*pat_type.ty, syn::Type::Ptr(_)) {
+                    bail_span!(pat_type.ty, "raw_ptr arguments must be raw pointers");
+                }
+
+                let name = parse_pat(&pat_type.pat)?;
+                let ty = *pat_type.ty.clone();
+                arguments.push(ExportedFunctionArg {
+                    name,
+                    ty,
+                    direction,
+                    raw_ptr,
+                    encoding,
+                    fill_buffer,
+                    default: None,
+                });
+            }
+        }
+    }
+
+    let return_ty = match &sig.output {
+        syn::ReturnType::Default => None,
+        syn::ReturnType::Type(_arrow, ty) => Some(*ty.clone()),
+    };
+
+    Ok((receiver, arguments, return_ty))
+}
+
+/// Rejects methods returning `&Self` or `&mut Self`, eg fluent builder methods.
+///
+/// Bound structs are copied across the boundary by value, so a borrow of one doesn't point at
+/// anything the C# caller can use.
+fn check_not_self_borrow(return_ty: Option<&syn::Type>, owner: &proc_macro2::Ident) -> Result<(), Diagnostic> {
+    let reference = match return_ty {
+        Some(syn::Type::Reference(reference)) => reference,
+        _ => return Ok(()),
+    };
+
+    let borrows_self = match &*reference.elem {
+        syn::Type::Path(p) if p.qself.is_none() => {
+            p.path.is_ident("Self") || p.path.is_ident(owner)
+        }
+        _ => false,
+    };
+
+    if borrows_self {
+        bail_span!(
+            reference,
+            "Can't return a borrow of self across the FFI boundary, as bound structs are passed by value. \
+             For builder style methods, take `mut self` and return `Self` instead, eg `fn with_x(mut self, x: i32) -> Self`"
+        );
+    }
+
+    Ok(())
+}
+
+/// The element type of a slice argument, ie the `T` of `&[T]`, `&mut [T]`, or `Cow<[T]>`.
+fn slice_elem_type(ty: &syn::Type) -> Option<&syn::Type> {
+    fn slice_elem(ty: &syn::Type) -> Option<&syn::Type> {
+        match ty {
+            syn::Type::Slice(slice) => Some(&*slice.elem),
+            _ => None,
+        }
+    }
+
+    match ty {
+        syn::Type::Group(group) => slice_elem_type(&group.elem),
+        syn::Type::Reference(reference) => slice_elem(&reference.elem),
+        syn::Type::Path(path) => {
+            let segment = path.path.segments.last()?;
+            if segment.ident != "Cow" {
+                return None;
+            }
+
+            match &segment.arguments {
+                syn::PathArguments::AngleBracketed(args) => args.args.iter().find_map(|arg| match arg {
+                    syn::GenericArgument::Type(ty) => slice_elem(ty),
+                    _ => None,
+                }),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Whether an argument is written as a `&str` or `Cow<str>`, so the thunk gets a flag to report
+/// invalid UTF-8 through.
+fn is_str_type(ty: &syn::Type) -> bool {
+    fn is_str(ty: &syn::Type) -> bool {
+        matches!(ty, syn::Type::Path(p) if p.qself.is_none() && p.path.is_ident("str"))
+    }
+
+    match ty {
+        syn::Type::Group(group) => is_str_type(&group.elem),
+        syn::Type::Reference(reference) => reference.mutability.is_none() && is_str(&reference.elem),
+        syn::Type::Path(path) => {
+            let segment = match path.path.segments.last() {
+                Some(segment) if segment.ident == "Cow" => segment,
+                _ => return false,
+            };
+
+            match &segment.arguments {
+                syn::PathArguments::AngleBracketed(args) => args.args.iter().any(|arg| {
+                    matches!(arg, syn::GenericArgument::Type(ty) if is_str(ty))
+                }),
+                _ => false,
+            }
+        }
+        _ => false,
+    }
+}
+
+/// Whether an argument is written as a name which might be a C-like enum, or a `&[T]`, `&mut [T]`,
+/// or `Option<&[T]>` of one, so the thunk gets a flag to report undefined discriminants through.
+///
+/// Any plain name but a primitive's might be an enum, as might `Self`.
+fn may_be_enum_type(ty: &syn::Type) -> bool {
+    fn is_enum_name(ty: &syn::Type) -> bool {
+        const NOT_ENUMS: &[&str] = &[
+            "bool", "char", "f32", "f64", "i8", "i16", "i32", "i64", "i128", "isize", "u8", "u16",
+            "u32", "u64", "u128", "usize", "String", "CString", "Duration",
+        ];
+
+        match ty {
+            syn::Type::Group(group) => is_enum_name(&group.elem),
+            syn::Type::Path(p) => {
+                p.qself.is_none()
+                    && p.path.segments.iter().all(|segment| segment.arguments.is_empty())
+                    && p.path.segments.last().is_some_and(|segment| {
+                        !NOT_ENUMS.iter().any(|name| segment.ident == name)
+                    })
+            }
+            _ => false,
+        }
+    }
+
+    fn is_enum_slice(ty: &syn::Type) -> bool {
+        match ty {
+            syn::Type::Group(group) => is_enum_slice(&group.elem),
+            syn::Type::Reference(reference) => {
+                matches!(&*reference.elem, syn::Type::Slice(slice) if is_enum_name(&slice.elem))
+            }
+            _ => false,
+        }
+    }
+
+    match ty {
+        syn::Type::Group(group) => may_be_enum_type(&group.elem),
+        syn::Type::Reference(_) => is_enum_slice(ty),
+        syn::Type::Path(path) => {
+            let segment = match path.path.segments.last() {
+                Some(segment) if segment.ident == "Option" => segment,
+                _ => return is_enum_name(ty),
+            };
+
+            match &segment.arguments {
+                syn::PathArguments::AngleBracketed(args) => args.args.iter().any(|arg| {
+                    matches!(arg, syn::GenericArgument::Type(ty) if is_enum_slice(ty))
+                }),
+                _ => false,
+            }
+        }
+        _ => false,
+    }
+}
+
+/// Parses the `direction = "in" | "out" | "inout"` option of a function argument.
+///
+/// Only a `&mut` borrow can carry data back to the caller, so anything else may only be `in`.
+fn parse_direction(
+    attrs: &mut BindgenAttrs,
+    ty: &syn::Type,
+) -> Result<Option<BindgenArgumentDirection>, Diagnostic> {
+    let lit = match attrs.take_str("direction")? {
+        Some(lit) => lit,
+        None => return Ok(None),
+    };
+
+    let direction = match lit.value().as_str() {
+        "in" => BindgenArgumentDirection::In,
+        "out" => BindgenArgumentDirection::Out,
+        "inout" => BindgenArgumentDirection::InOut,
+        _ => bail_span!(lit, "Expected one of \"in\", \"out\" or \"inout\""),
+    };
+
+    let is_mut_ref = matches!(ty, syn::Type::Reference(r) if r.mutability.is_some());
+    if direction != BindgenArgumentDirection::In && !is_mut_ref {
+        bail_span!(ty, "Only &mut arguments can be marked as out or inout");
+    }
+
+    Ok(Some(direction))
+}
+
+fn encoding_frag(encoding: Option<BindgenStringEncoding>) -> TokenStream {
+    match encoding {
+        None => quote! { None },
+        Some(BindgenStringEncoding::Utf8) => quote! {
+            Some(::dotnet_bindgen::core::BindgenStringEncoding::Utf8)
+        },
+        Some(BindgenStringEncoding::Utf16) => quote! {
+            Some(::dotnet_bindgen::core::BindgenStringEncoding::Utf16)
+        },
+        Some(BindgenStringEncoding::Ansi) => quote! {
+            Some(::dotnet_bindgen::core::BindgenStringEncoding::Ansi)
+        },
+    }
+}
+
+/// Parses an `encoding = "utf8" | "utf16" | "ansi"` style option, eg of a string argument or of
+/// the `return_encoding` of a function returning a `CString`.
+///
+/// Whether the type can hold the encoded string is checked by the CLI, from the type's descriptor,
+/// as type aliases make that impossible to tell here.
+fn parse_encoding(attrs: &mut BindgenAttrs, key: &str) -> Result<Option<BindgenStringEncoding>, Diagnostic> {
+    let lit = match attrs.take_str(key)? {
+        Some(lit) => lit,
+        None => return Ok(None),
+    };
+
+    match lit.value().as_str() {
+        "utf8" => Ok(Some(BindgenStringEncoding::Utf8)),
+        "utf16" => Ok(Some(BindgenStringEncoding::Utf16)),
+        "ansi" => Ok(Some(BindgenStringEncoding::Ansi)),
+        _ => bail_span!(lit, "Expected one of \"utf8\", \"utf16\" or \"ansi\""),
+    }
+}
+
+/// Parses the `returns_buffer(len_out = "...")` option, which fuses a `*const u8` return with the
+/// `*mut usize` argument the function writes the buffer's length to.
+///
+/// The length argument is passed along as a raw pointer, so is marked as such.
+fn parse_returns_buffer(
+    attrs: &mut BindgenAttrs,
+    sig: &syn::Signature,
+    arguments: &mut [ExportedFunctionArg],
+    return_ty: &Option<syn::Type>,
+) -> Result<Option<proc_macro2::Ident>, Diagnostic> {
+    let mut buffer_attrs = match attrs.take_list("returns_buffer")? {
+        Some(buffer_attrs) => buffer_attrs,
+        None => return Ok(None),
+    };
+
+    let len_out = match buffer_attrs.take_str("len_out")? {
+        Some(lit) => lit,
+        None => bail_span!(sig.ident, "returns_buffer needs the name of the length argument, eg returns_buffer(len_out = \"len\")"),
+    };
+    buffer_attrs.finish()?;
+
+    let is_ptr_to = |ty: &syn::Type, mutable: bool, elem: &str| matches!(
+        ty,
+        syn::Type::Ptr(ptr) if ptr.mutability.is_some() == mutable
+            && matches!(&*ptr.elem, syn::Type::Path(p) if p.path.is_ident(elem))
+    );
+
+    match return_ty {
+        Some(ty) if is_ptr_to(ty, false, "u8") => (),
+        _ => bail_span!(sig.output, "returns_buffer functions must return a *const u8"),
+    }
+
+    let len_arg = match arguments.iter_mut().find(|arg| arg.name == len_out.value()) {
+        Some(arg) => arg,
+        None => bail_span!(len_out, "No argument named {}", len_out.value()),
+    };
+
+    if !is_ptr_to(&len_arg.ty, true, "usize") {
+        bail_span!(len_arg.ty, "The length argument of a returns_buffer function must be a *mut usize");
+    }
+
+    len_arg.raw_ptr = true;
+
+    Ok(Some(len_arg.name.clone()))
+}
+
+/// Finds the argument marked `#[dotnet_bindgen(fill_buffer)]`, checking it's the only one, that
+/// it's a `&mut [u8]`, and that the function returns the `usize` size of buffer it needs.
+fn parse_fill_buffer(
+    sig: &syn::Signature,
+    arguments: &[ExportedFunctionArg],
+    return_ty: &Option<syn::Type>,
+) -> Result<Option<proc_macro2::Ident>, Diagnostic> {
+    let mut marked = arguments.iter().filter(|arg| arg.fill_buffer);
+    let buffer_arg = match (marked.next(), marked.next()) {
+        (None, _) => return Ok(None),
+        (Some(arg), None) => arg,
+        (Some(_), Some(second)) => bail_span!(second.name, "Only one argument can be marked fill_buffer"),
+    };
+
+    let is_byte_buffer = matches!(
+        &buffer_arg.ty,
+        syn::Type::Reference(r) if r.mutability.is_some() && matches!(
+            &*r.elem,
+            syn::Type::Slice(s) if matches!(&*s.elem, syn::Type::Path(p) if p.path.is_ident("u8"))
+        )
+    );
+    if !is_byte_buffer {
+        bail_span!(buffer_arg.ty, "fill_buffer arguments must be a &mut [u8]");
+    }
+
+    match return_ty {
+        Some(syn::Type::Path(p)) if p.path.is_ident("usize") => (),
+        _ => bail_span!(sig.output, "fill_buffer functions must return the usize size of buffer they need"),
+    }
+
+    Ok(Some(buffer_arg.name.clone()))
+}
+
+/// Parses the `default(name = <literal>, ...)` option, which gives the values that overloads
+/// omitting the named arguments pass in their place.
+///
+/// The overloads drop arguments from the end, so the defaulted arguments must be the last ones
+/// the C# wrapper takes, which excludes the length argument of a `returns_buffer` function.
+fn parse_defaults(
+    attrs: &mut BindgenAttrs,
+    arguments: &mut [ExportedFunctionArg],
+    buffer_len_arg: Option<&proc_macro2::Ident>,
+) -> Result<(), Diagnostic> {
+    let mut defaults = match attrs.take_list("default")? {
+        Some(defaults) => defaults,
+        None => return Ok(()),
+    };
+
+    for arg in arguments.iter_mut() {
+        if let Some((negated, lit)) = defaults.take_lit(&arg.name.to_string())? {
+            arg.default = Some(parse_default_value(negated, &lit, &arg.ty)?);
+        }
+    }
+    defaults.finish()?;
+
+    let mut defaulted = false;
+    for arg in arguments.iter().filter(|arg| Some(&arg.name) != buffer_len_arg) {
+        if arg.default.is_some() {
+            defaulted = true;
+        } else if defaulted {
+            bail_span!(
+                arg.name,
+                "Arguments with a default value must come after all those without, as the overloads omit them from the end"
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks a default value's literal, which may be negated, against the type of its argument.
+///
+/// Only arguments named as a primitive or `&str` can have a default, as the literal can't be
+/// checked against anything else here.
+fn parse_default_value(negated: bool, lit: &syn::Lit, ty: &syn::Type) -> Result<BindgenDefaultValue, Diagnostic> {
+    let type_name = match ty {
+        syn::Type::Reference(r) if r.mutability.is_none() => match &*r.elem {
+            syn::Type::Path(p) if p.qself.is_none() && p.path.is_ident("str") => Some("str".to_string()),
+            _ => None,
+        },
+        syn::Type::Path(p) if p.qself.is_none() => p.path.get_ident().map(|ident| ident.to_string()),
+        _ => None,
+    };
+
+    let check_suffix = |suffix: &str, type_name: &str| -> Result<(), Diagnostic> {
+        if !suffix.is_empty() && suffix != type_name {
+            bail_span!(lit, "The default of a {} argument can't be a {} literal", type_name, suffix);
+        }
+        Ok(())
+    };
+
+    let sign = if negated { "-" } else { "" };
+
+    let value = match (type_name.as_deref(), lit) {
+        (Some(name @ ("bool" | "str")), syn::Lit::Bool(_) | syn::Lit::Str(_)) if negated => {
+            bail_span!(lit, "The default of a {} argument can't be negated", name)
+        }
+        (Some("bool"), syn::Lit::Bool(b)) => BindgenDefaultValue::Bool(b.value),
+        (Some("str"), syn::Lit::Str(s)) => BindgenDefaultValue::Str(s.value()),
+        (Some(name @ ("f32" | "f64")), syn::Lit::Float(f)) => {
+            check_suffix(f.suffix(), name)?;
+            BindgenDefaultValue::Float(format!("{}{}", sign, f.base10_digits()))
+        }
+        (Some("f32" | "f64"), syn::Lit::Int(i)) if i.suffix().is_empty() => {
+            BindgenDefaultValue::Float(format!("{}{}", sign, i.base10_digits()))
+        }
+        (Some(name), syn::Lit::Int(i)) if int_max(name).is_some() => {
+            check_suffix(i.suffix(), name)?;
+            let (max, signed) = int_max(name).unwrap();
+            let value: u64 = i.base10_parse()?;
+            match (signed, negated) {
+                (false, true) if value != 0 => {
+                    bail_span!(lit, "The default of a {} argument can't be negative", name)
+                }
+                // The magnitude of the most negative value is one more than the largest
+                (true, true) if value > max + 1 => {
+                    bail_span!(lit, "-{} doesn't fit in a {}", value, name)
+                }
+                (_, false) if value > max => bail_span!(lit, "{} doesn't fit in a {}", value, name),
+                (true, true) => BindgenDefaultValue::Int((value as i64).wrapping_neg()),
+                (true, false) => BindgenDefaultValue::Int(value as i64),
+                (false, _) => BindgenDefaultValue::UInt(value),
+            }
+        }
+        (Some(name), _) if matches!(name, "bool" | "str" | "f32" | "f64") || int_max(name).is_some() => {
+            bail_span!(lit, "Expected a {} literal for the default value of this argument", name)
+        }
+        _ => bail_span!(ty, "Default values can only be given for integer, float, bool and &str arguments"),
+    };
+
+    Ok(value)
+}
+
+/// The largest value of a primitive integer type, and whether it is signed.
+fn int_max(type_name: &str) -> Option<(u64, bool)> {
+    let max = match type_name {
+        "i8" => (i8::MAX as u64, true),
+        "i16" => (i16::MAX as u64, true),
+        "i32" => (i32::MAX as u64, true),
+        "i64" | "isize" => (i64::MAX as u64, true),
+        "u8" => (u8::MAX as u64, false),
+        "u16" => (u16::MAX as u64, false),
+        "u32" => (u32::MAX as u64, false),
+        "u64" | "usize" => (u64::MAX, false),
+        _ => return None,
+    };
+    Some(max)
+}
+
+fn default_frag(default: Option<&BindgenDefaultValue>) -> TokenStream {
+    let value = match default {
+        None => return quote! { None },
+        Some(BindgenDefaultValue::Bool(b)) => quote! { Bool(#b) },
+        Some(BindgenDefaultValue::Int(i)) => quote! { Int(#i) },
+        Some(BindgenDefaultValue::UInt(u)) => quote! { UInt(#u) },
+        Some(BindgenDefaultValue::Float(digits)) => quote! {
+            Float(::dotnet_bindgen::core::__private::ToString::to_string(#digits))
+        },
+        Some(BindgenDefaultValue::Str(s)) => quote! {
+            Str(::dotnet_bindgen::core::__private::ToString::to_string(#s))
+        },
+    };
+    quote! { Some(::dotnet_bindgen::core::BindgenDefaultValue::#value) }
+}
+
+/// Whether the type is named as an `Arc<T>`, which gets a clone thunk when returned.
+///
+/// This can only go by the name, so an Arc behind a type alias isn't recognised.
+fn is_arc(ty: &syn::Type) -> bool {
+    matches!(
+        ty,
+        syn::Type::Path(p) if p.qself.is_none()
+            && p.path.segments.last().is_some_and(|seg| seg.ident == "Arc")
+    )
+}
+
+/// Parses the `returns_dictionary` flag, which is only valid on functions returning a `Vec`.
+///
+/// Whether the elements are pairs can only be checked once the element type has been described,
+/// so that is left to the CLI.
+fn parse_returns_dictionary(
+    attrs: &mut BindgenAttrs,
+    sig: &syn::Signature,
+    return_ty: &Option<syn::Type>,
+) -> Result<bool, Diagnostic> {
+    if !attrs.take_flag("returns_dictionary")? {
+        return Ok(false);
+    }
+
+    let returns_vec = matches!(
+        return_ty,
+        Some(syn::Type::Path(p)) if p.qself.is_none()
+            && p.path.segments.last().is_some_and(|seg| seg.ident == "Vec")
+    );
+
+    if !returns_vec {
+        bail_span!(sig.output, "returns_dictionary functions must return a Vec of key/value pairs");
+    }
+
+    Ok(true)
+}
+
+/// Parses the `returns_owned` and `returns_borrowed` flags, at most one of which can be given.
+///
+/// Whether the return type can be owned or borrowed is checked by the CLI, from the type's
+/// descriptor, as type aliases make that impossible to tell here.
+fn parse_return_ownership(
+    attrs: &mut BindgenAttrs,
+    sig: &syn::Signature,
+    return_ty: &Option<syn::Type>,
+) -> Result<Option<BindgenReturnOwnership>, Diagnostic> {
+    let owned = attrs.take_flag("returns_owned")?;
+    let borrowed = attrs.take_flag("returns_borrowed")?;
+
+    let ownership = match (owned, borrowed) {
+        (false, false) => return Ok(None),
+        (true, false) => BindgenReturnOwnership::Owned,
+        (false, true) => BindgenReturnOwnership::Borrowed,
+        (true, true) => bail_span!(sig.ident, "Only one of returns_owned or returns_borrowed can be given"),
+    };
+
+    if return_ty.is_none() {
+        bail_span!(sig.ident, "returns_owned and returns_borrowed need a function that returns something");
+    }
+
+    Ok(Some(ownership))
+}
+
+/// Parses the `module = "..."` option, a dotted path of C# identifiers, eg `Math.Trig`.
+fn parse_module(attrs: &mut BindgenAttrs) -> Result<Option<String>, Diagnostic> {
+    let lit = match attrs.take_str("module")? {
+        Some(lit) => lit,
+        None => return Ok(None),
+    };
+
+    let is_identifier = |part: &str| {
+        let mut chars = part.chars();
+        match chars.next() {
+            Some(c) if c.is_ascii_alphabetic() || c == '_' => {
+                chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+            }
+            _ => false,
+        }
+    };
+
+    let module = lit.value();
+    if !module.split('.').all(is_identifier) {
+        bail_span!(lit, "Expected a dotted path of identifiers, eg module = \"Math.Trig\"");
+    }
+
+    Ok(Some(module))
+}
+
+/// Parses the `blocking` and `no_gc_transition` flags, which contradict each other.
+fn parse_blocking(
+    attrs: &mut BindgenAttrs,
+    ident: &proc_macro2::Ident,
+) -> Result<(bool, bool), Diagnostic> {
+    let blocking = attrs.take_flag("blocking")?;
+    let no_gc_transition = attrs.take_flag("no_gc_transition")?;
+
+    if blocking && no_gc_transition {
+        bail_span!(ident, "A blocking function can't skip the GC transition, as it would stall the GC");
+    }
+
+    Ok((blocking, no_gc_transition))
+}
+
+/// Parses the `block_on` flag, which async functions must be given as a future can't be handed
+/// across the FFI boundary.
+fn parse_block_on(
+    attrs: &mut BindgenAttrs,
+    sig: &syn::Signature,
+    no_gc_transition: bool,
+) -> Result<bool, Diagnostic> {
+    let block_on = attrs.take_flag("block_on")?;
+
+    match &sig.asyncness {
+        Some(asyncness) if !block_on => bail_span!(
+            asyncness,
+            "Async functions can't be called across the FFI boundary. Add #[dotnet_bindgen(block_on)] to block on \
+             them with the executor registered through dotnet_bindgen::core::set_block_on"
+        ),
+        None if block_on => bail_span!(sig.ident, "block_on is only valid on async functions"),
+        _ => (),
+    }
+
+    if block_on && no_gc_transition {
+        bail_span!(sig.ident, "A block_on function can't skip the GC transition, as it would stall the GC");
+    }
+
+    Ok(block_on)
+}
+
+fn parse_abi(attrs: &mut BindgenAttrs) -> Result<BindgenCallingConvention, Diagnostic> {
+    let lit = match attrs.take_str("abi")? {
+        Some(lit) => lit,
+        None => return Ok(BindgenCallingConvention::C),
+    };
+
+    match lit.value().as_str() {
+        "C" => Ok(BindgenCallingConvention::C),
+        "system" => Ok(BindgenCallingConvention::System),
+        _ => bail_span!(lit, "Expected one of \"C\" or \"system\""),
+    }
+}
+
 impl MacroParse for syn::ItemStruct {
-    fn macro_parse(&self, program: &mut Program) -> Result<(), Diagnostic> {
+    fn macro_parse(&mut self, program: &mut Program, opts: &mut BindgenAttrs) -> Result<(), Diagnostic> {
         let name = self.ident.clone();
 
+        if opts.take_flag("opaque")? {
+            if !self.generics.params.is_empty() {
+                bail_span!(self.generics, "Opaque structs can't be generic");
+            }
+
+            program.exports.push(Export::Opaque(ExportedOpaque { name }));
+            return Ok(());
+        }
+
         let fields = match &self.fields {
-            syn::Fields::Named(n) => parse_named_fields(&n),
+            syn::Fields::Named(n) => parse_named_fields(n),
             _ => Err(Diagnostic::spanned_error(
                 self,
                 "Can only structs with named fields"
             ))
         }?;
 
+        check_struct_repr(self, fields.len())?;
+
         let span = self.ident.span();
 
+        let cs_name = opts.take_str("cs_name")?.map(|lit| lit.value());
+        let fixed_string = parse_fixed_string(opts, self, &fields)?;
+
         program.exports.push(Export::Struct(ExportedStruct {
             name,
             fields,
             span,
+            cs_name,
+            fixed_string,
+        }));
+
+        Ok(())
+    }
+}
+
+impl MacroParse for syn::ItemStatic {
+    fn macro_parse(&mut self, program: &mut Program, _opts: &mut BindgenAttrs) -> Result<(), Diagnostic> {
+        // The generated bindings look the static up by name, so it must have a predictable symbol.
+        if !self.attrs.iter().any(|attr| attr.path.is_ident("no_mangle")) {
+            self.attrs.push(syn::parse_quote!(#[no_mangle]));
+        }
+
+        program.exports.push(Export::Static(ExportedStatic {
+            name: self.ident.clone(),
+            ty: *self.ty.clone(),
+            mutable: self.mutability.is_some(),
+        }));
+
+        Ok(())
+    }
+}
+
+impl MacroParse for syn::ItemEnum {
+    fn macro_parse(&mut self, program: &mut Program, _opts: &mut BindgenAttrs) -> Result<(), Diagnostic> {
+        if !self.generics.params.is_empty() {
+            bail_span!(self.generics, "Can't generate binding metadata for generic enums");
+        }
+
+        let (repr, signed) = parse_enum_repr(self)?;
+
+        let mut variants = Vec::new();
+        for variant in &self.variants {
+            if !matches!(variant.fields, syn::Fields::Unit) {
+                bail_span!(variant, "Can only generate binding metadata for C-like enums");
+            }
+
+            variants.push(variant.ident.clone());
+        }
+
+        program.exports.push(Export::Enum(ExportedEnum {
+            name: self.ident.clone(),
+            variants,
+            repr,
+            signed,
         }));
 
         Ok(())
     }
 }
 
+/// Finds the integer repr of an enum, returning it as a type along with whether it is signed.
+///
+/// Without an explicit repr the size of an enum is up to rustc, so one is required.
+fn parse_enum_repr(item: &syn::ItemEnum) -> Result<(TokenStream, bool), Diagnostic> {
+    for attr in item.attrs.iter().filter(|attr| attr.path.is_ident("repr")) {
+        let repr: proc_macro2::Ident = match attr.parse_args() {
+            Ok(repr) => repr,
+            Err(_) => continue,
+        };
+
+        match repr.to_string().as_str() {
+            "C" => return Ok((quote! { ::core::ffi::c_int }, true)),
+            "i8" | "i16" | "i32" | "i64" => return Ok((repr.to_token_stream(), true)),
+            "u8" | "u16" | "u32" | "u64" => return Ok((repr.to_token_stream(), false)),
+            _ => bail_span!(repr, "Expected an enum repr of C or a fixed size integer type"),
+        }
+    }
+
+    bail_span!(item.ident, "Enums need a #[repr(...)] attribute to generate binding metadata")
+}
+
+/// Checks that the struct's repr gives it the same layout as the `Sequential` C# struct generated
+/// for it, reporting every mismatch found.
+///
+/// `#[repr(align(N))]` is fine, as the alignment is recorded in the descriptor.
+fn check_struct_repr(item: &syn::ItemStruct, field_count: usize) -> Result<(), Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let mut has_stable_layout = false;
+
+    for attr in item.attrs.iter().filter(|attr| attr.path.is_ident("repr")) {
+        let reprs = match attr.parse_args_with(
+            syn::punctuated::Punctuated::<syn::Meta, syn::Token![,]>::parse_terminated
+        ) {
+            Ok(reprs) => reprs,
+            Err(e) => {
+                diagnostics.push(e.into());
+                continue;
+            }
+        };
+
+        for repr in reprs {
+            let path = repr.path();
+            if path.is_ident("C") {
+                has_stable_layout = true;
+            } else if path.is_ident("transparent") {
+                has_stable_layout = true;
+                if field_count != 1 {
+                    diagnostics.push(err_span!(
+                        repr,
+                        "#[repr(transparent)] structs are laid out as their single field, but this struct has {} fields",
+                        field_count,
+                    ));
+                }
+            } else if path.is_ident("packed") {
+                diagnostics.push(err_span!(
+                    repr,
+                    "Packed structs aren't supported, as the generated C# struct aligns its fields naturally",
+                ));
+            } else if path.is_ident("Rust") {
+                diagnostics.push(err_span!(
+                    repr,
+                    "#[repr(Rust)] leaves the field layout up to rustc, so can't match the generated C# struct",
+                ));
+            }
+        }
+    }
+
+    if !has_stable_layout && diagnostics.is_empty() {
+        diagnostics.push(err_span!(
+            item.ident,
+            "Structs need #[repr(C)] to generate binding metadata, as the generated C# struct has a sequential layout",
+        ));
+    }
+
+    Diagnostic::from_vec(diagnostics)
+}
+
+/// Parses the `fixed_string` flag, checking the struct is a byte buffer followed by a `u8` length.
+fn parse_fixed_string(
+    attrs: &mut BindgenAttrs,
+    item: &syn::ItemStruct,
+    fields: &[ExportedStructField],
+) -> Result<bool, Diagnostic> {
+    if !attrs.take_flag("fixed_string")? {
+        return Ok(false);
+    }
+
+    let is_u8 = |ty: &syn::Type| matches!(ty, syn::Type::Path(p) if p.qself.is_none() && p.path.is_ident("u8"));
+
+    let is_layout = match fields {
+        [bytes, len] => matches!(&bytes.ty, syn::Type::Array(a) if is_u8(&a.elem)) && is_u8(&len.ty),
+        _ => false,
+    };
+
+    if !is_layout {
+        bail_span!(item.fields, "fixed_string structs must have exactly two fields, of types [u8; N] and u8");
+    }
+
+    Ok(true)
+}
+
 fn parse_named_fields(fields: &syn::FieldsNamed) -> Result<Vec<ExportedStructField>, Diagnostic> {
     let mut fields_parsed = Vec::new();
     for field in fields.named.iter() {
@@ -405,21 +2081,19 @@ fn parse_named_fields(fields: &syn::FieldsNamed) -> Result<Vec<ExportedStructFie
 
 fn parse_pat(pat: &syn::Pat) -> Result<proc_macro2::Ident, Diagnostic> {
     match pat {
-        syn::Pat::Ident(pat_ident) => parse_pat_ident(&pat_ident),
+        syn::Pat::Ident(pat_ident) => parse_pat_ident(pat_ident),
         _ => bail_span!(pat, "Can't generate binding metadata for this pattern"),
     }
 }
 
 fn parse_pat_ident(pat_ident: &syn::PatIdent) -> Result<proc_macro2::Ident, Diagnostic> {
-    match &pat_ident.by_ref {
-        Some(r) => bail_span!(r, "Can't generate binding metadata for ref types"),
-        None => (),
-    };
+    if let Some(r) = &pat_ident.by_ref {
+        bail_span!(r, "Can't generate binding metadata for ref types");
+    }
 
-    match &pat_ident.subpat {
-        Some((_at, pat)) => bail_span!(pat, "Can't generate binding metadata for subpatterns"),
-        None => (),
-    };
+    if let Some((_at, pat)) = &pat_ident.subpat {
+        bail_span!(pat, "Can't generate binding metadata for subpatterns");
+    }
 
     Ok(pat_ident.ident.clone())
 }
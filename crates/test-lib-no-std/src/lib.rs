@@ -0,0 +1,73 @@
+//! Checks that the code generated by `#[dotnet_bindgen]` only needs `core` and `alloc`.
+
+#![no_std]
+
+extern crate alloc;
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use dotnet_bindgen::dotnet_bindgen;
+
+#[dotnet_bindgen]
+fn add(a: u32, b: u32) -> u32 {
+    a.wrapping_add(b)
+}
+
+#[dotnet_bindgen]
+fn sum(values: &[u64]) -> u64 {
+    values.iter().sum()
+}
+
+#[dotnet_bindgen]
+fn str_len(s: &str) -> u64 {
+    s.len() as u64
+}
+
+#[dotnet_bindgen(returns_owned)]
+fn range(count: u32) -> Vec<u32> {
+    (0..count).collect()
+}
+
+#[dotnet_bindgen(returns_owned)]
+fn repeat(s: &str, count: u32) -> String {
+    s.repeat(count as usize)
+}
+
+#[dotnet_bindgen]
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct Point {
+    x: i32,
+    y: i32,
+}
+
+#[dotnet_bindgen]
+impl Point {
+    fn manhattan_length(&self) -> u32 {
+        self.x.unsigned_abs() + self.y.unsigned_abs()
+    }
+}
+
+#[dotnet_bindgen]
+#[repr(u8)]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    North,
+    East,
+    South,
+    West,
+}
+
+#[dotnet_bindgen]
+fn turn_right(direction: Direction) -> Direction {
+    match direction {
+        Direction::North => Direction::East,
+        Direction::East => Direction::South,
+        Direction::South => Direction::West,
+        Direction::West => Direction::North,
+    }
+}
+
+#[dotnet_bindgen]
+static ORIGIN: Point = Point { x: 0, y: 0 };
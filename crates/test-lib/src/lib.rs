@@ -23,6 +23,7 @@ fn slice_arg(slice: &[i32]) {
 }
 
 #[dotnet_bindgen]
+#[repr(C)]
 #[derive(Debug)]
 pub struct SimpleStruct {
     field_1: i32,
@@ -31,10 +32,611 @@ pub struct SimpleStruct {
 
 #[dotnet_bindgen]
 fn struct_arg_val(arg: SimpleStruct) {
-    dbg!(arg);
+    dbg!(arg.field_1, arg.field_2);
 }
 
 #[dotnet_bindgen]
 fn bool_arg(arg: bool) {
     dbg!(arg);
-}
\ No newline at end of file
+}
+
+#[dotnet_bindgen]
+fn fill_buffer(seed: u8, #[dotnet_bindgen(fill_buffer)] buffer: &mut [u8]) -> usize {
+    let required = 16;
+    if buffer.len() >= required {
+        for (i, b) in buffer.iter_mut().take(required).enumerate() {
+            *b = seed.wrapping_add(i as u8);
+        }
+    }
+
+    required
+}
+
+#[dotnet_bindgen]
+fn str_arg(s: &str) -> usize {
+    dbg!(s);
+    s.chars().count()
+}
+
+#[dotnet_bindgen]
+fn copy_buffer(
+    #[dotnet_bindgen(direction = "in")] src: &[u8],
+    #[dotnet_bindgen(direction = "out")] dst: &mut [u8],
+) {
+    let len = src.len().min(dst.len());
+    dst[..len].copy_from_slice(&src[..len]);
+}
+
+#[dotnet_bindgen]
+fn raw_ptr_arg(#[dotnet_bindgen(raw_ptr)] ptr: *const u32) -> bool {
+    ptr.is_null()
+}
+
+#[dotnet_bindgen]
+#[no_mangle]
+pub static DEFAULT_STRUCT: SimpleStruct = SimpleStruct {
+    field_1: 1,
+    field_2: 2,
+};
+
+#[dotnet_bindgen]
+static mut CALL_COUNT: u32 = 0;
+
+/// A type whose descriptor can't be built, to check that the generator survives a describe
+/// function panicking.
+#[repr(C)]
+pub struct Undescribable(u32);
+
+impl dotnet_bindgen::core::FfiStable for Undescribable {}
+
+impl dotnet_bindgen::core::BindgenTypeDescribe for Undescribable {
+    fn describe() -> dotnet_bindgen::core::BindgenTypeDescriptor {
+        panic!("Undescribable can't be described")
+    }
+}
+
+#[dotnet_bindgen]
+pub static UNDESCRIBABLE: Undescribable = Undescribable(0);
+
+#[dotnet_bindgen]
+fn fill_uninit(#[dotnet_bindgen(direction = "out")] buffer: &mut [std::mem::MaybeUninit<u8>]) {
+    for (i, b) in buffer.iter_mut().enumerate() {
+        *b = std::mem::MaybeUninit::new(i as u8);
+    }
+}
+
+#[dotnet_bindgen]
+impl SimpleStruct {
+    #[dotnet_bindgen(returns_owned)]
+    fn new(field_1: i32, field_2: u64) -> Self {
+        Self { field_1, field_2 }
+    }
+
+    fn sum(&self) -> i64 {
+        self.field_1 as i64 + self.field_2 as i64
+    }
+
+    fn double(&mut self) {
+        self.field_1 *= 2;
+        self.field_2 *= 2;
+    }
+
+    fn into_field_1(self) -> i32 {
+        self.field_1
+    }
+
+    /// Builder style methods take and return Self by value, as a borrow of self can't be returned.
+    #[dotnet_bindgen(returns_owned)]
+    fn with_field_2(mut self, field_2: u64) -> Self {
+        self.field_2 = field_2;
+        self
+    }
+}
+
+/// Would clash with System.String in the generated bindings without renaming.
+#[dotnet_bindgen(cs_name = "RustString")]
+#[repr(C)]
+pub struct String {
+    len: u32,
+}
+
+#[dotnet_bindgen]
+fn renamed_struct_arg(arg: String) -> u32 {
+    arg.len
+}
+
+#[dotnet_bindgen]
+fn many_complex_args(
+    a: &[u8],
+    flag_a: bool,
+    b: &mut [i32],
+    name: &str,
+    flag_b: bool,
+    c: &[u64],
+    d: &mut [u8],
+) -> bool {
+    dbg!(a, flag_a, &b, name, flag_b, c, &d);
+    flag_a && flag_b
+}
+
+/// Gets an `...Async` overload when generated with --async-wrappers.
+#[dotnet_bindgen(blocking)]
+fn slow_sum(a: u64, b: u64) -> u64 {
+    std::thread::sleep(std::time::Duration::from_millis(10));
+    a + b
+}
+
+#[dotnet_bindgen]
+fn double_duration(duration: std::time::Duration) -> std::time::Duration {
+    duration * 2
+}
+
+/// Bound with CallingConvention.StdCall, which only differs from Cdecl on 32 bit Windows.
+#[dotnet_bindgen(abi = "system")]
+fn system_abi_add(a: i32, b: i32) -> i32 {
+    a + b
+}
+
+#[dotnet_bindgen]
+#[repr(u8)]
+#[derive(Clone, Copy, PartialEq)]
+pub enum Color {
+    Red,
+    Green,
+    Blue = 5,
+}
+
+#[dotnet_bindgen]
+fn count_color(colors: &[Color], color: Color) -> u32 {
+    colors.iter().filter(|&&c| c == color).count() as u32
+}
+
+/// Round-trips a slice of enums, reversing it in place.
+#[dotnet_bindgen]
+fn reverse_colors(colors: &mut [Color]) {
+    colors.reverse();
+}
+
+/// Returned as a `Color[]`.
+#[dotnet_bindgen(returns_owned)]
+fn primary_colors() -> Vec<Color> {
+    vec![Color::Red, Color::Green, Color::Blue]
+}
+
+/// Symbols which happen to match the describe prefix, but must not be called as one.
+#[no_mangle]
+pub static __bindgen_describe_decoy_static: u32 = 0;
+
+#[no_mangle]
+pub extern "C" fn __bindgen_describe_decoy_func() -> u32 {
+    0
+}
+
+#[dotnet_bindgen]
+fn cow_args(values: std::borrow::Cow<[u32]>, label: std::borrow::Cow<str>) -> u32 {
+    values.iter().sum::<u32>() + label.len() as u32
+}
+
+/// Short enough to be called without a GC transition.
+#[dotnet_bindgen(no_gc_transition)]
+fn fast_add(a: u32, b: u32) -> u32 {
+    a.wrapping_add(b)
+}
+
+/// Returns a buffer allocated by Rust, which C# hands back to be freed.
+#[dotnet_bindgen(returns_owned)]
+fn squares(count: u32) -> Vec<u32> {
+    (0..count).map(|i| i * i).collect()
+}
+
+#[dotnet_bindgen(returns_owned)]
+fn greeting(name: &str) -> std::string::String {
+    format!("Hello, {}!", name)
+}
+
+/// Takes the same text in each of the supported string encodings.
+#[dotnet_bindgen]
+fn encoded_lengths(
+    utf8: &str,
+    #[dotnet_bindgen(encoding = "utf16")] utf16: &[u16],
+    #[dotnet_bindgen(encoding = "ansi")] ansi: &[u8],
+) -> u64 {
+    (utf8.len() + utf16.len() + ansi.len()) as u64
+}
+
+static GREETING_BYTES: &[u8] = b"hello";
+
+/// A C style API returning a pointer to a buffer, and writing its length to an out parameter.
+#[dotnet_bindgen(returns_buffer(len_out = "out_len"))]
+fn greeting_bytes(out_len: *mut usize) -> *const u8 {
+    unsafe { *out_len = GREETING_BYTES.len() };
+    GREETING_BYTES.as_ptr()
+}
+
+#[dotnet_bindgen]
+fn lerp(a: f32, b: f32, t: f64) -> f64 {
+    a as f64 + (b as f64 - a as f64) * t
+}
+
+#[dotnet_bindgen]
+fn scale_vector(v: [f32; 4], factor: f32) -> [f32; 4] {
+    [v[0] * factor, v[1] * factor, v[2] * factor, v[3] * factor]
+}
+
+#[dotnet_bindgen]
+#[repr(C)]
+pub struct Vertex {
+    position: [f32; 3],
+    uv: [f32; 2],
+}
+
+#[dotnet_bindgen]
+fn vertex_height(vertex: Vertex) -> f32 {
+    vertex.position[1]
+}
+
+/// Bound as `Int128`/`UInt128`, which need net7.0. Both cross the boundary split into two halves.
+#[dotnet_bindgen]
+fn negate_i128(value: i128) -> i128 {
+    -value
+}
+
+#[dotnet_bindgen]
+fn widening_mul(a: u64, b: u64) -> u128 {
+    u128::from(a) * u128::from(b)
+}
+
+/// Larger than the 16 bytes SysV passes in registers, so six of them spill onto the stack.
+#[dotnet_bindgen]
+#[repr(C)]
+pub struct Transform {
+    x: f64,
+    y: f64,
+    z: f64,
+    w: f64,
+}
+
+/// Takes more by-value structs than fit in registers on either linux-x64 or win-x64, followed by
+/// an integer which has to be found after them.
+#[dotnet_bindgen]
+fn sum_transforms(a: Transform, b: Transform, c: Transform, d: Transform, e: Transform, f: Transform, scale: i32) -> f64 {
+    [a, b, c, d, e, f].iter().map(|t| t.x + t.y + t.z + t.w).sum::<f64>() * scale as f64
+}
+
+/// Bound with an `in Vertex` parameter, so the vertex isn't copied on the way in.
+#[dotnet_bindgen]
+fn vertex_sum(vertex: &Vertex) -> f32 {
+    vertex.position.iter().chain(vertex.uv.iter()).sum()
+}
+
+/// Bound with a `ref Vertex` parameter.
+#[dotnet_bindgen]
+fn scale_vertex(vertex: &mut Vertex, factor: f32) {
+    for p in vertex.position.iter_mut() {
+        *p *= factor;
+    }
+}
+
+/// Bound as a `delegate* unmanaged[Cdecl]` when generating for C# 9 or later.
+#[dotnet_bindgen]
+fn apply_callback(callback: extern "C" fn(i32) -> i32, value: i32) -> i32 {
+    callback(value)
+}
+
+#[dotnet_bindgen]
+fn notify(callback: extern "C" fn(u64, f32)) {
+    callback(1, 0.5);
+}
+
+/// A table of callbacks for a plugin to fill in. Its fields are `delegate* unmanaged[Cdecl]` when
+/// generating for C# 9 or later.
+#[dotnet_bindgen]
+#[repr(C)]
+pub struct PluginVTable {
+    on_load: extern "C" fn(u32) -> i32,
+    on_event: extern "C" fn(u64, f32),
+}
+
+#[dotnet_bindgen]
+fn register_plugin(vtable: PluginVTable, version: u32) -> bool {
+    let loaded = (vtable.on_load)(version) != 0;
+    if loaded {
+        (vtable.on_event)(u64::from(version), 1.0);
+    }
+    loaded
+}
+
+/// Bound as a struct with an inline `fixed byte` buffer, and a `Value` property decoding it.
+#[dotnet_bindgen(fixed_string)]
+#[repr(C)]
+pub struct Name {
+    bytes: [u8; 32],
+    len: u8,
+}
+
+#[dotnet_bindgen]
+fn name_len(name: Name) -> u8 {
+    name.len
+}
+
+/// Bound as a struct with an inline `fixed UInt16` buffer, and a `SamplesSpan` property over it.
+#[dotnet_bindgen]
+#[repr(C)]
+pub struct Waveform {
+    samples: [u16; 8],
+    rate: u32,
+}
+
+#[dotnet_bindgen]
+fn waveform_peak(waveform: &Waveform) -> u16 {
+    waveform.samples.iter().copied().max().unwrap_or(0)
+}
+
+/// Shared with C# through an `EngineHandle`, which drops its reference when released.
+pub struct Engine {
+    revision: u32,
+}
+
+#[dotnet_bindgen(returns_owned)]
+fn new_engine(revision: u32) -> std::sync::Arc<Engine> {
+    std::sync::Arc::new(Engine { revision })
+}
+
+#[dotnet_bindgen]
+fn engine_revision(engine: std::sync::Arc<Engine>) -> u32 {
+    engine.revision
+}
+
+#[dotnet_bindgen]
+#[repr(C)]
+pub struct Pair {
+    key: u32,
+    value: u64,
+}
+
+/// Bound as returning a `Dictionary<UInt32, UInt64>` rather than an array of pairs.
+#[dotnet_bindgen(returns_dictionary, returns_owned)]
+fn cubes(count: u32) -> Vec<Pair> {
+    (0..count).map(|i| Pair { key: i, value: (i as u64).pow(3) }).collect()
+}
+
+/// Returned as bytes, and converted back into a `bool[]` by the bindings.
+#[dotnet_bindgen(returns_owned)]
+fn parities(values: &[u32]) -> Vec<bool> {
+    values.iter().map(|v| v % 2 == 0).collect()
+}
+
+/// Grüße aus Zürich, with a non-ASCII doc comment and string literal.
+#[dotnet_bindgen(returns_owned)]
+fn non_ascii_greeting() -> std::string::String {
+    "Grüße ☕".to_string()
+}
+
+/// Bound as `Math.Trig.Sine`, rather than in TopLevelMethods.
+#[dotnet_bindgen(module = "Math.Trig")]
+fn sine(x: f64) -> f64 {
+    x.sin()
+}
+
+#[dotnet_bindgen(module = "Math")]
+fn square(x: f64) -> f64 {
+    x * x
+}
+
+// Existing C functions, called directly by the bindings without a thunk.
+#[dotnet_bindgen]
+extern "C" {
+    fn abs(x: i32) -> i32;
+
+    #[dotnet_bindgen(no_gc_transition)]
+    fn llabs(x: i64) -> i64;
+}
+
+/// Takes a nullable `UInt32[]`, with null passed as `None`.
+#[dotnet_bindgen]
+fn optional_sum(values: Option<&[u32]>) -> i64 {
+    match values {
+        Some(values) => values.iter().map(|&v| v as i64).sum(),
+        None => -1,
+    }
+}
+
+/// A self-referential struct, whose `next` pointer is bound as an IntPtr.
+#[dotnet_bindgen]
+#[repr(C)]
+pub struct ListNode {
+    value: u32,
+    next: *mut ListNode,
+}
+
+#[dotnet_bindgen]
+fn list_sum(head: &ListNode) -> u64 {
+    let mut sum = head.value as u64;
+    let mut next = head.next;
+    while let Some(node) = unsafe { next.as_ref() } {
+        sum += node.value as u64;
+        next = node.next;
+    }
+    sum
+}
+
+/// Bound as `enum Priority : short`, matching its repr.
+#[dotnet_bindgen]
+#[repr(i16)]
+#[derive(Clone, Copy)]
+pub enum Priority {
+    Low = -100,
+    Normal = 0,
+    High = 1000,
+}
+
+/// Has a discriminant above i64::MAX, which is still bound as a `ulong`.
+#[dotnet_bindgen]
+#[repr(u64)]
+#[derive(Clone, Copy)]
+pub enum Mask {
+    Empty = 0,
+    TopBit = 1 << 63,
+}
+
+#[dotnet_bindgen]
+fn is_urgent(priority: Priority, mask: Mask) -> bool {
+    matches!(priority, Priority::High) && matches!(mask, Mask::TopBit)
+}
+
+/// Read back up to the NUL terminator, then handed back to be freed.
+#[dotnet_bindgen(returns_owned)]
+fn c_greeting(name: &str) -> std::ffi::CString {
+    std::ffi::CString::new(format!("Hello, {}!", name)).unwrap_or_default()
+}
+
+/// Decoded with `Marshal.PtrToStringAnsi` rather than as UTF-8.
+#[dotnet_bindgen(returns_owned, return_encoding = "ansi")]
+fn c_version() -> std::ffi::CString {
+    std::ffi::CString::new("1.0").unwrap()
+}
+
+/// Returned as a `string[]`, with every string freed along with the Vec holding them.
+#[dotnet_bindgen(returns_owned)]
+fn split_words(text: &str) -> Vec<std::string::String> {
+    text.split_whitespace().map(str::to_string).collect()
+}
+
+/// Takes a `RustBuffer`, which C# allocates with the Rust allocator and fills in, without a copy.
+#[dotnet_bindgen]
+fn take_buffer(buffer: Vec<u8>) -> u64 {
+    buffer.iter().map(|&b| b as u64).sum()
+}
+
+/// Wakes the thread blocked in `park_until_ready` by unparking it.
+struct ThreadWaker(std::thread::Thread);
+
+impl std::task::Wake for ThreadWaker {
+    fn wake(self: std::sync::Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+/// A minimal executor, polling the future on the calling thread and parking between wakeups.
+fn park_until_ready(mut future: std::pin::Pin<Box<dyn std::future::Future<Output = ()> + '_>>) {
+    let waker = std::sync::Arc::new(ThreadWaker(std::thread::current())).into();
+    let mut cx = std::task::Context::from_waker(&waker);
+    while future.as_mut().poll(&mut cx).is_pending() {
+        std::thread::park();
+    }
+}
+
+/// Must be called before any `block_on` function.
+#[dotnet_bindgen]
+fn init_executor() {
+    dotnet_bindgen::core::set_block_on(park_until_ready);
+}
+
+/// Bound as a synchronous `FetchLength`, which blocks until the future completes.
+#[dotnet_bindgen(block_on)]
+async fn fetch_length(url: &str) -> u64 {
+    std::future::ready(url.len() as u64).await
+}
+
+/// Never seen by C# other than through pointers, which are bound as `Scene*`.
+#[dotnet_bindgen(opaque)]
+pub struct Scene {
+    ticks: Vec<u32>,
+}
+
+#[dotnet_bindgen]
+fn scene_new() -> *mut Scene {
+    Box::into_raw(Box::new(Scene { ticks: Vec::new() }))
+}
+
+#[dotnet_bindgen]
+fn scene_tick(scene: *mut Scene, delta: u32) -> u32 {
+    let scene = unsafe { &mut *scene };
+    scene.ticks.push(delta);
+    scene.ticks.iter().sum()
+}
+
+#[dotnet_bindgen]
+fn scene_free(scene: *mut Scene) {
+    if !scene.is_null() {
+        drop(unsafe { Box::from_raw(scene) });
+    }
+}
+
+/// Copied out by C#, which never frees it, as it's borrowed from the binary.
+#[dotnet_bindgen(returns_borrowed)]
+fn version() -> &'static str {
+    env!("CARGO_PKG_VERSION")
+}
+
+#[dotnet_bindgen(returns_borrowed)]
+fn small_primes() -> &'static [u32] {
+    &[2, 3, 5, 7, 11, 13]
+}
+
+/// Holds owned buffers, which C# copies into a managed `WordStats` before freeing.
+#[dotnet_bindgen]
+#[repr(C)]
+pub struct WordStats {
+    lengths: dotnet_bindgen::core::OwnedSliceAbi<u32>,
+    longest: dotnet_bindgen::core::OwnedStrAbi,
+    count: u32,
+}
+
+#[dotnet_bindgen(returns_owned)]
+fn count_words(text: &str) -> WordStats {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let longest = words.iter().copied().max_by_key(|w| w.len()).unwrap_or("");
+
+    WordStats {
+        lengths: words.iter().map(|w| w.len() as u32).collect::<Vec<_>>().into(),
+        longest: longest.to_string().into(),
+        count: words.len() as u32,
+    }
+}
+
+/// Holds a buffer borrowed from the binary, which C# only copies.
+#[dotnet_bindgen]
+#[repr(C)]
+pub struct BuildInfo {
+    name: dotnet_bindgen::core::StrAbi,
+    primes: dotnet_bindgen::core::SliceAbi<u32>,
+}
+
+#[dotnet_bindgen(returns_borrowed)]
+fn get_build_info() -> BuildInfo {
+    BuildInfo {
+        name: env!("CARGO_PKG_NAME").into(),
+        primes: small_primes().into(),
+    }
+}
+
+/// Returns the rest of the values after the header, borrowed from the argument, which C# copies
+/// out of the tuple while the argument is still pinned.
+#[dotnet_bindgen(returns_borrowed)]
+fn take_header(values: &[i32]) -> (&[i32], i32) {
+    match values.split_first() {
+        Some((header, rest)) => (rest, *header),
+        None => (&[], 0),
+    }
+}
+
+/// Options can be split over several stacked attributes, which are merged.
+#[dotnet_bindgen(module = "Text")]
+#[dotnet_bindgen(returns_owned)]
+fn shout(text: &str) -> std::string::String {
+    text.to_uppercase()
+}
+
+/// Gets C# overloads taking just the text, and the text and width.
+#[dotnet_bindgen(returns_owned, default(width = 40, fill = "-"))]
+fn pad(text: &str, width: u32, fill: &str) -> std::string::String {
+    let padding = (width as usize).saturating_sub(text.chars().count());
+    format!("{}{}", text, fill.repeat(padding))
+}
+
+/// Defaults can be negative, and are checked against the argument's type like any other.
+#[dotnet_bindgen(default(by = -1, scale = -0.5))]
+fn nudge(value: i32, by: i32, scale: f32) -> f32 {
+    (value + by) as f32 * scale
+}
@@ -1,2 +1,4 @@
+#![no_std]
+
 pub use dotnet_bindgen_core as core;
 pub use dotnet_bindgen_macro::dotnet_bindgen;